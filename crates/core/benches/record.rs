@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::num::{NonZeroU128, NonZeroU64};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use referrals_core::hub::{
+    referral, Activity, DappCampaign, DappExternalQuery, MutableReferralStore, NonZeroPercent,
+    ReadonlyDappStore, ReadonlyReferralStore, ReferralCode,
+};
+use referrals_core::{FallibleApi, Id, IdRef};
+
+const DAPPS: u64 = 100;
+const CODES: u64 = 1000;
+
+/// In-memory store keyed by `String`, so borrowed ids can be looked up without allocating.
+#[derive(Default, Clone)]
+struct Store {
+    dapps: HashMap<String, (NonZeroPercent, String)>,
+    owners: HashMap<u64, String>,
+    invocations: HashMap<String, HashMap<u64, u64>>,
+    daily_invocations: HashMap<(String, u64), u64>,
+    total_earnings: HashMap<u64, NonZeroU128>,
+    dapp_earnings: HashMap<String, HashMap<u64, NonZeroU128>>,
+    dapp_contributions: HashMap<String, NonZeroU128>,
+    last_active: HashMap<String, (Activity, HashMap<u64, Activity>)>,
+}
+
+impl FallibleApi for Store {
+    type Error = Infallible;
+}
+
+impl ReadonlyDappStore for Store {
+    fn dapp_exists(&self, id: &Id) -> Result<bool, Self::Error> {
+        Ok(self.dapps.contains_key(id.as_str()))
+    }
+
+    fn percent(&self, id: &Id) -> Result<Option<NonZeroPercent>, Self::Error> {
+        Ok(self.dapps.get(id.as_str()).map(|(percent, _)| *percent))
+    }
+
+    fn collector(&self, id: &Id) -> Result<Id, Self::Error> {
+        Ok(Id::from(self.dapps[id.as_str()].1.as_str()))
+    }
+
+    fn has_rewards_pot(&self, _id: &Id) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+
+    fn rewards_pot(&self, id: &Id) -> Result<Id, Self::Error> {
+        Ok(Id::from(format!("{}_pot", id.as_str())))
+    }
+
+    fn self_referral_allowed(&self, _id: &Id) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn pending_collector(&self, _id: &Id) -> Result<Option<Id>, Self::Error> {
+        Ok(None)
+    }
+
+    fn campaign(&self, _id: &Id) -> Result<Option<DappCampaign>, Self::Error> {
+        Ok(None)
+    }
+
+    fn max_invocations_per_code(&self, _id: &Id) -> Result<Option<NonZeroU64>, Self::Error> {
+        Ok(None)
+    }
+
+    fn dapp_frozen(&self, _id: &Id) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn dapp_by_name(&self, name: &str) -> Result<Option<Id>, Self::Error> {
+        // dApps are named after their ids
+        Ok(self.dapps.contains_key(name).then(|| Id::from(name)))
+    }
+
+    fn relayer(&self, _id: &Id) -> Result<Option<Id>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl DappExternalQuery for Store {
+    fn self_id(&self) -> Result<Id, Self::Error> {
+        Ok(Id::from("hub"))
+    }
+
+    fn rewards_admin(&self, _id: &Id) -> Result<Id, Self::Error> {
+        Ok(Id::from("hub"))
+    }
+
+    fn rewards_pot_admin(&self, _id: &Id) -> Result<Id, Self::Error> {
+        Ok(Id::from("hub"))
+    }
+
+    fn rewards_pot_dapp(&self, _id: &Id) -> Result<Id, Self::Error> {
+        Ok(Id::from("dapp"))
+    }
+
+    fn current_fee(&self, _id: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(NonZeroU128::new(1000))
+    }
+
+    fn current_height(&self) -> Result<u64, Self::Error> {
+        Ok(12345)
+    }
+
+    fn current_time(&self) -> Result<u64, Self::Error> {
+        Ok(1_571_797_419)
+    }
+}
+
+impl ReadonlyReferralStore for Store {
+    fn code_exists(&self, code: ReferralCode) -> Result<bool, Self::Error> {
+        Ok(self.owners.contains_key(&code.to_u64()))
+    }
+
+    fn owner_exists(&self, owner: IdRef<'_>) -> Result<bool, Self::Error> {
+        Ok(self.owners.values().any(|o| o == owner.as_str()))
+    }
+
+    fn owner_of(&self, code: ReferralCode) -> Result<Option<Id>, Self::Error> {
+        Ok(self
+            .owners
+            .get(&code.to_u64())
+            .map(|o| Id::from(o.as_str())))
+    }
+
+    fn latest(&self) -> Result<Option<ReferralCode>, Self::Error> {
+        Ok(self
+            .owners
+            .keys()
+            .max()
+            .and_then(|&code| ReferralCode::try_from(code).ok()))
+    }
+
+    fn total_earnings(&self, code: ReferralCode) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self.total_earnings.get(&code.to_u64()).copied())
+    }
+
+    fn dapp_earnings(
+        &self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+    ) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self
+            .dapp_earnings
+            .get(dapp.as_str())
+            .and_then(|earnings| earnings.get(&code.to_u64()))
+            .copied())
+    }
+
+    fn code_dapp_count(&self, code: ReferralCode) -> Result<u64, Self::Error> {
+        Ok(self
+            .dapp_earnings
+            .values()
+            .filter(|earnings| earnings.contains_key(&code.to_u64()))
+            .count() as u64)
+    }
+
+    fn code_dapp(&self, _code: ReferralCode, _index: u64) -> Result<Option<Id>, Self::Error> {
+        Ok(None)
+    }
+
+    fn code_blocked(&self, _code: ReferralCode) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn invocations(&self, dapp: IdRef<'_>, code: ReferralCode) -> Result<u64, Self::Error> {
+        Ok(self
+            .invocations
+            .get(dapp.as_str())
+            .and_then(|invocations| invocations.get(&code.to_u64()))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn self_referral_blocked(&self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn dapp_contributions(&self, dapp: IdRef<'_>) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self.dapp_contributions.get(dapp.as_str()).copied())
+    }
+}
+
+impl MutableReferralStore for Store {
+    fn set_latest(&mut self, _code: ReferralCode) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_code_owner(&mut self, code: ReferralCode, owner: Id) -> Result<(), Self::Error> {
+        self.owners.insert(code.to_u64(), owner.into_string());
+        Ok(())
+    }
+
+    fn increment_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+    ) -> Result<(), Self::Error> {
+        if let Some(invocations) = self.invocations.get_mut(dapp.as_str()) {
+            *invocations.entry(code.to_u64()).or_default() += 1;
+        } else {
+            self.invocations
+                .insert(String::from(dapp), HashMap::from([(code.to_u64(), 1)]));
+        }
+
+        Ok(())
+    }
+
+    fn increment_daily_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        day: u64,
+    ) -> Result<(), Self::Error> {
+        *self
+            .daily_invocations
+            .entry((String::from(dapp), day))
+            .or_default() += 1;
+
+        Ok(())
+    }
+
+    fn touch(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+        activity: Activity,
+    ) -> Result<(), Self::Error> {
+        if let Some((last_active, codes)) = self.last_active.get_mut(dapp.as_str()) {
+            *last_active = activity;
+            codes.insert(code.to_u64(), activity);
+        } else {
+            self.last_active.insert(
+                String::from(dapp),
+                (activity, HashMap::from([(code.to_u64(), activity)])),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn set_total_earnings(
+        &mut self,
+        code: ReferralCode,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.total_earnings.insert(code.to_u64(), total);
+        Ok(())
+    }
+
+    fn set_dapp_earnings(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        if let Some(earnings) = self.dapp_earnings.get_mut(dapp.as_str()) {
+            earnings.insert(code.to_u64(), total);
+        } else {
+            self.dapp_earnings
+                .insert(String::from(dapp), HashMap::from([(code.to_u64(), total)]));
+        }
+
+        Ok(())
+    }
+
+    fn set_dapp_contributions(
+        &mut self,
+        dapp: IdRef<'_>,
+        contributions: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        if let Some(current) = self.dapp_contributions.get_mut(dapp.as_str()) {
+            *current = contributions;
+        } else {
+            self.dapp_contributions
+                .insert(String::from(dapp), contributions);
+        }
+
+        Ok(())
+    }
+
+    fn set_code_blocked(&mut self, _code: ReferralCode, _blocked: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_self_referral_blocked(&mut self, _blocked: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn store() -> Store {
+    let mut store = Store::default();
+
+    for dapp in 0..DAPPS {
+        store.dapps.insert(
+            format!("dapp{dapp}"),
+            (NonZeroPercent::new(10).unwrap(), format!("collector{dapp}")),
+        );
+    }
+
+    for code in 1..=CODES {
+        store.owners.insert(code, format!("referrer{code}"));
+    }
+
+    store
+}
+
+/// The code referring the `i`th dApp, cycling through every registered code.
+fn code(i: usize) -> ReferralCode {
+    ReferralCode::try_from(i as u64 % CODES + 1).unwrap()
+}
+
+fn record(c: &mut Criterion) {
+    let dapps: Vec<_> = (0..DAPPS)
+        .map(|dapp| Id::from(format!("dapp{dapp}")))
+        .collect();
+
+    let store = store();
+
+    c.bench_function("record", |b| {
+        b.iter_batched_ref(
+            || store.clone(),
+            |store| {
+                for (i, dapp) in dapps.iter().enumerate() {
+                    let code = code(i);
+                    referral::record(store, black_box(dapp), None, code).unwrap();
+                }
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    // steady state: every (dApp, code) pair already has storage entries
+    let mut warm = store;
+
+    for (i, dapp) in dapps.iter().enumerate() {
+        referral::record(&mut warm, dapp, None, code(i)).unwrap();
+    }
+
+    c.bench_function("record_existing", |b| {
+        b.iter(|| {
+            for (i, dapp) in dapps.iter().enumerate() {
+                let code = code(i);
+                referral::record(&mut warm, black_box(dapp), None, code).unwrap();
+            }
+        });
+    });
+}
+
+criterion_group!(benches, record);
+criterion_main!(benches);
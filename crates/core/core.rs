@@ -5,6 +5,14 @@ use std::error::Error as StdError;
 
 use serde::{Deserialize, Serialize};
 
+/// Emit a `tracing::debug!` event, compiles to nothing without the `tracing` feature.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!($($arg)*);
+    };
+}
+
 pub mod hub;
 pub mod rewards_pot;
 
@@ -12,10 +20,58 @@ pub trait FallibleApi {
     type Error: StdError;
 }
 
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum IdError {
+    #[error("empty id")]
+    Empty,
+    #[error("id too long - {len} characters, the maximum is {max}")]
+    TooLong { len: usize, max: usize },
+    #[error("id contains a control character")]
+    ControlCharacter,
+}
+
+/// An account or contract identifier.
+///
+/// `Id::from` performs no validation & is intended for trusted values e.g. those read from storage,
+/// untrusted values should be checked with [`Id::try_new`].
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Id(String);
 
 impl Id {
+    /// Maximum length of a valid id, in characters
+    pub const MAX_LEN: usize = 128;
+
+    /// Create an id from an untrusted value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value:
+    /// - Is empty or only whitespace.
+    /// - Is longer than [`Id::MAX_LEN`] characters.
+    /// - Contains any control characters, including NUL.
+    pub fn try_new(value: impl Into<String>) -> Result<Self, IdError> {
+        let value = value.into();
+
+        if value.trim().is_empty() {
+            return Err(IdError::Empty);
+        }
+
+        let len = value.chars().count();
+
+        if len > Self::MAX_LEN {
+            return Err(IdError::TooLong {
+                len,
+                max: Self::MAX_LEN,
+            });
+        }
+
+        if value.chars().any(char::is_control) {
+            return Err(IdError::ControlCharacter);
+        }
+
+        Ok(Id(value))
+    }
+
     #[must_use]
     pub fn into_string(self) -> String {
         self.0
@@ -25,6 +81,11 @@ impl Id {
     pub fn as_str(&self) -> &str {
         self.as_ref()
     }
+
+    #[must_use]
+    pub fn as_id_ref(&self) -> IdRef<'_> {
+        IdRef(&self.0)
+    }
 }
 
 impl<T> From<T> for Id
@@ -36,6 +97,12 @@ where
     }
 }
 
+impl std::fmt::Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 impl AsRef<str> for Id {
     fn as_ref(&self) -> &str {
         &self.0
@@ -47,3 +114,43 @@ impl AsRef<String> for Id {
         &self.0
     }
 }
+
+/// A borrowed [`Id`], used to key store lookups without cloning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRef<'a>(&'a str);
+
+impl<'a> IdRef<'a> {
+    #[must_use]
+    pub const fn as_str(self) -> &'a str {
+        self.0
+    }
+
+    #[must_use]
+    pub fn to_id(self) -> Id {
+        Id::from(self)
+    }
+}
+
+impl<'a> From<&'a str> for IdRef<'a> {
+    fn from(value: &'a str) -> Self {
+        IdRef(value)
+    }
+}
+
+impl<'a> From<&'a Id> for IdRef<'a> {
+    fn from(value: &'a Id) -> Self {
+        value.as_id_ref()
+    }
+}
+
+impl From<IdRef<'_>> for String {
+    fn from(value: IdRef<'_>) -> Self {
+        value.0.to_owned()
+    }
+}
+
+impl AsRef<str> for IdRef<'_> {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
@@ -1,15 +1,22 @@
+use std::num::NonZeroU128;
+
+use crate::{FallibleApi, Id};
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error<Api> {
     #[error(transparent)]
     Api(#[from] Api),
-    #[error("unauthorised")]
-    Unauthorized,
+    #[error("unauthorised - {sender} is not {required}")]
+    Unauthorized {
+        sender: Id,
+        required: AuthRequirement,
+    },
     #[error("already registered")]
     AlreadyRegistered,
-    #[error("dapp not activated")]
-    DappNotActivated,
-    #[error("referral code not registered")]
-    ReferralCodeNotRegistered,
+    #[error("dapp not activated - {0}")]
+    DappNotActivated(Id),
+    #[error("referral code not registered - {0}")]
+    ReferralCodeNotRegistered(ReferralCode),
     #[error("invalid rewards admin")]
     InvalidRewardsAdmin,
     #[error("invalid rewards pot admin")]
@@ -18,14 +25,158 @@ pub enum Error<Api> {
     FeeNotSet,
     #[error("rewards pot already set")]
     RewardsPotAlreadySet,
+    #[error("rewards pot belongs to another dApp")]
+    RewardsPotMismatch,
     #[error("math overflow")]
     Overflow,
-    #[error("nothing to collect")]
-    NothingToCollect,
+    #[error("nothing to collect - {0}")]
+    NothingToCollect(Earner),
+    #[error("self referral")]
+    SelfReferral,
+    #[error("referral code blocked - {0}")]
+    CodeBlocked(ReferralCode),
+    #[error("feature disabled - {0}")]
+    FeatureDisabled(&'static str),
+    #[error("invalid pagination cursor")]
+    InvalidCursor,
+    #[error("rewards pot not set - {0}")]
+    RewardsPotNotSet(Id),
+    #[error("invalid day range")]
+    InvalidDayRange,
+    #[error("below minimum collect - {amount} is less than {minimum}")]
+    BelowMinimumCollect {
+        amount: NonZeroU128,
+        minimum: NonZeroU128,
+    },
+    #[error("self registration - the hub may not activate itself as a dApp")]
+    SelfRegistration,
+    #[error("dapp deactivated - {0}")]
+    DappDeactivated(Id),
+    #[error("uncollected earnings - referrers of dApp {0} have earnings yet to collect")]
+    UncollectedEarnings(Id),
+    #[error("name taken - {0}")]
+    NameTaken(String),
+    #[error("dapp name not registered - {0}")]
+    DappNameNotRegistered(String),
+}
+
+impl<Api> Error<Api> {
+    /// A stable code identifying the error to clients, API errors are left to the implementor.
+    /// Codes are never reused, new variants take the next free code.
+    #[must_use]
+    pub const fn code(&self) -> Option<u16> {
+        let code = match self {
+            Self::Api(_) => return None,
+            Self::Unauthorized { .. } => 1,
+            Self::AlreadyRegistered => 2,
+            Self::DappNotActivated(_) => 3,
+            Self::ReferralCodeNotRegistered(_) => 4,
+            Self::InvalidRewardsAdmin => 5,
+            Self::InvalidRewardsPotAdmin => 6,
+            Self::FeeNotSet => 7,
+            Self::RewardsPotAlreadySet => 8,
+            Self::RewardsPotMismatch => 9,
+            Self::Overflow => 10,
+            Self::NothingToCollect(_) => 11,
+            Self::SelfReferral => 12,
+            Self::CodeBlocked(_) => 13,
+            Self::FeatureDisabled(_) => 14,
+            Self::InvalidCursor => 15,
+            Self::RewardsPotNotSet(_) => 16,
+            Self::InvalidDayRange => 17,
+            Self::BelowMinimumCollect { .. } => 18,
+            Self::SelfRegistration => 19,
+            Self::DappDeactivated(_) => 20,
+            Self::UncollectedEarnings(_) => 21,
+            Self::NameTaken(_) => 22,
+            Self::DappNameNotRegistered(_) => 23,
+        };
+
+        Some(code)
+    }
+
+    /// Display the error prefixed with it's code, if any, e.g. `[E0001] unauthorised`.
+    #[must_use]
+    pub const fn coded(&self) -> Coded<'_, Api> {
+        Coded(self)
+    }
+}
+
+/// Who may send a message, as reported by [`Error::Unauthorized`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthRequirement {
+    /// The hub itself
+    Hub,
+    /// The hub or it's collector
+    HubOrCollector,
+    /// The dApp or it's collector
+    DappOrCollector(Id),
+    /// The collector nominated by the dApp, yet to accept
+    PendingCollector(Id),
+    /// The dApp or the relayer it authorised to record referrals
+    DappOrRelayer(Id),
+    /// The owner of the referral code
+    CodeOwner(ReferralCode),
+}
+
+impl std::fmt::Display for AuthRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Hub => f.write_str("the hub"),
+            Self::HubOrCollector => f.write_str("the hub or it's collector"),
+            Self::DappOrCollector(dapp) => write!(f, "dApp {dapp} or it's collector"),
+            Self::PendingCollector(dapp) => write!(f, "the pending collector of dApp {dapp}"),
+            Self::DappOrRelayer(dapp) => write!(f, "dApp {dapp} or it's referral relayer"),
+            Self::CodeOwner(code) => write!(f, "the owner of referral code {code}"),
+        }
+    }
+}
+
+/// Whose earnings there were nothing of to collect, as reported by [`Error::NothingToCollect`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Earner {
+    /// A referral code, with a single dApp or across every dApp
+    Referrer {
+        code: ReferralCode,
+        dapp: Option<Id>,
+    },
+    /// A dApp
+    Dapp(Id),
+}
+
+impl std::fmt::Display for Earner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Referrer {
+                code,
+                dapp: Some(dapp),
+            } => write!(f, "referral code {code} with dApp {dapp}"),
+            Self::Referrer { code, dapp: None } => write!(f, "referral code {code}"),
+            Self::Dapp(dapp) => write!(f, "dApp {dapp}"),
+        }
+    }
+}
+
+/// An [`Error`] displayed with it's code, see [`Error::coded`].
+#[derive(Debug)]
+pub struct Coded<'a, Api>(&'a Error<Api>);
+
+impl<Api> std::fmt::Display for Coded<'_, Api>
+where
+    Error<Api>: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.code() {
+            Some(code) => write!(f, "[E{code:04}] {}", self.0),
+            None => self.0.fmt(f),
+        }
+    }
 }
 
+pub mod audit;
 pub mod collect;
 pub mod common;
+pub mod config;
 pub mod dapp;
 pub mod msg;
 pub mod query;
@@ -33,9 +184,18 @@ pub mod referral;
 pub mod reply;
 
 pub use common::*;
+pub use config::Features;
+pub use dapp::Campaign as DappCampaign;
+pub use dapp::DeactivationMode;
 pub use dapp::Metadata as DappMetadata;
+pub use dapp::RepoUrl as DappRepoUrl;
 pub use msg::*;
+pub use referral::Activity;
 pub use referral::Code as ReferralCode;
+pub use referral::ZeroCode as ZeroReferralCode;
+
+pub use config::MutableStore as MutableConfigStore;
+pub use config::ReadonlyStore as ReadonlyConfigStore;
 
 pub use collect::MutableStore as MutableCollectStore;
 pub use collect::ReadonlyStore as ReadonlyCollectStore;
@@ -46,6 +206,7 @@ pub use dapp::ReadonlyStore as ReadonlyDappStore;
 pub use referral::MutableStore as MutableReferralStore;
 pub use referral::ReadonlyStore as ReadonlyReferralStore;
 
+pub use collect::Owed;
 pub use collect::Query as CollectQuery;
 pub use dapp::ExternalQuery as DappExternalQuery;
 
@@ -54,11 +215,22 @@ pub use reply::Handle as HandleReply;
 pub use reply::{Command, Reply};
 
 pub use query::handle as handle_query;
+pub use query::DailyInvocations;
 pub use query::DappInfo;
+pub use query::DappRewards;
+pub use query::DappStatus;
 pub use query::Dapps as DappsQuery;
+pub use query::DappsPage;
+pub use query::ExportPage;
+pub use query::ExportRecord;
+pub use query::ExportSection;
+pub use query::HubTotals;
+pub use query::ReferrerStats;
 pub use query::Referrers as ReferrersQuery;
 pub use query::Request as QueryRequest;
 pub use query::Response as QueryResponse;
+pub use query::RewardsPots as RewardsPotsQuery;
+pub use query::Totals as TotalsQuery;
 
 /// Handle a message, this is the defacto entry point.
 ///
@@ -74,34 +246,70 @@ where
         + MutableReferralStore
         + ReadonlyCollectStore
         + MutableCollectStore
-        + CollectQuery,
+        + CollectQuery
+        + ReadonlyConfigStore
+        + MutableConfigStore,
 {
+    config::check_enabled(api, &msg.kind)?;
+
     match msg.kind {
         Kind::Register(reg) => match reg {
             Registration::Referrer => referral::register(api, msg.sender).map(Reply::from),
+            Registration::ReferrerFor { owner } => {
+                referral::register_for(api, &msg.sender, owner).map(Reply::from)
+            }
             Registration::ActivateDapp {
                 name,
                 percent,
                 collector,
-            } => dapp::activate(api, msg.sender, name, percent, collector).map(Reply::from),
+                fee,
+            } => dapp::activate(api, msg.sender, name, percent, collector, fee),
+            Registration::ActivateDappTrackingOnly { name, collector } => {
+                dapp::activate_tracking_only(api, msg.sender, name, collector).map(Reply::from)
+            }
             Registration::RewardsPot { dapp, rewards_pot } => {
                 dapp::set_rewards_pot(api, dapp, rewards_pot).map(Reply::from)
             }
+            Registration::RewardsPotFailed { dapp } => {
+                dapp::rollback_activation(api, &msg.sender, &dapp).map(|_| Reply::Empty)
+            }
             Registration::DeactivateDapp {
                 dapp,
                 rewards_admin,
                 rewards_recipient,
-            } => dapp::deactivate(api, &msg.sender, dapp, rewards_admin, rewards_recipient)
-                .map(Reply::from),
+                mode,
+            } => dapp::deactivate(
+                api,
+                &msg.sender,
+                dapp,
+                rewards_admin,
+                rewards_recipient,
+                mode,
+            ),
         },
 
-        Kind::Referral { code } => referral::record(api, &msg.sender, code).map(|_| Reply::Empty),
+        Kind::Referral { code, dapp } => referral::record(api, &msg.sender, dapp.as_ref(), code)
+            .map(|capped| {
+                if capped {
+                    Reply::InvocationCapReached(code)
+                } else {
+                    Reply::Empty
+                }
+            }),
 
         Kind::Collect(collection) => match collection {
-            Collection::Referrer { dapp, code } => {
-                collect::referrer(api, msg.sender, &dapp, code).map(Reply::from)
+            Collection::Referrer {
+                dapp,
+                code,
+                recipient,
+            } => collect::referrer(api, msg.sender, &dapp, code, recipient).map(Reply::from),
+            Collection::ReferrerAll { code, start, limit } => {
+                collect::referrer_all(api, &msg.sender, code, start, limit)
+                    .map(|(cmds, next)| Reply::CollectAll { cmds, next })
+            }
+            Collection::Dapp { dapp, recipient } => {
+                collect::dapp(api, msg.sender, &dapp, recipient).map(Reply::from)
             }
-            Collection::Dapp { dapp } => collect::dapp(api, msg.sender, &dapp).map(Reply::from),
         },
 
         Kind::Config(configure) => match configure {
@@ -111,9 +319,79 @@ where
             Configure::DappMetadata { dapp, metadata } => {
                 dapp::configure(api, &msg.sender, &dapp, metadata).map(|_| Reply::Empty)
             }
+            Configure::DappsMetadata { updates } => {
+                dapp::configure_all(api, &msg.sender, updates).map(|_| Reply::Empty)
+            }
+            Configure::AcceptCollector { dapp } => {
+                dapp::accept_collector(api, msg.sender, &dapp).map(|_| Reply::Empty)
+            }
             Configure::DappFee { dapp, fee } => {
                 dapp::set_fee(api, &msg.sender, dapp, fee).map(Reply::from)
             }
+            Configure::DappCampaign { dapp, campaign } => {
+                dapp::set_campaign(api, &msg.sender, &dapp, campaign).map(|_| Reply::Empty)
+            }
+            Configure::ReferralRelayer { dapp, relayer } => {
+                dapp::set_relayer(api, &msg.sender, &dapp, relayer).map(|_| Reply::Empty)
+            }
+            Configure::CodeStatus { code, blocked } => {
+                referral::set_code_status(api, &msg.sender, code, blocked).map(|_| Reply::Empty)
+            }
+            Configure::EnabledFeatures { features } => {
+                config::set_enabled_features(api, &msg.sender, features).map(|_| Reply::Empty)
+            }
+            Configure::MinCollectAmount { amount } => {
+                config::set_min_collect_amount(api, &msg.sender, amount).map(|_| Reply::Empty)
+            }
+            Configure::AllowSelfDapp { allowed } => {
+                config::set_self_dapp_allowed(api, &msg.sender, allowed).map(|_| Reply::Empty)
+            }
+            Configure::HandoverPots { dapps, new_admin } => {
+                dapp::handover_pots(api, &msg.sender, dapps, &new_admin).map(Reply::from)
+            }
         },
     }
 }
+
+/// An api recording the writes made while handling a message, rather than committing them.
+pub trait Recording: FallibleApi {
+    type Write;
+
+    /// The writes recorded so far
+    fn recorded_writes(&self) -> Vec<Self::Write>;
+}
+
+/// The outcome of a [`simulate`]d message.
+#[derive(Debug)]
+pub struct Simulation<Write> {
+    pub reply: Reply,
+    pub writes: Vec<Write>,
+}
+
+/// Handle a message as [`exec`] would against a recording api, returning the reply along with
+/// the writes it would make. Nothing is committed, so this is safe to run from a query.
+///
+/// # Errors
+///
+/// This function will return an error if [`exec`] would.
+pub fn simulate<Api>(api: &mut Api, msg: Msg) -> Result<Simulation<Api::Write>, Error<Api::Error>>
+where
+    Api: ReadonlyDappStore
+        + MutableDappStore
+        + DappExternalQuery
+        + ReadonlyReferralStore
+        + MutableReferralStore
+        + ReadonlyCollectStore
+        + MutableCollectStore
+        + CollectQuery
+        + ReadonlyConfigStore
+        + MutableConfigStore
+        + Recording,
+{
+    let reply = exec(api, msg)?;
+
+    Ok(Simulation {
+        reply,
+        writes: api.recorded_writes(),
+    })
+}
@@ -0,0 +1,114 @@
+use std::num::NonZeroU128;
+
+use crate::Id;
+
+use super::{ReadonlyCollectStore, ReadonlyReferralStore, ReferralCode};
+
+#[derive(Debug, thiserror::Error)]
+pub enum InvariantViolation<Api> {
+    #[error(transparent)]
+    Api(#[from] Api),
+    #[error("code {code} total earnings of {total} differ from the {sum} earned across dApps")]
+    CodeEarnings { code: u64, total: u128, sum: u128 },
+    #[error("dApp {} contributions of {total} differ from the {sum} earned by codes", .dapp.as_str())]
+    DappContributions { dapp: Id, total: u128, sum: u128 },
+    #[error("code {code} collected {collected}, more than the {earnings} earned")]
+    CodeOvercollected {
+        code: u64,
+        collected: u128,
+        earnings: u128,
+    },
+    #[error("code {code} collected {collected} from dApp {}, more than the {earnings} earned", .dapp.as_str())]
+    DappOvercollected {
+        dapp: Id,
+        code: u64,
+        collected: u128,
+        earnings: u128,
+    },
+}
+
+/// Check the accounting invariants hold across every referral code & the dApps they earn with:
+/// - A code's total earnings are the sum of it's earnings with each dApp.
+/// - A dApp's contributions are the sum of every code's earnings with it.
+/// - No code has collected more than it has earned, in total or from any dApp.
+///
+/// dApps are reached through the codes earning with them, those without referrer earnings are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - An invariant does not hold, the first violation found is returned.
+/// - There is an API error.
+pub fn check_invariants<Api>(api: &Api) -> Result<(), InvariantViolation<Api::Error>>
+where
+    Api: ReadonlyReferralStore + ReadonlyCollectStore,
+{
+    let mut dapp_sums: Vec<(Id, u128)> = Vec::new();
+
+    for code in ReferralCode::issued(api.latest()?) {
+        let mut code_sum: u128 = 0;
+
+        for index in 0..api.code_dapp_count(code)? {
+            let Some(dapp) = api.code_dapp(code, index)? else {
+                continue;
+            };
+
+            let earnings = api
+                .dapp_earnings(dapp.as_id_ref(), code)?
+                .map_or(0, NonZeroU128::get);
+            let collected = api
+                .referrer_dapp_collected(&dapp, code)?
+                .map_or(0, NonZeroU128::get);
+
+            if collected > earnings {
+                return Err(InvariantViolation::DappOvercollected {
+                    dapp,
+                    code: code.to_u64(),
+                    collected,
+                    earnings,
+                });
+            }
+
+            code_sum = code_sum.saturating_add(earnings);
+
+            match dapp_sums.iter_mut().find(|(id, _)| id == &dapp) {
+                Some((_, sum)) => *sum = sum.saturating_add(earnings),
+                None => dapp_sums.push((dapp, earnings)),
+            }
+        }
+
+        let total = api.total_earnings(code)?.map_or(0, NonZeroU128::get);
+
+        if total != code_sum {
+            return Err(InvariantViolation::CodeEarnings {
+                code: code.to_u64(),
+                total,
+                sum: code_sum,
+            });
+        }
+
+        let collected = api
+            .referrer_total_collected(code)?
+            .map_or(0, NonZeroU128::get);
+
+        if collected > total {
+            return Err(InvariantViolation::CodeOvercollected {
+                code: code.to_u64(),
+                collected,
+                earnings: total,
+            });
+        }
+    }
+
+    for (dapp, sum) in dapp_sums {
+        let total = api
+            .dapp_contributions(dapp.as_id_ref())?
+            .map_or(0, NonZeroU128::get);
+
+        if total != sum {
+            return Err(InvariantViolation::DappContributions { dapp, total, sum });
+        }
+    }
+
+    Ok(())
+}
@@ -2,7 +2,13 @@ use std::num::NonZeroU128;
 
 use crate::{FallibleApi, Id};
 
-use super::{Command, Error, ReadonlyDappStore, ReadonlyReferralStore, ReferralCode};
+use super::{
+    AuthRequirement, Command, Earner, Error, ReadonlyConfigStore, ReadonlyDappStore,
+    ReadonlyReferralStore, ReferralCode,
+};
+
+/// Commands collecting a referrer's earnings, with the index to continue from if any remain.
+pub type ReferrerAllCollection = (Vec<Command>, Option<u64>);
 
 pub trait ReadonlyStore: FallibleApi {
     /// Gets the total earnings of a referral code.
@@ -32,6 +38,13 @@ pub trait ReadonlyStore: FallibleApi {
     ///
     /// This function will return an error depending on the implementor.
     fn dapp_total_collected(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error>;
+
+    /// Gets the total earnings collected by all referrers of a dapp.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn dapp_referrers_collected(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error>;
 }
 
 pub trait MutableStore: FallibleApi {
@@ -79,44 +92,132 @@ pub trait Query: FallibleApi {
     fn dapp_total_rewards(&self, pot: &Id) -> Result<Option<NonZeroU128>, Self::Error>;
 }
 
-/// Collect a referrers earnings for a specific dApp.
+/// Default number of dApps processed by a single [`referrer_all`] call.
+pub const DEFAULT_COLLECT_ALL_LIMIT: u64 = 10;
+
+/// Maximum number of dApps processed by a single [`referrer_all`] call.
+pub const MAX_COLLECT_ALL_LIMIT: u64 = 30;
+
+/// Earnings owed to a referrer or dApp, alongside those already collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Owed {
+    /// Earnings yet to be collected
+    pub owed: Option<NonZeroU128>,
+    /// Earnings collected so far
+    pub already_collected: Option<NonZeroU128>,
+}
+
+impl Owed {
+    /// The total earnings, collected or not.
+    fn total(self) -> Option<NonZeroU128> {
+        match (self.owed, self.already_collected) {
+            (Some(owed), Some(collected)) => owed.checked_add(collected.get()),
+            (owed, collected) => owed.or(collected),
+        }
+    }
+}
+
+/// A referrers earnings for a specific dApp, without marking them collected.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// - The referral code is not registered.
-/// - The sender is not the owner of the referral code.
-/// - There are no earnings to collect.
 /// - There is an API error.
-pub fn referrer<Api>(
-    api: &mut Api,
-    sender: Id,
+pub fn referrer_owed<Api>(
+    api: &Api,
     dapp: &Id,
     code: ReferralCode,
-) -> Result<Command, Error<Api::Error>>
+) -> Result<Owed, Error<Api::Error>>
 where
-    Api: ReadonlyStore + MutableStore + Query + ReadonlyReferralStore + ReadonlyDappStore,
+    Api: ReadonlyStore + ReadonlyReferralStore,
 {
-    let Some(referrer_owner) = api.owner_of(code)? else {
-        return Err(Error::ReferralCodeNotRegistered);
+    let already_collected = api.referrer_dapp_collected(dapp, code)?;
+
+    let owed = api
+        .dapp_earnings(dapp.as_id_ref(), code)?
+        .and_then(|earnings| {
+            earnings
+                .get()
+                .checked_sub(already_collected.map_or(0, NonZeroU128::get))
+        })
+        .and_then(NonZeroU128::new);
+
+    Ok(Owed {
+        owed,
+        already_collected,
+    })
+}
+
+/// A dApp's remaining rewards, without marking them collected.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - There is an API error.
+pub fn dapp_owed<Api>(api: &Api, dapp: &Id) -> Result<Owed, Error<Api::Error>>
+where
+    Api: ReadonlyStore + Query + ReadonlyReferralStore + ReadonlyDappStore,
+{
+    let already_collected = api.dapp_total_collected(dapp)?;
+
+    let pot = api.rewards_pot(dapp)?;
+
+    let Some(total_rewards) = api.dapp_total_rewards(&pot)? else {
+        return Ok(Owed {
+            owed: None,
+            already_collected,
+        });
     };
 
-    if sender != referrer_owner {
-        return Err(Error::Unauthorized);
+    // tracking only dApps contribute nothing, leaving all their rewards owed
+    let contributions = api
+        .dapp_contributions(dapp.as_id_ref())?
+        .map_or(0, NonZeroU128::get);
+
+    // rewards not yet covering the contributions or already collected leave nothing owed
+    let owed = total_rewards
+        .get()
+        .checked_sub(contributions)
+        .and_then(|remaining| remaining.checked_sub(already_collected.map_or(0, NonZeroU128::get)))
+        .and_then(NonZeroU128::new);
+
+    Ok(Owed {
+        owed,
+        already_collected,
+    })
+}
+
+/// Reject paying out less than the `minimum` collect amount, if any.
+fn check_minimum<E>(owed: NonZeroU128, minimum: Option<NonZeroU128>) -> Result<(), Error<E>> {
+    match minimum {
+        Some(minimum) if owed < minimum => Err(Error::BelowMinimumCollect {
+            amount: owed,
+            minimum,
+        }),
+        _ => Ok(()),
     }
+}
+
+/// Mark a referrers earnings for a specific dApp as collected, returning the amount owed if any.
+/// Earnings below the `minimum` are rejected rather than collected.
+fn collect_referrer_owed<Api>(
+    api: &mut Api,
+    dapp: &Id,
+    code: ReferralCode,
+    minimum: Option<NonZeroU128>,
+) -> Result<Option<NonZeroU128>, Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + ReadonlyReferralStore,
+{
+    let earnings = referrer_owed(api, dapp, code)?;
 
-    let Some(dapp_earnings) = api.dapp_earnings(dapp, code)? else {
-        return Err(Error::NothingToCollect);
+    let Some(owed) = earnings.owed else {
+        return Ok(None);
     };
 
-    let already_collected = api.referrer_dapp_collected(dapp, code)?;
+    check_minimum(owed, minimum)?;
 
-    let Some(owed) = already_collected
-        .and_then(|collected| NonZeroU128::new(dapp_earnings.get() - collected.get()))
-        .or_else(|| already_collected.is_none().then_some(dapp_earnings))
-    else {
-        return Err(Error::NothingToCollect);
-    };
+    let dapp_earnings = earnings.total().ok_or(Error::Overflow)?;
 
     let total_collected = match api.referrer_total_collected(code)? {
         Some(total) => total.checked_add(owed.get()).ok_or(Error::Overflow)?,
@@ -127,60 +228,222 @@ where
 
     api.set_referrer_dapp_collected(dapp, code, dapp_earnings)?;
 
+    debug!(
+        owed = owed.get(),
+        total_collected = total_collected.get(),
+        "collected referrer rewards"
+    );
+
+    Ok(Some(owed))
+}
+
+/// Collect a referrers earnings for a specific dApp, paid to `recipient` if given otherwise the sender.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The referral code is not registered.
+/// - The sender is not the owner of the referral code.
+/// - The referral code is blocked.
+/// - There are no earnings to collect.
+/// - The earnings are below the hub's minimum collect amount.
+/// - There is an API error.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(api), err)
+)]
+pub fn referrer<Api>(
+    api: &mut Api,
+    sender: Id,
+    dapp: &Id,
+    code: ReferralCode,
+    recipient: Option<Id>,
+) -> Result<Command, Error<Api::Error>>
+where
+    Api: ReadonlyStore
+        + MutableStore
+        + Query
+        + ReadonlyReferralStore
+        + ReadonlyDappStore
+        + ReadonlyConfigStore,
+{
+    let Some(referrer_owner) = api.owner_of(code)? else {
+        return Err(Error::ReferralCodeNotRegistered(code));
+    };
+
+    if sender != referrer_owner {
+        return Err(Error::Unauthorized {
+            sender,
+            required: AuthRequirement::CodeOwner(code),
+        });
+    }
+
+    if api.code_blocked(code)? {
+        return Err(Error::CodeBlocked(code));
+    }
+
+    let minimum = api.min_collect_amount()?;
+
+    let Some(owed) = collect_referrer_owed(api, dapp, code, minimum)? else {
+        return Err(Error::NothingToCollect(Earner::Referrer {
+            code,
+            dapp: Some(dapp.clone()),
+        }));
+    };
+
     let pot = api.rewards_pot(dapp)?;
 
     Ok(Command::RedistributeRewards {
         amount: owed,
         pot,
-        receiver: sender,
+        receiver: recipient.unwrap_or(sender),
     })
 }
 
-/// Collect a dApp's remaining rewards.
+/// Collect a referrers earnings from every dApp it has earned with, starting from the `start` index.
+/// At most `limit` dApps are processed, returning the index to continue from if any remain.
+/// Batching is how small earnings are collected economically, so the minimum collect amount
+/// does not apply.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The referral code is not registered.
+/// - The sender is not the owner of the referral code.
+/// - The referral code is blocked.
+/// - There are no earnings to collect.
+/// - Calculated totals overflow 128-bits.
+/// - There is an API error.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(api), err)
+)]
+pub fn referrer_all<Api>(
+    api: &mut Api,
+    sender: &Id,
+    code: ReferralCode,
+    start: Option<u64>,
+    limit: Option<u64>,
+) -> Result<ReferrerAllCollection, Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + ReadonlyReferralStore + ReadonlyDappStore,
+{
+    let Some(referrer_owner) = api.owner_of(code)? else {
+        return Err(Error::ReferralCodeNotRegistered(code));
+    };
+
+    if sender != &referrer_owner {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::CodeOwner(code),
+        });
+    }
+
+    if api.code_blocked(code)? {
+        return Err(Error::CodeBlocked(code));
+    }
+
+    let count = api.code_dapp_count(code)?;
+
+    let start = start.unwrap_or_default();
+
+    let end = limit
+        .unwrap_or(DEFAULT_COLLECT_ALL_LIMIT)
+        .min(MAX_COLLECT_ALL_LIMIT)
+        .saturating_add(start)
+        .min(count);
+
+    let mut cmds = vec![];
+
+    for idx in start..end {
+        let Some(dapp) = api.code_dapp(code, idx)? else {
+            continue;
+        };
+
+        let Some(owed) = collect_referrer_owed(api, &dapp, code, None)? else {
+            continue;
+        };
+
+        let pot = api.rewards_pot(&dapp)?;
+
+        cmds.push(Command::WithdrawPending(pot.clone()));
+
+        cmds.push(Command::RedistributeRewards {
+            amount: owed,
+            pot,
+            receiver: sender.clone(),
+        });
+    }
+
+    let next = (end < count).then_some(end);
+
+    if cmds.is_empty() && next.is_none() {
+        return Err(Error::NothingToCollect(Earner::Referrer {
+            code,
+            dapp: None,
+        }));
+    }
+
+    Ok((cmds, next))
+}
+
+/// Collect a dApp's remaining rewards, paid to `recipient` if given otherwise the sender.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// - The sender is not either the dApp or it's nominated collector.
 /// - There are no rewards to collect.
+/// - The rewards are below the hub's minimum collect amount.
 /// - There is an API error.
-pub fn dapp<Api>(api: &mut Api, sender: Id, dapp: &Id) -> Result<Command, Error<Api::Error>>
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(api), err)
+)]
+pub fn dapp<Api>(
+    api: &mut Api,
+    sender: Id,
+    dapp: &Id,
+    recipient: Option<Id>,
+) -> Result<Command, Error<Api::Error>>
 where
-    Api: ReadonlyStore + MutableStore + Query + ReadonlyReferralStore + ReadonlyDappStore,
+    Api: ReadonlyStore
+        + MutableStore
+        + Query
+        + ReadonlyReferralStore
+        + ReadonlyDappStore
+        + ReadonlyConfigStore,
 {
     if &sender != dapp && sender != api.collector(dapp)? {
-        return Err(Error::Unauthorized);
+        return Err(Error::Unauthorized {
+            sender,
+            required: AuthRequirement::DappOrCollector(dapp.clone()),
+        });
     }
 
-    let pot = api.rewards_pot(dapp)?;
-
-    let Some(total_rewards) = api.dapp_total_rewards(&pot)? else {
-        return Err(Error::NothingToCollect);
-    };
+    let earnings = dapp_owed(api, dapp)?;
 
-    let Some(total_remaining) = api
-        .dapp_contributions(dapp)?
-        .and_then(|contributions| NonZeroU128::new(total_rewards.get() - contributions.get()))
-    else {
-        return Err(Error::NothingToCollect);
+    let Some(owed) = earnings.owed else {
+        return Err(Error::NothingToCollect(Earner::Dapp(dapp.clone())));
     };
 
-    let already_collected = api.dapp_total_collected(dapp)?;
+    check_minimum(owed, api.min_collect_amount()?)?;
 
-    let Some(owed) = already_collected
-        .and_then(|collected| NonZeroU128::new(total_remaining.get() - collected.get()))
-        .or_else(|| already_collected.is_none().then_some(total_remaining))
-    else {
-        return Err(Error::NothingToCollect);
-    };
+    let total_remaining = earnings.total().ok_or(Error::Overflow)?;
 
     api.set_dapp_total_collected(dapp, total_remaining)?;
 
     let pot = api.rewards_pot(dapp)?;
 
+    debug!(
+        owed = owed.get(),
+        total_remaining = total_remaining.get(),
+        "collected dApp rewards"
+    );
+
     Ok(Command::RedistributeRewards {
         amount: owed,
         pot,
-        receiver: sender,
+        receiver: recipient.unwrap_or(sender),
     })
 }
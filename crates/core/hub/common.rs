@@ -20,6 +20,25 @@ impl NonZeroPercent {
         self.0
     }
 
+    /// The percent left for the dApp once referrers are given their share
+    #[must_use]
+    pub const fn dapp_share(self) -> u8 {
+        100 - self.0
+    }
+
+    /// Check a percent leaving the dApp no share of rewards has been confirmed as intended.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the dApp has no share & `confirmed` is false.
+    pub const fn check_dapp_share(self, confirmed: bool) -> Result<Self, UnconfirmedNoDappShare> {
+        if self.dapp_share() == 0 && !confirmed {
+            return Err(UnconfirmedNoDappShare);
+        }
+
+        Ok(self)
+    }
+
     /// Apply the percentage to a give amount, will return `None` if an overflow occurs
     #[must_use]
     pub fn checked_apply_to(self, amount: NonZeroU128) -> Option<Option<NonZeroU128>> {
@@ -29,6 +48,24 @@ impl NonZeroPercent {
     }
 }
 
+/// A percent outside of the range 1 - 100, shared by every layer validating a percent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid percent - valid value is any integer between 1 & 100")]
+pub struct InvalidPercent;
+
+impl TryFrom<u8> for NonZeroPercent {
+    type Error = InvalidPercent;
+
+    fn try_from(percent: u8) -> Result<Self, Self::Error> {
+        Self::new(percent).ok_or(InvalidPercent)
+    }
+}
+
+/// A percent of 100 given without confirming the dApp is to keep no share of it's rewards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid percent - 100 leaves the dApp no share of rewards, set `confirm_no_dapp_share` to confirm")]
+pub struct UnconfirmedNoDappShare;
+
 impl From<NonZeroPercent> for NonZeroU128 {
     fn from(value: NonZeroPercent) -> Self {
         // safe due to checks on NonZeroPercent creation
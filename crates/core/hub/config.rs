@@ -0,0 +1,230 @@
+use std::num::NonZeroU128;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FallibleApi, Id};
+
+use super::{AuthRequirement, Configure, DappExternalQuery, Error, Kind, ReadonlyDappStore};
+
+/// The message kinds a hub accepts, as a bitflag mask.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features(u8);
+
+impl Features {
+    pub const NONE: Self = Self(0);
+    pub const REGISTER: Self = Self(1);
+    pub const RECORD: Self = Self(1 << 1);
+    pub const COLLECT: Self = Self(1 << 2);
+    pub const CONFIGURE: Self = Self(1 << 3);
+    pub const ALL: Self = Self(0b1111);
+
+    /// Create a mask from raw bits, any unknown bits are dropped.
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Enable or disable the features of `other`, leaving the rest as they are.
+    #[must_use]
+    pub const fn set(self, other: Self, enabled: bool) -> Self {
+        if enabled {
+            Self(self.0 | other.0)
+        } else {
+            Self(self.0 & !other.0)
+        }
+    }
+
+    /// The feature required to handle a message kind along with it's name, if any.
+    /// Setting the enabled features is always allowed, so a disabled hub can be re-enabled.
+    fn required_by(kind: &Kind) -> Option<(Self, &'static str)> {
+        match kind {
+            Kind::Register(_) => Some((Self::REGISTER, "register")),
+            Kind::Referral { .. } => Some((Self::RECORD, "record")),
+            Kind::Collect(_) => Some((Self::COLLECT, "collect")),
+            Kind::Config(Configure::EnabledFeatures { .. }) => None,
+            Kind::Config(_) => Some((Self::CONFIGURE, "configure")),
+        }
+    }
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+pub trait ReadonlyStore: FallibleApi {
+    /// Gets the message kinds the hub accepts, every kind if never set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn enabled_features(&self) -> Result<Features, Self::Error>;
+
+    /// Gets the smallest amount a single collection may pay out, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn min_collect_amount(&self) -> Result<Option<NonZeroU128>, Self::Error>;
+
+    /// Gets whether the hub may activate itself as a dApp, allowed if never set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn self_dapp_allowed(&self) -> Result<bool, Self::Error>;
+}
+
+pub trait MutableStore: FallibleApi {
+    /// Sets the message kinds the hub accepts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_enabled_features(&mut self, features: Features) -> Result<(), Self::Error>;
+
+    /// Sets the smallest amount a single collection may pay out, `None` removing the minimum.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_min_collect_amount(&mut self, amount: Option<NonZeroU128>) -> Result<(), Self::Error>;
+
+    /// Sets whether the hub may activate itself as a dApp.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_self_dapp_allowed(&mut self, allowed: bool) -> Result<(), Self::Error>;
+}
+
+/// Check the hub accepts the given message kind.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The feature required by the message kind is disabled.
+/// - There is an API error.
+pub fn check_enabled<Api>(api: &Api, kind: &Kind) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore,
+{
+    let Some((feature, name)) = Features::required_by(kind) else {
+        return Ok(());
+    };
+
+    if !api.enabled_features()?.contains(feature) {
+        return Err(Error::FeatureDisabled(name));
+    }
+
+    Ok(())
+}
+
+/// Set the message kinds the hub accepts, an action available to the hub and it's collector.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not either the hub or it's collector.
+/// - There is an API error.
+pub fn set_enabled_features<Api>(
+    api: &mut Api,
+    sender: &Id,
+    features: Features,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: MutableStore + DappExternalQuery + ReadonlyDappStore,
+{
+    let hub = api.self_id()?;
+
+    if sender != &hub && sender != &api.collector(&hub)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::HubOrCollector,
+        });
+    }
+
+    api.set_enabled_features(features)?;
+
+    debug!(features = features.bits(), "set enabled features");
+
+    Ok(())
+}
+
+/// Set the smallest amount a single collection may pay out, an action available to the hub and
+/// it's collector. Collections paying out less are rejected, leaving the earnings to accrue.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not either the hub or it's collector.
+/// - There is an API error.
+pub fn set_min_collect_amount<Api>(
+    api: &mut Api,
+    sender: &Id,
+    amount: Option<NonZeroU128>,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: MutableStore + DappExternalQuery + ReadonlyDappStore,
+{
+    let hub = api.self_id()?;
+
+    if sender != &hub && sender != &api.collector(&hub)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::HubOrCollector,
+        });
+    }
+
+    api.set_min_collect_amount(amount)?;
+
+    debug!(
+        amount = amount.map_or(0, NonZeroU128::get),
+        "set min collect amount"
+    );
+
+    Ok(())
+}
+
+/// Set whether the hub may activate itself as a dApp, an action available to the hub and it's
+/// collector. Deployments not expecting the hub to earn from it's own fees can disallow it.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not either the hub or it's collector.
+/// - There is an API error.
+pub fn set_self_dapp_allowed<Api>(
+    api: &mut Api,
+    sender: &Id,
+    allowed: bool,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: MutableStore + DappExternalQuery + ReadonlyDappStore,
+{
+    let hub = api.self_id()?;
+
+    if sender != &hub && sender != &api.collector(&hub)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::HubOrCollector,
+        });
+    }
+
+    api.set_self_dapp_allowed(allowed)?;
+
+    debug!(allowed, "set self dapp allowed");
+
+    Ok(())
+}
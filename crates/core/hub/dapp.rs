@@ -1,16 +1,75 @@
-use std::num::NonZeroU128;
+use std::num::{NonZeroU128, NonZeroU64};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{FallibleApi, Id};
 
-use super::{Command, Error, NonZeroPercent};
+use super::{
+    AuthRequirement, Command, Error, NonZeroPercent, ReadonlyCollectStore, ReadonlyConfigStore,
+    ReadonlyReferralStore, Reply,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Metadata {
+    pub name: Option<String>,
     pub percent: Option<NonZeroPercent>,
     pub collector: Option<Id>,
-    pub repo_url: Option<String>,
+    pub repo_url: Option<RepoUrl>,
+    pub allow_self_referral: Option<bool>,
+    pub max_invocations_per_code: Option<NonZeroU64>,
+}
+
+/// A change to a dApp's repository url.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RepoUrl {
+    Set(String),
+    Clear,
+}
+
+/// How a dApp is de-activated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeactivationMode {
+    /// Keep the dApp's data, rejecting new referrals while earnings & rewards can still be collected
+    #[default]
+    Freeze,
+    /// Remove the dApp, only once it's referrers have collected all their earnings
+    Purge,
+}
+
+/// A bonus multiplier applied to referrer shares between two block heights, inclusive.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Campaign {
+    pub multiplier_num: NonZeroU64,
+    pub multiplier_den: NonZeroU64,
+    pub start_height: u64,
+    pub end_height: u64,
+}
+
+impl Campaign {
+    /// Whether the campaign is running at the given block `height`.
+    #[must_use]
+    pub fn is_active_at(&self, height: u64) -> bool {
+        (self.start_height..=self.end_height).contains(&height)
+    }
+
+    /// Apply the multiplier to a referrer `share`, never exceeding `cap`.
+    /// Returns `None` if the multiplied share rounds down to zero.
+    #[must_use]
+    pub fn apply_to(&self, share: NonZeroU128, cap: NonZeroU128) -> Option<NonZeroU128> {
+        let num = u128::from(self.multiplier_num.get());
+        let den = u128::from(self.multiplier_den.get());
+
+        // the remainder is below a 64-bit denominator, so only the quotient product can overflow
+        let Some(boosted) = (share.get() / den)
+            .checked_mul(num)
+            .and_then(|quot| quot.checked_add(share.get() % den * num / den))
+        else {
+            // a share overflowing 128-bits is beyond any fee
+            return Some(cap);
+        };
+
+        NonZeroU128::new(boosted.min(cap.get()))
+    }
 }
 
 pub trait ReadonlyStore: FallibleApi {
@@ -21,12 +80,12 @@ pub trait ReadonlyStore: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn dapp_exists(&self, id: &Id) -> Result<bool, Self::Error>;
 
-    /// Gets the percentage of a dApp's fee to give to the referrer
+    /// Gets the percentage of a dApp's fee to give to the referrer, `None` for tracking only dApps
     ///
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
-    fn percent(&self, id: &Id) -> Result<NonZeroPercent, Self::Error>;
+    fn percent(&self, id: &Id) -> Result<Option<NonZeroPercent>, Self::Error>;
 
     /// Gets a dApp's rewards collector Id
     ///
@@ -48,17 +107,66 @@ pub trait ReadonlyStore: FallibleApi {
     ///
     /// This function will return an error depending on the implementor.
     fn rewards_pot(&self, id: &Id) -> Result<Id, Self::Error>;
+
+    /// Checks if the dApp with the given id allows referrals by itself or it's collector
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn self_referral_allowed(&self, id: &Id) -> Result<bool, Self::Error>;
+
+    /// Gets a dApp's nominated rewards collector Id, if any
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn pending_collector(&self, id: &Id) -> Result<Option<Id>, Self::Error>;
+
+    /// Gets a dApp's bonus campaign, if one has been set
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn campaign(&self, id: &Id) -> Result<Option<Campaign>, Self::Error>;
+
+    /// Gets the number of referred invocations a single code can earn from with a dApp, if capped
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn max_invocations_per_code(&self, id: &Id) -> Result<Option<NonZeroU64>, Self::Error>;
+
+    /// Checks if a dApp has been frozen by a de-activation
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn dapp_frozen(&self, id: &Id) -> Result<bool, Self::Error>;
+
+    /// Gets the dApp registered with the given name, if any
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn dapp_by_name(&self, name: &str) -> Result<Option<Id>, Self::Error>;
+
+    /// Gets the relayer a dApp has authorised to record referrals on it's behalf, if any
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn relayer(&self, id: &Id) -> Result<Option<Id>, Self::Error>;
 }
 
 pub trait MutableStore: FallibleApi {
-    /// Add a new named dApp
+    /// Add a new named dApp, the name resolving to it with `dapp_by_name`
     ///
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
     fn add_dapp(&mut self, id: &Id, name: String) -> Result<(), Self::Error>;
 
-    ///  Remove an existing dapp, once executed `dapp_exists` will return false.
+    ///  Remove an existing dapp, once executed `dapp_exists` will return false & it's name is freed.
     ///
     /// # Errors
     ///
@@ -72,6 +180,13 @@ pub trait MutableStore: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn set_percent(&mut self, id: &Id, percent: NonZeroPercent) -> Result<(), Self::Error>;
 
+    /// Sets a dApp's name, freeing it's previous name
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_name(&mut self, id: &Id, name: String) -> Result<(), Self::Error>;
+
     /// Sets a dApp's rewards collector Id
     ///
     /// # Errors
@@ -86,12 +201,99 @@ pub trait MutableStore: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn set_repo_url(&mut self, id: &Id, repo_url: String) -> Result<(), Self::Error>;
 
+    /// Removes a dApp's repository url
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn clear_repo_url(&mut self, id: &Id) -> Result<(), Self::Error>;
+
     /// Sets the Id of a dApp's rewards pot
     ///
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
     fn set_rewards_pot(&mut self, id: &Id, rewards_pot: Id) -> Result<(), Self::Error>;
+
+    /// Sets whether a dApp allows referrals by itself or it's collector
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_self_referral_allowed(&mut self, id: &Id, allowed: bool) -> Result<(), Self::Error>;
+
+    /// Sets a dApp's nominated rewards collector Id, pending acceptance
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_pending_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error>;
+
+    /// Removes a dApp's nominated rewards collector Id, once executed `pending_collector` will return `None`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn remove_pending_collector(&mut self, id: &Id) -> Result<(), Self::Error>;
+
+    /// Removes a dApp's referrer percentage.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn remove_percent(&mut self, id: &Id) -> Result<(), Self::Error>;
+
+    /// Removes a dApp's rewards collector Id.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn remove_collector(&mut self, id: &Id) -> Result<(), Self::Error>;
+
+    /// Sets a dApp's bonus campaign, replacing any previous campaign.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_campaign(&mut self, id: &Id, campaign: Campaign) -> Result<(), Self::Error>;
+
+    /// Sets the number of referred invocations a single code can earn from with a dApp
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_max_invocations_per_code(&mut self, id: &Id, max: NonZeroU64)
+        -> Result<(), Self::Error>;
+
+    /// Freeze or unfreeze a dApp, a frozen dApp is no longer counted as active
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_dapp_frozen(&mut self, id: &Id, frozen: bool) -> Result<(), Self::Error>;
+
+    /// Sets the relayer authorised to record referrals on a dApp's behalf, replacing any previous relayer
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_relayer(&mut self, id: &Id, relayer: Id) -> Result<(), Self::Error>;
+
+    /// Removes a dApp's relayer, once executed `relayer` will return `None`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn remove_relayer(&mut self, id: &Id) -> Result<(), Self::Error>;
+
+    /// Clears the referral counts, referrer earnings, contributions & collections left over from a dApp's previous activation.
+    /// Everything the dApp's rewards pot paid out before is kept as collected by the dApp, as the pot is reused.
+    /// Does nothing for a dApp that has never been activated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn reset_dapp_accounting(&mut self, id: &Id) -> Result<(), Self::Error>;
 }
 
 pub trait ExternalQuery: FallibleApi {
@@ -116,20 +318,45 @@ pub trait ExternalQuery: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn rewards_pot_admin(&self, id: &Id) -> Result<Id, Self::Error>;
 
+    /// Returns the dApp the given reward pot Id was created for.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn rewards_pot_dapp(&self, id: &Id) -> Result<Id, Self::Error>;
+
     /// Returns the current fee set by the dApp, if any.
     ///
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
     fn current_fee(&self, id: &Id) -> Result<Option<NonZeroU128>, Self::Error>;
+
+    /// Returns the current block height.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn current_height(&self) -> Result<u64, Self::Error>;
+
+    /// Returns the current block time, in seconds since the UNIX epoch.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn current_time(&self) -> Result<u64, Self::Error>;
 }
 
 /// Activate a dApp within the system, setting at least the initial percent & collector.
+/// When a fee is given it is set alongside the rewards pot creation.
+/// A purged dApp activating again takes up it's previous rewards pot.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// - The dApp is already registered.
+/// - The name is taken by another dApp.
+/// - The dApp is the hub itself, while the hub may not activate itself.
 /// - The dApp does not have the referral program set as rewards receiver.
 /// - There is an API error.
 pub fn activate<Api>(
@@ -138,24 +365,93 @@ pub fn activate<Api>(
     name: String,
     percent: NonZeroPercent,
     collector: Id,
+    fee: Option<NonZeroU128>,
+) -> Result<Reply, Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + ExternalQuery + ReadonlyConfigStore,
+{
+    let rewards_pot = activate_with(api, sender.clone(), name, Some(percent), collector)?;
+
+    let Some(amount) = fee else {
+        return Ok(Reply::from(rewards_pot));
+    };
+
+    Ok(Reply::from([
+        rewards_pot,
+        Command::SetDappFee {
+            dapp: sender,
+            amount,
+        },
+    ]))
+}
+
+/// Activate a dApp within the system that only tracks referred invocations, sharing no fees with referrers.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The dApp is already registered.
+/// - The name is taken by another dApp.
+/// - The dApp is the hub itself, while the hub may not activate itself.
+/// - The dApp does not have the referral program set as rewards receiver.
+/// - There is an API error.
+pub fn activate_tracking_only<Api>(
+    api: &mut Api,
+    sender: Id,
+    name: String,
+    collector: Id,
 ) -> Result<Command, Error<Api::Error>>
 where
-    Api: ReadonlyStore + MutableStore + ExternalQuery,
+    Api: ReadonlyStore + MutableStore + ExternalQuery + ReadonlyConfigStore,
 {
+    activate_with(api, sender, name, None, collector)
+}
+
+fn activate_with<Api>(
+    api: &mut Api,
+    sender: Id,
+    name: String,
+    percent: Option<NonZeroPercent>,
+    collector: Id,
+) -> Result<Command, Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + ExternalQuery + ReadonlyConfigStore,
+{
+    if sender == api.self_id()? && !api.self_dapp_allowed()? {
+        return Err(Error::SelfRegistration);
+    }
+
     if api.dapp_exists(&sender)? {
         return Err(Error::AlreadyRegistered);
     }
 
+    check_name(api, &sender, &name)?;
+
     if api.self_id()? != api.rewards_admin(&sender)? {
         return Err(Error::InvalidRewardsAdmin);
     }
 
+    // referrers must not inherit earnings from a previous activation of the same dApp
+    api.reset_dapp_accounting(&sender)?;
+
     api.add_dapp(&sender, name)?;
 
-    api.set_percent(&sender, percent)?;
+    if let Some(percent) = percent {
+        api.set_percent(&sender, percent)?;
+    }
 
     api.set_collector(&sender, collector)?;
 
+    // a re-activated dApp gets it's previous rewards pot back, the pot address is fixed per dApp
+    if api.has_rewards_pot(&sender)? {
+        let recipient = api.rewards_pot(&sender)?;
+
+        return Ok(Command::SetRewardsRecipient {
+            dapp: sender,
+            recipient,
+        });
+    }
+
     Ok(Command::CreateRewardsPot(sender))
 }
 
@@ -167,6 +463,7 @@ where
 /// - The dApp is not registered.
 /// - There is already a rewards pot set for the dApp
 /// - Self ID is not the admin of the rewards pot
+/// - The rewards pot was created for a different dApp
 /// - There is an API error.
 pub fn set_rewards_pot<Api>(
     api: &mut Api,
@@ -177,7 +474,7 @@ where
     Api: ReadonlyStore + MutableStore + ExternalQuery,
 {
     if !api.dapp_exists(&dapp)? {
-        return Err(Error::DappNotActivated);
+        return Err(Error::DappNotActivated(dapp));
     }
 
     if api.has_rewards_pot(&dapp)? {
@@ -188,6 +485,10 @@ where
         return Err(Error::InvalidRewardsPotAdmin);
     }
 
+    if api.rewards_pot_dapp(&rewards_pot)? != dapp {
+        return Err(Error::RewardsPotMismatch);
+    }
+
     api.set_rewards_pot(&dapp, rewards_pot.clone())?;
 
     Ok(Command::SetRewardsRecipient {
@@ -196,13 +497,60 @@ where
     })
 }
 
+/// Undo the activation of a dApp whose rewards pot could not be created, allowing it to activate again.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not the referral system itself.
+/// - The dApp is not activated.
+/// - There is already a rewards pot set for the dApp.
+/// - There is an API error.
+pub fn rollback_activation<Api>(
+    api: &mut Api,
+    sender: &Id,
+    dapp: &Id,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + ExternalQuery,
+{
+    if sender != &api.self_id()? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::Hub,
+        });
+    }
+
+    if !api.dapp_exists(dapp)? {
+        return Err(Error::DappNotActivated(dapp.clone()));
+    }
+
+    if api.has_rewards_pot(dapp)? {
+        return Err(Error::RewardsPotAlreadySet);
+    }
+
+    api.remove_dapp(dapp)?;
+
+    api.remove_percent(dapp)?;
+
+    api.remove_collector(dapp)?;
+
+    Ok(())
+}
+
 /// De-activate a dApp in the system, collecting any outstanding rewards before relinquishing reward admin rights.
 ///
+/// A frozen dApp keeps all of it's data, no longer takes referrals & may still be collected from.
+/// A purged dApp is removed, which requires every referrer to have collected their earnings first.
+/// Purging a frozen dApp only removes it, as the rewards admin rights were relinquished when frozen.
+///
 /// # Errors
 ///
 /// This function will return an error if:
 /// - The dApp is not activated.
 /// - The sender is not either the dApp or it's collector.
+/// - Freezing a dApp that is already frozen.
+/// - Purging a dApp with earnings yet to be collected by it's referrers.
 /// - There is an API error.
 pub fn deactivate<Api>(
     api: &mut Api,
@@ -210,42 +558,127 @@ pub fn deactivate<Api>(
     dapp: Id,
     rewards_admin: Id,
     rewards_recipient: Id,
-) -> Result<[Command; 3], Error<Api::Error>>
+    mode: DeactivationMode,
+) -> Result<Reply, Error<Api::Error>>
 where
-    Api: ReadonlyStore + MutableStore + ExternalQuery,
+    Api:
+        ReadonlyStore + MutableStore + ExternalQuery + ReadonlyReferralStore + ReadonlyCollectStore,
 {
     if !api.dapp_exists(&dapp)? {
-        return Err(Error::DappNotActivated);
+        return Err(Error::DappNotActivated(dapp));
     }
 
     if sender != &dapp && sender != &api.collector(&dapp)? {
-        return Err(Error::Unauthorized);
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::DappOrCollector(dapp),
+        });
     }
 
-    api.remove_dapp(&dapp)?;
+    let frozen = api.dapp_frozen(&dapp)?;
+
+    match mode {
+        DeactivationMode::Freeze => {
+            if frozen {
+                return Err(Error::DappDeactivated(dapp));
+            }
+
+            api.set_dapp_frozen(&dapp, true)?;
+        }
+        DeactivationMode::Purge => {
+            let contributions = api
+                .dapp_contributions(dapp.as_id_ref())?
+                .map_or(0, NonZeroU128::get);
+
+            let collected = api
+                .dapp_referrers_collected(&dapp)?
+                .map_or(0, NonZeroU128::get);
+
+            if contributions > collected {
+                return Err(Error::UncollectedEarnings(dapp));
+            }
+
+            api.set_dapp_frozen(&dapp, false)?;
+
+            api.remove_relayer(&dapp)?;
+
+            api.remove_dapp(&dapp)?;
+
+            if frozen {
+                return Ok(Reply::Empty);
+            }
+        }
+    }
 
     let pot = api.rewards_pot(&dapp)?;
 
-    Ok([
-        Command::WithdrawPending(pot),
-        Command::SetRewardsRecipient {
-            dapp: dapp.clone(),
-            recipient: rewards_recipient,
-        },
-        Command::SetRewardsAdmin {
-            dapp,
-            admin: rewards_admin,
-        },
-    ])
+    // rewards admin rights must only be relinquished once the withdrawal has settled
+    Ok(Reply::from(Command::WithdrawPendingThen {
+        pot,
+        then: vec![
+            Command::SetRewardsRecipient {
+                dapp: dapp.clone(),
+                recipient: rewards_recipient,
+            },
+            Command::SetRewardsAdmin {
+                dapp,
+                admin: rewards_admin,
+            },
+        ],
+    }))
+}
+
+/// Hand the rewards pots of the given dApps over to a new admin, an action available to the hub and it's collector.
+/// Used when the hub is redeployed, so the new hub can continue to distribute the pots' rewards.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not either the hub or it's collector.
+/// - A dApp does not have a rewards pot.
+/// - There is an API error.
+pub fn handover_pots<Api>(
+    api: &Api,
+    sender: &Id,
+    dapps: Vec<Id>,
+    new_admin: &Id,
+) -> Result<Vec<Command>, Error<Api::Error>>
+where
+    Api: ReadonlyStore + ExternalQuery,
+{
+    let hub = api.self_id()?;
+
+    if sender != &hub && sender != &api.collector(&hub)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::HubOrCollector,
+        });
+    }
+
+    dapps
+        .into_iter()
+        .map(|dapp| {
+            if !api.has_rewards_pot(&dapp)? {
+                return Err(Error::RewardsPotNotSet(dapp));
+            }
+
+            Ok(Command::UpdateRewardsPotAdmin {
+                pot: api.rewards_pot(&dapp)?,
+                admin: new_admin.clone(),
+            })
+        })
+        .collect()
 }
 
 /// Configure a dApp's metadata, an action available to the dApp and it's collector.
+/// A new collector is only nominated, it must be accepted by the nominee with `accept_collector`.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// - The dApp is not activated.
 /// - The sender is not either the dApp or it's collector.
+/// - The name is taken by another dApp.
 /// - There is an API error.
 pub fn configure<Api>(
     api: &mut Api,
@@ -255,13 +688,100 @@ pub fn configure<Api>(
 ) -> Result<(), Error<Api::Error>>
 where
     Api: ReadonlyStore + MutableStore,
+{
+    check_configurable(api, sender, dapp)?;
+
+    if let Some(name) = &metadata.name {
+        check_name(api, dapp, name)?;
+    }
+
+    apply_metadata(api, dapp, metadata)
+}
+
+/// Configure the metadata of several dApps at once, each must be configurable by the sender.
+/// Every dApp is checked before any are configured, so a single failure leaves all untouched.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Any of the dApps are not activated.
+/// - The sender is not either the dApp or it's collector, for any of the dApps.
+/// - Any of the names are taken by another dApp, or by more than one dApp of the batch.
+/// - There is an API error.
+pub fn configure_all<Api>(
+    api: &mut Api,
+    sender: &Id,
+    updates: Vec<(Id, Metadata)>,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore,
+{
+    for (dapp, metadata) in &updates {
+        check_configurable(api, sender, dapp)?;
+
+        if let Some(name) = &metadata.name {
+            check_name(api, dapp, name)?;
+        }
+    }
+
+    // two dApps of the batch must not take the same name
+    let mut names: Vec<_> = updates
+        .iter()
+        .filter_map(|(_, metadata)| metadata.name.as_ref())
+        .collect();
+
+    names.sort_unstable();
+
+    if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(Error::NameTaken(pair[0].clone()));
+    }
+
+    for (dapp, metadata) in updates {
+        apply_metadata(api, &dapp, metadata)?;
+    }
+
+    Ok(())
+}
+
+fn check_configurable<Api>(api: &Api, sender: &Id, dapp: &Id) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore,
 {
     if !api.dapp_exists(dapp)? {
-        return Err(Error::DappNotActivated);
+        return Err(Error::DappNotActivated(dapp.clone()));
     }
 
     if sender != dapp && sender != &api.collector(dapp)? {
-        return Err(Error::Unauthorized);
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::DappOrCollector(dapp.clone()),
+        });
+    }
+
+    Ok(())
+}
+
+/// Check the `name` is not taken by a dApp other than `dapp`.
+fn check_name<Api>(api: &Api, dapp: &Id, name: &str) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore,
+{
+    match api.dapp_by_name(name)? {
+        Some(owner) if &owner != dapp => Err(Error::NameTaken(name.to_owned())),
+        _ => Ok(()),
+    }
+}
+
+fn apply_metadata<Api>(
+    api: &mut Api,
+    dapp: &Id,
+    metadata: Metadata,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: MutableStore,
+{
+    if let Some(name) = metadata.name {
+        api.set_name(dapp, name)?;
     }
 
     if let Some(percent) = metadata.percent {
@@ -269,13 +789,53 @@ where
     }
 
     if let Some(collector) = metadata.collector {
-        api.set_collector(dapp, collector)?;
+        api.set_pending_collector(dapp, collector)?;
+    }
+
+    match metadata.repo_url {
+        Some(RepoUrl::Set(repo)) => api.set_repo_url(dapp, repo)?,
+        Some(RepoUrl::Clear) => api.clear_repo_url(dapp)?,
+        None => {}
+    }
+
+    if let Some(allowed) = metadata.allow_self_referral {
+        api.set_self_referral_allowed(dapp, allowed)?;
+    }
+
+    if let Some(max) = metadata.max_invocations_per_code {
+        api.set_max_invocations_per_code(dapp, max)?;
     }
 
-    if let Some(repo) = metadata.repo_url {
-        api.set_repo_url(dapp, repo)?;
+    Ok(())
+}
+
+/// Accept the nomination as a dApp's rewards collector, replacing the current collector.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The dApp is not activated.
+/// - The sender is not the nominated collector.
+/// - There is an API error.
+pub fn accept_collector<Api>(api: &mut Api, sender: Id, dapp: &Id) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore,
+{
+    if !api.dapp_exists(dapp)? {
+        return Err(Error::DappNotActivated(dapp.clone()));
+    }
+
+    if api.pending_collector(dapp)?.as_ref() != Some(&sender) {
+        return Err(Error::Unauthorized {
+            sender,
+            required: AuthRequirement::PendingCollector(dapp.clone()),
+        });
     }
 
+    api.remove_pending_collector(dapp)?;
+
+    api.set_collector(dapp, sender)?;
+
     Ok(())
 }
 
@@ -297,12 +857,76 @@ where
     Api: ReadonlyStore,
 {
     if !api.dapp_exists(&dapp)? {
-        return Err(Error::DappNotActivated);
+        return Err(Error::DappNotActivated(dapp));
     }
 
     if sender != &dapp && sender != &api.collector(&dapp)? {
-        return Err(Error::Unauthorized);
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::DappOrCollector(dapp),
+        });
     }
 
     Ok(Command::SetDappFee { dapp, amount })
 }
+
+/// Set a dApp's bonus campaign, replacing any previous campaign.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The dApp is not activated.
+/// - The sender is not either the dApp or it's collector.
+/// - There is an API error.
+pub fn set_campaign<Api>(
+    api: &mut Api,
+    sender: &Id,
+    dapp: &Id,
+    campaign: Campaign,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore,
+{
+    if !api.dapp_exists(dapp)? {
+        return Err(Error::DappNotActivated(dapp.clone()));
+    }
+
+    if sender != dapp && sender != &api.collector(dapp)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::DappOrCollector(dapp.clone()),
+        });
+    }
+
+    api.set_campaign(dapp, campaign)?;
+
+    Ok(())
+}
+
+/// Authorise a relayer to record referrals on a dApp's behalf, replacing any previous relayer,
+/// or remove the relayer if `None`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The dApp is not activated.
+/// - The sender is not either the dApp or it's collector.
+/// - There is an API error.
+pub fn set_relayer<Api>(
+    api: &mut Api,
+    sender: &Id,
+    dapp: &Id,
+    relayer: Option<Id>,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore,
+{
+    check_configurable(api, sender, dapp)?;
+
+    match relayer {
+        Some(relayer) => api.set_relayer(dapp, relayer)?,
+        None => api.remove_relayer(dapp)?,
+    }
+
+    Ok(())
+}
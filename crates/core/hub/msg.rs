@@ -4,41 +4,68 @@ use serde::{Deserialize, Serialize};
 
 use crate::Id;
 
-use super::{DappMetadata, NonZeroPercent, ReferralCode};
+use super::{DappCampaign, DappMetadata, DeactivationMode, Features, NonZeroPercent, ReferralCode};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Registration {
     /// Register for a referral code
     Referrer,
+    /// Register a referral code on behalf of the given owner
+    ReferrerFor { owner: Id },
     /// Dapp self-activation to take referrals
     ActivateDapp {
         name: String,
         percent: NonZeroPercent,
         collector: Id,
+        fee: Option<NonZeroU128>,
     },
+    /// Activate a dApp that only tracks referred invocations
+    ActivateDappTrackingOnly { name: String, collector: Id },
     /// Set the rewards pot for the given dApp
     RewardsPot { dapp: Id, rewards_pot: Id },
+    /// Rewards pot creation failed for the given dApp, undo it's activation
+    RewardsPotFailed { dapp: Id },
     /// Dapp de-activation to stop taking referrals
     DeactivateDapp {
         dapp: Id,
         rewards_admin: Id,
         rewards_recipient: Id,
+        mode: DeactivationMode,
     },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Collection {
-    /// Collect referrer earnings
-    Referrer { dapp: Id, code: ReferralCode },
-    /// Collect dApp remaining rewards
-    Dapp { dapp: Id },
+    /// Collect referrer earnings, to the given recipient or the sender
+    Referrer {
+        dapp: Id,
+        code: ReferralCode,
+        recipient: Option<Id>,
+    },
+    /// Collect referrer earnings from every dApp, a page at a time
+    ReferrerAll {
+        code: ReferralCode,
+        start: Option<u64>,
+        limit: Option<u64>,
+    },
+    /// Collect dApp remaining rewards, to the given recipient or the sender
+    Dapp { dapp: Id, recipient: Option<Id> },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Configure {
     TransferReferralCodeOwnership { code: ReferralCode, owner: Id },
     DappMetadata { dapp: Id, metadata: DappMetadata },
+    DappsMetadata { updates: Vec<(Id, DappMetadata)> },
+    AcceptCollector { dapp: Id },
     DappFee { dapp: Id, fee: NonZeroU128 },
+    DappCampaign { dapp: Id, campaign: DappCampaign },
+    ReferralRelayer { dapp: Id, relayer: Option<Id> },
+    CodeStatus { code: ReferralCode, blocked: bool },
+    EnabledFeatures { features: Features },
+    MinCollectAmount { amount: Option<NonZeroU128> },
+    AllowSelfDapp { allowed: bool },
+    HandoverPots { dapps: Vec<Id>, new_admin: Id },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -47,6 +74,8 @@ pub enum Kind {
     /// Record a referral code invocation
     Referral {
         code: ReferralCode,
+        /// The dApp to record for when relayed, honoured only for it's relayer
+        dapp: Option<Id>,
     },
     Collect(Collection),
     Config(Configure),
@@ -2,9 +2,10 @@ use std::num::NonZeroU128;
 
 use crate::{FallibleApi, Id};
 
+use super::collect::{self, Owed};
 use super::{
-    CollectQuery, DappExternalQuery, Error, NonZeroPercent, ReadonlyDappStore,
-    ReadonlyReferralStore, ReferralCode,
+    Activity, CollectQuery, DappCampaign, DappExternalQuery, Error, NonZeroPercent,
+    ReadonlyCollectStore, ReadonlyDappStore, ReadonlyReferralStore, ReferralCode,
 };
 
 pub trait Dapps: FallibleApi {
@@ -16,13 +17,21 @@ pub trait Dapps: FallibleApi {
     fn total_dapp_count(&self) -> Result<u64, Self::Error>;
 
     /// All the dApp ids in the order they were first activated.
-    /// Supports optional pagination, by specifying `start` & `limit`.
+    /// Supports optional pagination, by specifying the `start` index & `limit`, the maximum number of
+    /// ids returned. A `start` past the last dApp is an empty page.
     ///
     /// # Errors
     ///
     /// This function will return an error if the implementor encounters an error.
     fn all_dapp_ids(&self, start: Option<u64>, limit: Option<u64>) -> Result<Vec<Id>, Self::Error>;
 
+    /// Get the position of the dApp with the given `id` in `all_dapp_ids`, if it was ever activated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn dapp_index(&self, dapp: &Id) -> Result<Option<u64>, Self::Error>;
+
     /// Get the name of the dApp with given `id`, if it exists.
     ///
     /// # Errors
@@ -50,6 +59,20 @@ pub trait Dapps: FallibleApi {
     ///
     /// This function will return an error if the implementor encounters an error.
     fn dapp_discrete_referrers(&self, dapp: &Id) -> Result<u64, Self::Error>;
+
+    /// Get the block at which a referral was last recorded for the dApp with the given `id`, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn dapp_last_active(&self, dapp: &Id) -> Result<Option<Activity>, Self::Error>;
+
+    /// Get the number of invocations from referrers for the dApp with the given `id` on `day`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn dapp_daily_invocations(&self, dapp: &Id, day: u64) -> Result<u64, Self::Error>;
 }
 
 pub trait Referrers: FallibleApi {
@@ -59,43 +82,282 @@ pub trait Referrers: FallibleApi {
     ///
     /// This function will return an error if the implementor encounters an error.
     fn referral_code(&self, referrer: &Id) -> Result<Option<ReferralCode>, Self::Error>;
+
+    /// Number of invocations of the dApp with the given `id` referred with `code`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn referrer_invocations(&self, dapp: &Id, code: ReferralCode) -> Result<u64, Self::Error>;
+
+    /// The block at which `code` last referred an invocation of the dApp with the given `id`, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn referrer_last_active(
+        &self,
+        dapp: &Id,
+        code: ReferralCode,
+    ) -> Result<Option<Activity>, Self::Error>;
+}
+
+pub trait RewardsPots: FallibleApi {
+    /// The address a rewards pot for the dApp with the given `id` will be created at.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn expected_rewards_pot(&self, dapp: &Id) -> Result<Id, Self::Error>;
+}
+
+pub trait Totals: FallibleApi {
+    /// Number of dApps currently activated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn active_dapp_count(&self) -> Result<u64, Self::Error>;
+
+    /// Total number of referral codes ever issued.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn total_referral_codes(&self) -> Result<u64, Self::Error>;
+
+    /// Total number of referred invocations across all dApps.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn total_invocations(&self) -> Result<u64, Self::Error>;
+
+    /// Total contributions made to referrers across all dApps.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn total_contributions(&self) -> Result<u128, Self::Error>;
+
+    /// Total rewards collected by referrers across all dApps.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the implementor encounters an error.
+    fn total_collected(&self) -> Result<u128, Self::Error>;
 }
 
 pub struct DappInfo {
     pub id: Id,
     pub active: bool,
     pub name: Option<String>,
-    pub percent: NonZeroPercent,
+    pub percent: Option<NonZeroPercent>,
     pub repo_url: Option<String>,
     pub fee: Option<NonZeroU128>,
     pub total_invocations: u64,
     pub discrete_referrers: u64,
     pub total_contributions: u128,
     pub total_rewards: u128,
+    pub last_active: Option<Activity>,
+    pub campaign: Option<DappCampaign>,
+    /// Position in the order dApps were first activated, breaking ties between equal names
+    pub activation_index: u64,
+}
+
+/// A page of [`all_dapps`].
+pub struct DappsPage {
+    pub dapps: Vec<DappInfo>,
+    /// dApps whose info could not be read, e.g. if their rewards pot could not be queried
+    pub errors: Vec<(Id, String)>,
+    /// The last dApp of a full page, to continue from with `after`
+    pub next_key: Option<Id>,
+}
+
+/// Invocations referred on a single day, counted in days since the UNIX epoch.
+pub struct DailyInvocations {
+    pub day: u64,
+    pub invocations: u64,
+}
+
+/// The rewards of a dApp's rewards pot & how they've been distributed.
+pub struct DappRewards {
+    pub rewards_pot: Id,
+    pub total_rewards: u128,
+    pub contributions: u128,
+    pub referrers_collected: u128,
+    pub dapp_collected: u128,
+    /// Rewards held by the pot, yet to be collected by the dApp or it's referrers
+    pub undistributed: u128,
+}
+
+/// Whether an address is a registered dApp & if it is active.
+pub struct DappStatus {
+    pub registered: bool,
+    pub active: bool,
+}
+
+pub struct ReferrerStats {
+    pub invocations: u64,
+    pub earnings: u128,
+    pub last_active: Option<Activity>,
+    pub blocked: bool,
+}
+
+pub struct HubTotals {
+    pub total_dapps: u64,
+    pub active_dapps: u64,
+    pub referral_codes: u64,
+    pub total_invocations: u64,
+    pub total_contributions: u128,
+    pub total_collected: u128,
+}
+
+/// A section of the hub state exported by [`Request::ExportState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportSection {
+    /// Every dApp ever activated, keyed by activation index
+    Dapps,
+    /// Every referral code issued, keyed by code
+    Codes,
+    /// The earnings of each referral code with each dApp it has referred, keyed by code then dApp activation index
+    Earnings,
+    /// The contributions & collections of each dApp, keyed by activation index
+    Collections,
+}
+
+pub struct DappExport {
+    pub id: Id,
+    pub activation_index: u64,
+    pub name: Option<String>,
+    pub percent: Option<NonZeroPercent>,
+    pub collector: Option<Id>,
+    pub rewards_pot: Option<Id>,
+    pub repo_url: Option<String>,
+}
+
+pub struct CodeExport {
+    pub code: ReferralCode,
+    pub owner: Option<Id>,
+    pub total_earnings: u128,
+    pub total_collected: u128,
+    pub blocked: bool,
+}
+
+pub struct EarningsExport {
+    pub code: ReferralCode,
+    pub dapp: Id,
+    pub invocations: u64,
+    pub earnings: u128,
+    pub collected: u128,
+}
+
+pub struct CollectionsExport {
+    pub dapp: Id,
+    pub contributions: u128,
+    pub dapp_collected: u128,
+    pub referrers_collected: u128,
+}
+
+pub enum ExportRecord {
+    Dapp(DappExport),
+    Code(CodeExport),
+    Earnings(EarningsExport),
+    Collections(CollectionsExport),
+}
+
+/// A page of exported hub state.
+pub struct ExportPage {
+    pub records: Vec<ExportRecord>,
+    /// Key of the last record of a full page, to continue from with `start_after`
+    pub next_key: Option<Vec<u8>>,
 }
 
 pub enum Request {
     TotalDappCount,
     Dapp(Id),
+    DappByName(String),
     AllDapps {
         start: Option<u64>,
+        /// Continue from the dApp after this one, taking precedence over `start`
+        after: Option<Id>,
         limit: Option<u64>,
+        only_active: bool,
     },
     ReferralCode(Id),
+    Totals,
+    RewardsPotAddress(Id),
+    OwedToReferrer {
+        code: ReferralCode,
+        dapp: Id,
+    },
+    OwedToDapp(Id),
+    ReferrerStats {
+        code: ReferralCode,
+        dapp: Id,
+    },
+    DappInvocationsOverRange {
+        dapp: Id,
+        from_day: u64,
+        /// Last day of the range, inclusive
+        to_day: u64,
+    },
+    CodeOwner(ReferralCode),
+    DappRewards(Id),
+    IsDapp(Id),
+    ExportState {
+        section: ExportSection,
+        /// Key of the record to continue after, as returned in [`ExportPage::next_key`]
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    },
 }
 
 pub enum Response {
     TotalDappCount(u64),
     Dapp(DappInfo),
-    AllDapps(Vec<DappInfo>),
+    AllDapps {
+        dapps: Vec<DappInfo>,
+        /// dApps left out of the page as their info could not be read, with the reason
+        errors: Vec<(Id, String)>,
+        /// Total number of dApps ever activated, regardless of pagination
+        total: u64,
+        /// The last dApp of a full page, to continue from with `after`
+        next_key: Option<Id>,
+    },
     ReferralCode(Option<ReferralCode>),
+    Totals(HubTotals),
+    RewardsPotAddress(Id),
+    OwedToReferrer(Owed),
+    OwedToDapp(Owed),
+    ReferrerStats(ReferrerStats),
+    DappInvocationsOverRange(Vec<DailyInvocations>),
+    CodeOwner(Option<Id>),
+    DappRewards(DappRewards),
+    IsDapp(DappStatus),
+    ExportState(ExportPage),
 }
 
+/// Maximum number of days covered by a single [`Request::DappInvocationsOverRange`].
+pub const MAX_INVOCATION_DAYS: u64 = 90;
+
+/// Maximum number of records in a single page of [`Request::ExportState`], larger limits are capped.
+pub const MAX_EXPORT_LIMIT: u32 = 100;
+
+/// Maximum number of dApps in a single page of [`Request::AllDapps`], larger or unset limits are
+/// capped, as each dApp's rewards pot is queried.
+pub const MAX_DAPPS_LIMIT: u64 = 30;
+
+/// Maximum number of dApps read for a single page of [`Request::AllDapps`] skipping inactive dApps,
+/// so a run of inactive dApps can't exhaust the gas limit.
+pub const MAX_DAPPS_SCAN: u64 = 100;
+
 /// All the info for the dApp with the given `id`.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
+/// - The dApp has never been activated.
 /// - There is an API error.
 pub fn dapp_info<Api>(api: &Api, id: Id) -> Result<DappInfo, Error<Api::Error>>
 where
@@ -107,13 +369,24 @@ where
     let fee = api.current_fee(&id)?;
     let total_invocations = api.dapp_total_invocations(&id)?;
     let discrete_referrers = api.dapp_discrete_referrers(&id)?;
-    let total_contributions = api.dapp_contributions(&id)?.map_or(0, NonZeroU128::get);
+    let total_contributions = api
+        .dapp_contributions(id.as_id_ref())?
+        .map_or(0, NonZeroU128::get);
     let rewards_pot = api.rewards_pot(&id)?;
     let total_rewards = api
         .dapp_total_rewards(&rewards_pot)?
         .map_or(0, NonZeroU128::get);
+    let last_active = api.dapp_last_active(&id)?;
+    let height = api.current_height()?;
+    let campaign = api
+        .campaign(&id)?
+        .filter(|campaign| campaign.is_active_at(height));
+
+    let activation_index = api
+        .dapp_index(&id)?
+        .ok_or_else(|| Error::DappNotActivated(id.clone()))?;
 
-    let active = name.is_some() && fee.is_some();
+    let active = is_active(api, &id, name.is_some(), fee)?;
 
     Ok(DappInfo {
         id,
@@ -126,30 +399,470 @@ where
         discrete_referrers,
         total_contributions,
         total_rewards,
+        last_active,
+        campaign,
+        activation_index,
+    })
+}
+
+/// Whether the dApp with the given `id` & `fee` is active, which it never is once frozen.
+fn is_active<Api>(
+    api: &Api,
+    id: &Id,
+    registered: bool,
+    fee: Option<NonZeroU128>,
+) -> Result<bool, Error<Api::Error>>
+where
+    Api: ReadonlyDappStore,
+{
+    Ok(registered && fee.is_some() && !api.dapp_frozen(id)?)
+}
+
+/// Whether the given `id` is a registered dApp & if it is active.
+///
+/// # Errors
+///
+/// This function will return an error if there is an API error.
+pub fn dapp_status<Api>(api: &Api, id: &Id) -> Result<DappStatus, Error<Api::Error>>
+where
+    Api: ReadonlyDappStore + DappExternalQuery,
+{
+    if !api.dapp_exists(id)? {
+        return Ok(DappStatus {
+            registered: false,
+            active: false,
+        });
+    }
+
+    let fee = api.current_fee(id)?;
+
+    Ok(DappStatus {
+        registered: true,
+        active: is_active(api, id, true, fee)?,
     })
 }
 
 /// All the dApps in the order they were first activated, respecting the pagination parameters if specified.
+/// When `only_active` is set, inactive dApps are skipped and `limit` applies to the remaining active dApps.
+/// When `after` is set, the dApps following it are returned and `start` is ignored.
+/// `limit` is capped at [`MAX_DAPPS_LIMIT`], so a capped page is followed by a `next_key`.
+/// At most [`MAX_DAPPS_SCAN`] dApps are read for a page, so a page cut short by it is followed by a
+/// `next_key` too.
+/// A dApp whose info can't be read is reported in [`DappsPage::errors`] rather than failing the
+/// page, counting towards `limit` as it's status is unknown.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// - There is an API error.
+/// - The `after` dApp has never been activated.
+/// - There is an API error listing the dApps.
 pub fn all_dapps<Api>(
     api: &Api,
     start: Option<u64>,
+    after: Option<&Id>,
     limit: Option<u64>,
-) -> Result<Vec<DappInfo>, Error<Api::Error>>
+    only_active: bool,
+) -> Result<DappsPage, Error<Api::Error>>
 where
     Api: ReadonlyDappStore + Dapps + DappExternalQuery + ReadonlyReferralStore + CollectQuery,
 {
-    api.all_dapp_ids(start, limit)?
-        .into_iter()
-        .try_fold(Vec::new(), |mut dapps, id| {
-            let dapp = dapp_info(api, id)?;
-            dapps.push(dapp);
-            Ok(dapps)
+    let start = match after {
+        Some(after) => {
+            let index = api.dapp_index(after)?.ok_or(Error::InvalidCursor)?;
+            Some(index + 1)
+        }
+        None => start,
+    };
+
+    let limit = limit.map_or(MAX_DAPPS_LIMIT, |limit| limit.min(MAX_DAPPS_LIMIT));
+
+    // inactive dApps are only known to be skipped once their info is read
+    let scan = if only_active { MAX_DAPPS_SCAN } else { limit };
+
+    let ids = api.all_dapp_ids(start, Some(scan))?;
+
+    let max = usize::try_from(limit).unwrap_or(usize::MAX);
+    let scanned_all = ids.len() < usize::try_from(scan).unwrap_or(usize::MAX);
+
+    let mut page = DappsPage {
+        dapps: Vec::new(),
+        errors: Vec::new(),
+        next_key: None,
+    };
+
+    let mut taken = 0;
+    let mut last = None;
+
+    for id in ids {
+        if taken == max {
+            break;
+        }
+
+        last = Some(id.clone());
+
+        match dapp_info(api, id.clone()) {
+            Ok(dapp) if only_active && !dapp.active => continue,
+            Ok(dapp) => page.dapps.push(dapp),
+            Err(err) => page.errors.push((id, err.coded().to_string())),
+        }
+
+        taken += 1;
+    }
+
+    // only a full page, or one cut short by the scan cap, may be followed by more dApps
+    if (max > 0 && taken == max) || !scanned_all {
+        page.next_key = last;
+    }
+
+    Ok(page)
+}
+
+/// Statistics for the whole hub.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - There is an API error.
+pub fn totals<Api>(api: &Api) -> Result<HubTotals, Error<Api::Error>>
+where
+    Api: Dapps + Totals,
+{
+    Ok(HubTotals {
+        total_dapps: api.total_dapp_count()?,
+        active_dapps: api.active_dapp_count()?,
+        referral_codes: api.total_referral_codes()?,
+        total_invocations: api.total_invocations()?,
+        total_contributions: api.total_contributions()?,
+        total_collected: api.total_collected()?,
+    })
+}
+
+/// The activity of a referral code with the dApp with the given `id`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - There is an API error.
+pub fn referrer_stats<Api>(
+    api: &Api,
+    dapp: &Id,
+    code: ReferralCode,
+) -> Result<ReferrerStats, Error<Api::Error>>
+where
+    Api: Referrers + ReadonlyReferralStore,
+{
+    Ok(ReferrerStats {
+        invocations: api.referrer_invocations(dapp, code)?,
+        earnings: api
+            .dapp_earnings(dapp.as_id_ref(), code)?
+            .map_or(0, NonZeroU128::get),
+        last_active: api.referrer_last_active(dapp, code)?,
+        blocked: api.code_blocked(code)?,
+    })
+}
+
+/// The invocations of the dApp with the given `id` on each day from `from_day` to `to_day`, inclusive.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The range is empty or covers more than [`MAX_INVOCATION_DAYS`].
+/// - The dApp has never been activated.
+/// - There is an API error.
+pub fn dapp_invocations_over_range<Api>(
+    api: &Api,
+    dapp: &Id,
+    from_day: u64,
+    to_day: u64,
+) -> Result<Vec<DailyInvocations>, Error<Api::Error>>
+where
+    Api: Dapps,
+{
+    if from_day > to_day || to_day - from_day >= MAX_INVOCATION_DAYS {
+        return Err(Error::InvalidDayRange);
+    }
+
+    if api.dapp_index(dapp)?.is_none() {
+        return Err(Error::DappNotActivated(dapp.clone()));
+    }
+
+    (from_day..=to_day)
+        .map(|day| {
+            Ok(DailyInvocations {
+                day,
+                invocations: api.dapp_daily_invocations(dapp, day)?,
+            })
         })
+        .collect()
+}
+
+/// The rewards of the dApp with the given `id` & how they've been distributed.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The dApp has no rewards pot.
+/// - There is an API error.
+pub fn dapp_rewards<Api>(api: &Api, dapp: &Id) -> Result<DappRewards, Error<Api::Error>>
+where
+    Api: ReadonlyDappStore + ReadonlyReferralStore + ReadonlyCollectStore + CollectQuery,
+{
+    if !api.has_rewards_pot(dapp)? {
+        return Err(Error::RewardsPotNotSet(dapp.clone()));
+    }
+
+    let rewards_pot = api.rewards_pot(dapp)?;
+
+    let total_rewards = api
+        .dapp_total_rewards(&rewards_pot)?
+        .map_or(0, NonZeroU128::get);
+    let contributions = api
+        .dapp_contributions(dapp.as_id_ref())?
+        .map_or(0, NonZeroU128::get);
+    let referrers_collected = api
+        .dapp_referrers_collected(dapp)?
+        .map_or(0, NonZeroU128::get);
+    let dapp_collected = api.dapp_total_collected(dapp)?.map_or(0, NonZeroU128::get);
+
+    let undistributed = total_rewards
+        .saturating_sub(referrers_collected)
+        .saturating_sub(dapp_collected);
+
+    Ok(DappRewards {
+        rewards_pot,
+        total_rewards,
+        contributions,
+        referrers_collected,
+        dapp_collected,
+        undistributed,
+    })
+}
+
+/// The address of the rewards pot for the dApp with the given `id`,
+/// either as created or as it will be once the dApp is activated.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - There is an API error.
+pub fn rewards_pot_address<Api>(api: &Api, dapp: &Id) -> Result<Id, Error<Api::Error>>
+where
+    Api: ReadonlyDappStore + RewardsPots,
+{
+    if api.has_rewards_pot(dapp)? {
+        return api.rewards_pot(dapp).map_err(Error::from);
+    }
+
+    api.expected_rewards_pot(dapp).map_err(Error::from)
+}
+
+/// Export keys are the big-endian `u64` parts identifying a record, which never change between versions.
+fn export_key(parts: &[u64]) -> Vec<u8> {
+    parts.iter().flat_map(|part| part.to_be_bytes()).collect()
+}
+
+fn parse_export_key<const N: usize, E>(key: &[u8]) -> Result<[u64; N], Error<E>> {
+    if key.len() != N * 8 {
+        return Err(Error::InvalidCursor);
+    }
+
+    let mut parts = [0; N];
+
+    for (part, bytes) in parts.iter_mut().zip(key.chunks_exact(8)) {
+        *part = u64::from_be_bytes(bytes.try_into().map_err(|_| Error::InvalidCursor)?);
+    }
+
+    Ok(parts)
+}
+
+fn export_dapp<Api>(
+    api: &Api,
+    id: Id,
+    activation_index: u64,
+) -> Result<DappExport, Error<Api::Error>>
+where
+    Api: ReadonlyDappStore + Dapps,
+{
+    let active = api.dapp_exists(&id)?;
+
+    Ok(DappExport {
+        name: api.dapp_name(&id)?,
+        percent: api.percent(&id)?,
+        collector: if active {
+            Some(api.collector(&id)?)
+        } else {
+            None
+        },
+        rewards_pot: if api.has_rewards_pot(&id)? {
+            Some(api.rewards_pot(&id)?)
+        } else {
+            None
+        },
+        repo_url: api.dapp_repo_url(&id)?,
+        id,
+        activation_index,
+    })
+}
+
+fn export_code<Api>(api: &Api, code: ReferralCode) -> Result<CodeExport, Error<Api::Error>>
+where
+    Api: ReadonlyReferralStore + ReadonlyCollectStore,
+{
+    Ok(CodeExport {
+        code,
+        owner: api.owner_of(code)?,
+        total_earnings: api.total_earnings(code)?.map_or(0, NonZeroU128::get),
+        total_collected: api
+            .referrer_total_collected(code)?
+            .map_or(0, NonZeroU128::get),
+        blocked: api.code_blocked(code)?,
+    })
+}
+
+fn export_collections<Api>(api: &Api, dapp: Id) -> Result<CollectionsExport, Error<Api::Error>>
+where
+    Api: ReadonlyReferralStore + ReadonlyCollectStore,
+{
+    Ok(CollectionsExport {
+        contributions: api
+            .dapp_contributions(dapp.as_id_ref())?
+            .map_or(0, NonZeroU128::get),
+        dapp_collected: api.dapp_total_collected(&dapp)?.map_or(0, NonZeroU128::get),
+        referrers_collected: api
+            .dapp_referrers_collected(&dapp)?
+            .map_or(0, NonZeroU128::get),
+        dapp,
+    })
+}
+
+/// The earnings of `code` with each dApp it has referred, keyed by dApp activation index.
+fn code_earnings<Api>(
+    api: &Api,
+    code: ReferralCode,
+) -> Result<Vec<(u64, EarningsExport)>, Error<Api::Error>>
+where
+    Api: Dapps + ReadonlyReferralStore + ReadonlyCollectStore,
+{
+    let mut earnings = Vec::new();
+
+    for index in 0..api.code_dapp_count(code)? {
+        let Some(dapp) = api.code_dapp(code, index)? else {
+            continue;
+        };
+
+        let activation_index = api
+            .dapp_index(&dapp)?
+            .ok_or_else(|| Error::DappNotActivated(dapp.clone()))?;
+
+        earnings.push((
+            activation_index,
+            EarningsExport {
+                code,
+                invocations: api.invocations(dapp.as_id_ref(), code)?,
+                earnings: api
+                    .dapp_earnings(dapp.as_id_ref(), code)?
+                    .map_or(0, NonZeroU128::get),
+                collected: api
+                    .referrer_dapp_collected(&dapp, code)?
+                    .map_or(0, NonZeroU128::get),
+                dapp,
+            },
+        ));
+    }
+
+    // the code's dApp list is unordered, order by activation so keys page consistently
+    earnings.sort_by_key(|(activation_index, _)| *activation_index);
+
+    Ok(earnings)
+}
+
+/// A page of the given `section` of the hub state, for indexers to bootstrap from without replaying events.
+/// Records are ordered by key, starting after `start_after` if set, and `limit` is capped at [`MAX_EXPORT_LIMIT`].
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The `start_after` key is not a key of the `section`.
+/// - There is an API error.
+pub fn export_state<Api>(
+    api: &Api,
+    section: ExportSection,
+    start_after: Option<&[u8]>,
+    limit: u32,
+) -> Result<ExportPage, Error<Api::Error>>
+where
+    Api: Dapps + ReadonlyDappStore + ReadonlyReferralStore + ReadonlyCollectStore,
+{
+    let limit = limit.min(MAX_EXPORT_LIMIT) as usize;
+
+    let mut records = Vec::with_capacity(limit);
+    let mut last_key = None;
+
+    match section {
+        ExportSection::Dapps | ExportSection::Collections => {
+            let start = start_after
+                .map(parse_export_key::<1, _>)
+                .transpose()?
+                .map(|[index]| index.checked_add(1).ok_or(Error::InvalidCursor))
+                .transpose()?;
+
+            for id in api.all_dapp_ids(start, Some(limit as u64))? {
+                // the dApp index may have gaps, so the position of each dApp is looked up
+                let index = api
+                    .dapp_index(&id)?
+                    .ok_or_else(|| Error::DappNotActivated(id.clone()))?;
+
+                records.push(match section {
+                    ExportSection::Dapps => ExportRecord::Dapp(export_dapp(api, id, index)?),
+                    _ => ExportRecord::Collections(export_collections(api, id)?),
+                });
+                last_key = Some(export_key(&[index]));
+            }
+        }
+        ExportSection::Codes => {
+            let after = start_after
+                .map(parse_export_key::<1, _>)
+                .transpose()?
+                .map_or(0, |[code]| code);
+
+            for code in ReferralCode::issued(api.latest()?)
+                .filter(|code| code.to_u64() > after)
+                .take(limit)
+            {
+                records.push(ExportRecord::Code(export_code(api, code)?));
+                last_key = Some(export_key(&[code.to_u64()]));
+            }
+        }
+        ExportSection::Earnings => {
+            let after = start_after.map(parse_export_key::<2, _>).transpose()?;
+
+            for code in ReferralCode::issued(api.latest()?)
+                .filter(|code| code.to_u64() >= after.map_or(0, |[after, _]| after))
+            {
+                if records.len() == limit {
+                    break;
+                }
+
+                for (activation_index, earnings) in code_earnings(api, code)? {
+                    if after.is_some_and(|after| [code.to_u64(), activation_index] <= after) {
+                        continue;
+                    }
+
+                    if records.len() == limit {
+                        break;
+                    }
+
+                    records.push(ExportRecord::Earnings(earnings));
+                    last_key = Some(export_key(&[code.to_u64(), activation_index]));
+                }
+            }
+        }
+    }
+
+    // only a full page may be followed by more records
+    let next_key = last_key.filter(|_| limit > 0 && records.len() == limit);
+
+    Ok(ExportPage { records, next_key })
 }
 
 /// Handle a query request.
@@ -163,7 +876,10 @@ where
         + ReadonlyDappStore
         + DappExternalQuery
         + Referrers
+        + Totals
+        + RewardsPots
         + ReadonlyReferralStore
+        + ReadonlyCollectStore
         + CollectQuery,
 {
     match request {
@@ -172,10 +888,63 @@ where
             .map(Response::TotalDappCount)
             .map_err(Error::from),
         Request::Dapp(id) => dapp_info(api, id).map(Response::Dapp),
-        Request::AllDapps { start, limit } => all_dapps(api, start, limit).map(Response::AllDapps),
+        Request::DappByName(name) => {
+            let id = api
+                .dapp_by_name(&name)?
+                .ok_or(Error::DappNameNotRegistered(name))?;
+
+            dapp_info(api, id).map(Response::Dapp)
+        }
+        Request::AllDapps {
+            start,
+            after,
+            limit,
+            only_active,
+        } => {
+            let DappsPage {
+                dapps,
+                errors,
+                next_key,
+            } = all_dapps(api, start, after.as_ref(), limit, only_active)?;
+
+            Ok(Response::AllDapps {
+                dapps,
+                errors,
+                total: api.total_dapp_count()?,
+                next_key,
+            })
+        }
         Request::ReferralCode(id) => api
             .referral_code(&id)
             .map(Response::ReferralCode)
             .map_err(Error::from),
+        Request::Totals => totals(api).map(Response::Totals),
+        Request::RewardsPotAddress(dapp) => {
+            rewards_pot_address(api, &dapp).map(Response::RewardsPotAddress)
+        }
+        Request::OwedToReferrer { code, dapp } => {
+            collect::referrer_owed(api, &dapp, code).map(Response::OwedToReferrer)
+        }
+        Request::OwedToDapp(dapp) => collect::dapp_owed(api, &dapp).map(Response::OwedToDapp),
+        Request::ReferrerStats { code, dapp } => {
+            referrer_stats(api, &dapp, code).map(Response::ReferrerStats)
+        }
+        Request::DappInvocationsOverRange {
+            dapp,
+            from_day,
+            to_day,
+        } => dapp_invocations_over_range(api, &dapp, from_day, to_day)
+            .map(Response::DappInvocationsOverRange),
+        Request::CodeOwner(code) => api
+            .owner_of(code)
+            .map(Response::CodeOwner)
+            .map_err(Error::from),
+        Request::DappRewards(dapp) => dapp_rewards(api, &dapp).map(Response::DappRewards),
+        Request::IsDapp(id) => dapp_status(api, &id).map(Response::IsDapp),
+        Request::ExportState {
+            section,
+            start_after,
+            limit,
+        } => export_state(api, section, start_after.as_deref(), limit).map(Response::ExportState),
     }
 }
@@ -1,31 +1,85 @@
-use std::num::NonZeroU128;
+use std::num::{NonZeroU128, NonZeroU64};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{FallibleApi, Id};
+use crate::{FallibleApi, Id, IdRef};
 
-use super::{DappExternalQuery, Error, ReadonlyDappStore};
+use super::{AuthRequirement, DappExternalQuery, Error, ReadonlyDappStore};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
-pub struct Code(u64);
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("referral code must be non-zero")]
+pub struct ZeroCode;
+
+/// A referral code, codes are issued sequentially starting from [`Code::FIRST`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Code(NonZeroU64);
 
 impl Code {
-    fn next(self) -> Code {
-        Code(self.0 + 1)
+    /// The first code issued.
+    pub const FIRST: Self = Self(NonZeroU64::MIN);
+
+    fn next(self) -> Option<Code> {
+        self.0.checked_add(1).map(Code)
     }
 
     #[must_use]
     pub fn to_u64(self) -> u64 {
-        self.0
+        self.0.get()
+    }
+
+    /// Every code issued up to & including `latest`, in order.
+    pub fn issued(latest: Option<Code>) -> impl Iterator<Item = Code> {
+        (1..=latest.map_or(0, Code::to_u64))
+            .filter_map(NonZeroU64::new)
+            .map(Code)
     }
 }
 
-impl From<u64> for Code {
-    fn from(value: u64) -> Self {
+impl From<NonZeroU64> for Code {
+    fn from(value: NonZeroU64) -> Self {
         Self(value)
     }
 }
 
+impl From<Code> for NonZeroU64 {
+    fn from(value: Code) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl TryFrom<u64> for Code {
+    type Error = ZeroCode;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        NonZeroU64::new(value).map(Self).ok_or(ZeroCode)
+    }
+}
+
+/// The block at which a referral was last recorded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Activity {
+    pub height: u64,
+    /// Block time in seconds since the UNIX epoch
+    pub time: u64,
+}
+
+/// Length of the days invocations are counted over, in seconds.
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+impl Activity {
+    /// The day of the activity, counted in days since the UNIX epoch.
+    #[must_use]
+    pub const fn day(self) -> u64 {
+        self.time / SECONDS_PER_DAY
+    }
+}
+
 pub trait ReadonlyStore: FallibleApi {
     /// Checks whether the given `code` exists.
     ///
@@ -39,7 +93,7 @@ pub trait ReadonlyStore: FallibleApi {
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
-    fn owner_exists(&self, owner: &Id) -> Result<bool, Self::Error>;
+    fn owner_exists(&self, owner: IdRef<'_>) -> Result<bool, Self::Error>;
 
     /// Gets the owner of the given code (if one exists).
     ///
@@ -67,14 +121,53 @@ pub trait ReadonlyStore: FallibleApi {
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
-    fn dapp_earnings(&self, dapp: &Id, code: Code) -> Result<Option<NonZeroU128>, Self::Error>;
+    fn dapp_earnings(
+        &self,
+        dapp: IdRef<'_>,
+        code: Code,
+    ) -> Result<Option<NonZeroU128>, Self::Error>;
+
+    /// Gets the number of dApps a referral code has earnings with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn code_dapp_count(&self, code: Code) -> Result<u64, Self::Error>;
+
+    /// Gets the dApp at `index` of those a referral code has earnings with, in the order first earned.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn code_dapp(&self, code: Code, index: u64) -> Result<Option<Id>, Self::Error>;
 
     /// Gets the total contributions from a dApp to all referrers.
     ///
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
-    fn dapp_contributions(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error>;
+    fn dapp_contributions(&self, dapp: IdRef<'_>) -> Result<Option<NonZeroU128>, Self::Error>;
+
+    /// Checks whether the given `code` is blocked from earning & collecting.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn code_blocked(&self, code: Code) -> Result<bool, Self::Error>;
+
+    /// Gets the number of invocations referred by a code to a dApp.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn invocations(&self, dapp: IdRef<'_>, code: Code) -> Result<u64, Self::Error>;
+
+    /// Checks whether referrals by the dApp itself or it's collector are blocked hub-wide.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn self_referral_blocked(&self) -> Result<bool, Self::Error>;
 }
 
 pub trait MutableStore: FallibleApi {
@@ -97,7 +190,23 @@ pub trait MutableStore: FallibleApi {
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
-    fn increment_invocations(&mut self, dapp: &Id, code: Code) -> Result<(), Self::Error>;
+    fn increment_invocations(&mut self, dapp: IdRef<'_>, code: Code) -> Result<(), Self::Error>;
+
+    /// Increments number of invocations of a dApp by any referrer on the given `day`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn increment_daily_invocations(&mut self, dapp: IdRef<'_>, day: u64)
+        -> Result<(), Self::Error>;
+
+    /// Sets the latest activity of both a dApp and a referral code with that dApp.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn touch(&mut self, dapp: IdRef<'_>, code: Code, activity: Activity)
+        -> Result<(), Self::Error>;
 
     /// Sets the total earnings of a referral code.
     ///
@@ -113,7 +222,7 @@ pub trait MutableStore: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn set_dapp_earnings(
         &mut self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         code: Code,
         total: NonZeroU128,
     ) -> Result<(), Self::Error>;
@@ -125,9 +234,23 @@ pub trait MutableStore: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn set_dapp_contributions(
         &mut self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         contributions: NonZeroU128,
     ) -> Result<(), Self::Error>;
+
+    /// Sets whether a referral code is blocked from earning & collecting.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_code_blocked(&mut self, code: Code, blocked: bool) -> Result<(), Self::Error>;
+
+    /// Sets whether referrals by the dApp itself or it's collector are blocked hub-wide.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn set_self_referral_blocked(&mut self, blocked: bool) -> Result<(), Self::Error>;
 }
 
 /// Register for a referral code.
@@ -136,24 +259,58 @@ pub trait MutableStore: FallibleApi {
 ///
 /// This function will return an error if:
 /// - The sender already has a referral code.
+/// - Every referral code has been issued.
 /// - There is an API error.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(api), err)
+)]
 pub fn register<Api>(api: &mut Api, sender: Id) -> Result<Code, Error<Api::Error>>
 where
     Api: ReadonlyStore + MutableStore,
 {
-    if api.owner_exists(&sender)? {
+    if api.owner_exists(sender.as_id_ref())? {
         return Err(Error::AlreadyRegistered);
     }
 
-    let code = api.latest()?.unwrap_or_default().next();
+    let code = match api.latest()? {
+        Some(latest) => latest.next().ok_or(Error::Overflow)?,
+        None => Code::FIRST,
+    };
 
     api.set_code_owner(code, sender)?;
 
     api.set_latest(code)?;
 
+    debug!(code = code.to_u64(), "registered referral code");
+
     Ok(code)
 }
 
+/// Register a referral code on behalf of `owner`, an action available to the hub and it's collector.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not either the hub or it's collector.
+/// - The owner already has a referral code.
+/// - There is an API error.
+pub fn register_for<Api>(api: &mut Api, sender: &Id, owner: Id) -> Result<Code, Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + DappExternalQuery + ReadonlyDappStore,
+{
+    let hub = api.self_id()?;
+
+    if sender != &hub && sender != &api.collector(&hub)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::HubOrCollector,
+        });
+    }
+
+    register(api, owner)
+}
+
 /// Transfer ownership of a referral code
 ///
 /// # Errors
@@ -172,11 +329,14 @@ where
     Api: ReadonlyStore + MutableStore,
 {
     let Some(current_owner) = api.owner_of(code)? else {
-        return Err(Error::ReferralCodeNotRegistered);
+        return Err(Error::ReferralCodeNotRegistered(code));
     };
 
     if sender != &current_owner {
-        return Err(Error::Unauthorized);
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::CodeOwner(code),
+        });
     }
 
     api.set_code_owner(code, new_owner)?;
@@ -184,39 +344,161 @@ where
     Ok(())
 }
 
-/// Record an invocation with a referral code.
+/// Block or unblock a referral code, an action available to the hub and it's collector.
+/// A blocked code can neither earn nor collect, earnings made before it was blocked are kept.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// - The sender is not a registered dApp.
+/// - The referral code is not registered.
+/// - The sender is not either the hub or it's collector.
+/// - There is an API error.
+pub fn set_code_status<Api>(
+    api: &mut Api,
+    sender: &Id,
+    code: Code,
+    blocked: bool,
+) -> Result<(), Error<Api::Error>>
+where
+    Api: ReadonlyStore + MutableStore + DappExternalQuery + ReadonlyDappStore,
+{
+    if !api.code_exists(code)? {
+        return Err(Error::ReferralCodeNotRegistered(code));
+    }
+
+    let hub = api.self_id()?;
+
+    if sender != &hub && sender != &api.collector(&hub)? {
+        return Err(Error::Unauthorized {
+            sender: sender.clone(),
+            required: AuthRequirement::HubOrCollector,
+        });
+    }
+
+    api.set_code_blocked(code, blocked)?;
+
+    debug!(code = code.to_u64(), blocked, "set referral code status");
+
+    Ok(())
+}
+
+/// Record an invocation with a referral code, for the sender or the `dapp` that authorised the sender
+/// as it's relayer.
+/// A running campaign of the dApp multiplies the referrer's share, up to the full fee.
+///
+/// Returns `true` if the code had already reached the dApp's per code invocation cap,
+/// in which case the invocation is counted but earns nothing.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not the relayer of the given `dapp`.
+/// - The dApp is not registered.
+/// - The dApp has been frozen by a de-activation.
 /// - The referral code does not exist.
+/// - The referral code is blocked.
+/// - Self referrals are blocked and the code owner is the dApp or it's collector, unless the dApp allows self referrals.
 /// - Calculated earnings/contributions overflow 128-bits.
 /// - There is an API error.
-pub fn record<Api>(api: &mut Api, sender: &Id, code: Code) -> Result<(), Error<Api::Error>>
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(api), err)
+)]
+pub fn record<Api>(
+    api: &mut Api,
+    sender: &Id,
+    dapp: Option<&Id>,
+    code: Code,
+) -> Result<bool, Error<Api::Error>>
 where
     Api: ReadonlyStore + MutableStore + DappExternalQuery + ReadonlyDappStore,
 {
-    if !api.dapp_exists(sender)? {
-        return Err(Error::DappNotActivated);
+    // a relayer records on behalf of the dApp that authorised it
+    let id = match dapp {
+        Some(dapp) if dapp != sender => {
+            if api.relayer(dapp)?.as_ref() != Some(sender) {
+                return Err(Error::Unauthorized {
+                    sender: sender.clone(),
+                    required: AuthRequirement::DappOrRelayer(dapp.clone()),
+                });
+            }
+
+            dapp
+        }
+        _ => sender,
+    };
+
+    if !api.dapp_exists(id)? {
+        return Err(Error::DappNotActivated(id.clone()));
     }
 
-    if !api.code_exists(code)? {
-        return Err(Error::ReferralCodeNotRegistered);
+    if api.dapp_frozen(id)? {
+        return Err(Error::DappDeactivated(id.clone()));
+    }
+
+    let Some(owner) = api.owner_of(code)? else {
+        return Err(Error::ReferralCodeNotRegistered(code));
+    };
+
+    if api.code_blocked(code)? {
+        return Err(Error::CodeBlocked(code));
     }
 
-    api.increment_invocations(sender, code)?;
+    // a dApp may opt out of the hub-wide block on self referrals
+    if api.self_referral_blocked()?
+        && !api.self_referral_allowed(id)?
+        && (&owner == id || owner == api.collector(id)?)
+    {
+        return Err(Error::SelfReferral);
+    }
 
-    let Some(current_fee) = api.current_fee(sender)? else {
+    let dapp = id.as_id_ref();
+
+    let invocations = api.invocations(dapp, code)?;
+
+    api.increment_invocations(dapp, code)?;
+
+    let activity = Activity {
+        height: api.current_height()?,
+        time: api.current_time()?,
+    };
+
+    api.touch(dapp, code, activity)?;
+
+    api.increment_daily_invocations(dapp, activity.day())?;
+
+    // tracking only dApps share nothing with referrers
+    let Some(percent) = api.percent(id)? else {
+        return Ok(false);
+    };
+
+    if let Some(max) = api.max_invocations_per_code(id)? {
+        if invocations >= max.get() {
+            debug!(invocations, max = max.get(), "invocation cap reached");
+            return Ok(true);
+        }
+    }
+
+    let Some(current_fee) = api.current_fee(id)? else {
         return Err(Error::FeeNotSet);
     };
 
-    let Some(referrer_share) = api
-        .percent(sender)?
+    let Some(referrer_share) = percent
         .checked_apply_to(current_fee)
         .ok_or(Error::Overflow)?
     else {
-        return Ok(());
+        return Ok(false);
+    };
+
+    let referrer_share = match api.campaign(id)? {
+        Some(campaign) if campaign.is_active_at(activity.height) => {
+            let Some(boosted) = campaign.apply_to(referrer_share, current_fee) else {
+                return Ok(false);
+            };
+
+            boosted
+        }
+        _ => referrer_share,
     };
 
     let total_earnings = match api.total_earnings(code)? {
@@ -228,23 +510,30 @@ where
 
     api.set_total_earnings(code, total_earnings)?;
 
-    let dapp_earnings = match api.dapp_earnings(sender, code)? {
+    let dapp_earnings = match api.dapp_earnings(dapp, code)? {
         Some(cur) => cur
             .checked_add(referrer_share.get())
             .ok_or(Error::Overflow)?,
         None => referrer_share,
     };
 
-    api.set_dapp_earnings(sender, code, dapp_earnings)?;
+    api.set_dapp_earnings(dapp, code, dapp_earnings)?;
 
-    let dapp_contributions = match api.dapp_contributions(sender)? {
+    let dapp_contributions = match api.dapp_contributions(dapp)? {
         Some(cur) => cur
             .checked_add(referrer_share.get())
             .ok_or(Error::Overflow)?,
         None => referrer_share,
     };
 
-    api.set_dapp_contributions(sender, dapp_contributions)?;
+    api.set_dapp_contributions(dapp, dapp_contributions)?;
 
-    Ok(())
+    debug!(
+        referrer_share = referrer_share.get(),
+        total_earnings = total_earnings.get(),
+        dapp_contributions = dapp_contributions.get(),
+        "recorded referral"
+    );
+
+    Ok(false)
 }
@@ -54,6 +54,13 @@ pub trait Handle: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn withdraw_rewards(&mut self, pot: Id) -> Result<(), Self::Error>;
 
+    /// Withdraw any pending rewards for the given pot, only enacting `then` once the withdrawal has settled
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn withdraw_rewards_then(&mut self, pot: Id, then: Vec<Command>) -> Result<(), Self::Error>;
+
     /// Distribute an amount of rewards from the rewards pot to a receiver.
     ///
     /// # Errors
@@ -65,6 +72,27 @@ pub trait Handle: FallibleApi {
         amount: NonZeroU128,
         receiver: Id,
     ) -> Result<(), Self::Error>;
+
+    /// Hand the rewards pot over to a new admin
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn update_rewards_pot_admin(&mut self, pot: Id, admin: Id) -> Result<(), Self::Error>;
+
+    /// Add the index to continue collecting from to the response
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn add_collect_cursor(&mut self, next: Option<u64>) -> Result<(), Self::Error>;
+
+    /// Signal the referral code has reached the dApp's invocation cap, earning nothing
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn add_invocation_cap_reached(&mut self, code: ReferralCode) -> Result<(), Self::Error>;
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -85,6 +113,10 @@ pub enum Command {
     },
     /// Withdraw pending rewards for Id
     WithdrawPending(Id),
+    /// Withdraw pending rewards for `pot`, enacting `then` in the given order once settled
+    WithdrawPendingThen { pot: Id, then: Vec<Command> },
+    /// Hand `pot` over to a new admin
+    UpdateRewardsPotAdmin { pot: Id, admin: Id },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -97,6 +129,13 @@ pub enum Reply {
     Cmd(Command),
     /// Multiple commands to enact in the given order
     MultiCmd(Vec<Command>),
+    /// Commands to enact in the given order, with the index to continue collecting from
+    CollectAll {
+        cmds: Vec<Command>,
+        next: Option<u64>,
+    },
+    /// Referral recorded without earnings, the code reached the dApp's invocation cap
+    InvocationCapReached(ReferralCode),
 }
 
 /// Handle a command issued after core message execution
@@ -121,6 +160,8 @@ where
             receiver,
         } => api.distribute_rewards(pot, amount, receiver),
         Command::WithdrawPending(pot) => api.withdraw_rewards(pot),
+        Command::WithdrawPendingThen { pot, then } => api.withdraw_rewards_then(pot, then),
+        Command::UpdateRewardsPotAdmin { pot, admin } => api.update_rewards_pot_admin(pot, admin),
     }
 }
 
@@ -142,6 +183,13 @@ where
                 handle_cmd(&mut api, cmd)?;
             }
         }
+        Reply::CollectAll { cmds, next } => {
+            for cmd in cmds {
+                handle_cmd(&mut api, cmd)?;
+            }
+            api.add_collect_cursor(next)?;
+        }
+        Reply::InvocationCapReached(code) => api.add_invocation_cap_reached(code)?,
     }
 
     Ok(api.into_response())
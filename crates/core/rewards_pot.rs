@@ -10,12 +10,26 @@ pub enum Error<Api> {
     Api(#[from] Api),
     #[error("unauthorized")]
     Unauthorized,
+    #[error("withdraw cooldown - {remaining} seconds remaining")]
+    WithdrawCooldown { remaining: u64 },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Kind {
     WithdrawPending,
-    Distribute { recipient: Id, amount: NonZeroU128 },
+    Distribute {
+        recipient: Id,
+        amount: NonZeroU128,
+        denom: Option<String>,
+        clamp: bool,
+    },
+    Sweep {
+        denom: String,
+        recipient: Id,
+    },
+    UpdateAdmin {
+        new_admin: Id,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -27,7 +41,17 @@ pub struct Msg {
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Command {
     WithdrawPending,
-    Send { recipient: Id, amount: NonZeroU128 },
+    Send {
+        recipient: Id,
+        amount: NonZeroU128,
+        denom: Option<String>,
+        clamp: bool,
+    },
+    Sweep {
+        denom: String,
+        recipient: Id,
+    },
+    SetAdmin(Id),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -50,6 +74,27 @@ pub trait Query: FallibleApi {
     ///
     /// This function will return an error depending on the implementor.
     fn has_uncollected_rewards(&self) -> Result<bool, Self::Error>;
+
+    /// Gets the minimum number of seconds between withdrawals, zero if there is no cooldown
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn min_withdraw_interval(&self) -> Result<u64, Self::Error>;
+
+    /// Gets the time of the last withdrawal in seconds, if any
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn last_withdraw_time(&self) -> Result<Option<u64>, Self::Error>;
+
+    /// Gets the current time in seconds
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn current_time(&self) -> Result<u64, Self::Error>;
 }
 
 /// Attempt to withdraw any pending rewards
@@ -58,6 +103,7 @@ pub trait Query: FallibleApi {
 ///
 /// This function will return an error if:
 /// - The sender is not the owner
+/// - The minimum interval since the last withdrawal has yet to elapse
 pub fn withdraw_pending<Api>(api: &mut Api, sender: &Id) -> Result<Vec<Command>, Error<Api::Error>>
 where
     Api: Query,
@@ -66,6 +112,17 @@ where
         return Err(Error::Unauthorized);
     }
 
+    if let Some(last) = api.last_withdraw_time()? {
+        let interval = api.min_withdraw_interval()?;
+        let elapsed = api.current_time()?.saturating_sub(last);
+
+        if elapsed < interval {
+            return Err(Error::WithdrawCooldown {
+                remaining: interval - elapsed,
+            });
+        }
+    }
+
     let mut commands = vec![];
 
     if api.has_uncollected_rewards()? {
@@ -75,7 +132,9 @@ where
     Ok(commands)
 }
 
-/// Attempt to distibute an amount of rewards to the specified recipient
+/// Attempt to distibute an amount of rewards to the specified recipient, if `clamp` is set
+/// the amount sent is limited to the pot's available balance. Rewards are sent in `denom`,
+/// or the rewards denom if none is given.
 ///
 /// # Errors
 ///
@@ -86,6 +145,8 @@ pub fn distribute<Api>(
     sender: &Id,
     recipient: Id,
     amount: NonZeroU128,
+    denom: Option<String>,
+    clamp: bool,
 ) -> Result<Vec<Command>, Error<Api::Error>>
 where
     Api: Query,
@@ -100,13 +161,61 @@ where
         commands.push(Command::WithdrawPending);
     }
 
-    // we assume that this will fail if for some reason the pot
-    // has an insufficient balance
-    commands.push(Command::Send { recipient, amount });
+    // the implementor is responsible for checking the pot's balance
+    commands.push(Command::Send {
+        recipient,
+        amount,
+        denom,
+        clamp,
+    });
 
     Ok(commands)
 }
 
+/// Attempt to sweep the pot's balance of a given denom to the specified recipient
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not the owner
+pub fn sweep<Api>(
+    api: &mut Api,
+    sender: &Id,
+    denom: String,
+    recipient: Id,
+) -> Result<Vec<Command>, Error<Api::Error>>
+where
+    Api: Query,
+{
+    if sender != &api.owner_id()? {
+        return Err(Error::Unauthorized);
+    }
+
+    // the implementor is responsible for protecting undistributed rewards
+    Ok(vec![Command::Sweep { denom, recipient }])
+}
+
+/// Attempt to hand the pot over to a new admin, the new admin takes the place of the owner
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The sender is not the owner
+pub fn update_admin<Api>(
+    api: &mut Api,
+    sender: &Id,
+    new_admin: Id,
+) -> Result<Vec<Command>, Error<Api::Error>>
+where
+    Api: Query,
+{
+    if sender != &api.owner_id()? {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(vec![Command::SetAdmin(new_admin)])
+}
+
 pub trait HandleReply: FallibleApi {
     type Response;
 
@@ -120,12 +229,33 @@ pub trait HandleReply: FallibleApi {
     /// This function will return an error depending on the implementor.
     fn withdraw_pending(&mut self) -> Result<(), Self::Error>;
 
-    /// Send a given amount of rewards to a recipient
+    /// Send a given amount of rewards to a recipient, limiting the amount to the available balance if `clamp` is set
+    /// Rewards are sent in the given denom, or the rewards denom if none is given
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn send_rewards(
+        &mut self,
+        receiver: Id,
+        amount: NonZeroU128,
+        denom: Option<String>,
+        clamp: bool,
+    ) -> Result<(), Self::Error>;
+
+    /// Send the balance of a given denom to a recipient, excluding any undistributed rewards
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn sweep(&mut self, denom: String, recipient: Id) -> Result<(), Self::Error>;
+
+    /// Set the admin of the pot, replacing the owner
     ///
     /// # Errors
     ///
     /// This function will return an error depending on the implementor.
-    fn send_rewards(&mut self, receiver: Id, amount: NonZeroU128) -> Result<(), Self::Error>;
+    fn set_admin(&mut self, admin: Id) -> Result<(), Self::Error>;
 }
 
 /// Handle a message, this is the defacto entry point.
@@ -139,8 +269,17 @@ where
 {
     match msg.kind {
         Kind::WithdrawPending => withdraw_pending(api, &msg.sender).map(Reply::Commands),
-        Kind::Distribute { recipient, amount } => {
-            distribute(api, &msg.sender, recipient, amount).map(Reply::Commands)
+        Kind::Distribute {
+            recipient,
+            amount,
+            denom,
+            clamp,
+        } => distribute(api, &msg.sender, recipient, amount, denom, clamp).map(Reply::Commands),
+        Kind::Sweep { denom, recipient } => {
+            sweep(api, &msg.sender, denom, recipient).map(Reply::Commands)
+        }
+        Kind::UpdateAdmin { new_admin } => {
+            update_admin(api, &msg.sender, new_admin).map(Reply::Commands)
         }
     }
 }
@@ -160,7 +299,14 @@ where
             for cmd in cmds {
                 match cmd {
                     Command::WithdrawPending => api.withdraw_pending()?,
-                    Command::Send { recipient, amount } => api.send_rewards(recipient, amount)?,
+                    Command::Send {
+                        recipient,
+                        amount,
+                        denom,
+                        clamp,
+                    } => api.send_rewards(recipient, amount, denom, clamp)?,
+                    Command::Sweep { denom, recipient } => api.sweep(denom, recipient)?,
+                    Command::SetAdmin(admin) => api.set_admin(admin)?,
                 }
             }
         }
@@ -1,28 +1,82 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::num::NonZeroU128;
+
 use archway_bindings::{ArchwayMsg, ArchwayQuery};
-use cosmwasm_std::{Env, QuerierWrapper, Response as CwResponse, StdError, Storage as CwStorage};
+use cosmwasm_std::{
+    Api as CwApi, Env, QuerierWrapper, Response as CwResponse, StdError, Storage as CwStorage,
+};
 
 use kv_storage::{Error as KvStoreError, KvStore, MutStorage};
 use kv_storage_bincode::{Bincode, Error as BincodeError};
 use kv_storage_cosmwasm::{CosmwasmRepo, Error as CosmwasmRepoError};
 
+use referrals_core::hub::NonZeroPercent;
 use referrals_core::FallibleApi;
 
 use referrals_storage::Error as CoreStorageError;
+use referrals_storage::RecordingStore;
 use referrals_storage::Storage as CoreStorage;
 
+use crate::rewards::RewardsMetadataProvider;
+
 pub type Querier<'a> = QuerierWrapper<'a, ArchwayQuery>;
 pub type Response = CwResponse<ArchwayMsg>;
 
 pub type CwStore<'a> = KvStore<Bincode, CosmwasmRepo<&'a dyn CwStorage>>;
 pub type CwMutStore<'a> = KvStore<Bincode, CosmwasmRepo<&'a mut dyn CwStorage>>;
+pub type CwRecordingStore<'a> = KvStore<Bincode, RecordingStore<CosmwasmRepo<&'a dyn CwStorage>>>;
 pub type CwStoreError = KvStoreError<BincodeError, CosmwasmRepoError>;
 
 pub mod cache;
 pub mod hub;
+pub mod rewards;
 pub mod rewards_pot;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+/// The kind of submessage a reply is for, sent as the reply id.
+/// Each kind has a distinct id across contracts, so a reply can't be mistaken for another kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyId {
+    /// A rewards pot instantiation, issued by the hub
+    CreateRewardsPot,
+    /// A rewards withdrawal, issued by a rewards pot or by the hub to a rewards pot
+    WithdrawRewards,
+    /// A referral credited on behalf of a new registrant, issued by the hub to itself
+    CreditReferral,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown reply id - {0}")]
+pub struct UnknownReplyId(pub u64);
+
+impl ReplyId {
+    #[must_use]
+    pub const fn as_u64(self) -> u64 {
+        match self {
+            Self::CreateRewardsPot => 0,
+            Self::WithdrawRewards => 1,
+            Self::CreditReferral => 2,
+        }
+    }
+}
+
+impl TryFrom<u64> for ReplyId {
+    type Error = UnknownReplyId;
+
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::CreateRewardsPot),
+            1 => Ok(Self::WithdrawRewards),
+            2 => Ok(Self::CreditReferral),
+            id => Err(UnknownReplyId(id)),
+        }
+    }
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<StoreError, ModeError> {
@@ -36,11 +90,22 @@ pub enum Error<StoreError, ModeError> {
     Mode(ModeError),
 }
 
+/// Values read repeatedly while handling a single message, keyed by dApp address.
+/// Never persisted, so only lives as long as the [`Api`] it belongs to.
+#[derive(Default)]
+struct Memo {
+    fees: RefCell<HashMap<String, NonZeroU128>>,
+    percents: RefCell<HashMap<String, NonZeroPercent>>,
+}
+
 pub struct Api<'a, Mode, Store> {
     store: Store,
     env: &'a Env,
+    cw_api: &'a dyn CwApi,
     querier: Querier<'a>,
+    rewards: Box<dyn RewardsMetadataProvider + 'a>,
     response: Response,
+    memo: Memo,
     _m: Mode,
 }
 
@@ -53,18 +118,28 @@ impl<'a, Mode, Store> Api<'a, Mode, Store> {
     pub fn core_storage_mut(&mut self) -> CoreStorage<&mut Store> {
         CoreStorage::new(&mut self.store)
     }
+
+    /// Answer rewards module queries from the `provider` rather than the querier
+    #[must_use]
+    pub fn with_rewards_metadata(mut self, provider: impl RewardsMetadataProvider + 'a) -> Self {
+        self.rewards = Box::new(provider);
+        self
+    }
 }
 
 impl<'a, Mode, Store> Api<'a, Mode, Store>
 where
     Mode: Default,
 {
-    pub fn new(store: Store, env: &'a Env, querier: Querier<'a>) -> Self {
+    pub fn new(store: Store, env: &'a Env, cw_api: &'a dyn CwApi, querier: Querier<'a>) -> Self {
         Self {
             store,
             env,
+            cw_api,
             querier,
+            rewards: Box::new(querier),
             response: Response::default(),
+            memo: Memo::default(),
             _m: Mode::default(),
         }
     }
@@ -75,7 +150,7 @@ where
     Mode: FallibleApi,
     Store: MutStorage,
 {
-    /// Get the rewards denom, either from the cache or query
+    /// Get the rewards denom, either as set on initialization, from the cache or query
     ///
     /// # Errors
     ///
@@ -1,6 +1,7 @@
 use std::num::NonZeroU128;
 
 use kv_storage::{item, map, Fallible, Item, Map, MutStorage, Storage};
+use referrals_core::hub::{Command, Features};
 use referrals_core::Id;
 
 #[derive(Debug, thiserror::Error)]
@@ -38,8 +39,16 @@ pub mod hub {
     use super::*;
 
     static REWARD_POT_CODE_ID: Item<u64> = item!("reward_pot_code_id");
-    static REWARD_POT_COUNT: Item<u64> = item!("reward_pot_count");
     static DAPP_FEES_CACHE: Map<1024, &str, NonZeroU128> = map!("dapp_fees_cache");
+    static PENDING_POT_DAPP: Item<String> = item!("pending_pot_dapp");
+    static PENDING_POT_FEES: Map<1024, &str, NonZeroU128> = map!("pending_pot_fees");
+    static PENDING_CREDIT_CODE: Item<u64> = item!("pending_credit_code");
+    static REWARD_POT_COUNT: Item<u64> = item!("reward_pot_count");
+    static CONTRACT_PREMIUM: Item<u128> = item!("contract_premium");
+    static DEFERRED_CMDS: Item<Vec<Command>> = item!("deferred_cmds");
+    static ENABLED_FEATURES: Item<u8> = item!("enabled_features");
+    static MIN_COLLECT_AMOUNT: Item<u128> = item!("min_collect_amount");
+    static ALLOW_SELF_DAPP: Item<bool> = item!("allow_self_dapp");
 
     /// Set the reward pot contract code id
     ///
@@ -63,6 +72,72 @@ pub mod hub {
         REWARD_POT_CODE_ID.may_load(store).map_err(Error::from)
     }
 
+    /// Increment the number of reward pots created
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn increment_reward_pot_count<Store: MutStorage>(store: &mut Store) -> StoreResult<Store> {
+        let count = reward_pot_count(store)?;
+        REWARD_POT_COUNT.save(store, count + 1)?;
+        Ok(())
+    }
+
+    /// Get the number of reward pots created
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn reward_pot_count<Store: Storage>(store: &Store) -> StoreResult<Store, u64> {
+        let count = REWARD_POT_COUNT.may_load(store)?.unwrap_or_default();
+        Ok(count)
+    }
+
+    /// Set the hub's contract premium
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_contract_premium<Store: MutStorage>(
+        store: &mut Store,
+        premium: u128,
+    ) -> StoreResult<Store> {
+        CONTRACT_PREMIUM.save(store, premium)?;
+        Ok(())
+    }
+
+    /// Get the hub's contract premium
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn contract_premium<Store: Storage>(store: &Store) -> StoreResult<Store, Option<u128>> {
+        CONTRACT_PREMIUM.may_load(store).map_err(Error::from)
+    }
+
+    /// Set the commands to enact once a rewards withdrawal has settled
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_deferred_cmds<Store: MutStorage>(
+        store: &mut Store,
+        cmds: &Vec<Command>,
+    ) -> StoreResult<Store> {
+        DEFERRED_CMDS.save(store, cmds)?;
+        Ok(())
+    }
+
+    /// Get the commands to enact once a rewards withdrawal has settled
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn deferred_cmds<Store: Storage>(store: &Store) -> StoreResult<Store, Vec<Command>> {
+        let cmds = DEFERRED_CMDS.may_load(store)?.unwrap_or_default();
+        Ok(cmds)
+    }
+
     /// Cache the dapp's flat fee
     ///
     /// # Errors
@@ -91,17 +166,152 @@ pub mod hub {
             .map_err(Error::from)
     }
 
-    /// Increment the number of reward pots created, returning the new value.
+    /// Set the dapp whose rewards pot is currently being instantiated
     ///
     /// # Errors
     ///
     /// This function will return an error if there is an issue with the underlying storage.
-    pub fn increment_reward_pot_count<Store: MutStorage>(
+    pub fn set_pending_pot_dapp<Store: MutStorage>(
         store: &mut Store,
-    ) -> StoreResult<Store, u64> {
-        let count = REWARD_POT_COUNT.may_load(store)?.unwrap_or_default();
-        REWARD_POT_COUNT.save(store, count + 1)?;
-        Ok(count)
+        dapp: &Id,
+    ) -> StoreResult<Store> {
+        PENDING_POT_DAPP.save(store, &dapp.as_str().to_owned())?;
+        Ok(())
+    }
+
+    /// Get the dapp whose rewards pot was most recently instantiated
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn pending_pot_dapp<Store: Storage>(store: &Store) -> StoreResult<Store, Option<Id>> {
+        let dapp = PENDING_POT_DAPP.may_load(store)?;
+        Ok(dapp.map(Id::from))
+    }
+
+    /// Set the flat fee to give the dapp once it's rewards pot is instantiated
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_pending_pot_fee<Store: MutStorage>(
+        store: &mut Store,
+        dapp: &Id,
+        fee: NonZeroU128,
+    ) -> StoreResult<Store> {
+        PENDING_POT_FEES.save(store, dapp.as_str(), fee)?;
+        Ok(())
+    }
+
+    /// Take the flat fee to give the dapp once it's rewards pot is instantiated
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn take_pending_pot_fee<Store: MutStorage>(
+        store: &mut Store,
+        dapp: &Id,
+    ) -> StoreResult<Store, Option<NonZeroU128>> {
+        let fee = PENDING_POT_FEES.may_load(store, dapp.as_str())?;
+        PENDING_POT_FEES.remove(store, dapp.as_str())?;
+        Ok(fee)
+    }
+
+    /// Set the referral code most recently credited on behalf of a new registrant
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_pending_credit_code<Store: MutStorage>(
+        store: &mut Store,
+        code: u64,
+    ) -> StoreResult<Store> {
+        PENDING_CREDIT_CODE.save(store, &code)?;
+        Ok(())
+    }
+
+    /// Get the referral code most recently credited on behalf of a new registrant
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn pending_credit_code<Store: Storage>(store: &Store) -> StoreResult<Store, Option<u64>> {
+        PENDING_CREDIT_CODE.may_load(store).map_err(Error::from)
+    }
+
+    /// Set the message kinds the hub accepts
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_enabled_features<Store: MutStorage>(
+        store: &mut Store,
+        features: Features,
+    ) -> StoreResult<Store> {
+        ENABLED_FEATURES.save(store, features.bits())?;
+        Ok(())
+    }
+
+    /// Get the message kinds the hub accepts, every kind if never set
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn enabled_features<Store: Storage>(store: &Store) -> StoreResult<Store, Features> {
+        let features = ENABLED_FEATURES
+            .may_load(store)?
+            .map_or_else(Features::default, Features::from_bits);
+        Ok(features)
+    }
+
+    /// Set the smallest amount a single collection may pay out, `None` removing the minimum
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_min_collect_amount<Store: MutStorage>(
+        store: &mut Store,
+        amount: Option<NonZeroU128>,
+    ) -> StoreResult<Store> {
+        MIN_COLLECT_AMOUNT.save(store, amount.map_or(0, NonZeroU128::get))?;
+        Ok(())
+    }
+
+    /// Get the smallest amount a single collection may pay out, if any
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn min_collect_amount<Store: Storage>(
+        store: &Store,
+    ) -> StoreResult<Store, Option<NonZeroU128>> {
+        let amount = MIN_COLLECT_AMOUNT
+            .may_load(store)?
+            .and_then(NonZeroU128::new);
+        Ok(amount)
+    }
+
+    /// Set whether the hub may activate itself as a dApp
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn set_self_dapp_allowed<Store: MutStorage>(
+        store: &mut Store,
+        allowed: bool,
+    ) -> StoreResult<Store> {
+        ALLOW_SELF_DAPP.save(store, &allowed)?;
+        Ok(())
+    }
+
+    /// Get whether the hub may activate itself as a dApp, allowed if never set
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with the underlying storage.
+    pub fn self_dapp_allowed<Store: Storage>(store: &Store) -> StoreResult<Store, bool> {
+        let allowed = ALLOW_SELF_DAPP.may_load(store)?.unwrap_or(true);
+        Ok(allowed)
     }
 }
 
@@ -111,8 +321,12 @@ pub mod rewards_pot {
 
     static DAPP: Item<String> = item!("dapp");
     static CREATOR: Item<String> = item!("creator");
-    static REWARDS_COLLECTED: Item<u128> = item!("rewards_collected");
+    static REWARDS_COLLECTED: Map<1024, &str, u128> = map!("rewards_collected");
     static REWARDS_RECORDS_COLLECTED: Item<u64> = item!("rewards_records_collected");
+    static REWARDS_DISTRIBUTED: Map<1024, &str, u128> = map!("rewards_distributed");
+    static WITHDRAW_RECORDS_LIMIT: Item<u64> = item!("withdraw_records_limit");
+    static MIN_WITHDRAW_INTERVAL: Item<u64> = item!("min_withdraw_interval");
+    static LAST_WITHDRAW_TIME: Item<u64> = item!("last_withdraw_time");
 
     /// Set owner dApp address
     ///
@@ -155,29 +369,133 @@ pub mod rewards_pot {
         CREATOR.may_load(store).map_err(Error::from)
     }
 
-    /// Set the total rewards collected
+    /// Set the total rewards collected in a denom
     ///
     /// # Errors
     ///
     /// This function will return an error if there is an underlying storage issue.
     pub fn set_total_rewards_collected<Store: MutStorage>(
         store: &mut Store,
+        denom: &str,
         amount: u128,
     ) -> StoreResult<Store, ()> {
-        REWARDS_COLLECTED.save(store, amount)?;
+        REWARDS_COLLECTED.save(store, denom, amount)?;
         Ok(())
     }
 
-    /// Get total rewards collected
+    /// Get total rewards collected in a denom
     ///
     /// # Errors
     ///
     /// This function will return an error if there is an underlying storage issue.
-    pub fn total_rewards_collected<Store: Storage>(store: &Store) -> StoreResult<Store, u128> {
-        let collected = REWARDS_COLLECTED.may_load(store)?.unwrap_or_default();
+    pub fn total_rewards_collected<Store: Storage>(
+        store: &Store,
+        denom: &str,
+    ) -> StoreResult<Store, u128> {
+        let collected = REWARDS_COLLECTED
+            .may_load(store, denom)?
+            .unwrap_or_default();
         Ok(collected)
     }
 
+    /// Set the maximum number of rewards records withdrawn at once
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn set_withdraw_records_limit<Store: MutStorage>(
+        store: &mut Store,
+        limit: u64,
+    ) -> StoreResult<Store, ()> {
+        WITHDRAW_RECORDS_LIMIT.save(store, limit)?;
+        Ok(())
+    }
+
+    /// Get the maximum number of rewards records withdrawn at once, if set
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn withdraw_records_limit<Store: Storage>(
+        store: &Store,
+    ) -> StoreResult<Store, Option<u64>> {
+        WITHDRAW_RECORDS_LIMIT.may_load(store).map_err(Error::from)
+    }
+
+    /// Set the minimum number of seconds between withdrawals
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn set_min_withdraw_interval<Store: MutStorage>(
+        store: &mut Store,
+        secs: u64,
+    ) -> StoreResult<Store, ()> {
+        MIN_WITHDRAW_INTERVAL.save(store, secs)?;
+        Ok(())
+    }
+
+    /// Get the minimum number of seconds between withdrawals, zero if never set
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn min_withdraw_interval<Store: Storage>(store: &Store) -> StoreResult<Store, u64> {
+        let secs = MIN_WITHDRAW_INTERVAL.may_load(store)?.unwrap_or_default();
+        Ok(secs)
+    }
+
+    /// Set the block time of the last withdrawal, in seconds
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn set_last_withdraw_time<Store: MutStorage>(
+        store: &mut Store,
+        time: u64,
+    ) -> StoreResult<Store, ()> {
+        LAST_WITHDRAW_TIME.save(store, time)?;
+        Ok(())
+    }
+
+    /// Get the block time of the last withdrawal in seconds, if any
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn last_withdraw_time<Store: Storage>(store: &Store) -> StoreResult<Store, Option<u64>> {
+        LAST_WITHDRAW_TIME.may_load(store).map_err(Error::from)
+    }
+
+    /// Set the total rewards distributed in a denom
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn set_total_rewards_distributed<Store: MutStorage>(
+        store: &mut Store,
+        denom: &str,
+        amount: u128,
+    ) -> StoreResult<Store, ()> {
+        REWARDS_DISTRIBUTED.save(store, denom, amount)?;
+        Ok(())
+    }
+
+    /// Get total rewards distributed in a denom
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an underlying storage issue.
+    pub fn total_rewards_distributed<Store: Storage>(
+        store: &Store,
+        denom: &str,
+    ) -> StoreResult<Store, u128> {
+        let distributed = REWARDS_DISTRIBUTED
+            .may_load(store, denom)?
+            .unwrap_or_default();
+        Ok(distributed)
+    }
+
     /// Set the number of rewards records collected
     ///
     /// # Errors
@@ -1,24 +1,34 @@
-use std::num::NonZeroU128;
+use std::num::{NonZeroU128, NonZeroU64};
 
-use archway_bindings::types::rewards::{ContractMetadataResponse, FlatFeeResponse};
 use archway_bindings::{ArchwayMsg, ArchwayQuery};
-use cosmwasm_std::{Coin, Deps, DepsMut, Env, SubMsg, WasmMsg};
+use cosmwasm_std::{Addr, Attribute, BankMsg, Coin, Deps, DepsMut, Env, SubMsg, Uint128, WasmMsg};
 
 use kv_storage::{MutStorage, Storage};
+use kv_storage_cosmwasm::CosmwasmRepo;
 
 use referrals_core::hub::{
-    CollectQuery, DappExternalQuery, DappsQuery, HandleReply, MutableCollectStore,
-    MutableDappStore, MutableReferralStore, NonZeroPercent, ReadonlyCollectStore,
-    ReadonlyDappStore, ReadonlyReferralStore, ReferralCode, ReferrersQuery,
+    Activity, CollectQuery, Command, DappCampaign, DappExternalQuery, DappsQuery, Features,
+    HandleReply, MutableCollectStore, MutableConfigStore, MutableDappStore, MutableReferralStore,
+    NonZeroPercent, ReadonlyCollectStore, ReadonlyConfigStore, ReadonlyDappStore,
+    ReadonlyReferralStore, Recording, ReferralCode, ReferrersQuery, RewardsPotsQuery, TotalsQuery,
 };
-use referrals_core::{FallibleApi, Id};
+use referrals_core::{FallibleApi, Id, IdRef};
+use referrals_cw::address::rewards_pot_salt;
 use referrals_cw::rewards_pot::{
-    AdminResponse, ExecuteMsg as PotExecMsg, InstantiateMsg as PotInitMsg,
-    QueryMsg as RewardsPotQuery, TotalRewardsResponse,
+    AdminResponse, DappResponse as PotDappResponse, ExecuteMsg as PotExecMsg,
+    InstantiateMsg as PotInitMsg, QueryMsg as RewardsPotQuery, TotalRewardsResponse,
 };
-use referrals_cw::ReferralCodeResponse;
+use referrals_cw::{
+    CollectReferrerAllResponse, EnabledFeatures as CwEnabledFeatures, HubConfigResponse,
+    ReferralCodeResponse,
+};
+use referrals_storage::Error as CoreStorageError;
+use referrals_storage::{RecordedWrite, RecordingStore};
 
-use crate::{cache, Api, CwMutStore, CwStore, CwStoreError, Error as BaseApiError, Response};
+use crate::{
+    cache, Api, CwMutStore, CwRecordingStore, CwStore, CwStoreError, Error as BaseApiError,
+    ReplyId, Response,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -26,6 +36,14 @@ pub enum Error {
     NotInitialized,
     #[error("dApp fee has not been set")]
     DappFeeNotSet,
+    #[error("rewards denom has not been configured")]
+    RewardsDenomNotConfigured,
+    #[error("invalid rewards denom - {0:?} is blank")]
+    InvalidRewardsDenom(String),
+    #[error("invalid rewards pot address - {0}")]
+    RewardsPotAddress(String),
+    #[error("insufficient premium - required {required}, attached {attached}")]
+    InsufficientPremium { required: u128, attached: u128 },
 }
 
 pub type ApiError<StoreError> = BaseApiError<StoreError, Error>;
@@ -37,7 +55,12 @@ pub struct Hub;
 
 #[must_use]
 pub fn from_deps<'a>(deps: Deps<'a, ArchwayQuery>, env: &'a Env) -> Api<'a, Hub, CwStore<'a>> {
-    Api::new(CwStore::from_repo(deps.storage), env, deps.querier)
+    Api::new(
+        CwStore::from_repo(deps.storage),
+        env,
+        deps.api,
+        deps.querier,
+    )
 }
 
 #[must_use]
@@ -46,13 +69,55 @@ pub fn from_deps_mut<'a>(
     env: &'a Env,
 ) -> Api<'a, Hub, CwMutStore<'a>> {
     let deps = deps.branch();
-    Api::new(CwMutStore::from_repo(deps.storage), env, deps.querier)
+    Api::new(
+        CwMutStore::from_repo(deps.storage),
+        env,
+        deps.api,
+        deps.querier,
+    )
+}
+
+/// An api over the hub's storage that records writes rather than committing them, see
+/// [`referrals_core::hub::simulate`].
+#[must_use]
+pub fn from_deps_recording<'a>(
+    deps: Deps<'a, ArchwayQuery>,
+    env: &'a Env,
+) -> Api<'a, Hub, CwRecordingStore<'a>> {
+    Api::new(
+        CwRecordingStore::from_repo(RecordingStore::new(CosmwasmRepo::from(deps.storage))),
+        env,
+        deps.api,
+        deps.querier,
+    )
 }
 
 impl FallibleApi for Hub {
     type Error = Error;
 }
 
+/// Label for a dApp's rewards pot, the dApp name is limited to label-safe characters.
+fn rewards_pot_label(name: &str, count: u64) -> String {
+    const MAX_LABEL_LEN: usize = 128;
+    const PREFIX: &str = "referrals-reward-pot-";
+
+    let suffix = format!("-{count}");
+
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(MAX_LABEL_LEN - PREFIX.len() - suffix.len())
+        .collect();
+
+    format!("{PREFIX}{name}{suffix}")
+}
+
 impl<'a, Store> Api<'a, Hub, Store>
 where
     Store: MutStorage,
@@ -61,13 +126,230 @@ where
     ///
     /// # Errors
     ///
-    /// This function will return an error if there is an issue with underlying storage.
-    pub fn initialize(&mut self, rewards_pot_code_id: u64) -> ApiResult<(), Store::Error> {
+    /// This function will return an error if:
+    /// - The `rewards_denom` is empty or only whitespace, as dApp fees could not be set.
+    /// - There is an issue with underlying storage.
+    pub fn initialize(
+        &mut self,
+        rewards_pot_code_id: u64,
+        contract_premium: u128,
+        rewards_denom: &String,
+        block_self_referral: bool,
+    ) -> ApiResult<(), Store::Error> {
+        if rewards_denom.trim().is_empty() {
+            return Err(ApiError::Mode(Error::InvalidRewardsDenom(
+                rewards_denom.clone(),
+            )));
+        }
+
         cache::hub::set_reward_pot_code_id(&mut self.store, rewards_pot_code_id)?;
+        cache::hub::set_contract_premium(&mut self.store, contract_premium)?;
+        cache::set_rewards_denom(&mut self.store, rewards_denom)?;
+        self.set_self_referral_blocked(block_self_referral)?;
+
+        Ok(())
+    }
+
+    /// Set the referral code about to be credited on behalf of a new registrant,
+    /// so a failure to do so can be reported.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with underlying storage.
+    pub fn set_pending_credit_code(&mut self, code: u64) -> ApiResult<(), Store::Error> {
+        cache::hub::set_pending_credit_code(&mut self.store, code)?;
+        Ok(())
+    }
+
+    /// Take the fee set while activating a dApp, held back until it's rewards pot is instantiated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with underlying storage.
+    pub fn take_pending_dapp_fee(
+        &mut self,
+        dapp: &Id,
+    ) -> ApiResult<Option<NonZeroU128>, Store::Error> {
+        cache::hub::take_pending_pot_fee(&mut self.store, dapp).map_err(ApiError::from)
+    }
+
+    /// Check the funds attached to a collection cover the contract premium.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The API has not been initialized.
+    /// - Less than the contract premium is attached in the rewards denom.
+    /// - There is an issue with the underlying querier or storage.
+    pub fn check_premium(&mut self, funds: &[Coin]) -> ApiResult<(), Store::Error> {
+        let required = cache::hub::contract_premium(&self.store)?
+            .ok_or(Error::NotInitialized)
+            .map_err(ApiError::Mode)?;
+
+        if required == 0 {
+            return Ok(());
+        }
+
+        let denom = self.rewards_denom()?;
+
+        let attached = funds
+            .iter()
+            .find(|coin| coin.denom == denom)
+            .map_or(0, |coin| coin.amount.u128());
+
+        if attached < required {
+            return Err(ApiError::Mode(Error::InsufficientPremium {
+                required,
+                attached,
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Pass the contract premium attached to a collection on to the hub's collector, rather than
+    /// leaving it in the hub. A hub without a collector keeps it. Anything attached beyond the
+    /// premium, in the rewards denom or otherwise, is refunded to the `sender`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The API has not been initialized.
+    /// - There is an issue with underlying storage.
+    pub fn forward_premium(
+        &mut self,
+        sender: &Addr,
+        funds: Vec<Coin>,
+    ) -> ApiResult<(), Store::Error> {
+        if funds.is_empty() {
+            return Ok(());
+        }
+
+        let required = cache::hub::contract_premium(&self.store)?
+            .ok_or(Error::NotInitialized)
+            .map_err(ApiError::Mode)?;
+
+        let denom = self.rewards_denom()?;
+
+        let mut premium = 0;
+        let mut excess = vec![];
+
+        for mut coin in funds {
+            if coin.denom == denom {
+                let taken = coin.amount.u128().min(required - premium);
+                premium += taken;
+                coin.amount -= Uint128::new(taken);
+            }
+
+            if !coin.amount.is_zero() {
+                excess.push(coin);
+            }
+        }
+
+        let hub = Id::from(self.env.contract.address.clone());
+
+        let collector = match self.core_storage().collector(&hub) {
+            Ok(collector) => Some(collector),
+            Err(CoreStorageError::NotFound) => None,
+            Err(err) => return Err(ApiError::from(err)),
+        };
+
+        if let Some(collector) = collector.filter(|_| premium > 0) {
+            self.response.messages.push(SubMsg::new(BankMsg::Send {
+                to_address: collector.into_string(),
+                amount: vec![Coin::new(premium, denom)],
+            }));
+        }
+
+        if !excess.is_empty() {
+            self.response.messages.push(SubMsg::new(BankMsg::Send {
+                to_address: sender.to_string(),
+                amount: excess,
+            }));
+        }
+
         Ok(())
     }
 }
 
+impl<'a, Store> Api<'a, Hub, Store>
+where
+    Store: Storage,
+{
+    /// The dApp whose rewards pot instantiation is awaiting a reply.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with underlying storage.
+    pub fn pending_rewards_pot_dapp(&self) -> ApiResult<Option<Id>, Store::Error> {
+        cache::hub::pending_pot_dapp(&self.store).map_err(ApiError::from)
+    }
+
+    /// The referral code whose crediting on behalf of a new registrant is awaiting a reply.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with underlying storage.
+    pub fn pending_credit_code(&self) -> ApiResult<Option<u64>, Store::Error> {
+        cache::hub::pending_credit_code(&self.store).map_err(ApiError::from)
+    }
+
+    /// The commands deferred until the latest rewards withdrawal settles.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue with underlying storage.
+    pub fn deferred_commands(&self) -> ApiResult<Vec<Command>, Store::Error> {
+        cache::hub::deferred_cmds(&self.store).map_err(ApiError::from)
+    }
+
+    /// The hub's configuration.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The API has not been initialized.
+    /// - There is an issue with underlying storage.
+    pub fn hub_config(&self) -> ApiResult<HubConfigResponse, Store::Error> {
+        let rewards_pot_code_id = cache::hub::reward_pot_code_id(&self.store)?
+            .ok_or(Error::NotInitialized)
+            .map_err(ApiError::Mode)?;
+
+        let contract_premium = cache::hub::contract_premium(&self.store)?
+            .ok_or(Error::NotInitialized)
+            .map_err(ApiError::Mode)?;
+
+        let enabled_features = cache::hub::enabled_features(&self.store)?;
+
+        let min_collect_amount = cache::hub::min_collect_amount(&self.store)?;
+
+        let allow_self_dapp = cache::hub::self_dapp_allowed(&self.store)?;
+
+        // the hub only has a collector once it's self-activation has been processed,
+        // or from instantiation if it opted out of self-activation
+        let collector = match self.core_storage().collector(&self.self_id()?) {
+            Ok(collector) => Some(collector.into_string()),
+            Err(CoreStorageError::NotFound) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(HubConfigResponse {
+            rewards_pot_code_id,
+            rewards_pot_count: cache::hub::reward_pot_count(&self.store)?,
+            contract_premium: contract_premium.into(),
+            collector,
+            enabled_features: CwEnabledFeatures {
+                register: enabled_features.contains(Features::REGISTER),
+                record: enabled_features.contains(Features::RECORD),
+                collect: enabled_features.contains(Features::COLLECT),
+                configure: enabled_features.contains(Features::CONFIGURE),
+            },
+            min_collect_amount: min_collect_amount.map(|amount| amount.get().into()),
+            allow_self_dapp,
+        })
+    }
+}
+
 impl<'a, Store> FallibleApi for Api<'a, Hub, Store>
 where
     Store: Storage,
@@ -75,6 +357,14 @@ where
     type Error = ApiError<Store::Error>;
 }
 
+impl<'a> Recording for Api<'a, Hub, CwRecordingStore<'a>> {
+    type Write = RecordedWrite;
+
+    fn recorded_writes(&self) -> Vec<RecordedWrite> {
+        self.store.repo().writes()
+    }
+}
+
 impl<'a, Store> HandleReply for Api<'a, Hub, Store>
 where
     Store: MutStorage,
@@ -100,21 +390,31 @@ where
             .ok_or(Error::NotInitialized)
             .map_err(ApiError::Mode)?;
 
-        let count = cache::hub::increment_reward_pot_count(&mut self.store)?;
+        let name = self.dapp_name(&dapp)?.unwrap_or_default();
+
+        let label = rewards_pot_label(&name, cache::hub::reward_pot_count(&self.store)?);
+
+        let salt = rewards_pot_salt(dapp.as_str());
+
+        cache::hub::set_pending_pot_dapp(&mut self.store, &dapp)?;
 
         let msg = cosmwasm_std::to_binary(&PotInitMsg {
             dapp: dapp.into_string(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: cache::rewards_denom(&self.store)?,
         })?;
 
-        self.response.messages.push(SubMsg::reply_on_success(
-            WasmMsg::Instantiate {
+        self.response.messages.push(SubMsg::reply_always(
+            WasmMsg::Instantiate2 {
                 admin: None,
                 code_id,
+                label,
                 msg,
                 funds: vec![],
-                label: format!("referrals-reward-pot-{count}"),
+                salt,
             },
-            0, // only reply_on message we send
+            ReplyId::CreateRewardsPot.as_u64(),
         ));
 
         Ok(())
@@ -143,7 +443,22 @@ where
     }
 
     fn set_dapp_fee(&mut self, dapp: Id, amount: NonZeroU128) -> Result<(), Self::Error> {
-        let denom = self.rewards_denom()?;
+        // the chain rejects a flat fee without a denom, so one must have been configured
+        let denom = cache::rewards_denom(&self.store)?
+            .filter(|denom| !denom.is_empty())
+            .ok_or(Error::RewardsDenomNotConfigured)
+            .map_err(ApiError::Mode)?;
+
+        // a dApp without a pot is still being activated, it's fee waits for the pot to be in place
+        if !self.core_storage().has_rewards_pot(&dapp)? {
+            cache::hub::set_pending_pot_fee(&mut self.store, &dapp, amount)?;
+            return Ok(());
+        }
+
+        cache::hub::cache_dapp_fee(&mut self.store, &dapp, amount)?;
+
+        self.memo.fees.get_mut().remove(dapp.as_str());
+
         self.response
             .messages
             .push(SubMsg::new(ArchwayMsg::set_flat_fee(
@@ -166,6 +481,24 @@ where
         Ok(())
     }
 
+    fn withdraw_rewards_then(&mut self, pot: Id, then: Vec<Command>) -> Result<(), Self::Error> {
+        let msg = cosmwasm_std::to_binary(&PotExecMsg::WithdrawRewards {})?;
+
+        cache::hub::set_deferred_cmds(&mut self.store, &then)?;
+
+        self.response.messages.push(SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: pot.into_string(),
+                msg,
+                funds: vec![],
+            },
+            // deferred commands are enacted once the withdrawal replies
+            ReplyId::WithdrawRewards.as_u64(),
+        ));
+
+        Ok(())
+    }
+
     fn distribute_rewards(
         &mut self,
         pot: Id,
@@ -175,6 +508,22 @@ where
         let msg = cosmwasm_std::to_binary(&PotExecMsg::DistributeRewards {
             recipient: receiver.into_string(),
             amount: amount.get().into(),
+            clamp: None,
+            denom: None,
+        })?;
+
+        self.response.messages.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: pot.into_string(),
+            msg,
+            funds: vec![],
+        }));
+
+        Ok(())
+    }
+
+    fn update_rewards_pot_admin(&mut self, pot: Id, admin: Id) -> Result<(), Self::Error> {
+        let msg = cosmwasm_std::to_binary(&PotExecMsg::UpdateAdmin {
+            new_admin: admin.into_string(),
         })?;
 
         self.response.messages.push(SubMsg::new(WasmMsg::Execute {
@@ -185,6 +534,23 @@ where
 
         Ok(())
     }
+
+    fn add_collect_cursor(&mut self, next: Option<u64>) -> Result<(), Self::Error> {
+        let data = cosmwasm_std::to_binary(&CollectReferrerAllResponse { next })?;
+
+        self.response.data = Some(data);
+
+        Ok(())
+    }
+
+    fn add_invocation_cap_reached(&mut self, code: ReferralCode) -> Result<(), Self::Error> {
+        self.response.attributes.push(Attribute::new(
+            "invocation_cap_reached",
+            code.to_u64().to_string(),
+        ));
+
+        Ok(())
+    }
 }
 
 impl<'a, Store> DappExternalQuery for Api<'a, Hub, Store>
@@ -196,9 +562,9 @@ where
     }
 
     fn rewards_admin(&self, id: &Id) -> Result<Id, Self::Error> {
-        let contract_metadata: ContractMetadataResponse = self
-            .querier
-            .query(&ArchwayQuery::contract_metadata(id.clone().into_string()).into())
+        let contract_metadata = self
+            .rewards
+            .contract_metadata(id.as_str())
             .map_err(ApiError::CosmWasmStd)?;
 
         Ok(Id::from(contract_metadata.owner_address))
@@ -213,13 +579,49 @@ where
         Ok(Id::from(response.admin))
     }
 
-    fn current_fee(&self, id: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
-        let response: FlatFeeResponse = self
+    fn rewards_pot_dapp(&self, id: &Id) -> Result<Id, Self::Error> {
+        let response: PotDappResponse = self
             .querier
-            .query(&ArchwayQuery::flat_fee(id.as_str()).into())
+            .query_wasm_smart(id.clone().into_string(), &RewardsPotQuery::Dapp {})
             .map_err(ApiError::CosmWasmStd)?;
 
-        Ok(NonZeroU128::new(response.flat_fee_amount.amount.u128()))
+        Ok(Id::from(response.dapp))
+    }
+
+    fn current_fee(&self, id: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        if let Some(&fee) = self.memo.fees.borrow().get(id.as_str()) {
+            return Ok(Some(fee));
+        }
+
+        // fees set through the hub are cached, for chains without flat fee support
+        let fee = match cache::hub::cached_dapp_fee(&self.store, id)? {
+            Some(fee) => Some(fee),
+            None => {
+                let response = self
+                    .rewards
+                    .flat_fee(id.as_str())
+                    .map_err(ApiError::CosmWasmStd)?;
+
+                NonZeroU128::new(response.flat_fee_amount.amount.u128())
+            }
+        };
+
+        if let Some(fee) = fee {
+            self.memo
+                .fees
+                .borrow_mut()
+                .insert(id.as_str().to_owned(), fee);
+        }
+
+        Ok(fee)
+    }
+
+    fn current_height(&self) -> Result<u64, Self::Error> {
+        Ok(self.env.block.height)
+    }
+
+    fn current_time(&self) -> Result<u64, Self::Error> {
+        Ok(self.env.block.time.seconds())
     }
 }
 
@@ -237,6 +639,40 @@ where
     }
 }
 
+impl<'a, Store> ReadonlyConfigStore for Api<'a, Hub, Store>
+where
+    Store: Storage,
+{
+    fn enabled_features(&self) -> Result<Features, Self::Error> {
+        cache::hub::enabled_features(&self.store).map_err(ApiError::from)
+    }
+
+    fn min_collect_amount(&self) -> Result<Option<NonZeroU128>, Self::Error> {
+        cache::hub::min_collect_amount(&self.store).map_err(ApiError::from)
+    }
+
+    fn self_dapp_allowed(&self) -> Result<bool, Self::Error> {
+        cache::hub::self_dapp_allowed(&self.store).map_err(ApiError::from)
+    }
+}
+
+impl<'a, Store> MutableConfigStore for Api<'a, Hub, Store>
+where
+    Store: MutStorage,
+{
+    fn set_enabled_features(&mut self, features: Features) -> Result<(), Self::Error> {
+        cache::hub::set_enabled_features(&mut self.store, features).map_err(ApiError::from)
+    }
+
+    fn set_min_collect_amount(&mut self, amount: Option<NonZeroU128>) -> Result<(), Self::Error> {
+        cache::hub::set_min_collect_amount(&mut self.store, amount).map_err(ApiError::from)
+    }
+
+    fn set_self_dapp_allowed(&mut self, allowed: bool) -> Result<(), Self::Error> {
+        cache::hub::set_self_dapp_allowed(&mut self.store, allowed).map_err(ApiError::from)
+    }
+}
+
 // Delegation to CoreStorage boilerplate
 
 impl<'a, Store> ReadonlyDappStore for Api<'a, Hub, Store>
@@ -247,8 +683,21 @@ where
         self.core_storage().dapp_exists(id).map_err(ApiError::from)
     }
 
-    fn percent(&self, id: &Id) -> Result<NonZeroPercent, Self::Error> {
-        self.core_storage().percent(id).map_err(ApiError::from)
+    fn percent(&self, id: &Id) -> Result<Option<NonZeroPercent>, Self::Error> {
+        if let Some(&percent) = self.memo.percents.borrow().get(id.as_str()) {
+            return Ok(Some(percent));
+        }
+
+        let percent = self.core_storage().percent(id)?;
+
+        if let Some(percent) = percent {
+            self.memo
+                .percents
+                .borrow_mut()
+                .insert(id.as_str().to_owned(), percent);
+        }
+
+        Ok(percent)
     }
 
     fn collector(&self, id: &Id) -> Result<Id, Self::Error> {
@@ -264,6 +713,42 @@ where
     fn rewards_pot(&self, id: &Id) -> Result<Id, Self::Error> {
         self.core_storage().rewards_pot(id).map_err(ApiError::from)
     }
+
+    fn self_referral_allowed(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.core_storage()
+            .self_referral_allowed(id)
+            .map_err(ApiError::from)
+    }
+
+    fn pending_collector(&self, id: &Id) -> Result<Option<Id>, Self::Error> {
+        self.core_storage()
+            .pending_collector(id)
+            .map_err(ApiError::from)
+    }
+
+    fn campaign(&self, id: &Id) -> Result<Option<DappCampaign>, Self::Error> {
+        self.core_storage().campaign(id).map_err(ApiError::from)
+    }
+
+    fn max_invocations_per_code(&self, id: &Id) -> Result<Option<NonZeroU64>, Self::Error> {
+        self.core_storage()
+            .max_invocations_per_code(id)
+            .map_err(ApiError::from)
+    }
+
+    fn dapp_frozen(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.core_storage().dapp_frozen(id).map_err(ApiError::from)
+    }
+
+    fn dapp_by_name(&self, name: &str) -> Result<Option<Id>, Self::Error> {
+        self.core_storage()
+            .dapp_by_name(name)
+            .map_err(ApiError::from)
+    }
+
+    fn relayer(&self, id: &Id) -> Result<Option<Id>, Self::Error> {
+        self.core_storage().relayer(id).map_err(ApiError::from)
+    }
 }
 
 impl<'a, Store> MutableDappStore for Api<'a, Hub, Store>
@@ -283,11 +768,19 @@ where
     }
 
     fn set_percent(&mut self, id: &Id, percent: NonZeroPercent) -> Result<(), Self::Error> {
+        self.memo.percents.get_mut().remove(id.as_str());
+
         self.core_storage_mut()
             .set_percent(id, percent)
             .map_err(ApiError::from)
     }
 
+    fn set_name(&mut self, id: &Id, name: String) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_name(id, name)
+            .map_err(ApiError::from)
+    }
+
     fn set_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
         self.core_storage_mut()
             .set_collector(id, collector)
@@ -300,9 +793,87 @@ where
             .map_err(ApiError::from)
     }
 
+    fn clear_repo_url(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .clear_repo_url(id)
+            .map_err(ApiError::from)
+    }
+
     fn set_rewards_pot(&mut self, id: &Id, rewards_pot: Id) -> Result<(), Self::Error> {
+        self.core_storage_mut().set_rewards_pot(id, rewards_pot)?;
+        cache::hub::increment_reward_pot_count(&mut self.store)?;
+        Ok(())
+    }
+
+    fn set_self_referral_allowed(&mut self, id: &Id, allowed: bool) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_self_referral_allowed(id, allowed)
+            .map_err(ApiError::from)
+    }
+
+    fn set_pending_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_pending_collector(id, collector)
+            .map_err(ApiError::from)
+    }
+
+    fn remove_pending_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .remove_pending_collector(id)
+            .map_err(ApiError::from)
+    }
+
+    fn remove_percent(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.memo.percents.get_mut().remove(id.as_str());
+
         self.core_storage_mut()
-            .set_rewards_pot(id, rewards_pot)
+            .remove_percent(id)
+            .map_err(ApiError::from)
+    }
+
+    fn remove_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .remove_collector(id)
+            .map_err(ApiError::from)
+    }
+
+    fn set_campaign(&mut self, id: &Id, campaign: DappCampaign) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_campaign(id, campaign)
+            .map_err(ApiError::from)
+    }
+
+    fn set_max_invocations_per_code(
+        &mut self,
+        id: &Id,
+        max: NonZeroU64,
+    ) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_max_invocations_per_code(id, max)
+            .map_err(ApiError::from)
+    }
+
+    fn set_dapp_frozen(&mut self, id: &Id, frozen: bool) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_dapp_frozen(id, frozen)
+            .map_err(ApiError::from)
+    }
+
+    fn set_relayer(&mut self, id: &Id, relayer: Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_relayer(id, relayer)
+            .map_err(ApiError::from)
+    }
+
+    fn remove_relayer(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .remove_relayer(id)
+            .map_err(ApiError::from)
+    }
+
+    fn reset_dapp_accounting(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .reset_dapp_accounting(id)
             .map_err(ApiError::from)
     }
 }
@@ -317,7 +888,7 @@ where
             .map_err(ApiError::from)
     }
 
-    fn owner_exists(&self, owner: &Id) -> Result<bool, Self::Error> {
+    fn owner_exists(&self, owner: IdRef<'_>) -> Result<bool, Self::Error> {
         self.core_storage()
             .owner_exists(owner)
             .map_err(ApiError::from)
@@ -339,7 +910,7 @@ where
 
     fn dapp_earnings(
         &self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         code: ReferralCode,
     ) -> Result<Option<NonZeroU128>, Self::Error> {
         self.core_storage()
@@ -347,11 +918,41 @@ where
             .map_err(ApiError::from)
     }
 
-    fn dapp_contributions(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+    fn dapp_contributions(&self, dapp: IdRef<'_>) -> Result<Option<NonZeroU128>, Self::Error> {
         self.core_storage()
             .dapp_contributions(dapp)
             .map_err(ApiError::from)
     }
+
+    fn code_dapp_count(&self, code: ReferralCode) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .code_dapp_count(code)
+            .map_err(ApiError::from)
+    }
+
+    fn code_dapp(&self, code: ReferralCode, index: u64) -> Result<Option<Id>, Self::Error> {
+        self.core_storage()
+            .code_dapp(code, index)
+            .map_err(ApiError::from)
+    }
+
+    fn code_blocked(&self, code: ReferralCode) -> Result<bool, Self::Error> {
+        self.core_storage()
+            .code_blocked(code)
+            .map_err(ApiError::from)
+    }
+
+    fn invocations(&self, dapp: IdRef<'_>, code: ReferralCode) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .invocations(dapp, code)
+            .map_err(ApiError::from)
+    }
+
+    fn self_referral_blocked(&self) -> Result<bool, Self::Error> {
+        self.core_storage()
+            .self_referral_blocked()
+            .map_err(ApiError::from)
+    }
 }
 
 impl<'a, Store> MutableReferralStore for Api<'a, Hub, Store>
@@ -370,12 +971,37 @@ where
             .map_err(ApiError::from)
     }
 
-    fn increment_invocations(&mut self, dapp: &Id, code: ReferralCode) -> Result<(), Self::Error> {
+    fn increment_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+    ) -> Result<(), Self::Error> {
         self.core_storage_mut()
             .increment_invocations(dapp, code)
             .map_err(ApiError::from)
     }
 
+    fn increment_daily_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        day: u64,
+    ) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .increment_daily_invocations(dapp, day)
+            .map_err(ApiError::from)
+    }
+
+    fn touch(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+        activity: Activity,
+    ) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .touch(dapp, code, activity)
+            .map_err(ApiError::from)
+    }
+
     fn set_total_earnings(
         &mut self,
         code: ReferralCode,
@@ -388,7 +1014,7 @@ where
 
     fn set_dapp_earnings(
         &mut self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         code: ReferralCode,
         total: NonZeroU128,
     ) -> Result<(), Self::Error> {
@@ -399,13 +1025,25 @@ where
 
     fn set_dapp_contributions(
         &mut self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         contributions: NonZeroU128,
     ) -> Result<(), Self::Error> {
         self.core_storage_mut()
             .set_dapp_contributions(dapp, contributions)
             .map_err(ApiError::from)
     }
+
+    fn set_code_blocked(&mut self, code: ReferralCode, blocked: bool) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_code_blocked(code, blocked)
+            .map_err(ApiError::from)
+    }
+
+    fn set_self_referral_blocked(&mut self, blocked: bool) -> Result<(), Self::Error> {
+        self.core_storage_mut()
+            .set_self_referral_blocked(blocked)
+            .map_err(ApiError::from)
+    }
 }
 
 impl<'a, Store> ReadonlyCollectStore for Api<'a, Hub, Store>
@@ -436,6 +1074,12 @@ where
             .dapp_total_collected(dapp)
             .map_err(ApiError::from)
     }
+
+    fn dapp_referrers_collected(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.core_storage()
+            .dapp_referrers_collected(dapp)
+            .map_err(ApiError::from)
+    }
 }
 
 impl<'a, Store> MutableCollectStore for Api<'a, Hub, Store>
@@ -490,6 +1134,10 @@ where
             .map_err(ApiError::from)
     }
 
+    fn dapp_index(&self, dapp: &Id) -> Result<Option<u64>, Self::Error> {
+        self.core_storage().dapp_index(dapp).map_err(ApiError::from)
+    }
+
     fn dapp_name(&self, dapp: &Id) -> Result<Option<String>, Self::Error> {
         self.core_storage().dapp_name(dapp).map_err(ApiError::from)
     }
@@ -511,6 +1159,82 @@ where
             .dapp_discrete_referrers(dapp)
             .map_err(ApiError::from)
     }
+
+    fn dapp_last_active(&self, dapp: &Id) -> Result<Option<Activity>, Self::Error> {
+        self.core_storage()
+            .dapp_last_active(dapp)
+            .map_err(ApiError::from)
+    }
+
+    fn dapp_daily_invocations(&self, dapp: &Id, day: u64) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .dapp_daily_invocations(dapp, day)
+            .map_err(ApiError::from)
+    }
+}
+
+impl<'a, Store> RewardsPotsQuery for Api<'a, Hub, Store>
+where
+    Store: Storage,
+{
+    fn expected_rewards_pot(&self, dapp: &Id) -> Result<Id, Self::Error> {
+        let code_id = cache::hub::reward_pot_code_id(&self.store)?
+            .ok_or(Error::NotInitialized)
+            .map_err(ApiError::Mode)?;
+
+        let code_info = self.querier.query_wasm_code_info(code_id)?;
+
+        let creator = self
+            .cw_api
+            .addr_canonicalize(self.env.contract.address.as_str())?;
+
+        let address = cosmwasm_std::instantiate2_address(
+            code_info.checksum.as_slice(),
+            &creator,
+            rewards_pot_salt(dapp.as_str()).as_slice(),
+        )
+        .map_err(|err| ApiError::Mode(Error::RewardsPotAddress(err.to_string())))?;
+
+        self.cw_api
+            .addr_humanize(&address)
+            .map(Id::from)
+            .map_err(ApiError::from)
+    }
+}
+
+impl<'a, Store> TotalsQuery for Api<'a, Hub, Store>
+where
+    Store: Storage,
+{
+    fn active_dapp_count(&self) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .active_dapp_count()
+            .map_err(ApiError::from)
+    }
+
+    fn total_referral_codes(&self) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .total_referral_codes()
+            .map_err(ApiError::from)
+    }
+
+    fn total_invocations(&self) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .total_invocations()
+            .map_err(ApiError::from)
+    }
+
+    fn total_contributions(&self) -> Result<u128, Self::Error> {
+        self.core_storage()
+            .total_contributions()
+            .map_err(ApiError::from)
+    }
+
+    fn total_collected(&self) -> Result<u128, Self::Error> {
+        self.core_storage()
+            .total_collected()
+            .map_err(ApiError::from)
+    }
 }
 
 impl<'a, Store> ReferrersQuery for Api<'a, Hub, Store>
@@ -522,4 +1246,20 @@ where
             .referral_code(referrer)
             .map_err(ApiError::from)
     }
+
+    fn referrer_invocations(&self, dapp: &Id, code: ReferralCode) -> Result<u64, Self::Error> {
+        self.core_storage()
+            .referrer_invocations(dapp, code)
+            .map_err(ApiError::from)
+    }
+
+    fn referrer_last_active(
+        &self,
+        dapp: &Id,
+        code: ReferralCode,
+    ) -> Result<Option<Activity>, Self::Error> {
+        self.core_storage()
+            .referrer_last_active(dapp, code)
+            .map_err(ApiError::from)
+    }
 }
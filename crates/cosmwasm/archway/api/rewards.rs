@@ -0,0 +1,55 @@
+//! The queries made of the archway rewards module, behind a trait so the api can be exercised
+//! without a querier that understands [`ArchwayQuery`].
+
+use archway_bindings::types::rewards::{
+    ContractMetadataResponse, FlatFeeResponse, RewardsRecordsResponse,
+};
+use archway_bindings::{ArchwayQuery, PageRequest};
+use cosmwasm_std::StdResult;
+
+use crate::Querier;
+
+pub trait RewardsMetadataProvider {
+    /// The rewards metadata of a contract, including it's rewards owner.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn contract_metadata(&self, contract: &str) -> StdResult<ContractMetadataResponse>;
+
+    /// The flat fee charged to invoke a contract.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn flat_fee(&self, contract: &str) -> StdResult<FlatFeeResponse>;
+
+    /// A page of the rewards records yet to be withdrawn by the `rewards_address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementor.
+    fn rewards_records(
+        &self,
+        rewards_address: &str,
+        page: PageRequest,
+    ) -> StdResult<RewardsRecordsResponse>;
+}
+
+impl<'a> RewardsMetadataProvider for Querier<'a> {
+    fn contract_metadata(&self, contract: &str) -> StdResult<ContractMetadataResponse> {
+        self.query(&ArchwayQuery::contract_metadata(contract).into())
+    }
+
+    fn flat_fee(&self, contract: &str) -> StdResult<FlatFeeResponse> {
+        self.query(&ArchwayQuery::flat_fee(contract).into())
+    }
+
+    fn rewards_records(
+        &self,
+        rewards_address: &str,
+        page: PageRequest,
+    ) -> StdResult<RewardsRecordsResponse> {
+        self.query(&ArchwayQuery::rewards_records_with_pagination(rewards_address, page).into())
+    }
+}
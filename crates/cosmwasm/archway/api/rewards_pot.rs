@@ -1,16 +1,23 @@
 use std::num::NonZeroU128;
 
-use archway_bindings::types::rewards::{RewardsRecordsResponse, WithdrawRewardsResponse};
+use archway_bindings::types::rewards::WithdrawRewardsResponse;
 use archway_bindings::{ArchwayMsg, ArchwayQuery, PageRequest};
-use cosmwasm_std::{coins, Addr, BankMsg, Deps, DepsMut, Env, Reply as CwReply, SubMsg, Uint128};
+use cosmwasm_std::{
+    coins, Addr, BankMsg, Deps, DepsMut, Env, Event, Reply as CwReply, SubMsg, Uint128,
+};
 use kv_storage::{MutStorage, Storage};
 
 use referrals_core::rewards_pot::{HandleReply, Query};
 use referrals_core::{FallibleApi, Id};
-use referrals_cw::rewards_pot::{AdminResponse, DappResponse, TotalRewardsResponse};
+use referrals_cw::rewards_pot::{
+    AdminResponse, DappResponse, OutstandingRecordsResponse, RewardsBreakdownResponse,
+    TotalRewardsResponse,
+};
 
 pub use crate::{cache, Api, CwMutStore, CwStore, CwStoreError, Error as BaseApiError, Response};
 
+use crate::ReplyId;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("API not initialized")]
@@ -23,12 +30,27 @@ pub enum Error {
     TotalCollectedOverflow,
     #[error("overflow adding total rewards")]
     TotalRewardsOverflow,
+    #[error("insufficient rewards pot balance - requested {requested}, available {available}")]
+    InsufficientPotBalance { requested: u128, available: u128 },
+    #[error("overflow adding distributed rewards")]
+    TotalDistributedOverflow,
+    #[error("nothing to sweep")]
+    NothingToSweep,
+    #[error("too many outstanding rewards records to total")]
+    TooManyRewardsRecords,
 }
 
 pub type ApiError<StoreError> = BaseApiError<StoreError, Error>;
 pub type ApiResult<T, StoreError> = Result<T, ApiError<StoreError>>;
 pub type CwApiError = ApiError<CwStoreError>;
 
+/// Number of rewards records requested per page when totalling outstanding rewards
+pub const REWARDS_RECORDS_PAGE_SIZE: u64 = 100;
+/// Maximum number of pages of rewards records queried when totalling outstanding rewards
+pub const MAX_REWARDS_RECORDS_PAGES: u64 = 10;
+/// Default maximum number of rewards records withdrawn per withdrawal
+pub const DEFAULT_WITHDRAW_RECORDS_LIMIT: u64 = 500;
+
 #[derive(Default)]
 pub struct RewardsPot;
 
@@ -37,7 +59,12 @@ pub fn from_deps<'a>(
     deps: Deps<'a, ArchwayQuery>,
     env: &'a Env,
 ) -> Api<'a, RewardsPot, CwStore<'a>> {
-    Api::new(CwStore::from_repo(deps.storage), env, deps.querier)
+    Api::new(
+        CwStore::from_repo(deps.storage),
+        env,
+        deps.api,
+        deps.querier,
+    )
 }
 
 #[must_use]
@@ -46,7 +73,12 @@ pub fn from_deps_mut<'a>(
     env: &'a Env,
 ) -> Api<'a, RewardsPot, CwMutStore<'a>> {
     let deps = deps.branch();
-    Api::new(CwMutStore::from_repo(deps.storage), env, deps.querier)
+    Api::new(
+        CwMutStore::from_repo(deps.storage),
+        env,
+        deps.api,
+        deps.querier,
+    )
 }
 
 impl FallibleApi for RewardsPot {
@@ -63,12 +95,9 @@ where
     ///
     /// This function will return an error if there is an issue with the underlying querier.
     pub fn total_rewards_records(&self) -> ApiResult<u64, Store::Error> {
-        let rewards_records_response: RewardsRecordsResponse = self.querier.query(
-            &ArchwayQuery::rewards_records_with_pagination(
-                &self.env.contract.address,
-                PageRequest::new().limit(0),
-            )
-            .into(),
+        let rewards_records_response = self.rewards.rewards_records(
+            self.env.contract.address.as_str(),
+            PageRequest::new().limit(0),
         )?;
 
         let total_records = rewards_records_response
@@ -98,39 +127,131 @@ where
     ///
     /// This function will return an error if:
     /// - Calculating the total rewards overflows.
+    /// - There are more outstanding records than can be queried in `MAX_REWARDS_RECORDS_PAGES`.
     /// - There is an issue with the underlying querier or storage.
     pub fn total_rewards(&self) -> ApiResult<TotalRewardsResponse, Store::Error> {
-        let rewards_collected = cache::rewards_pot::total_rewards_collected(&self.store)?;
+        self.total_rewards_paged(REWARDS_RECORDS_PAGE_SIZE, MAX_REWARDS_RECORDS_PAGES)
+    }
 
-        let outstanding_records = self.outstanding_records()?;
+    /// The total amount of the rewards received and receivable by the rewards pot,
+    /// querying outstanding records in pages of `page_size` up to `max_pages`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Calculating the total rewards overflows.
+    /// - There are more outstanding records than can be queried in `max_pages`.
+    /// - There is an issue with the underlying querier or storage.
+    pub fn total_rewards_paged(
+        &self,
+        page_size: u64,
+        max_pages: u64,
+    ) -> ApiResult<TotalRewardsResponse, Store::Error> {
+        let collected =
+            cache::rewards_pot::total_rewards_collected(&self.store, &self.stored_denom()?)?;
+
+        let total = collected
+            .checked_add(self.pending_rewards_paged(page_size, max_pages)?)
+            .ok_or(Error::TotalRewardsOverflow)
+            .map_err(ApiError::Mode)?;
 
-        if outstanding_records == 0 {
-            return Ok(TotalRewardsResponse {
-                total: rewards_collected.into(),
-            });
-        }
+        Ok(TotalRewardsResponse {
+            total: Uint128::from(total),
+        })
+    }
 
-        let rewards_records_response: RewardsRecordsResponse = self.querier.query(
-            &ArchwayQuery::rewards_records_with_pagination(
-                &self.env.contract.address,
-                PageRequest::new().reverse().limit(outstanding_records),
-            )
-            .into(),
-        )?;
+    /// The rewards received and receivable by the rewards pot, split by whether they've been withdrawn.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Calculating the total rewards overflows.
+    /// - There are more outstanding records than can be queried in `MAX_REWARDS_RECORDS_PAGES`.
+    /// - There is an issue with the underlying querier or storage.
+    pub fn rewards_breakdown(&self) -> ApiResult<RewardsBreakdownResponse, Store::Error> {
+        let collected =
+            cache::rewards_pot::total_rewards_collected(&self.store, &self.stored_denom()?)?;
+
+        let pending =
+            self.pending_rewards_paged(REWARDS_RECORDS_PAGE_SIZE, MAX_REWARDS_RECORDS_PAGES)?;
 
-        let total = rewards_records_response
-            .records
-            .into_iter()
-            .flat_map(|record| record.rewards)
-            .try_fold(rewards_collected, |total, reward| {
-                total
-                    .checked_add(reward.amount.u128())
-                    .ok_or(Error::TotalRewardsOverflow)
-            })
-            .map(Uint128::from)
+        let total = collected
+            .checked_add(pending)
+            .ok_or(Error::TotalRewardsOverflow)
             .map_err(ApiError::Mode)?;
 
-        Ok(TotalRewardsResponse { total })
+        Ok(RewardsBreakdownResponse {
+            collected: collected.into(),
+            pending: pending.into(),
+            total: total.into(),
+        })
+    }
+
+    /// The rewards denom, queried if it has yet to be cached.
+    fn stored_denom(&self) -> ApiResult<String, Store::Error> {
+        match cache::rewards_denom(&self.store)? {
+            Some(denom) => Ok(denom),
+            None => Ok(self.querier.query_bonded_denom()?),
+        }
+    }
+
+    /// The sum of the outstanding rewards records,
+    /// querying them in pages of `page_size` up to `max_pages`.
+    fn pending_rewards_paged(
+        &self,
+        page_size: u64,
+        max_pages: u64,
+    ) -> ApiResult<u128, Store::Error> {
+        let mut pending: u128 = 0;
+
+        let mut outstanding_records = self.outstanding_records()?;
+
+        let mut next_key = None;
+
+        for _ in 0..max_pages {
+            if outstanding_records == 0 {
+                break;
+            }
+
+            let mut page = PageRequest::new()
+                .reverse()
+                .limit(outstanding_records.min(page_size));
+
+            page.key = next_key.take();
+
+            let rewards_records_response = self
+                .rewards
+                .rewards_records(self.env.contract.address.as_str(), page)?;
+
+            let records_len = rewards_records_response.records.len() as u64;
+
+            pending = rewards_records_response
+                .records
+                .into_iter()
+                .flat_map(|record| record.rewards)
+                .try_fold(pending, |pending, reward| {
+                    pending
+                        .checked_add(reward.amount.u128())
+                        .ok_or(Error::TotalRewardsOverflow)
+                })
+                .map_err(ApiError::Mode)?;
+
+            outstanding_records = outstanding_records.saturating_sub(records_len);
+
+            next_key = rewards_records_response
+                .pagination
+                .and_then(|page| page.next_key);
+
+            if next_key.is_none() {
+                break;
+            }
+        }
+
+        if outstanding_records > 0 && next_key.is_some() {
+            return Err(ApiError::Mode(Error::TooManyRewardsRecords));
+        }
+
+        Ok(pending)
     }
 
     /// The dApp associated with the pot
@@ -160,6 +281,23 @@ where
 
         Ok(AdminResponse { admin })
     }
+
+    /// The number of rewards records yet to be withdrawn, along with those already withdrawn
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with the underlying querier or storage.
+    pub fn outstanding(&self) -> ApiResult<OutstandingRecordsResponse, Store::Error> {
+        let outstanding = self.outstanding_records()?;
+        let total = self.total_rewards_records()?;
+
+        Ok(OutstandingRecordsResponse {
+            outstanding,
+            collected: total.saturating_sub(outstanding),
+            total,
+        })
+    }
 }
 
 impl<'a, Store> Api<'a, RewardsPot, Store>
@@ -171,10 +309,29 @@ where
     /// # Errors
     ///
     /// This function will return an error if there is an issue with underlying storage.
-    pub fn initialize(&mut self, creator: Addr, dapp: &String) -> ApiResult<(), Store::Error> {
+    pub fn initialize(
+        &mut self,
+        creator: Addr,
+        dapp: &String,
+        withdraw_records_limit: Option<u64>,
+        min_withdraw_interval_secs: Option<u64>,
+        rewards_denom: Option<&String>,
+    ) -> ApiResult<(), Store::Error> {
         cache::rewards_pot::set_creator(&mut self.store, &creator.into_string())?;
         cache::rewards_pot::set_dapp(&mut self.store, dapp)?;
 
+        if let Some(denom) = rewards_denom.filter(|denom| !denom.is_empty()) {
+            cache::set_rewards_denom(&mut self.store, denom)?;
+        }
+
+        if let Some(limit) = withdraw_records_limit {
+            cache::rewards_pot::set_withdraw_records_limit(&mut self.store, limit)?;
+        }
+
+        if let Some(secs) = min_withdraw_interval_secs {
+            cache::rewards_pot::set_min_withdraw_interval(&mut self.store, secs)?;
+        }
+
         Ok(())
     }
 
@@ -188,22 +345,31 @@ where
         &mut self,
         response: &WithdrawRewardsResponse,
     ) -> ApiResult<(), Store::Error> {
-        let Some(rewards) = response.total_rewards.first() else {
-            return Ok(());
-        };
-
-        let current_total_collected = cache::rewards_pot::total_rewards_collected(&self.store)?;
-
-        let new_total_collected = current_total_collected
-            .checked_add(rewards.amount.u128())
-            .ok_or(Error::TotalCollectedOverflow)
-            .map_err(ApiError::Mode)?;
+        // only advance by the records actually withdrawn, so a capped withdrawal can be resumed.
+        // records withdrawn for nothing still count, else they'd be re-requested forever
+        let records_collected = cache::rewards_pot::reward_records_collected(&self.store)?
+            .saturating_add(response.records_num);
 
-        cache::rewards_pot::set_total_rewards_collected(&mut self.store, new_total_collected)?;
+        cache::rewards_pot::set_rewards_records_collected(&mut self.store, records_collected)?;
 
-        let total_records = self.total_rewards_records()?;
+        // rewards withdrawn in a single denom confirm the denom set on initialization
+        if let [rewards] = response.total_rewards.as_slice() {
+            cache::set_rewards_denom(&mut self.store, &rewards.denom)?;
+        }
 
-        cache::rewards_pot::set_rewards_records_collected(&mut self.store, total_records)?;
+        for rewards in &response.total_rewards {
+            let new_total_collected =
+                cache::rewards_pot::total_rewards_collected(&self.store, &rewards.denom)?
+                    .checked_add(rewards.amount.u128())
+                    .ok_or(Error::TotalCollectedOverflow)
+                    .map_err(ApiError::Mode)?;
+
+            cache::rewards_pot::set_total_rewards_collected(
+                &mut self.store,
+                &rewards.denom,
+                new_total_collected,
+            )?;
+        }
 
         Ok(())
     }
@@ -255,24 +421,113 @@ where
             return Ok(());
         }
 
+        let limit = cache::rewards_pot::withdraw_records_limit(&self.store)?
+            .unwrap_or(DEFAULT_WITHDRAW_RECORDS_LIMIT);
+
+        // any records beyond the limit are left for the next withdrawal
         self.response.messages.push(SubMsg::reply_on_success(
-            ArchwayMsg::withdraw_rewards_by_limit(outstanding_records),
-            0, // the only reply_on submessage we send
+            ArchwayMsg::withdraw_rewards_by_limit(outstanding_records.min(limit)),
+            ReplyId::WithdrawRewards.as_u64(),
         ));
 
+        cache::rewards_pot::set_last_withdraw_time(&mut self.store, self.env.block.time.seconds())?;
+
         Ok(())
     }
 
-    fn send_rewards(&mut self, receiver: Id, amount: NonZeroU128) -> Result<(), Self::Error> {
-        let rewards_denom = self.rewards_denom()?;
+    fn send_rewards(
+        &mut self,
+        receiver: Id,
+        amount: NonZeroU128,
+        denom: Option<String>,
+        clamp: bool,
+    ) -> Result<(), Self::Error> {
+        let denom = match denom {
+            Some(denom) => denom,
+            None => self.rewards_denom()?,
+        };
+
+        let mut amount = amount.get();
+
+        // rewards pending withdrawal only arrive once the withdraw submessage executes,
+        // so the current balance can only be checked when there are none.
+        if self.outstanding_records()? == 0 {
+            let available = self
+                .querier
+                .query_balance(&self.env.contract.address, &denom)?
+                .amount
+                .u128();
+
+            if amount > available {
+                if !clamp {
+                    return Err(ApiError::Mode(Error::InsufficientPotBalance {
+                        requested: amount,
+                        available,
+                    }));
+                }
+
+                amount = available;
+            }
+        }
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let total_distributed = cache::rewards_pot::total_rewards_distributed(&self.store, &denom)?
+            .checked_add(amount)
+            .ok_or(Error::TotalDistributedOverflow)
+            .map_err(ApiError::Mode)?;
+
+        cache::rewards_pot::set_total_rewards_distributed(
+            &mut self.store,
+            &denom,
+            total_distributed,
+        )?;
 
         self.response.messages.push(SubMsg::new(BankMsg::Send {
             to_address: receiver.into_string(),
-            amount: coins(amount.get(), rewards_denom),
+            amount: coins(amount, denom),
         }));
 
         Ok(())
     }
+
+    fn sweep(&mut self, denom: String, recipient: Id) -> Result<(), Self::Error> {
+        let balance = self
+            .querier
+            .query_balance(&self.env.contract.address, &denom)?
+            .amount
+            .u128();
+
+        // rewards collected in the denom are protected until distributed
+        let collected = cache::rewards_pot::total_rewards_collected(&self.store, &denom)?;
+        let distributed = cache::rewards_pot::total_rewards_distributed(&self.store, &denom)?;
+        let amount = balance.saturating_sub(collected.saturating_sub(distributed));
+
+        if amount == 0 {
+            return Err(ApiError::Mode(Error::NothingToSweep));
+        }
+
+        self.response.messages.push(SubMsg::new(BankMsg::Send {
+            to_address: recipient.into_string(),
+            amount: coins(amount, denom),
+        }));
+
+        Ok(())
+    }
+
+    fn set_admin(&mut self, admin: Id) -> Result<(), Self::Error> {
+        let admin = admin.into_string();
+
+        cache::rewards_pot::set_creator(&mut self.store, &admin)?;
+
+        self.response
+            .events
+            .push(Event::new("update_admin").add_attribute("new_admin", admin));
+
+        Ok(())
+    }
 }
 
 impl<'a, Store> Query for Api<'a, RewardsPot, Store>
@@ -290,4 +545,16 @@ where
         let outstanding_records = self.outstanding_records()?;
         Ok(outstanding_records > 0)
     }
+
+    fn min_withdraw_interval(&self) -> Result<u64, Self::Error> {
+        cache::rewards_pot::min_withdraw_interval(&self.store).map_err(ApiError::from)
+    }
+
+    fn last_withdraw_time(&self) -> Result<Option<u64>, Self::Error> {
+        cache::rewards_pot::last_withdraw_time(&self.store).map_err(ApiError::from)
+    }
+
+    fn current_time(&self) -> Result<u64, Self::Error> {
+        Ok(self.env.block.time.seconds())
+    }
 }
@@ -0,0 +1,113 @@
+//! An in-memory rewards module for tests, so the api can be exercised without a querier that
+//! understands archway queries.
+
+use std::collections::BTreeMap;
+
+use archway_bindings::types::rewards::{
+    ContractMetadataResponse, FlatFeeResponse, RewardsRecord, RewardsRecordsResponse,
+};
+use archway_bindings::{PageRequest, PageResponse};
+use cosmwasm_std::{Binary, Coin, StdError, StdResult};
+
+use crate::rewards::RewardsMetadataProvider;
+
+/// Rewards module state keyed by contract address.
+/// Metadata queries for a contract without metadata fail with [`StdError::NotFound`],
+/// contracts without a flat fee are charged nothing.
+#[derive(Debug, Clone, Default)]
+pub struct MockRewardsMetadata {
+    metadata: BTreeMap<String, ContractMetadataResponse>,
+    flat_fees: BTreeMap<String, Coin>,
+    records: BTreeMap<String, Vec<RewardsRecord>>,
+}
+
+impl MockRewardsMetadata {
+    #[must_use]
+    pub fn metadata(mut self, contract: &str, owner: &str, rewards_address: &str) -> Self {
+        self.metadata.insert(
+            contract.to_owned(),
+            ContractMetadataResponse {
+                owner_address: owner.to_owned(),
+                rewards_address: rewards_address.to_owned(),
+            },
+        );
+        self
+    }
+
+    #[must_use]
+    pub fn fee(mut self, contract: &str, fee: Coin) -> Self {
+        self.flat_fees.insert(contract.to_owned(), fee);
+        self
+    }
+
+    #[must_use]
+    pub fn records(mut self, rewards_address: &str, records: Vec<RewardsRecord>) -> Self {
+        self.records.insert(rewards_address.to_owned(), records);
+        self
+    }
+}
+
+impl RewardsMetadataProvider for MockRewardsMetadata {
+    fn contract_metadata(&self, contract: &str) -> StdResult<ContractMetadataResponse> {
+        self.metadata
+            .get(contract)
+            .cloned()
+            .ok_or_else(|| StdError::not_found(format!("contract metadata of {contract}")))
+    }
+
+    fn flat_fee(&self, contract: &str) -> StdResult<FlatFeeResponse> {
+        Ok(FlatFeeResponse {
+            flat_fee_amount: self.flat_fees.get(contract).cloned().unwrap_or_default(),
+        })
+    }
+
+    fn rewards_records(
+        &self,
+        rewards_address: &str,
+        page: PageRequest,
+    ) -> StdResult<RewardsRecordsResponse> {
+        let records = self
+            .records
+            .get(rewards_address)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let total = records.len();
+
+        // the page key is the big-endian index of the next record to serve
+        let start = match page.key {
+            Some(key) => <[u8; 8]>::try_from(key.as_slice())
+                .map(u64::from_be_bytes)
+                .ok()
+                .and_then(|start| usize::try_from(start).ok())
+                .ok_or_else(|| StdError::generic_err("invalid page key"))?,
+            None => 0,
+        };
+
+        // a zero limit is treated as no limit
+        let end = page
+            .limit
+            .filter(|&limit| limit > 0)
+            .and_then(|limit| usize::try_from(limit).ok())
+            .map_or(total, |limit| start.saturating_add(limit).min(total));
+
+        let ordered: Vec<_> = if page.reverse == Some(true) {
+            records.iter().rev().cloned().collect()
+        } else {
+            records.to_vec()
+        };
+
+        let next_key = (end < total).then(|| Binary::from((end as u64).to_be_bytes().to_vec()));
+
+        Ok(RewardsRecordsResponse {
+            records: ordered
+                .get(start..end)
+                .map(<[_]>::to_vec)
+                .unwrap_or_default(),
+            pagination: Some(PageResponse {
+                next_key,
+                total: Some(total as u64),
+            }),
+        })
+    }
+}
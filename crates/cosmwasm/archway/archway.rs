@@ -1,23 +1,33 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
-use cosmwasm_std::{Addr, CosmosMsg, Response, StdError, SubMsg, Uint128, WasmMsg};
+use cosmwasm_std::{Addr, Coin, CosmosMsg, Response, StdError, SubMsg, Uint128, WasmMsg};
 
 use archway_bindings::ArchwayMsg;
+use referrals_core::hub::NonZeroPercent;
 use referrals_cw::ExecuteMsg;
 
 pub trait ResponseExt {
+    type ActivateDappReferralsCustom;
     type SetDappFeeCustom;
     type RecordReferralCustom;
+    type CollectReferrerCustom;
+    type CollectDappCustom;
 
     /// Start the activation process
-    fn activate_dapp_referrals(self) -> Activation;
+    fn activate_dapp_referrals(self) -> Activation<Self::ActivateDappReferralsCustom>;
 
     /// Start the dApp fee setting process
     fn set_dapp_fee(self) -> SetDappFee<Self::SetDappFeeCustom>;
 
     /// Start the referral recording process
     fn record_referral(self) -> RecordReferral<Self::RecordReferralCustom>;
+
+    /// Start the referrer earnings collection process
+    fn collect_referrer(self) -> CollectReferrer<Self::CollectReferrerCustom>;
+
+    /// Start the dApp rewards collection process
+    fn collect_dapp(self) -> CollectDapp<Self::CollectDappCustom>;
 }
 
 // compile-time-checked-as-much-as-possible message builders
@@ -25,6 +35,7 @@ pub trait ResponseExt {
 pub struct HubMsg<Msg, CustomMsg, ReferralsHubAddr = ()> {
     msg: Msg,
     referrals_hub_addr: ReferralsHubAddr,
+    funds: Vec<Coin>,
     response: Response<CustomMsg>,
 }
 
@@ -32,6 +43,7 @@ pub struct Activate<Name = (), Percent = (), Collector = ()> {
     name: Name,
     percent: Percent,
     collector: Collector,
+    confirm_no_dapp_share: bool,
 }
 
 pub struct DappFee<Dapp = (), Fee = ()> {
@@ -43,22 +55,40 @@ pub struct Referral<Code = ()> {
     code: Code,
 }
 
-pub type Activation = HubMsg<Activate, ArchwayMsg>;
+pub struct ReferrerCollection<Code = (), Dapp = (), Premium = ()> {
+    code: Code,
+    dapp: Dapp,
+    premium: Premium,
+}
+
+pub struct DappCollection<Dapp = (), Premium = ()> {
+    dapp: Dapp,
+    premium: Premium,
+}
+
+pub type Activation<C = ArchwayMsg> = HubMsg<Activate, C>;
 pub type SetDappFee<C = ()> = HubMsg<DappFee, C>;
 pub type RecordReferral<C = ()> = HubMsg<Referral, C>;
+pub type CollectReferrer<C = ()> = HubMsg<ReferrerCollection, C>;
+pub type CollectDapp<C = ()> = HubMsg<DappCollection, C>;
 
 impl ResponseExt for Response<ArchwayMsg> {
+    type ActivateDappReferralsCustom = ArchwayMsg;
     type SetDappFeeCustom = ArchwayMsg;
     type RecordReferralCustom = ArchwayMsg;
+    type CollectReferrerCustom = ArchwayMsg;
+    type CollectDappCustom = ArchwayMsg;
 
-    fn activate_dapp_referrals(self) -> Activation {
+    fn activate_dapp_referrals(self) -> Activation<Self::ActivateDappReferralsCustom> {
         Activation {
             msg: Activate {
                 name: (),
                 percent: (),
                 collector: (),
+                confirm_no_dapp_share: false,
             },
             referrals_hub_addr: (),
+            funds: vec![],
             response: self,
         }
     }
@@ -67,6 +97,7 @@ impl ResponseExt for Response<ArchwayMsg> {
         SetDappFee {
             msg: DappFee { dapp: (), fee: () },
             referrals_hub_addr: (),
+            funds: vec![],
             response: self,
         }
     }
@@ -75,49 +106,97 @@ impl ResponseExt for Response<ArchwayMsg> {
         RecordReferral {
             msg: Referral { code: () },
             referrals_hub_addr: (),
+            funds: vec![],
+            response: self,
+        }
+    }
+
+    fn collect_referrer(self) -> CollectReferrer<Self::CollectReferrerCustom> {
+        CollectReferrer {
+            msg: ReferrerCollection {
+                code: (),
+                dapp: (),
+                premium: (),
+            },
+            referrals_hub_addr: (),
+            funds: vec![],
+            response: self,
+        }
+    }
+
+    fn collect_dapp(self) -> CollectDapp<Self::CollectDappCustom> {
+        CollectDapp {
+            msg: DappCollection {
+                dapp: (),
+                premium: (),
+            },
+            referrals_hub_addr: (),
+            funds: vec![],
             response: self,
         }
     }
 }
 
-fn to_archway_response(old: Response) -> Response<ArchwayMsg> {
+fn to_archway_msg(msg: CosmosMsg) -> Result<CosmosMsg<ArchwayMsg>, StdError> {
+    let msg = match msg {
+        CosmosMsg::Bank(msg) => CosmosMsg::Bank(msg),
+        CosmosMsg::Stargate { type_url, value } => CosmosMsg::Stargate { type_url, value },
+        CosmosMsg::Ibc(msg) => CosmosMsg::Ibc(msg),
+        CosmosMsg::Wasm(msg) => CosmosMsg::Wasm(msg),
+        CosmosMsg::Gov(msg) => CosmosMsg::Gov(msg),
+        #[cfg(feature = "staking")]
+        CosmosMsg::Staking(msg) => CosmosMsg::Staking(msg),
+        #[cfg(feature = "staking")]
+        CosmosMsg::Distribution(msg) => CosmosMsg::Distribution(msg),
+        // custom messages have no archway equivalent & new variants may be added
+        m => {
+            return Err(StdError::generic_err(format!(
+                "unhandled msg not converted to archway msg: {m:?}"
+            )))
+        }
+    };
+
+    Ok(msg)
+}
+
+fn to_archway_response(old: Response) -> Result<Response<ArchwayMsg>, StdError> {
     let mut new = Response::default();
     new.messages = old
         .messages
         .into_iter()
-        .map(|msg| SubMsg {
-            id: msg.id,
-            msg: match msg.msg {
-                CosmosMsg::Bank(msg) => CosmosMsg::Bank(msg),
-                CosmosMsg::Stargate { type_url, value } => CosmosMsg::Stargate { type_url, value },
-                CosmosMsg::Ibc(msg) => CosmosMsg::Ibc(msg),
-                CosmosMsg::Wasm(msg) => CosmosMsg::Wasm(msg),
-                CosmosMsg::Gov(msg) => CosmosMsg::Gov(msg),
-                m => panic!("unhandled msg not converted to archway msg: {m:?}"),
-            },
-            gas_limit: msg.gas_limit,
-            reply_on: msg.reply_on,
+        .map(|msg| {
+            Ok(SubMsg {
+                id: msg.id,
+                msg: to_archway_msg(msg.msg)?,
+                gas_limit: msg.gas_limit,
+                reply_on: msg.reply_on,
+            })
         })
-        .collect();
+        .collect::<Result<_, StdError>>()?;
     new.attributes = old.attributes;
     new.events = old.events;
     new.data = old.data;
-    new
+    Ok(new)
 }
 
 impl ResponseExt for Response {
+    type ActivateDappReferralsCustom = cosmwasm_std::Empty;
     type SetDappFeeCustom = cosmwasm_std::Empty;
     type RecordReferralCustom = cosmwasm_std::Empty;
+    type CollectReferrerCustom = cosmwasm_std::Empty;
+    type CollectDappCustom = cosmwasm_std::Empty;
 
-    fn activate_dapp_referrals(self) -> Activation {
+    fn activate_dapp_referrals(self) -> Activation<Self::ActivateDappReferralsCustom> {
         Activation {
             msg: Activate {
                 name: (),
                 percent: (),
                 collector: (),
+                confirm_no_dapp_share: false,
             },
             referrals_hub_addr: (),
-            response: to_archway_response(self),
+            funds: vec![],
+            response: self,
         }
     }
 
@@ -125,6 +204,7 @@ impl ResponseExt for Response {
         SetDappFee {
             msg: DappFee { dapp: (), fee: () },
             referrals_hub_addr: (),
+            funds: vec![],
             response: self,
         }
     }
@@ -133,6 +213,32 @@ impl ResponseExt for Response {
         RecordReferral {
             msg: Referral { code: () },
             referrals_hub_addr: (),
+            funds: vec![],
+            response: self,
+        }
+    }
+
+    fn collect_referrer(self) -> CollectReferrer<Self::CollectReferrerCustom> {
+        CollectReferrer {
+            msg: ReferrerCollection {
+                code: (),
+                dapp: (),
+                premium: (),
+            },
+            referrals_hub_addr: (),
+            funds: vec![],
+            response: self,
+        }
+    }
+
+    fn collect_dapp(self) -> CollectDapp<Self::CollectDappCustom> {
+        CollectDapp {
+            msg: DappCollection {
+                dapp: (),
+                premium: (),
+            },
+            referrals_hub_addr: (),
+            funds: vec![],
             response: self,
         }
     }
@@ -144,11 +250,31 @@ impl<Msg, Custom> HubMsg<Msg, Custom> {
         HubMsg {
             msg: self.msg,
             referrals_hub_addr: addr,
+            funds: self.funds,
             response: self.response,
         }
     }
 }
 
+impl<Msg, Custom, Addr> HubMsg<Msg, Custom, Addr> {
+    /// Attach `funds` to the message sent to the Referral Hub, in addition to any premium
+    #[must_use]
+    pub fn with_funds(mut self, funds: Vec<Coin>) -> Self {
+        for coin in funds {
+            add_coin(&mut self.funds, coin);
+        }
+
+        self
+    }
+}
+
+fn add_coin(funds: &mut Vec<Coin>, coin: Coin) {
+    match funds.iter_mut().find(|c| c.denom == coin.denom) {
+        Some(existing) => existing.amount += coin.amount,
+        None => funds.push(coin),
+    }
+}
+
 impl<Custom, Addr, Percent, Collector> HubMsg<Activate<(), Percent, Collector>, Custom, Addr> {
     /// The name of the dApp registering
     pub fn dapp_name(
@@ -160,8 +286,10 @@ impl<Custom, Addr, Percent, Collector> HubMsg<Activate<(), Percent, Collector>,
                 name: name.into(),
                 percent: self.msg.percent,
                 collector: self.msg.collector,
+                confirm_no_dapp_share: self.msg.confirm_no_dapp_share,
             },
             referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
             response: self.response,
         }
     }
@@ -178,8 +306,10 @@ impl<Custom, Addr, Name, Collector> HubMsg<Activate<Name, (), Collector>, Custom
                 name: self.msg.name,
                 percent,
                 collector: self.msg.collector,
+                confirm_no_dapp_share: self.msg.confirm_no_dapp_share,
             },
             referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
             response: self.response,
         }
     }
@@ -193,13 +323,26 @@ impl<Custom, Addr, Name, Percent> HubMsg<Activate<Name, Percent, ()>, Custom, Ad
                 name: self.msg.name,
                 percent: self.msg.percent,
                 collector,
+                confirm_no_dapp_share: self.msg.confirm_no_dapp_share,
             },
             referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
             response: self.response,
         }
     }
 }
 
+impl<Custom, Addr, Name, Percent, Collector>
+    HubMsg<Activate<Name, Percent, Collector>, Custom, Addr>
+{
+    /// Confirm a referrer percent of 100 is intended, leaving the dApp no share of rewards
+    #[must_use]
+    pub fn confirm_no_dapp_share(mut self) -> Self {
+        self.msg.confirm_no_dapp_share = true;
+        self
+    }
+}
+
 impl HubMsg<Activate<String, u8, Addr>, ArchwayMsg, Addr> {
     /// Add the required registration messages to the response.
     /// NOTE: This will transfer rewards admin rights to the Hub.
@@ -209,18 +352,20 @@ impl HubMsg<Activate<String, u8, Addr>, ArchwayMsg, Addr> {
     ///
     /// This function will return an error if:
     /// - The given percent is not in the range 1-100
+    /// - The given percent is 100 without confirming the dApp is left no share
     /// - There is an issue with `cosmwasm_std` serialization
     pub fn done(self) -> Result<Response<ArchwayMsg>, StdError> {
-        if !(1..=100).contains(&self.msg.percent) {
-            return Err(StdError::generic_err(
-                "Invalid referrer percent - must be in the range 1 - 100",
-            ));
-        }
+        NonZeroPercent::try_from(self.msg.percent)
+            .map_err(|err| StdError::generic_err(err.to_string()))?
+            .check_dapp_share(self.msg.confirm_no_dapp_share)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
 
         let register = cosmwasm_std::to_binary(&ExecuteMsg::ActivateDapp {
             name: self.msg.name,
             percent: self.msg.percent,
             collector: self.msg.collector.into_string(),
+            fee: None,
+            confirm_no_dapp_share: self.msg.confirm_no_dapp_share.then_some(true),
         })?;
 
         Ok(self
@@ -233,11 +378,34 @@ impl HubMsg<Activate<String, u8, Addr>, ArchwayMsg, Addr> {
             .add_message(WasmMsg::Execute {
                 contract_addr: self.referrals_hub_addr.into_string(),
                 msg: register,
-                funds: vec![],
+                funds: self.funds,
             }))
     }
 }
 
+impl HubMsg<Activate<String, u8, Addr>, cosmwasm_std::Empty, Addr> {
+    /// Convert the response to an archway response & add the required registration messages.
+    /// NOTE: This will transfer rewards admin rights to the Hub.
+    /// Either the dApp or the nominated collector can re-gain these rights by de-registering.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The given percent is not in the range 1-100
+    /// - The given percent is 100 without confirming the dApp is left no share
+    /// - The response contains a message that cannot be converted, e.g. a custom message
+    /// - There is an issue with `cosmwasm_std` serialization
+    pub fn done(self) -> Result<Response<ArchwayMsg>, StdError> {
+        HubMsg {
+            msg: self.msg,
+            referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
+            response: to_archway_response(self.response)?,
+        }
+        .done()
+    }
+}
+
 impl<Custom, HubAddr, Fee> HubMsg<DappFee<(), Fee>, Custom, HubAddr> {
     /// The dApp to set the fee for
     pub fn dapp(self, dapp: Addr) -> HubMsg<DappFee<Addr, Fee>, Custom, HubAddr> {
@@ -247,6 +415,7 @@ impl<Custom, HubAddr, Fee> HubMsg<DappFee<(), Fee>, Custom, HubAddr> {
                 fee: self.msg.fee,
             },
             referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
             response: self.response,
         }
     }
@@ -261,6 +430,7 @@ impl<Custom, Addr, Dapp> HubMsg<DappFee<Dapp>, Custom, Addr> {
                 fee: fee.into(),
             },
             referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
             response: self.response,
         }
     }
@@ -281,7 +451,7 @@ impl<Custom> HubMsg<DappFee<Addr, Uint128>, Custom, Addr> {
         Ok(self.response.add_message(WasmMsg::Execute {
             contract_addr: self.referrals_hub_addr.into_string(),
             msg: set_fee,
-            funds: vec![],
+            funds: self.funds,
         }))
     }
 }
@@ -292,6 +462,7 @@ impl<Custom, Addr> HubMsg<Referral, Custom, Addr> {
         HubMsg {
             msg: Referral { code },
             referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
             response: self.response,
         }
     }
@@ -306,12 +477,148 @@ impl<Custom> HubMsg<Referral<u64>, Custom, Addr> {
     pub fn done(self) -> Result<Response<Custom>, StdError> {
         let record_referral = cosmwasm_std::to_binary(&ExecuteMsg::RecordReferral {
             code: self.msg.code,
+            dapp: None,
         })?;
 
         Ok(self.response.add_message(WasmMsg::Execute {
             contract_addr: self.referrals_hub_addr.into_string(),
             msg: record_referral,
-            funds: vec![],
+            funds: self.funds,
+        }))
+    }
+}
+
+impl<Custom, Addr, Dapp, Premium> HubMsg<ReferrerCollection<(), Dapp, Premium>, Custom, Addr> {
+    /// The referral code to collect the earnings of
+    pub fn code(self, code: u64) -> HubMsg<ReferrerCollection<u64, Dapp, Premium>, Custom, Addr> {
+        HubMsg {
+            msg: ReferrerCollection {
+                code,
+                dapp: self.msg.dapp,
+                premium: self.msg.premium,
+            },
+            referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
+            response: self.response,
+        }
+    }
+}
+
+impl<Custom, HubAddr, Code, Premium>
+    HubMsg<ReferrerCollection<Code, (), Premium>, Custom, HubAddr>
+{
+    /// The dApp the earnings were made with
+    pub fn dapp(
+        self,
+        dapp: Addr,
+    ) -> HubMsg<ReferrerCollection<Code, Addr, Premium>, Custom, HubAddr> {
+        HubMsg {
+            msg: ReferrerCollection {
+                code: self.msg.code,
+                dapp,
+                premium: self.msg.premium,
+            },
+            referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
+            response: self.response,
+        }
+    }
+}
+
+impl<Custom, Addr, Code, Dapp> HubMsg<ReferrerCollection<Code, Dapp, ()>, Custom, Addr> {
+    /// The contract premium to attach, as required by the Hub
+    pub fn premium(
+        self,
+        premium: Coin,
+    ) -> HubMsg<ReferrerCollection<Code, Dapp, Coin>, Custom, Addr> {
+        HubMsg {
+            msg: ReferrerCollection {
+                code: self.msg.code,
+                dapp: self.msg.dapp,
+                premium,
+            },
+            referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
+            response: self.response,
+        }
+    }
+}
+
+impl<Custom> HubMsg<ReferrerCollection<u64, Addr, Coin>, Custom, Addr> {
+    /// Add the required 'collect referrer' messages to the response.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue serializing the messages.
+    pub fn done(self) -> Result<Response<Custom>, StdError> {
+        let collect = cosmwasm_std::to_binary(&ExecuteMsg::CollectReferrer {
+            code: self.msg.code,
+            dapp: self.msg.dapp.into_string(),
+            recipient: None,
+        })?;
+
+        let mut funds = self.funds;
+
+        add_coin(&mut funds, self.msg.premium);
+
+        Ok(self.response.add_message(WasmMsg::Execute {
+            contract_addr: self.referrals_hub_addr.into_string(),
+            msg: collect,
+            funds,
+        }))
+    }
+}
+
+impl<Custom, HubAddr, Premium> HubMsg<DappCollection<(), Premium>, Custom, HubAddr> {
+    /// The dApp to collect the remaining rewards of
+    pub fn dapp(self, dapp: Addr) -> HubMsg<DappCollection<Addr, Premium>, Custom, HubAddr> {
+        HubMsg {
+            msg: DappCollection {
+                dapp,
+                premium: self.msg.premium,
+            },
+            referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
+            response: self.response,
+        }
+    }
+}
+
+impl<Custom, Addr, Dapp> HubMsg<DappCollection<Dapp>, Custom, Addr> {
+    /// The contract premium to attach, as required by the Hub
+    pub fn premium(self, premium: Coin) -> HubMsg<DappCollection<Dapp, Coin>, Custom, Addr> {
+        HubMsg {
+            msg: DappCollection {
+                dapp: self.msg.dapp,
+                premium,
+            },
+            referrals_hub_addr: self.referrals_hub_addr,
+            funds: self.funds,
+            response: self.response,
+        }
+    }
+}
+
+impl<Custom> HubMsg<DappCollection<Addr, Coin>, Custom, Addr> {
+    /// Add the required 'collect dApp' messages to the response.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue serializing the messages.
+    pub fn done(self) -> Result<Response<Custom>, StdError> {
+        let collect = cosmwasm_std::to_binary(&ExecuteMsg::CollectDapp {
+            dapp: self.msg.dapp.into_string(),
+            recipient: None,
+        })?;
+
+        let mut funds = self.funds;
+
+        add_coin(&mut funds, self.msg.premium);
+
+        Ok(self.response.add_message(WasmMsg::Execute {
+            contract_addr: self.referrals_hub_addr.into_string(),
+            msg: collect,
+            funds,
         }))
     }
 }
@@ -1,13 +1,23 @@
-use cosmwasm_std::{Binary, Env, MessageInfo, Reply, StdError};
+use cosmwasm_std::{
+    Binary, Env, Event, MessageInfo, Reply, StdError, SubMsg, SubMsgResult, WasmMsg,
+};
 
 use referrals_archway::ResponseExt;
-use referrals_cw::{ExecuteMsg as HubExecuteMsg, WithReferralCode};
+use referrals_cw::{
+    ExecuteMsg as HubExecuteMsg, HubInstantiateResponse, ReferralCodeResponse, SimulateResponse,
+    StorageWrite, WithReferralCode, WithReferralCodeResponse,
+};
 use referrals_parse_cw::Error as ParseError;
 
 use referrals_archway_api::hub as api;
+use referrals_archway_api::{ReplyId, UnknownReplyId};
 use referrals_core::hub as _core;
+use referrals_core::Id;
 
-use _core::Error as CoreError;
+use _core::{
+    Error as CoreError, HandleReply, MutableDappStore, ReadonlyDappStore, ReadonlyReferralStore,
+    Reply as CoreReply, RewardsPotsQuery,
+};
 use api::CwApiError;
 
 pub use referrals_archway_api::Response;
@@ -21,12 +31,14 @@ use crate::{Deps, DepsMut};
 pub enum Error {
     #[error(transparent)]
     Api(#[from] CwApiError),
-    #[error(transparent)]
+    #[error("{}", .0.coded())]
     Core(#[from] CoreError<CwApiError>),
     #[error(transparent)]
     Parse(#[from] ParseError),
     #[error(transparent)]
     CosmWasm(#[from] StdError),
+    #[error(transparent)]
+    UnknownReplyId(#[from] UnknownReplyId),
 }
 
 /// Handle the `referrals_cw::InstantiateMsg`.
@@ -34,6 +46,7 @@ pub enum Error {
 /// # Errors
 ///
 /// This function will return an error if:
+/// - The rewards denom is blank
 /// - There is an issue with storage
 #[allow(clippy::needless_pass_by_value)]
 pub fn init(
@@ -42,13 +55,44 @@ pub fn init(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, Error> {
-    api::from_deps_mut(&mut deps, &env).initialize(msg.rewards_pot_code_id)?;
+    let mut api = api::from_deps_mut(&mut deps, &env);
+
+    api.initialize(
+        msg.rewards_pot_code_id,
+        msg.contract_premium.u128(),
+        &msg.rewards_denom,
+        msg.block_self_referral.unwrap_or_default(),
+    )?;
+
+    // the hub may already be activated when init is re-run, activating again would fail
+    let activated = api.dapp_exists(&Id::from(env.contract.address.clone()))?;
+
+    let data = cosmwasm_std::to_binary(&HubInstantiateResponse {
+        address: env.contract.address.to_string(),
+        rewards_pot_code_id: msg.rewards_pot_code_id,
+    })?;
+
+    let response = Response::default().set_data(data);
 
-    Response::default()
+    if activated {
+        return Ok(response);
+    }
+
+    // without self-activation the sender is still made the hub's collector, to administer it
+    if !msg.self_activate.unwrap_or(true) {
+        api.set_collector(
+            &Id::from(env.contract.address.clone()),
+            Id::from(info.sender),
+        )?;
+        return Ok(response);
+    }
+
+    response
         .activate_dapp_referrals()
         .referral_hub(env.contract.address.clone())
         .dapp_name("referrals_hub")
         .referrer_percent(100)
+        .confirm_no_dapp_share()
         .collector(info.sender)
         .done()?
         .set_dapp_fee()
@@ -65,6 +109,7 @@ pub fn init(
 ///
 /// This function will return an error if:
 /// - There is an issue parsing the input
+/// - A collection is not attached the contract premium
 /// - There is an issue in `referrals_core`
 /// - There is a problem with `cosmwasm_std` storage or serialization.
 #[allow(clippy::needless_pass_by_value)]
@@ -74,24 +119,82 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, Error> {
+    // the premium attached to a collection is passed on once the collection succeeds
+    let premium = if matches!(
+        msg.msg,
+        HubExecuteMsg::CollectReferrer { .. }
+            | HubExecuteMsg::CollectReferrerAll { .. }
+            | HubExecuteMsg::CollectDapp { .. }
+    ) {
+        api::from_deps_mut(&mut deps, &env).check_premium(&info.funds)?;
+        info.funds.clone()
+    } else {
+        vec![]
+    };
+
+    // anything attached beyond the premium is refunded to the sender
+    let sender = info.sender.clone();
+
     let core_msg = referrals_parse_cw::parse_hub_exec(deps.api, info, msg.msg)?;
 
     let mut api = api::from_deps_mut(&mut deps, &env);
 
     let reply = _core::exec(&mut api, core_msg)?;
 
+    api.forward_premium(&sender, premium)?;
+
+    let registered = match &reply {
+        CoreReply::ReferralCode(code) => Some(code.to_u64()),
+        _ => None,
+    };
+
+    // registering with the code about to be issued would refer oneself
+    if registered.is_some()
+        && registered == msg.referral_code
+        && api.self_referral_blocked()?
+        && !api.self_referral_allowed(&Id::from(env.contract.address.clone()))?
+    {
+        return Err(Error::Core(CoreError::SelfReferral));
+    }
+
+    if let (Some(code), Some(_)) = (msg.referral_code, registered) {
+        api.set_pending_credit_code(code)?;
+    }
+
     let response = _core::handle_reply(api, reply)?;
 
     let Some(code) = msg.referral_code else {
         return Ok(response);
     };
 
-    response
-        .record_referral()
-        .referral_code(code)
-        .referral_hub(env.contract.address)
-        .done()
-        .map_err(Error::from)
+    let Some(registered) = registered else {
+        return response
+            .record_referral()
+            .referral_code(code)
+            .referral_hub(env.contract.address)
+            .done()
+            .map_err(Error::from);
+    };
+
+    let data = cosmwasm_std::to_binary(&WithReferralCodeResponse {
+        referral_code: code,
+        data: ReferralCodeResponse { code: registered },
+    })?;
+
+    let record_referral =
+        cosmwasm_std::to_binary(&HubExecuteMsg::RecordReferral { code, dapp: None })?;
+
+    // a failed credit is reported by the reply, rather than reverting the registration
+    let credit = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.into_string(),
+            msg: record_referral,
+            funds: vec![],
+        },
+        ReplyId::CreditReferral.as_u64(),
+    );
+
+    Ok(response.set_data(data).add_submessage(credit))
 }
 
 /// Handle the reply from any issued sub-messages.
@@ -104,13 +207,78 @@ pub fn execute(
 /// - There is a problem with `cosmwasm_std` storage or serialization.
 #[allow(clippy::needless_pass_by_value)]
 pub fn reply(mut deps: DepsMut, env: Env, reply: Reply) -> Result<Response, Error> {
+    let cw_api = deps.api;
+
     let mut api = api::from_deps_mut(&mut deps, &env);
 
-    let msg = referrals_parse_cw::parse_init_pot_reply(reply)?;
+    match ReplyId::try_from(reply.id)? {
+        ReplyId::WithdrawRewards => {
+            let cmds = api.deferred_commands()?;
+            return _core::handle_reply(api, CoreReply::MultiCmd(cmds)).map_err(Error::from);
+        }
+
+        // only replied to on error
+        ReplyId::CreditReferral => {
+            let mut event = Event::new("referral_credit_failed");
+
+            if let Some(code) = api.pending_credit_code()? {
+                event = event.add_attribute("code", code.to_string());
+            }
+
+            if let SubMsgResult::Err(err) = reply.result {
+                event = event.add_attribute("error", err);
+            }
+
+            return Ok(Response::default().add_event(event));
+        }
+
+        ReplyId::CreateRewardsPot => {}
+    }
+
+    let pending_dapp = api.pending_rewards_pot_dapp()?;
+
+    let failure = match &reply.result {
+        SubMsgResult::Err(err) => pending_dapp.clone().map(|dapp| (dapp, err.clone())),
+        SubMsgResult::Ok(_) => None,
+    };
+
+    // the pot is instantiated with a salt derived from the pending dApp, so it's address is known
+    let expected_pot = match (&reply.result, &pending_dapp) {
+        (SubMsgResult::Ok(_), Some(dapp)) => Some(api.expected_rewards_pot(dapp)?),
+        _ => None,
+    };
+
+    let msg = referrals_parse_cw::parse_init_pot_reply(
+        cw_api,
+        reply,
+        Id::from(env.contract.address.clone()),
+        pending_dapp.clone(),
+        expected_pot,
+    )?;
 
     let reply = _core::exec(&mut api, msg)?;
 
-    _core::handle_reply(api, reply).map_err(Error::from)
+    // a fee set while activating is only set on chain once the pot is in place,
+    // a rolled back activation drops it
+    if let Some(dapp) = pending_dapp {
+        let fee = api.take_pending_dapp_fee(&dapp)?;
+
+        if let (Some(fee), None) = (fee, &failure) {
+            api.set_dapp_fee(dapp, fee)?;
+        }
+    }
+
+    let response = _core::handle_reply(api, reply)?;
+
+    let Some((dapp, err)) = failure else {
+        return Ok(response);
+    };
+
+    Ok(response.add_event(
+        Event::new("rewards_pot_init_failed")
+            .add_attribute("dapp", dapp.into_string())
+            .add_attribute("error", err),
+    ))
 }
 
 /// Handle a `referrals_cw::QueryMsg`.
@@ -123,6 +291,33 @@ pub fn reply(mut deps: DepsMut, env: Env, reply: Reply) -> Result<Response, Erro
 /// - There is a problem with `cosmwasm_std` storage or serialization.
 #[allow(clippy::needless_pass_by_value)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, Error> {
+    if let QueryMsg::HubConfig {} = msg {
+        let config = api::from_deps(deps, &env).hub_config()?;
+        return cosmwasm_std::to_binary(&config).map_err(Error::from);
+    }
+
+    if let QueryMsg::Simulate { msg, sender } = msg {
+        let core_msg = referrals_parse_cw::parse_hub_simulation(deps.api, &sender, msg)?;
+
+        let mut api = api::from_deps_recording(deps, &env);
+
+        let simulation = _core::simulate(&mut api, core_msg)?;
+
+        let response = SimulateResponse {
+            reply: cosmwasm_std::to_binary(&simulation.reply)?,
+            writes: simulation
+                .writes
+                .into_iter()
+                .map(|write| StorageWrite {
+                    key: Binary::from(write.key),
+                    value: write.value.map(Binary::from),
+                })
+                .collect(),
+        };
+
+        return cosmwasm_std::to_binary(&response).map_err(Error::from);
+    }
+
     let request = referrals_parse_cw::parse_hub_query(deps.api, msg)?;
 
     let api = api::from_deps(deps, &env);
@@ -3,6 +3,7 @@ use cosmwasm_std::{Binary, Env, MessageInfo, Reply, StdError};
 use referrals_parse_cw::Error as ParseError;
 
 use referrals_archway_api::rewards_pot as api;
+use referrals_archway_api::{ReplyId, UnknownReplyId};
 use referrals_core::rewards_pot as _core;
 use referrals_cw::rewards_pot::InstantiateResponse;
 
@@ -24,6 +25,8 @@ pub enum Error {
     Parse(#[from] ParseError),
     #[error(transparent)]
     CosmWasm(#[from] StdError),
+    #[error(transparent)]
+    UnknownReplyId(#[from] UnknownReplyId),
 }
 
 /// Handle the rewards-pot `InstantiateMsg`.
@@ -39,7 +42,13 @@ pub fn init(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, Error> {
-    api::from_deps_mut(&mut deps, &env).initialize(info.sender, &msg.dapp)?;
+    api::from_deps_mut(&mut deps, &env).initialize(
+        info.sender,
+        &msg.dapp,
+        msg.withdraw_records_limit,
+        msg.min_withdraw_interval_secs,
+        msg.rewards_denom.as_ref(),
+    )?;
 
     let data = cosmwasm_std::to_binary(&InstantiateResponse { dapp: msg.dapp })?;
 
@@ -78,6 +87,11 @@ pub fn execute(
 /// This function will return an error if the original request cannot be completed
 #[allow(clippy::needless_pass_by_value)]
 pub fn reply(mut deps: DepsMut, env: Env, reply: Reply) -> Result<Response, Error> {
+    // withdrawals are the only submessages a rewards pot replies to
+    if ReplyId::try_from(reply.id)? != ReplyId::WithdrawRewards {
+        return Err(Error::UnknownReplyId(UnknownReplyId(reply.id)));
+    }
+
     api::from_deps_mut(&mut deps, &env).handle_cw_reply(reply)?;
 
     Ok(Response::default())
@@ -107,6 +121,16 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, Error> {
             let admin = api.admin()?;
             cosmwasm_std::to_binary(&admin)?
         }
+
+        QueryMsg::OutstandingRecords {} => {
+            let outstanding = api.outstanding()?;
+            cosmwasm_std::to_binary(&outstanding)?
+        }
+
+        QueryMsg::RewardsBreakdown {} => {
+            let breakdown = api.rewards_breakdown()?;
+            cosmwasm_std::to_binary(&breakdown)?
+        }
     };
 
     Ok(response)
@@ -0,0 +1,52 @@
+//! Helpers for predicting the addresses of contracts instantiated with `instantiate2`,
+//! so clients can know a contract's address before it exists.
+
+use bech32::{FromBase32, ToBase32, Variant};
+use cosmwasm_std::{Binary, CanonicalAddr, HexBinary, StdError, StdResult};
+use sha2::{Digest, Sha256};
+
+/// Salt for instantiating a contract with the given label, as hex.
+#[must_use]
+pub fn label_salt(label: &str) -> String {
+    HexBinary::from(label.as_bytes()).to_hex()
+}
+
+/// Salt the hub instantiates a dApp's rewards pot with, so it's address can be derived from the
+/// dApp address alone.
+#[must_use]
+pub fn rewards_pot_salt(dapp: &str) -> Binary {
+    Binary::from(Sha256::digest(dapp.as_bytes()).to_vec())
+}
+
+/// Predict the address of a contract instantiated with `instantiate2`, without an instantiate
+/// message checksum. The address takes the same bech32 prefix as the `creator`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The `code_hash` or `salt` are not valid hex.
+/// - The `creator` is not a valid bech32 address.
+/// - The salt is empty or too long.
+pub fn predict_instantiate2_address(
+    code_hash: &str,
+    creator: &str,
+    salt: &str,
+) -> StdResult<String> {
+    let checksum = HexBinary::from_hex(code_hash)?;
+
+    let salt = HexBinary::from_hex(salt)?;
+
+    let (prefix, creator, _) = bech32::decode(creator)
+        .map_err(|err| StdError::generic_err(format!("invalid creator address - {err}")))?;
+
+    let creator = Vec::<u8>::from_base32(&creator)
+        .map(CanonicalAddr::from)
+        .map_err(|err| StdError::generic_err(format!("invalid creator address - {err}")))?;
+
+    let address =
+        cosmwasm_std::instantiate2_address(checksum.as_slice(), &creator, salt.as_slice())
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    bech32::encode(&prefix, address.as_slice().to_base32(), Variant::Bech32)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
@@ -2,17 +2,40 @@
 #![warn(clippy::pedantic)]
 
 use cosmwasm_schema::{cw_serde, schemars::JsonSchema, serde::Deserialize, serde::Serialize};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Timestamp, Uint128};
 
+#[path = "address-cw.rs"]
+pub mod address;
 #[path = "rewards-pot-cw.rs"]
 pub mod rewards_pot;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     /// Rewards pot contract code ID
+    #[cfg_attr(feature = "camel-compat", serde(alias = "rewardsPotCodeId"))]
     pub rewards_pot_code_id: u64,
     /// Contract premium amount
+    #[cfg_attr(feature = "camel-compat", serde(alias = "contractPremium"))]
     pub contract_premium: Uint128,
+    /// Denom of the rewards, flat fees & contract premium, must not be blank
+    #[cfg_attr(feature = "camel-compat", serde(alias = "rewardsDenom"))]
+    pub rewards_denom: String,
+    /// Activate the hub as a dApp of it's own, defaults to true
+    #[cfg_attr(feature = "camel-compat", serde(alias = "selfActivate"))]
+    pub self_activate: Option<bool>,
+    /// Reject referrals where the code owner is the dApp or it's collector, unless the dApp allows
+    /// them, defaults to `false`
+    #[cfg_attr(feature = "camel-compat", serde(alias = "blockSelfReferral"))]
+    pub block_self_referral: Option<bool>,
+}
+
+/// Set as the data of the hub's instantiation response
+#[cw_serde]
+pub struct HubInstantiateResponse {
+    /// The address of the instantiated hub
+    pub address: String,
+    /// Rewards pot contract code ID
+    pub rewards_pot_code_id: u64,
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -36,9 +59,18 @@ pub struct WithReferralCode<Msg> {
 pub enum ExecuteMsg {
     /// Register as a referrer.
     /// Responds with `ReferralCodeResponse`
+    #[cfg_attr(feature = "camel-compat", serde(alias = "registerReferrer"))]
     RegisterReferrer {},
+    /// Register a referral code on behalf of `owner`, only the hub & it's collector may issue.
+    /// Responds with `ReferralCodeResponse`
+    #[cfg_attr(feature = "camel-compat", serde(alias = "registerReferrerFor"))]
+    RegisterReferrerFor {
+        /// Address to own the referral code
+        owner: String,
+    },
     /// Activate as a dApp
     /// Rewards admin rights must be transferred prior to issuing
+    #[cfg_attr(feature = "camel-compat", serde(alias = "activateDapp"))]
     ActivateDapp {
         /// The name of the dApp
         name: String,
@@ -46,17 +78,37 @@ pub enum ExecuteMsg {
         percent: u8,
         /// Address of nominated rewards collector
         collector: String,
+        /// Initial flat fee, set along with the activation if provided
+        fee: Option<Uint128>,
+        /// Confirm a percent of 100 is intended, leaving the dApp no share of rewards
+        #[cfg_attr(feature = "camel-compat", serde(alias = "confirmNoDappShare"))]
+        confirm_no_dapp_share: Option<bool>,
+    },
+    /// Activate as a dApp that only tracks referred invocations, sharing no fees with referrers
+    /// Rewards admin rights must be transferred prior to issuing
+    #[cfg_attr(feature = "camel-compat", serde(alias = "activateDappTrackingOnly"))]
+    ActivateDappTrackingOnly {
+        /// The name of the dApp
+        name: String,
+        /// Address of nominated rewards collector
+        collector: String,
     },
     /// De-activate a dApp
+    #[cfg_attr(feature = "camel-compat", serde(alias = "deactivateDapp"))]
     DeactivateDapp {
         /// dApp address to de-register
         dapp: String,
         /// Address of nominated rewards admin
+        #[cfg_attr(feature = "camel-compat", serde(alias = "rewardsAdmin"))]
         rewards_admin: String,
         /// Address of nominated rewards recipient
+        #[cfg_attr(feature = "camel-compat", serde(alias = "rewardsRecipient"))]
         rewards_recipient: String,
+        /// Keep or remove the dApp's data, `Freeze` if not provided
+        mode: Option<DeactivationMode>,
     },
     /// Set a dApp's flat fee
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setDappFee"))]
     SetDappFee {
         /// dApp address to set fee for
         dapp: String,
@@ -64,23 +116,46 @@ pub enum ExecuteMsg {
         fee: Uint128,
     },
     /// Record a referral
+    #[cfg_attr(feature = "camel-compat", serde(alias = "recordReferral"))]
     RecordReferral {
         /// Referral code of referrer
         code: u64,
+        /// dApp address to record for, only accepted from it's relayer set with `SetReferralRelayer`
+        dapp: Option<String>,
     },
     /// Collect referrer earnings
+    /// The contract premium must be attached in the rewards denom, anything more is refunded
+    #[cfg_attr(feature = "camel-compat", serde(alias = "collectReferrer"))]
     CollectReferrer {
         /// Referral code to collect on behalf of
         code: u64,
         /// dApp address to collect earnings from
         dapp: String,
+        /// Address to receive the earnings, the sender if not given
+        recipient: Option<String>,
+    },
+    /// Collect referrer earnings from every dApp, a page at a time
+    /// The contract premium must be attached in the rewards denom, anything more is refunded
+    #[cfg_attr(feature = "camel-compat", serde(alias = "collectReferrerAll"))]
+    CollectReferrerAll {
+        /// Referral code to collect on behalf of
+        code: u64,
+        /// Index of the dApp to start from, the `next` of a previous call
+        start: Option<u64>,
+        /// Maximum number of dApps to collect from
+        limit: Option<u64>,
     },
     /// Collect a dApps remaining rewards
+    /// The contract premium must be attached in the rewards denom, anything more is refunded
+    #[cfg_attr(feature = "camel-compat", serde(alias = "collectDapp"))]
     CollectDapp {
         /// dApp address to collect rewards on behalf of
         dapp: String,
+        /// Address to receive the rewards, the sender if not given
+        recipient: Option<String>,
     },
     /// Transfer the ownership of a referral code
+    #[cfg_attr(feature = "camel-compat", serde(alias = "transferOwnership"))]
     TransferOwnership {
         /// Referral code to transfer ownership of
         code: u64,
@@ -88,16 +163,153 @@ pub enum ExecuteMsg {
         owner: String,
     },
     // Configure a registered dApp
+    #[cfg_attr(feature = "camel-compat", serde(alias = "configureDapp"))]
     ConfigureDapp {
         /// dApp address to configure
         dapp: String,
+        /// Rename the dApp
+        name: Option<String>,
         /// Set the a new percentage of fees paid to referrers
         percent: Option<u8>,
-        /// Set a new collector address
+        /// Nominate a new collector address, to be accepted with `AcceptCollector`
         collector: Option<String>,
-        /// Set a repository URL
+        /// Set a http(s) repository URL
+        #[cfg_attr(feature = "camel-compat", serde(alias = "repoUrl"))]
         repo_url: Option<String>,
+        /// Remove the repository URL, cannot be combined with `repo_url`
+        #[cfg_attr(feature = "camel-compat", serde(alias = "clearRepoUrl"))]
+        clear_repo_url: Option<bool>,
+        /// Allow referrals using a code owned by the dApp or it's collector
+        #[cfg_attr(feature = "camel-compat", serde(alias = "allowSelfReferral"))]
+        allow_self_referral: Option<bool>,
+        /// Cap the invocations a single referral code earns from, later invocations are counted but earn nothing
+        #[cfg_attr(feature = "camel-compat", serde(alias = "maxInvocationsPerCode"))]
+        max_invocations_per_code: Option<u64>,
+        /// Confirm a percent of 100 is intended, leaving the dApp no share of rewards
+        #[cfg_attr(feature = "camel-compat", serde(alias = "confirmNoDappShare"))]
+        confirm_no_dapp_share: Option<bool>,
+    },
+    /// Configure several registered dApps, if any update is invalid none are applied
+    #[cfg_attr(feature = "camel-compat", serde(alias = "configureDapps"))]
+    ConfigureDapps { updates: Vec<ConfigureDappEntry> },
+    /// Accept the nomination as a dApp's collector
+    #[cfg_attr(feature = "camel-compat", serde(alias = "acceptCollector"))]
+    AcceptCollector {
+        /// dApp address nominating the sender as collector
+        dapp: String,
+    },
+    /// Multiply referrer shares of a dApp's fee between two block heights, replacing any previous campaign
+    /// The multiplied share never exceeds the full fee
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setCampaign"))]
+    SetCampaign {
+        /// dApp address to run the campaign for
+        dapp: String,
+        /// Numerator of the multiplier, non-zero
+        #[cfg_attr(feature = "camel-compat", serde(alias = "multiplierNum"))]
+        multiplier_num: u64,
+        /// Denominator of the multiplier, non-zero
+        #[cfg_attr(feature = "camel-compat", serde(alias = "multiplierDen"))]
+        multiplier_den: u64,
+        /// First block height of the campaign
+        #[cfg_attr(feature = "camel-compat", serde(alias = "startHeight"))]
+        start_height: u64,
+        /// Last block height of the campaign, inclusive
+        #[cfg_attr(feature = "camel-compat", serde(alias = "endHeight"))]
+        end_height: u64,
+    },
+    /// Authorise a relayer to `RecordReferral` on behalf of a dApp, replacing any previous relayer
+    /// Only the dApp or its collector can set the relayer
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setReferralRelayer"))]
+    SetReferralRelayer {
+        /// dApp address to set the relayer for
+        dapp: String,
+        /// Address of the relayer, removing the relayer if not given
+        relayer: Option<String>,
     },
+    /// Block or unblock a referral code from recording referrals & collecting earnings
+    /// Only the hub or its collector can set the status
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setCodeStatus"))]
+    SetCodeStatus { code: u64, blocked: bool },
+    /// Enable or disable the message kinds the hub accepts, setting them is always accepted
+    /// Only the hub or its collector can set the enabled features
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setEnabledFeatures"))]
+    SetEnabledFeatures {
+        register: bool,
+        record: bool,
+        collect: bool,
+        configure: bool,
+    },
+    /// Set the smallest amount a single `CollectReferrer` or `CollectDapp` may pay out,
+    /// `None` or zero removing the minimum. `CollectReferrerAll` is exempt, batching dust instead
+    /// Only the hub or its collector can set the minimum
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setMinCollectAmount"))]
+    SetMinCollectAmount { amount: Option<Uint128> },
+    /// Set whether the hub may activate itself as a dApp, allowed unless set otherwise
+    /// Only the hub or its collector can set whether it's allowed
+    #[cfg_attr(feature = "camel-compat", serde(alias = "setAllowSelfDapp"))]
+    SetAllowSelfDapp { allowed: bool },
+    /// Hand the rewards pots of the given dApps over to a new admin, e.g. a redeployed hub
+    /// Only the hub or its collector can hand over the pots
+    #[cfg_attr(feature = "camel-compat", serde(alias = "handoverPots"))]
+    HandoverPots {
+        dapps: Vec<String>,
+        #[cfg_attr(feature = "camel-compat", serde(alias = "newAdmin"))]
+        new_admin: String,
+    },
+}
+
+/// How `DeactivateDapp` treats the dApp's data
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum DeactivationMode {
+    /// Keep the dApp's data, rejecting new referrals while earnings & rewards can still be collected
+    #[default]
+    Freeze,
+    /// Remove the dApp, only once it's referrers have collected all their earnings
+    Purge,
+}
+
+/// A single dApp's configuration within `ConfigureDapps`, as per `ConfigureDapp`
+#[cw_serde]
+pub struct ConfigureDappEntry {
+    /// dApp address to configure
+    pub dapp: String,
+    /// Rename the dApp
+    pub name: Option<String>,
+    /// Set the a new percentage of fees paid to referrers
+    pub percent: Option<u8>,
+    /// Nominate a new collector address, to be accepted with `AcceptCollector`
+    pub collector: Option<String>,
+    /// Set a http(s) repository URL
+    #[cfg_attr(feature = "camel-compat", serde(alias = "repoUrl"))]
+    pub repo_url: Option<String>,
+    /// Remove the repository URL, cannot be combined with `repo_url`
+    #[cfg_attr(feature = "camel-compat", serde(alias = "clearRepoUrl"))]
+    pub clear_repo_url: Option<bool>,
+    /// Allow referrals using a code owned by the dApp or it's collector
+    #[cfg_attr(feature = "camel-compat", serde(alias = "allowSelfReferral"))]
+    pub allow_self_referral: Option<bool>,
+    /// Cap the invocations a single referral code earns from
+    #[cfg_attr(feature = "camel-compat", serde(alias = "maxInvocationsPerCode"))]
+    pub max_invocations_per_code: Option<u64>,
+    /// Confirm a percent of 100 is intended, leaving the dApp no share of rewards
+    #[cfg_attr(feature = "camel-compat", serde(alias = "confirmNoDappShare"))]
+    pub confirm_no_dapp_share: Option<bool>,
+}
+
+/// Set as the data of a registration wrapped with a referral code
+#[cw_serde]
+pub struct WithReferralCodeResponse<Data> {
+    /// Referral code credited with the registration
+    pub referral_code: u64,
+    /// Response data of the wrapped msg
+    pub data: Data,
+}
+
+#[cw_serde]
+pub struct CollectReferrerAllResponse {
+    /// Index to `start` the next `CollectReferrerAll` from, if any dApps remain
+    pub next: Option<u64>,
 }
 
 #[cw_serde]
@@ -110,16 +322,89 @@ pub struct ReferralCodeResponse {
 #[derive(cosmwasm_schema::QueryResponses)]
 pub enum QueryMsg {
     #[returns(TotalDappsResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "totalDapps"))]
     TotalDapps {},
     #[returns(DappResponse)]
     Dapp { dapp: String },
+    /// The dApp activated with the given name, names being unique
+    #[returns(DappResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "dappByName"))]
+    DappByName { name: String },
     #[returns(AllDappsResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "allDapps"))]
     AllDapps {
         start: Option<u64>,
+        /// Continue from the dApp after this `next_key`, taking precedence over `start`
+        after: Option<String>,
+        /// Capped at 30 dApps, the default when unset
         limit: Option<u64>,
+        /// Skip inactive dApps, `limit` then applies to active dApps only
+        #[cfg_attr(feature = "camel-compat", serde(alias = "onlyActive"))]
+        only_active: Option<bool>,
     },
     #[returns(ReferralCodeResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "refferalCode"))]
     RefferalCode { referrer: String },
+    #[returns(TotalsResponse)]
+    Totals {},
+    #[returns(RewardsPotAddressResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "rewardsPotAddress"))]
+    RewardsPotAddress { dapp: String },
+    #[returns(HubConfigResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "hubConfig"))]
+    HubConfig {},
+    /// Earnings owed to a referral code by a dApp, the amount `CollectReferrer` would pay
+    #[returns(OwedResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "owedToReferrer"))]
+    OwedToReferrer { code: u64, dapp: String },
+    /// Rewards owed to a dApp, the amount `CollectDapp` would pay
+    #[returns(OwedResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "owedToDapp"))]
+    OwedToDapp { dapp: String },
+    /// Activity of a referral code with a dApp
+    #[returns(ReferrerStatsResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "referrerStats"))]
+    ReferrerStats { code: u64, dapp: String },
+    /// Referred invocations of a dApp on each day of a range, up to 90 days
+    #[returns(DappInvocationsOverRangeResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "dappInvocationsOverRange"))]
+    DappInvocationsOverRange {
+        dapp: String,
+        /// First day of the range, counted in days since the UNIX epoch
+        #[cfg_attr(feature = "camel-compat", serde(alias = "fromDay"))]
+        from_day: u64,
+        /// Last day of the range, inclusive
+        #[cfg_attr(feature = "camel-compat", serde(alias = "toDay"))]
+        to_day: u64,
+    },
+    /// Current owner of a referral code
+    #[returns(CodeOwnerResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "codeOwner"))]
+    CodeOwner { code: u64 },
+    /// Rewards of a dApp's rewards pot & how they've been distributed
+    #[returns(DappRewardsResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "dappRewards"))]
+    DappRewards { dapp: String },
+    /// Whether an address is a registered dApp, to check before recording a referral
+    #[returns(IsDappResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "isDapp"))]
+    IsDapp { address: String },
+    /// Handle `msg` as if sent by `sender` without committing any state, for estimating fees.
+    /// The `sender` is supplied by the caller, so passing authorization here proves nothing.
+    /// Attached funds are not simulated, so the premium of a collection is not checked.
+    #[returns(SimulateResponse)]
+    Simulate { msg: ExecuteMsg, sender: String },
+    /// A page of a section of the hub state, for indexers to bootstrap from without replaying events.
+    /// `limit` is capped at 100 records.
+    #[returns(ExportStateResponse)]
+    #[cfg_attr(feature = "camel-compat", serde(alias = "exportState"))]
+    ExportState {
+        section: ExportSection,
+        /// Continue from the record after this `next_key`
+        #[cfg_attr(feature = "camel-compat", serde(alias = "startAfter"))]
+        start_after: Option<Binary>,
+        limit: u32,
+    },
 }
 
 #[cw_serde]
@@ -136,8 +421,11 @@ pub struct DappResponse {
     pub active: bool,
     /// Name of the dApp (if Active)
     pub name: Option<String>,
-    /// Percent of fee shared with referrers
+    /// Percent of fee shared with referrers, 0 for tracking only dApps
     pub percent: u8,
+    /// Percent of fee retained by the dApp, 0 when everything is shared with referrers
+    #[serde(default)]
+    pub dapp_share_percent: u8,
     /// Repo URL if set
     pub repo_url: Option<String>,
     /// Fee amount if set
@@ -150,12 +438,262 @@ pub struct DappResponse {
     pub total_contributions: Uint128,
     /// Total rewards earned by dApp
     pub total_rewards: Uint128,
+    /// Block of the latest referred invocation, if any
+    #[serde(default)]
+    pub last_active: Option<Activity>,
+    /// Campaign running at the current block, if any
+    #[serde(default)]
+    pub campaign: Option<Campaign>,
+    /// Position in the order dApps were first activated, to break ties when sorting by name
+    #[serde(default)]
+    pub activation_index: u64,
+}
+
+#[cw_serde]
+pub struct Campaign {
+    /// Numerator of the multiplier
+    pub multiplier_num: u64,
+    /// Denominator of the multiplier
+    pub multiplier_den: u64,
+    /// First block height of the campaign
+    pub start_height: u64,
+    /// Last block height of the campaign, inclusive
+    pub end_height: u64,
+}
+
+#[cw_serde]
+pub struct Activity {
+    /// Block height
+    pub height: u64,
+    /// Block time
+    pub time: Timestamp,
 }
 
 #[cw_serde]
 pub struct AllDappsResponse {
     /// All the dApp's requested
     pub dapps: Vec<DappResponse>,
+    /// Total number of dApps ever activated, regardless of pagination
+    #[serde(default)]
+    pub total: u64,
+    /// Cursor to pass as `after` for the next page, set when a full or capped page was returned
+    pub next_key: Option<String>,
+    /// dApps left out of the page as their info could not be read
+    #[serde(default)]
+    pub errors: Vec<DappError>,
+}
+
+#[cw_serde]
+pub struct DappError {
+    /// Address of the dApp
+    pub dapp: String,
+    /// Why the dApp's info could not be read
+    pub error: String,
+}
+
+#[cw_serde]
+pub struct TotalsResponse {
+    /// Total number of dApps ever activated
+    pub total_dapps: u64,
+    /// Number of dApps currently active
+    pub active_dapps: u64,
+    /// Total number of referral codes issued
+    pub referral_codes: u64,
+    /// Total invocations by all referrers across all dApps
+    pub total_invocations: u64,
+    /// Total contributions made to referrers across all dApps
+    pub total_contributions: Uint128,
+    /// Total rewards collected by referrers
+    pub total_collected: Uint128,
+}
+
+#[cw_serde]
+pub struct RewardsPotAddressResponse {
+    /// Address of the dApp's rewards pot, whether created yet or not
+    pub address: String,
+}
+
+#[cw_serde]
+pub struct HubConfigResponse {
+    /// Rewards pot contract code ID
+    pub rewards_pot_code_id: u64,
+    /// Number of rewards pots created
+    pub rewards_pot_count: u64,
+    /// Contract premium amount
+    pub contract_premium: Uint128,
+    /// The hub's owner, collecting it's premiums, once the hub has activated itself
+    pub collector: Option<String>,
+    /// Message kinds the hub accepts
+    pub enabled_features: EnabledFeatures,
+    /// Smallest amount a single collection may pay out, if any
+    pub min_collect_amount: Option<Uint128>,
+    /// Whether the hub may activate itself as a dApp
+    pub allow_self_dapp: bool,
+}
+
+#[cw_serde]
+pub struct EnabledFeatures {
+    /// Registering referrers & activating or deactivating dApps
+    pub register: bool,
+    /// Recording referrals
+    pub record: bool,
+    /// Collecting earnings & rewards
+    pub collect: bool,
+    /// Configuring dApps & referral codes
+    pub configure: bool,
+}
+
+#[cw_serde]
+pub struct OwedResponse {
+    /// Amount yet to be collected
+    pub owed: Uint128,
+    /// Amount collected so far
+    pub already_collected: Uint128,
+}
+
+#[cw_serde]
+pub struct ReferrerStatsResponse {
+    /// Number of invocations referred
+    pub invocations: u64,
+    /// Total earnings with the dApp, collected or not
+    pub earnings: Uint128,
+    /// Block of the latest referred invocation, if any
+    pub last_active: Option<Activity>,
+    /// Blocked status of the referral code
+    pub blocked: bool,
+}
+
+#[cw_serde]
+pub struct DailyInvocations {
+    /// Day counted in days since the UNIX epoch
+    pub day: u64,
+    /// Number of invocations referred on the day
+    pub invocations: u64,
+}
+
+#[cw_serde]
+pub struct DappInvocationsOverRangeResponse {
+    /// Invocations on every day of the range, in order
+    pub days: Vec<DailyInvocations>,
+}
+
+#[cw_serde]
+pub struct DappRewardsResponse {
+    /// Address of the dApp's rewards pot
+    pub rewards_pot: String,
+    /// Total rewards received & receivable by the rewards pot
+    pub total_rewards: Uint128,
+    /// Total contributions made to referrers
+    pub contributions: Uint128,
+    /// Amount collected by referrers
+    pub referrers_collected: Uint128,
+    /// Amount collected by the dApp
+    pub dapp_collected: Uint128,
+    /// Rewards yet to be collected by the dApp or it's referrers
+    pub undistributed: Uint128,
+}
+
+#[cw_serde]
+pub struct IsDappResponse {
+    /// The address has been activated as a dApp & not purged
+    pub registered: bool,
+    /// The dApp can record referrals
+    pub active: bool,
+}
+
+#[cw_serde]
+pub struct CodeOwnerResponse {
+    /// Address owning the referral code, if it has been registered
+    pub owner: Option<String>,
+}
+
+#[cw_serde]
+pub struct SimulateResponse {
+    /// The hub's reply to the simulated msg, JSON encoded
+    pub reply: Binary,
+    /// Storage writes the simulated msg would make, the final write to each key ordered by key
+    pub writes: Vec<StorageWrite>,
+}
+
+#[cw_serde]
+pub struct StorageWrite {
+    pub key: Binary,
+    /// Value written to the key, `None` if the key would be removed
+    pub value: Option<Binary>,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum ExportSection {
+    /// Every dApp ever activated, in activation order
+    Dapps,
+    /// Every referral code issued, in order
+    Codes,
+    /// Earnings of each referral code with each dApp it has referred, by code then dApp activation order
+    Earnings,
+    /// Contributions & collections of each dApp, in activation order
+    Collections,
+}
+
+#[cw_serde]
+pub struct DappExport {
+    pub address: String,
+    /// Position in the order dApps were first activated
+    pub activation_index: u64,
+    /// Name of the dApp, `None` if deactivated
+    pub name: Option<String>,
+    pub percent: u8,
+    pub collector: Option<String>,
+    pub rewards_pot: Option<String>,
+    pub repo_url: Option<String>,
+}
+
+#[cw_serde]
+pub struct CodeExport {
+    pub code: u64,
+    pub owner: Option<String>,
+    /// Total earnings across all dApps, collected or not
+    pub total_earnings: Uint128,
+    pub total_collected: Uint128,
+    pub blocked: bool,
+}
+
+#[cw_serde]
+pub struct EarningsExport {
+    pub code: u64,
+    pub dapp: String,
+    pub invocations: u64,
+    /// Earnings with the dApp, collected or not
+    pub earnings: Uint128,
+    pub collected: Uint128,
+}
+
+#[cw_serde]
+pub struct CollectionsExport {
+    pub dapp: String,
+    /// Total contributions made to referrers
+    pub contributions: Uint128,
+    /// Amount collected by the dApp
+    pub dapp_collected: Uint128,
+    /// Amount collected by referrers
+    pub referrers_collected: Uint128,
+}
+
+#[cw_serde]
+pub enum ExportRecord {
+    Dapp(DappExport),
+    Code(CodeExport),
+    Earnings(EarningsExport),
+    Collections(CollectionsExport),
+}
+
+#[cw_serde]
+pub struct ExportStateResponse {
+    /// Records of the section, ordered by key
+    pub records: Vec<ExportRecord>,
+    /// Key of the last record of a full page, to continue from with `start_after`.
+    /// Keys identify records rather than positions in storage, so remain valid across versions
+    pub next_key: Option<Binary>,
 }
 
 impl From<ExecuteMsg> for WithReferralCode<ExecuteMsg> {
@@ -212,6 +750,8 @@ where
                     {
                         match v {
                             "referral_code" => Ok(Field::ReferralCode),
+                            #[cfg(feature = "camel-compat")]
+                            "referralCode" => Ok(Field::ReferralCode),
                             _ => Ok(Field::Msg(Value::String(v.to_owned()))),
                         }
                     }
@@ -222,6 +762,8 @@ where
                     {
                         match v {
                             "referral_code" => Ok(Field::ReferralCode),
+                            #[cfg(feature = "camel-compat")]
+                            "referralCode" => Ok(Field::ReferralCode),
                             _ => Ok(Field::Msg(Value::String(v.to_owned()))),
                         }
                     }
@@ -232,6 +774,8 @@ where
                     {
                         match v {
                             b"referral_code" => Ok(Field::ReferralCode),
+                            #[cfg(feature = "camel-compat")]
+                            b"referralCode" => Ok(Field::ReferralCode),
                             _ => Ok(Field::Msg(Value::Bytes(v.to_owned()))),
                         }
                     }
@@ -242,6 +786,8 @@ where
                     {
                         match v.as_slice() {
                             b"referral_code" => Ok(Field::ReferralCode),
+                            #[cfg(feature = "camel-compat")]
+                            b"referralCode" => Ok(Field::ReferralCode),
                             _ => Ok(Field::Msg(Value::Bytes(v))),
                         }
                     }
@@ -252,6 +798,8 @@ where
                     {
                         match v {
                             b"referral_code" => Ok(Field::ReferralCode),
+                            #[cfg(feature = "camel-compat")]
+                            b"referralCode" => Ok(Field::ReferralCode),
                             _ => Ok(Field::Msg(Value::Bytes(v.to_owned()))),
                         }
                     }
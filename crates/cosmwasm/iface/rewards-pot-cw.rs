@@ -4,6 +4,12 @@ use cosmwasm_std::Uint128;
 #[cw_serde]
 pub struct InstantiateMsg {
     pub dapp: String,
+    /// Maximum number of rewards records withdrawn per `WithdrawRewards`, defaults to 500
+    pub withdraw_records_limit: Option<u64>,
+    /// Minimum number of seconds between `WithdrawRewards`, defaults to zero (disabled)
+    pub min_withdraw_interval_secs: Option<u64>,
+    /// Denom of the rewards until the first withdrawal confirms it, defaults to the bonded denom
+    pub rewards_denom: Option<String>,
 }
 
 #[cw_serde]
@@ -15,8 +21,18 @@ pub struct InstantiateResponse {
 pub enum ExecuteMsg {
     /// Withdraw any pending rewards
     WithdrawRewards {},
-    /// Distribute some collected rewards
-    DistributeRewards { recipient: String, amount: Uint128 },
+    /// Distribute some collected rewards, if `clamp` is set the amount is limited to the pot's balance
+    /// Rewards are sent in `denom`, defaulting to the rewards denom
+    DistributeRewards {
+        recipient: String,
+        amount: Uint128,
+        clamp: Option<bool>,
+        denom: Option<String>,
+    },
+    /// Send the pot's balance of a denom to a recipient, undistributed rewards are excluded
+    Sweep { denom: String, recipient: String },
+    /// Hand the pot over to a new admin, only the current admin may issue
+    UpdateAdmin { new_admin: String },
 }
 
 #[cw_serde]
@@ -29,6 +45,12 @@ pub enum QueryMsg {
     Dapp {},
     #[returns(AdminResponse)]
     Admin {},
+    /// The number of rewards records yet to be withdrawn, along with those already withdrawn
+    #[returns(OutstandingRecordsResponse)]
+    OutstandingRecords {},
+    /// The total rewards split into those collected and those pending withdrawal
+    #[returns(RewardsBreakdownResponse)]
+    RewardsBreakdown {},
 }
 
 #[cw_serde]
@@ -48,3 +70,23 @@ pub struct AdminResponse {
     /// The rewards pot admin address
     pub admin: String,
 }
+
+#[cw_serde]
+pub struct OutstandingRecordsResponse {
+    /// The number of rewards records yet to be withdrawn
+    pub outstanding: u64,
+    /// The number of rewards records withdrawn into the pot
+    pub collected: u64,
+    /// The total number of rewards records, `outstanding` + `collected`
+    pub total: u64,
+}
+
+#[cw_serde]
+pub struct RewardsBreakdownResponse {
+    /// The rewards withdrawn into the pot
+    pub collected: Uint128,
+    /// The rewards in records yet to be withdrawn
+    pub pending: Uint128,
+    /// The total amount of rewards received, `collected` + `pending`
+    pub total: Uint128,
+}
@@ -1,40 +1,219 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
-use std::num::NonZeroU128;
+use std::num::{NonZeroU128, NonZeroU64};
 
-use cosmwasm_std::{to_binary, Api, Binary, MessageInfo, Reply, StdError, Uint128};
+use cosmwasm_std::{
+    to_binary, Api, Binary, MessageInfo, Reply, StdError, SubMsgResult, Timestamp, Uint128,
+};
 
 use cw_utils::ParseReplyError;
 
 use referrals_core::hub::{
-    Collection, Configure, DappInfo, DappMetadata, Kind as HubMsgKind, Msg as HubMsg,
-    NonZeroPercent, QueryRequest, QueryResponse, ReferralCode, Registration,
+    Activity, Collection, Configure, DappCampaign, DappInfo, DappMetadata, DappRepoUrl,
+    DeactivationMode, ExportRecord, ExportSection, Features, InvalidPercent, Kind as HubMsgKind,
+    Msg as HubMsg, NonZeroPercent, Owed, QueryRequest, QueryResponse, ReferralCode, Registration,
+    UnconfirmedNoDappShare,
 };
 use referrals_core::rewards_pot::{Kind as RewardsPotKind, Msg as RewardsPotMsg};
-use referrals_core::Id;
+use referrals_core::{Id, IdError};
 
 use referrals_cw::rewards_pot::ExecuteMsg as PotExecuteMsg;
 use referrals_cw::rewards_pot::InstantiateResponse as PotInitResponse;
-use referrals_cw::{AllDappsResponse, DappResponse, QueryMsg as HubQueryMsg, ReferralCodeResponse};
-use referrals_cw::{ExecuteMsg as HubExecuteMsg, TotalDappsResponse};
+use referrals_cw::{
+    Activity as CwActivity, AllDappsResponse, Campaign as CwCampaign, CodeExport,
+    CodeOwnerResponse, CollectionsExport, DailyInvocations as CwDailyInvocations, DappError,
+    DappExport as CwDappExport, DappInvocationsOverRangeResponse, DappResponse,
+    DappRewardsResponse, EarningsExport, ExportRecord as CwExportRecord,
+    ExportSection as CwExportSection, ExportStateResponse, IsDappResponse, OwedResponse,
+    QueryMsg as HubQueryMsg, ReferralCodeResponse, ReferrerStatsResponse,
+    RewardsPotAddressResponse, TotalsResponse,
+};
+use referrals_cw::{
+    ConfigureDappEntry, DeactivationMode as CwDeactivationMode, ExecuteMsg as HubExecuteMsg,
+    TotalDappsResponse,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("invalid address - {0}")]
     InvalidAddress(#[from] StdError),
-    #[error("invalid percent - valid value is any integer between 1 & 100")]
-    InvalidPercent,
+    #[error("invalid id - {0}")]
+    InvalidId(#[from] IdError),
+    #[error("invalid address - {len} bytes exceeds the maximum of {max}")]
+    AddressTooLong { len: usize, max: usize },
+    #[error("invalid name - expected a non-empty name")]
+    EmptyName,
+    #[error("invalid name - {len} bytes exceeds the maximum of {max}")]
+    NameTooLong { len: usize, max: usize },
+    #[error("invalid repo url - {len} bytes exceeds the maximum of {max}")]
+    RepoUrlTooLong { len: usize, max: usize },
+    #[error("invalid repo url - expected an http(s) url with a host")]
+    InvalidRepoUrl,
+    #[error("invalid repo url - cannot both set & clear the repo url")]
+    ConflictingRepoUrl,
+    #[error(transparent)]
+    InvalidPercent(#[from] InvalidPercent),
+    #[error(transparent)]
+    UnconfirmedNoDappShare(#[from] UnconfirmedNoDappShare),
     #[error("invalid fee - expected non-zero value")]
     InvalidFee,
+    #[error("invalid referral code - expected non-zero value")]
+    InvalidReferralCode,
     #[error("invalid amount - expected non-zero value")]
     InvalidAmount,
+    #[error("invalid invocation cap - expected non-zero value")]
+    InvalidInvocationCap,
+    #[error("invalid multiplier - expected non-zero numerator & denominator")]
+    InvalidMultiplier,
+    #[error("invalid campaign - start height is after end height")]
+    InvalidCampaignWindow,
     #[error(transparent)]
     Reply(#[from] ParseReplyError),
     #[error("invalid reply - expected data")]
     ExpectedReplyData,
     #[error("invalid reply - error parsing data - {0}")]
     InvalidReplyData(StdError),
+    #[error("invalid reply - no rewards pot instantiation pending")]
+    NoPendingRewardsPot,
+    #[error("invalid reply - rewards pot instantiated at {actual}, expected {expected}")]
+    UnexpectedRewardsPot { expected: String, actual: String },
+    #[error("invalid query - not handled by referrals core")]
+    NonCoreQuery,
+}
+
+/// Maximum length of an address in bytes, that of the longest bech32 string
+pub const MAX_ADDRESS_LEN: usize = 90;
+/// Maximum length of a dApp name in bytes
+pub const MAX_NAME_LEN: usize = 128;
+/// Maximum length of a dApp repository URL in bytes
+pub const MAX_REPO_URL_LEN: usize = 512;
+
+/// Validate an untrusted address, rejecting any too long to be used as a storage key.
+fn validate_addr(api: &dyn Api, addr: &str) -> Result<Id, Error> {
+    if addr.len() > MAX_ADDRESS_LEN {
+        return Err(Error::AddressTooLong {
+            len: addr.len(),
+            max: MAX_ADDRESS_LEN,
+        });
+    }
+
+    api.addr_validate(addr).map(Id::from).map_err(Error::from)
+}
+
+fn validate_name(name: String) -> Result<String, Error> {
+    if name.trim().is_empty() {
+        return Err(Error::EmptyName);
+    }
+
+    if name.len() > MAX_NAME_LEN {
+        return Err(Error::NameTooLong {
+            len: name.len(),
+            max: MAX_NAME_LEN,
+        });
+    }
+
+    Ok(name)
+}
+
+fn validate_repo_url(repo_url: String) -> Result<String, Error> {
+    if repo_url.len() > MAX_REPO_URL_LEN {
+        return Err(Error::RepoUrlTooLong {
+            len: repo_url.len(),
+            max: MAX_REPO_URL_LEN,
+        });
+    }
+
+    let scheme_len = ["https://", "http://"]
+        .into_iter()
+        .find(|scheme| {
+            repo_url
+                .get(..scheme.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+        })
+        .map(str::len)
+        .ok_or(Error::InvalidRepoUrl)?;
+
+    let authority = repo_url[scheme_len..]
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+
+    // any credentials precede the host, a port follows it
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+
+    let valid_host = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+
+    if !valid_host
+        || repo_url
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(Error::InvalidRepoUrl);
+    }
+
+    Ok(repo_url)
+}
+
+fn validate_percent(
+    percent: u8,
+    confirm_no_dapp_share: Option<bool>,
+) -> Result<NonZeroPercent, Error> {
+    NonZeroPercent::try_from(percent)?
+        .check_dapp_share(confirm_no_dapp_share.unwrap_or_default())
+        .map_err(Error::from)
+}
+
+fn parse_repo_url(
+    repo_url: Option<String>,
+    clear_repo_url: Option<bool>,
+) -> Result<Option<DappRepoUrl>, Error> {
+    match (repo_url, clear_repo_url.unwrap_or_default()) {
+        (Some(_), true) => Err(Error::ConflictingRepoUrl),
+        (Some(repo_url), false) => {
+            validate_repo_url(repo_url).map(|url| Some(DappRepoUrl::Set(url)))
+        }
+        (None, true) => Ok(Some(DappRepoUrl::Clear)),
+        (None, false) => Ok(None),
+    }
+}
+
+fn parse_dapp_update(
+    api: &dyn Api,
+    update: ConfigureDappEntry,
+) -> Result<(Id, DappMetadata), Error> {
+    let ConfigureDappEntry {
+        dapp,
+        name,
+        percent,
+        collector,
+        repo_url,
+        clear_repo_url,
+        allow_self_referral,
+        max_invocations_per_code,
+        confirm_no_dapp_share,
+    } = update;
+
+    let dapp = validate_addr(api, &dapp)?;
+
+    let metadata = DappMetadata {
+        name: name.map(validate_name).transpose()?,
+        percent: percent
+            .map(|percent| validate_percent(percent, confirm_no_dapp_share))
+            .transpose()?,
+        collector: collector.map(|c| validate_addr(api, &c)).transpose()?,
+        repo_url: parse_repo_url(repo_url, clear_repo_url)?,
+        allow_self_referral,
+        max_invocations_per_code: max_invocations_per_code
+            .map(|max| NonZeroU64::new(max).ok_or(Error::InvalidInvocationCap))
+            .transpose()?,
+    };
+
+    Ok((dapp, metadata))
 }
 
 /// Parse an untrusted user provided `referrals_cw::ExecuteMsg` into a trusted core msg
@@ -50,70 +229,199 @@ pub fn parse_hub_exec(
     let kind = match cw_msg {
         HubExecuteMsg::RegisterReferrer {} => HubMsgKind::Register(Registration::Referrer),
 
+        HubExecuteMsg::RegisterReferrerFor { owner } => {
+            HubMsgKind::Register(Registration::ReferrerFor {
+                owner: validate_addr(api, &owner)?,
+            })
+        }
+
         HubExecuteMsg::ActivateDapp {
             name,
             percent,
             collector,
+            fee,
+            confirm_no_dapp_share,
         } => HubMsgKind::Register(Registration::ActivateDapp {
-            name,
-            percent: NonZeroPercent::new(percent).ok_or(Error::InvalidPercent)?,
-            collector: api.addr_validate(&collector).map(Id::from)?,
+            name: validate_name(name)?,
+            percent: validate_percent(percent, confirm_no_dapp_share)?,
+            collector: validate_addr(api, &collector)?,
+            fee: fee
+                .map(|fee| NonZeroU128::new(fee.u128()).ok_or(Error::InvalidFee))
+                .transpose()?,
         }),
 
+        HubExecuteMsg::ActivateDappTrackingOnly { name, collector } => {
+            HubMsgKind::Register(Registration::ActivateDappTrackingOnly {
+                name: validate_name(name)?,
+                collector: validate_addr(api, &collector)?,
+            })
+        }
+
         HubExecuteMsg::DeactivateDapp {
             dapp,
             rewards_admin,
             rewards_recipient,
+            mode,
         } => HubMsgKind::Register(Registration::DeactivateDapp {
-            dapp: api.addr_validate(&dapp).map(Id::from)?,
-            rewards_admin: api.addr_validate(&rewards_admin).map(Id::from)?,
-            rewards_recipient: api.addr_validate(&rewards_recipient).map(Id::from)?,
+            dapp: validate_addr(api, &dapp)?,
+            rewards_admin: validate_addr(api, &rewards_admin)?,
+            rewards_recipient: validate_addr(api, &rewards_recipient)?,
+            mode: match mode.unwrap_or_default() {
+                CwDeactivationMode::Freeze => DeactivationMode::Freeze,
+                CwDeactivationMode::Purge => DeactivationMode::Purge,
+            },
         }),
 
         HubExecuteMsg::SetDappFee { dapp, fee } => HubMsgKind::Config(Configure::DappFee {
-            dapp: api.addr_validate(&dapp).map(Id::from)?,
+            dapp: validate_addr(api, &dapp)?,
             fee: NonZeroU128::new(fee.u128()).ok_or(Error::InvalidFee)?,
         }),
 
-        HubExecuteMsg::RecordReferral { code } => HubMsgKind::Referral {
-            code: ReferralCode::from(code),
+        HubExecuteMsg::RecordReferral { code, dapp } => HubMsgKind::Referral {
+            code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+            dapp: dapp.map(|d| validate_addr(api, &d)).transpose()?,
         },
 
-        HubExecuteMsg::CollectReferrer { code, dapp } => {
-            HubMsgKind::Collect(Collection::Referrer {
-                dapp: api.addr_validate(&dapp).map(Id::from)?,
-                code: ReferralCode::from(code),
+        HubExecuteMsg::CollectReferrer {
+            code,
+            dapp,
+            recipient,
+        } => HubMsgKind::Collect(Collection::Referrer {
+            dapp: validate_addr(api, &dapp)?,
+            code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+            recipient: recipient.map(|r| validate_addr(api, &r)).transpose()?,
+        }),
+
+        HubExecuteMsg::CollectReferrerAll { code, start, limit } => {
+            HubMsgKind::Collect(Collection::ReferrerAll {
+                code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+                start,
+                limit,
             })
         }
 
-        HubExecuteMsg::CollectDapp { dapp } => HubMsgKind::Collect(Collection::Dapp {
-            dapp: api.addr_validate(&dapp).map(Id::from)?,
+        HubExecuteMsg::CollectDapp { dapp, recipient } => HubMsgKind::Collect(Collection::Dapp {
+            dapp: validate_addr(api, &dapp)?,
+            recipient: recipient.map(|r| validate_addr(api, &r)).transpose()?,
         }),
 
         HubExecuteMsg::TransferOwnership { code, owner } => {
             HubMsgKind::Config(Configure::TransferReferralCodeOwnership {
-                code: ReferralCode::from(code),
-                owner: api.addr_validate(&owner).map(Id::from)?,
+                code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+                owner: validate_addr(api, &owner)?,
             })
         }
 
         HubExecuteMsg::ConfigureDapp {
             dapp,
+            name,
             percent,
             collector,
             repo_url,
-        } => HubMsgKind::Config(Configure::DappMetadata {
-            dapp: api.addr_validate(&dapp).map(Id::from)?,
-            metadata: DappMetadata {
-                percent: percent
-                    .map(|p| NonZeroPercent::new(p).ok_or(Error::InvalidPercent))
-                    .transpose()?,
-                collector: collector
-                    .map(|c| api.addr_validate(&c).map(Id::from).map_err(Error::from))
-                    .transpose()?,
-                repo_url,
-            },
+            clear_repo_url,
+            allow_self_referral,
+            max_invocations_per_code,
+            confirm_no_dapp_share,
+        } => {
+            let (dapp, metadata) = parse_dapp_update(
+                api,
+                ConfigureDappEntry {
+                    dapp,
+                    name,
+                    percent,
+                    collector,
+                    repo_url,
+                    clear_repo_url,
+                    allow_self_referral,
+                    max_invocations_per_code,
+                    confirm_no_dapp_share,
+                },
+            )?;
+
+            HubMsgKind::Config(Configure::DappMetadata { dapp, metadata })
+        }
+
+        HubExecuteMsg::ConfigureDapps { updates } => HubMsgKind::Config(Configure::DappsMetadata {
+            updates: updates
+                .into_iter()
+                .map(|update| parse_dapp_update(api, update))
+                .collect::<Result<_, _>>()?,
+        }),
+
+        HubExecuteMsg::AcceptCollector { dapp } => HubMsgKind::Config(Configure::AcceptCollector {
+            dapp: validate_addr(api, &dapp)?,
+        }),
+
+        HubExecuteMsg::SetCampaign {
+            dapp,
+            multiplier_num,
+            multiplier_den,
+            start_height,
+            end_height,
+        } => {
+            if start_height > end_height {
+                return Err(Error::InvalidCampaignWindow);
+            }
+
+            HubMsgKind::Config(Configure::DappCampaign {
+                dapp: validate_addr(api, &dapp)?,
+                campaign: DappCampaign {
+                    multiplier_num: NonZeroU64::new(multiplier_num)
+                        .ok_or(Error::InvalidMultiplier)?,
+                    multiplier_den: NonZeroU64::new(multiplier_den)
+                        .ok_or(Error::InvalidMultiplier)?,
+                    start_height,
+                    end_height,
+                },
+            })
+        }
+
+        HubExecuteMsg::SetReferralRelayer { dapp, relayer } => {
+            HubMsgKind::Config(Configure::ReferralRelayer {
+                dapp: validate_addr(api, &dapp)?,
+                relayer: relayer.map(|r| validate_addr(api, &r)).transpose()?,
+            })
+        }
+
+        HubExecuteMsg::SetCodeStatus { code, blocked } => {
+            HubMsgKind::Config(Configure::CodeStatus {
+                code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+                blocked,
+            })
+        }
+
+        HubExecuteMsg::SetEnabledFeatures {
+            register,
+            record,
+            collect,
+            configure,
+        } => HubMsgKind::Config(Configure::EnabledFeatures {
+            features: Features::NONE
+                .set(Features::REGISTER, register)
+                .set(Features::RECORD, record)
+                .set(Features::COLLECT, collect)
+                .set(Features::CONFIGURE, configure),
         }),
+
+        HubExecuteMsg::SetMinCollectAmount { amount } => {
+            HubMsgKind::Config(Configure::MinCollectAmount {
+                amount: amount.and_then(|amount| NonZeroU128::new(amount.u128())),
+            })
+        }
+
+        HubExecuteMsg::SetAllowSelfDapp { allowed } => {
+            HubMsgKind::Config(Configure::AllowSelfDapp { allowed })
+        }
+
+        HubExecuteMsg::HandoverPots { dapps, new_admin } => {
+            HubMsgKind::Config(Configure::HandoverPots {
+                dapps: dapps
+                    .iter()
+                    .map(|dapp| validate_addr(api, dapp))
+                    .collect::<Result<_, _>>()?,
+                new_admin: validate_addr(api, &new_admin)?,
+            })
+        }
     };
 
     Ok(HubMsg {
@@ -122,23 +430,108 @@ pub fn parse_hub_exec(
     })
 }
 
+/// Parse the msg of a `referrals_cw::QueryMsg::Simulate` into a core hub `Msg`, sent by the
+/// caller-supplied `sender` without funds.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The `sender` is not a valid address.
+/// - The user provided message contains invalid fields.
+pub fn parse_hub_simulation(
+    api: &dyn Api,
+    sender: &str,
+    cw_msg: HubExecuteMsg,
+) -> Result<HubMsg, Error> {
+    let msg_info = MessageInfo {
+        sender: api.addr_validate(sender)?,
+        funds: vec![],
+    };
+
+    parse_hub_exec(api, msg_info, cw_msg)
+}
+
 /// Parse a `referrals_cw::QueryMsg` into a core hub `QueryRequest`
 ///
 /// # Errors
 ///
-/// This function will return an error if the user provided message contains invalid fields.
+/// This function will return an error if:
+/// - The user provided message contains invalid fields.
+/// - The query is not handled by `referrals_core`, e.g. `HubConfig`.
 pub fn parse_hub_query(api: &dyn Api, cw_msg: HubQueryMsg) -> Result<QueryRequest, Error> {
     let request = match cw_msg {
         HubQueryMsg::TotalDapps {} => QueryRequest::TotalDappCount,
         HubQueryMsg::Dapp { dapp } => {
-            let id = api.addr_validate(&dapp).map(Id::from)?;
+            let id = validate_addr(api, &dapp)?;
             QueryRequest::Dapp(id)
         }
-        HubQueryMsg::AllDapps { start, limit } => QueryRequest::AllDapps { start, limit },
+        HubQueryMsg::DappByName { name } => QueryRequest::DappByName(name),
+        HubQueryMsg::AllDapps {
+            start,
+            after,
+            limit,
+            only_active,
+        } => QueryRequest::AllDapps {
+            start,
+            after: after.map(|after| validate_addr(api, &after)).transpose()?,
+            limit,
+            only_active: only_active.unwrap_or_default(),
+        },
         HubQueryMsg::RefferalCode { referrer } => {
-            let id = api.addr_validate(&referrer).map(Id::from)?;
+            let id = validate_addr(api, &referrer)?;
             QueryRequest::ReferralCode(id)
         }
+        HubQueryMsg::Totals {} => QueryRequest::Totals,
+        HubQueryMsg::RewardsPotAddress { dapp } => {
+            let id = validate_addr(api, &dapp)?;
+            QueryRequest::RewardsPotAddress(id)
+        }
+        HubQueryMsg::OwedToReferrer { code, dapp } => QueryRequest::OwedToReferrer {
+            code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+            dapp: validate_addr(api, &dapp)?,
+        },
+        HubQueryMsg::OwedToDapp { dapp } => {
+            let id = validate_addr(api, &dapp)?;
+            QueryRequest::OwedToDapp(id)
+        }
+        HubQueryMsg::ReferrerStats { code, dapp } => QueryRequest::ReferrerStats {
+            code: ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+            dapp: validate_addr(api, &dapp)?,
+        },
+        HubQueryMsg::DappInvocationsOverRange {
+            dapp,
+            from_day,
+            to_day,
+        } => QueryRequest::DappInvocationsOverRange {
+            dapp: validate_addr(api, &dapp)?,
+            from_day,
+            to_day,
+        },
+        HubQueryMsg::CodeOwner { code } => QueryRequest::CodeOwner(
+            ReferralCode::try_from(code).map_err(|_| Error::InvalidReferralCode)?,
+        ),
+        HubQueryMsg::DappRewards { dapp } => {
+            let id = validate_addr(api, &dapp)?;
+            QueryRequest::DappRewards(id)
+        }
+        HubQueryMsg::IsDapp { address } => QueryRequest::IsDapp(validate_addr(api, &address)?),
+        HubQueryMsg::ExportState {
+            section,
+            start_after,
+            limit,
+        } => QueryRequest::ExportState {
+            section: match section {
+                CwExportSection::Dapps => ExportSection::Dapps,
+                CwExportSection::Codes => ExportSection::Codes,
+                CwExportSection::Earnings => ExportSection::Earnings,
+                CwExportSection::Collections => ExportSection::Collections,
+            },
+            start_after: start_after.map(Vec::from),
+            limit,
+        },
+        HubQueryMsg::HubConfig {} | HubQueryMsg::Simulate { .. } => {
+            return Err(Error::NonCoreQuery)
+        }
     };
 
     Ok(request)
@@ -150,53 +543,198 @@ pub fn parse_hub_query(api: &dyn Api, cw_msg: HubQueryMsg) -> Result<QueryReques
 ///
 /// This function will return an error if there is an issue with `cosmwasm_std` serialization.
 pub fn convert_hub_query_response(response: QueryResponse) -> Result<Binary, Error> {
+    let to_cw_activity = |a: Activity| CwActivity {
+        height: a.height,
+        time: Timestamp::from_seconds(a.time),
+    };
+
+    let to_cw_campaign = |c: DappCampaign| CwCampaign {
+        multiplier_num: c.multiplier_num.get(),
+        multiplier_den: c.multiplier_den.get(),
+        start_height: c.start_height,
+        end_height: c.end_height,
+    };
+
     let to_cw_dapp = |d: DappInfo| DappResponse {
         address: d.id.into_string(),
         active: d.active,
         name: d.name,
-        percent: d.percent.to_u8(),
+        percent: d.percent.map_or(0, NonZeroPercent::to_u8),
+        dapp_share_percent: d.percent.map_or(100, NonZeroPercent::dapp_share),
         repo_url: d.repo_url,
         fee: d.fee.map(NonZeroU128::get).map(Uint128::from),
         total_invocations: d.total_invocations,
         discrete_referrers: d.discrete_referrers,
         total_contributions: d.total_contributions.into(),
         total_rewards: d.total_rewards.into(),
+        last_active: d.last_active.map(to_cw_activity),
+        campaign: d.campaign.map(to_cw_campaign),
+        activation_index: d.activation_index,
+    };
+
+    let to_cw_owed = |o: Owed| OwedResponse {
+        owed: o.owed.map_or(0, NonZeroU128::get).into(),
+        already_collected: o.already_collected.map_or(0, NonZeroU128::get).into(),
+    };
+
+    let to_cw_export = |r: ExportRecord| match r {
+        ExportRecord::Dapp(d) => CwExportRecord::Dapp(CwDappExport {
+            address: d.id.into_string(),
+            activation_index: d.activation_index,
+            name: d.name,
+            percent: d.percent.map_or(0, NonZeroPercent::to_u8),
+            collector: d.collector.map(Id::into_string),
+            rewards_pot: d.rewards_pot.map(Id::into_string),
+            repo_url: d.repo_url,
+        }),
+        ExportRecord::Code(c) => CwExportRecord::Code(CodeExport {
+            code: c.code.to_u64(),
+            owner: c.owner.map(Id::into_string),
+            total_earnings: c.total_earnings.into(),
+            total_collected: c.total_collected.into(),
+            blocked: c.blocked,
+        }),
+        ExportRecord::Earnings(e) => CwExportRecord::Earnings(EarningsExport {
+            code: e.code.to_u64(),
+            dapp: e.dapp.into_string(),
+            invocations: e.invocations,
+            earnings: e.earnings.into(),
+            collected: e.collected.into(),
+        }),
+        ExportRecord::Collections(c) => CwExportRecord::Collections(CollectionsExport {
+            dapp: c.dapp.into_string(),
+            contributions: c.contributions.into(),
+            dapp_collected: c.dapp_collected.into(),
+            referrers_collected: c.referrers_collected.into(),
+        }),
     };
 
     match response {
         QueryResponse::TotalDappCount(total) => to_binary(&TotalDappsResponse { total }),
         QueryResponse::Dapp(dapp) => to_binary(&to_cw_dapp(dapp)),
-        QueryResponse::AllDapps(dapps) => to_binary(&AllDappsResponse {
+        QueryResponse::AllDapps {
+            dapps,
+            errors,
+            total,
+            next_key,
+        } => to_binary(&AllDappsResponse {
             dapps: dapps.into_iter().map(to_cw_dapp).collect(),
+            total,
+            next_key: next_key.map(Id::into_string),
+            errors: errors
+                .into_iter()
+                .map(|(dapp, error)| DappError {
+                    dapp: dapp.into_string(),
+                    error,
+                })
+                .collect(),
         }),
         QueryResponse::ReferralCode(code) => to_binary(&ReferralCodeResponse {
             code: code.map_or(0, ReferralCode::to_u64),
         }),
+        QueryResponse::Totals(totals) => to_binary(&TotalsResponse {
+            total_dapps: totals.total_dapps,
+            active_dapps: totals.active_dapps,
+            referral_codes: totals.referral_codes,
+            total_invocations: totals.total_invocations,
+            total_contributions: totals.total_contributions.into(),
+            total_collected: totals.total_collected.into(),
+        }),
+        QueryResponse::RewardsPotAddress(pot) => to_binary(&RewardsPotAddressResponse {
+            address: pot.into_string(),
+        }),
+        QueryResponse::OwedToReferrer(owed) | QueryResponse::OwedToDapp(owed) => {
+            to_binary(&to_cw_owed(owed))
+        }
+        QueryResponse::ReferrerStats(stats) => to_binary(&ReferrerStatsResponse {
+            invocations: stats.invocations,
+            earnings: stats.earnings.into(),
+            last_active: stats.last_active.map(to_cw_activity),
+            blocked: stats.blocked,
+        }),
+        QueryResponse::DappInvocationsOverRange(days) => {
+            to_binary(&DappInvocationsOverRangeResponse {
+                days: days
+                    .into_iter()
+                    .map(|d| CwDailyInvocations {
+                        day: d.day,
+                        invocations: d.invocations,
+                    })
+                    .collect(),
+            })
+        }
+        QueryResponse::CodeOwner(owner) => to_binary(&CodeOwnerResponse {
+            owner: owner.map(Id::into_string),
+        }),
+        QueryResponse::DappRewards(rewards) => to_binary(&DappRewardsResponse {
+            rewards_pot: rewards.rewards_pot.into_string(),
+            total_rewards: rewards.total_rewards.into(),
+            contributions: rewards.contributions.into(),
+            referrers_collected: rewards.referrers_collected.into(),
+            dapp_collected: rewards.dapp_collected.into(),
+            undistributed: rewards.undistributed.into(),
+        }),
+        QueryResponse::IsDapp(status) => to_binary(&IsDappResponse {
+            registered: status.registered,
+            active: status.active,
+        }),
+        QueryResponse::ExportState(page) => to_binary(&ExportStateResponse {
+            records: page.records.into_iter().map(to_cw_export).collect(),
+            next_key: page.next_key.map(Binary::from),
+        }),
     }
     .map_err(Error::from)
 }
 
-/// Parse a trusted cosmwasm reply into a core msg
+/// Parse a cosmwasm reply into a core msg, the dApp reported by the instantiated pot is validated.
+///
+/// A successful instantiation must be at the `expected_pot` address, precomputed from the pending dApp.
+/// A failed instantiation is parsed into a msg from the `hub` undoing the activation of the `pending_dapp`.
 ///
 /// # Errors
 ///
 /// This function will return an error if the reply is invalid.
-pub fn parse_init_pot_reply(reply: Reply) -> Result<HubMsg, Error> {
+pub fn parse_init_pot_reply(
+    api: &dyn Api,
+    reply: Reply,
+    hub: Id,
+    pending_dapp: Option<Id>,
+    expected_pot: Option<Id>,
+) -> Result<HubMsg, Error> {
+    if let SubMsgResult::Err(_) = reply.result {
+        let dapp = pending_dapp.ok_or(Error::NoPendingRewardsPot)?;
+
+        return Ok(HubMsg {
+            sender: hub,
+            kind: HubMsgKind::Register(Registration::RewardsPotFailed { dapp }),
+        });
+    }
+
+    let expected_pot = expected_pot.ok_or(Error::NoPendingRewardsPot)?;
+
     let contract_init_res = cw_utils::parse_reply_instantiate_data(reply)?;
 
+    if contract_init_res.contract_address != expected_pot.as_str() {
+        return Err(Error::UnexpectedRewardsPot {
+            expected: expected_pot.into_string(),
+            actual: contract_init_res.contract_address,
+        });
+    }
+
     let pot_init_response: PotInitResponse = contract_init_res
         .data
         .ok_or(Error::ExpectedReplyData)
         .and_then(|data| cosmwasm_std::from_binary(&data).map_err(Error::InvalidReplyData))?;
 
+    let dapp = Id::try_new(pot_init_response.dapp)?;
+
+    validate_addr(api, dapp.as_str())?;
+
     let rewards_pot = Id::from(contract_init_res.contract_address);
 
     Ok(HubMsg {
         sender: rewards_pot.clone(),
-        kind: HubMsgKind::Register(Registration::RewardsPot {
-            dapp: Id::from(pot_init_response.dapp),
-            rewards_pot,
-        }),
+        kind: HubMsgKind::Register(Registration::RewardsPot { dapp, rewards_pot }),
     })
 }
 
@@ -212,9 +750,23 @@ pub fn parse_pot_exec(
 ) -> Result<RewardsPotMsg, Error> {
     let kind = match cw_msg {
         PotExecuteMsg::WithdrawRewards {} => RewardsPotKind::WithdrawPending,
-        PotExecuteMsg::DistributeRewards { recipient, amount } => RewardsPotKind::Distribute {
-            recipient: api.addr_validate(&recipient).map(Id::from)?,
+        PotExecuteMsg::DistributeRewards {
+            recipient,
+            amount,
+            clamp,
+            denom,
+        } => RewardsPotKind::Distribute {
+            recipient: validate_addr(api, &recipient)?,
             amount: NonZeroU128::new(amount.u128()).ok_or(Error::InvalidAmount)?,
+            denom,
+            clamp: clamp.unwrap_or_default(),
+        },
+        PotExecuteMsg::Sweep { denom, recipient } => RewardsPotKind::Sweep {
+            denom,
+            recipient: validate_addr(api, &recipient)?,
+        },
+        PotExecuteMsg::UpdateAdmin { new_admin } => RewardsPotKind::UpdateAdmin {
+            new_admin: validate_addr(api, &new_admin)?,
         },
     };
 
@@ -0,0 +1,95 @@
+//! A key-value repo buffering writes in memory over a readonly snapshot, so a message can be
+//! handled without committing any state.
+
+use std::collections::BTreeMap;
+
+use kv_storage::{Fallible, HasKey, Read, Remove, Write};
+
+/// A write made to a [`RecordingStore`], `value` is `None` if the key was removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedWrite {
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+}
+
+/// Reads from the `snapshot` unless the key was written since, never writing to the `snapshot`.
+pub struct RecordingStore<S> {
+    snapshot: S,
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<S> RecordingStore<S> {
+    pub fn new(snapshot: S) -> Self {
+        Self {
+            snapshot,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    pub fn snapshot(&self) -> &S {
+        &self.snapshot
+    }
+
+    /// The final write made to each key, ordered by key.
+    #[must_use]
+    pub fn writes(&self) -> Vec<RecordedWrite> {
+        self.writes
+            .iter()
+            .map(|(key, value)| RecordedWrite {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+}
+
+impl<S> Fallible for RecordingStore<S>
+where
+    S: Fallible,
+{
+    type Error = S::Error;
+}
+
+impl<S> Read for RecordingStore<S>
+where
+    S: Read,
+{
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.writes.get(key) {
+            Some(value) => Ok(value.clone()),
+            None => self.snapshot.read(key),
+        }
+    }
+}
+
+impl<S> HasKey for RecordingStore<S>
+where
+    S: HasKey,
+{
+    fn has_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        match self.writes.get(key) {
+            Some(value) => Ok(value.is_some()),
+            None => self.snapshot.has_key(key),
+        }
+    }
+}
+
+impl<S> Write for RecordingStore<S>
+where
+    S: Fallible,
+{
+    fn write(&mut self, key: &[u8], bytes: &[u8]) -> Result<(), Self::Error> {
+        self.writes.insert(key.to_owned(), Some(bytes.to_owned()));
+        Ok(())
+    }
+}
+
+impl<S> Remove for RecordingStore<S>
+where
+    S: Fallible,
+{
+    fn remove(&mut self, key: &[u8]) -> Result<(), Self::Error> {
+        self.writes.insert(key.to_owned(), None);
+        Ok(())
+    }
+}
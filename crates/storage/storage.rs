@@ -1,9 +1,46 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
-use referrals_core::FallibleApi;
+use referrals_core::hub::Recording;
+use referrals_core::{FallibleApi, Id};
 
-use kv_storage::Storage as ReadonlyKvStorage;
+use kv_storage::{KvStore, Storage as ReadonlyKvStorage};
+
+/// Size of the key buffer of every map, longer keys fail deep within `kv_storage`.
+pub const MAX_KEY_LEN: usize = 1024;
+
+/// Maximum length of an id used as a key part, in bytes.
+pub const MAX_ID_KEY_LEN: usize = Id::MAX_LEN * 4;
+
+/// Maximum length of an integer used as a key part, in bytes.
+pub const MAX_INT_KEY_LEN: usize = 16;
+
+/// Upper bound on the length of a map key composed from it's namespace & parts of the given
+/// lengths, allowing for a 2 byte separator before each part.
+#[must_use]
+pub const fn composed_key_len(namespace: &str, parts: &[usize]) -> usize {
+    let mut len = namespace.len();
+    let mut i = 0;
+
+    while i < parts.len() {
+        len += parts[i] + 2;
+        i += 1;
+    }
+
+    len
+}
+
+/// Assert at compile time that the keys of a map declared in the calling module fit within
+/// [`MAX_KEY_LEN`], given the maximum lengths of it's key parts.
+macro_rules! assert_key_fits {
+    ($name:literal: $($part:expr),+) => {
+        const _: () = assert!(
+            $crate::composed_key_len(concat!(module_path!(), "::", $name), &[$($part),+])
+                <= $crate::MAX_KEY_LEN,
+            concat!("keys of ", module_path!(), "::", $name, " may exceed the key buffer"),
+        );
+    };
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<S> {
@@ -11,8 +48,8 @@ pub enum Error<S> {
     Storage(#[from] S),
     #[error("not found")]
     NotFound,
-    #[error("index out of bounds")]
-    IndexOutOfBounds,
+    #[error("counter overflow")]
+    Overflow,
 }
 
 pub struct Storage<T>(T);
@@ -38,23 +75,99 @@ where
     type Error = Error<T::Error>;
 }
 
+impl<Ser, S> Recording for Storage<KvStore<Ser, RecordingStore<S>>>
+where
+    KvStore<Ser, RecordingStore<S>>: ReadonlyKvStorage,
+{
+    type Write = RecordedWrite;
+
+    fn recorded_writes(&self) -> Vec<RecordedWrite> {
+        self.0.repo().writes()
+    }
+}
+
+pub mod recording;
+
+pub use recording::{RecordedWrite, RecordingStore};
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 mod hub {
-    use std::num::NonZeroU128;
+    use std::num::{NonZeroU128, NonZeroU64};
 
     use referrals_core::hub::{
-        DappsQuery, MutableCollectStore, MutableDappStore, MutableReferralStore, NonZeroPercent,
-        ReadonlyCollectStore, ReadonlyDappStore, ReadonlyReferralStore, ReferralCode,
-        ReferrersQuery,
+        Activity, DappCampaign, DappsQuery, MutableCollectStore, MutableDappStore,
+        MutableReferralStore, NonZeroPercent, ReadonlyCollectStore, ReadonlyDappStore,
+        ReadonlyReferralStore, ReferralCode, ReferrersQuery, TotalsQuery,
     };
-    use referrals_core::Id;
+    use referrals_core::{Id, IdRef};
 
     use kv_storage::{MutStorage as MutKvStorage, Storage as ReadonlyKvStorage};
 
     use crate::{Error, Storage};
 
+    mod totals {
+        use ::kv_storage::{item, Item};
+
+        pub static ACTIVE_DAPPS: Item<u64> = item!("active_dapps");
+
+        pub static REFERRAL_CODES: Item<u64> = item!("referral_codes");
+
+        pub static INVOCATIONS: Item<u64> = item!("invocations");
+
+        pub static CONTRIBUTIONS: Item<u128> = item!("contributions");
+
+        pub static COLLECTED: Item<u128> = item!("collected");
+    }
+
+    impl<T> TotalsQuery for Storage<T>
+    where
+        T: ReadonlyKvStorage,
+    {
+        fn active_dapp_count(&self) -> Result<u64, Self::Error> {
+            totals::ACTIVE_DAPPS
+                .may_load(&self.0)
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn total_referral_codes(&self) -> Result<u64, Self::Error> {
+            totals::REFERRAL_CODES
+                .may_load(&self.0)
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn total_invocations(&self) -> Result<u64, Self::Error> {
+            totals::INVOCATIONS
+                .may_load(&self.0)
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn total_contributions(&self) -> Result<u128, Self::Error> {
+            totals::CONTRIBUTIONS
+                .may_load(&self.0)
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn total_collected(&self) -> Result<u128, Self::Error> {
+            totals::COLLECTED
+                .may_load(&self.0)
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+    }
+
     mod dapp {
         use ::kv_storage::{item, map, Item, Map};
 
+        use referrals_core::hub::DappCampaign;
+
+        use crate::{MAX_ID_KEY_LEN, MAX_INT_KEY_LEN};
+
         pub static DAPP_LAST_INDEX: Item<u64> = item!("dapp_last_index");
 
         pub static DAPP_INDEX: Map<1024, u64, String> = map!("dapp_index");
@@ -65,11 +178,42 @@ mod hub {
 
         pub static PERCENT: Map<1024, &str, u8> = map!("percent");
 
+        pub static MAX_INVOCATIONS_PER_CODE: Map<1024, &str, u64> =
+            map!("max_invocations_per_code");
+
         pub static COLLECTOR: Map<1024, &str, String> = map!("collector");
 
+        pub static PENDING_COLLECTOR: Map<1024, &str, String> = map!("pending_collector");
+
         pub static REPO_URL: Map<1024, &str, String> = map!("repo_url");
 
         pub static REWARDS_POT: Map<1024, &str, String> = map!("rewards_pot");
+
+        pub static SELF_REFERRAL_ALLOWED: Map<1024, &str, bool> = map!("self_referral_allowed");
+
+        pub static CAMPAIGN: Map<1024, &str, DappCampaign> = map!("campaign");
+
+        pub static FROZEN: Map<1024, &str, bool> = map!("frozen");
+
+        pub static NAMES: Map<1024, &str, String> = map!("dapp_names");
+
+        pub static RELAYER: Map<1024, &str, String> = map!("relayer");
+
+        assert_key_fits!("dapp_index": MAX_INT_KEY_LEN);
+        assert_key_fits!("dapp_reverse_index": MAX_ID_KEY_LEN);
+        assert_key_fits!("dapps": MAX_ID_KEY_LEN);
+        assert_key_fits!("percent": MAX_ID_KEY_LEN);
+        assert_key_fits!("max_invocations_per_code": MAX_ID_KEY_LEN);
+        assert_key_fits!("collector": MAX_ID_KEY_LEN);
+        assert_key_fits!("pending_collector": MAX_ID_KEY_LEN);
+        assert_key_fits!("repo_url": MAX_ID_KEY_LEN);
+        assert_key_fits!("rewards_pot": MAX_ID_KEY_LEN);
+        assert_key_fits!("self_referral_allowed": MAX_ID_KEY_LEN);
+        assert_key_fits!("campaign": MAX_ID_KEY_LEN);
+        assert_key_fits!("frozen": MAX_ID_KEY_LEN);
+        // names are validated to be no longer than ids
+        assert_key_fits!("dapp_names": MAX_ID_KEY_LEN);
+        assert_key_fits!("relayer": MAX_ID_KEY_LEN);
     }
 
     impl<T> ReadonlyDappStore for Storage<T>
@@ -82,12 +226,13 @@ mod hub {
                 .map_err(Error::from)
         }
 
-        fn percent(&self, id: &Id) -> Result<NonZeroPercent, Self::Error> {
-            dapp::PERCENT
+        fn percent(&self, id: &Id) -> Result<Option<NonZeroPercent>, Self::Error> {
+            let percent = dapp::PERCENT
                 .may_load(&self.0, id.as_str())?
-                .ok_or(Error::NotFound)
                 .map(NonZeroPercent::new)
-                .map(Option::unwrap) // safe as only NonZeroPercent's accepted into storage
+                .map(Option::unwrap); // safe as only NonZeroPercent's accepted into storage
+
+            Ok(percent)
         }
 
         fn collector(&self, id: &Id) -> Result<Id, Self::Error> {
@@ -109,6 +254,53 @@ mod hub {
                 .ok_or(Error::NotFound)
                 .map(Id::from)
         }
+
+        fn self_referral_allowed(&self, id: &Id) -> Result<bool, Self::Error> {
+            dapp::SELF_REFERRAL_ALLOWED
+                .may_load(&self.0, id.as_str())
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn pending_collector(&self, id: &Id) -> Result<Option<Id>, Self::Error> {
+            dapp::PENDING_COLLECTOR
+                .may_load(&self.0, id.as_str())
+                .map(|maybe| maybe.map(Id::from))
+                .map_err(Error::from)
+        }
+
+        fn campaign(&self, id: &Id) -> Result<Option<DappCampaign>, Self::Error> {
+            dapp::CAMPAIGN
+                .may_load(&self.0, id.as_str())
+                .map_err(Error::from)
+        }
+
+        fn max_invocations_per_code(&self, id: &Id) -> Result<Option<NonZeroU64>, Self::Error> {
+            let max = dapp::MAX_INVOCATIONS_PER_CODE
+                .may_load(&self.0, id.as_str())?
+                .and_then(NonZeroU64::new); // only non-zero caps are accepted into storage
+
+            Ok(max)
+        }
+
+        fn dapp_frozen(&self, id: &Id) -> Result<bool, Self::Error> {
+            dapp::FROZEN
+                .may_load(&self.0, id.as_str())
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn dapp_by_name(&self, name: &str) -> Result<Option<Id>, Self::Error> {
+            let id = dapp::NAMES.may_load(&self.0, name)?;
+            Ok(id.map(Id::from))
+        }
+
+        fn relayer(&self, id: &Id) -> Result<Option<Id>, Self::Error> {
+            dapp::RELAYER
+                .may_load(&self.0, id.as_str())
+                .map(|maybe| maybe.map(Id::from))
+                .map_err(Error::from)
+        }
     }
 
     impl<T> MutableDappStore for Storage<T>
@@ -126,12 +318,32 @@ mod hub {
                 dapp::DAPP_LAST_INDEX.save(&mut self.0, index)?;
             }
 
+            if !dapp::DAPPS.has_key(&self.0, id.as_str())? {
+                let active = self.active_dapp_count()?;
+                totals::ACTIVE_DAPPS
+                    .save(&mut self.0, active.checked_add(1).ok_or(Error::Overflow)?)?;
+            }
+
+            self.release_name(id)?;
+
+            dapp::NAMES.save(&mut self.0, &name, id.as_str().to_owned())?;
+
             dapp::DAPPS
                 .save(&mut self.0, id.as_str(), &name)
                 .map_err(Error::from)
         }
 
         fn remove_dapp(&mut self, id: &Id) -> Result<(), Self::Error> {
+            // a frozen dApp is no longer counted as active
+            if dapp::DAPPS.has_key(&self.0, id.as_str())? && !self.dapp_frozen(id)? {
+                let active = self.active_dapp_count()?;
+                totals::ACTIVE_DAPPS.save(&mut self.0, active.saturating_sub(1))?;
+            }
+
+            dapp::FROZEN.remove(&mut self.0, id.as_str())?;
+
+            self.release_name(id)?;
+
             dapp::DAPPS
                 .remove(&mut self.0, id.as_str())
                 .map_err(Error::from)
@@ -143,6 +355,16 @@ mod hub {
                 .map_err(Error::from)
         }
 
+        fn set_name(&mut self, id: &Id, name: String) -> Result<(), Self::Error> {
+            self.release_name(id)?;
+
+            dapp::NAMES.save(&mut self.0, &name, id.as_str().to_owned())?;
+
+            dapp::DAPPS
+                .save(&mut self.0, id.as_str(), name)
+                .map_err(Error::from)
+        }
+
         fn set_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
             dapp::COLLECTOR
                 .save(&mut self.0, id.as_str(), collector.as_ref())
@@ -155,36 +377,282 @@ mod hub {
                 .map_err(Error::from)
         }
 
+        fn clear_repo_url(&mut self, id: &Id) -> Result<(), Self::Error> {
+            dapp::REPO_URL
+                .remove(&mut self.0, id.as_str())
+                .map_err(Error::from)
+        }
+
         fn set_rewards_pot(&mut self, id: &Id, rewards_pot: Id) -> Result<(), Self::Error> {
             dapp::REWARDS_POT
                 .save(&mut self.0, id.as_str(), rewards_pot.as_ref())
                 .map_err(Error::from)
         }
+
+        fn set_self_referral_allowed(&mut self, id: &Id, allowed: bool) -> Result<(), Self::Error> {
+            dapp::SELF_REFERRAL_ALLOWED
+                .save(&mut self.0, id.as_str(), allowed)
+                .map_err(Error::from)
+        }
+
+        fn set_pending_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
+            dapp::PENDING_COLLECTOR
+                .save(&mut self.0, id.as_str(), collector.as_ref())
+                .map_err(Error::from)
+        }
+
+        fn remove_pending_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+            dapp::PENDING_COLLECTOR
+                .remove(&mut self.0, id.as_str())
+                .map_err(Error::from)
+        }
+
+        fn remove_percent(&mut self, id: &Id) -> Result<(), Self::Error> {
+            dapp::PERCENT
+                .remove(&mut self.0, id.as_str())
+                .map_err(Error::from)
+        }
+
+        fn remove_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+            dapp::COLLECTOR
+                .remove(&mut self.0, id.as_str())
+                .map_err(Error::from)
+        }
+
+        fn set_campaign(&mut self, id: &Id, campaign: DappCampaign) -> Result<(), Self::Error> {
+            dapp::CAMPAIGN
+                .save(&mut self.0, id.as_str(), campaign)
+                .map_err(Error::from)
+        }
+
+        fn set_max_invocations_per_code(
+            &mut self,
+            id: &Id,
+            max: NonZeroU64,
+        ) -> Result<(), Self::Error> {
+            dapp::MAX_INVOCATIONS_PER_CODE
+                .save(&mut self.0, id.as_str(), max.get())
+                .map_err(Error::from)
+        }
+
+        fn set_dapp_frozen(&mut self, id: &Id, frozen: bool) -> Result<(), Self::Error> {
+            if frozen != self.dapp_frozen(id)? && dapp::DAPPS.has_key(&self.0, id.as_str())? {
+                let active = self.active_dapp_count()?;
+
+                let active = if frozen {
+                    active.saturating_sub(1)
+                } else {
+                    active.checked_add(1).ok_or(Error::Overflow)?
+                };
+
+                totals::ACTIVE_DAPPS.save(&mut self.0, active)?;
+            }
+
+            if frozen {
+                dapp::FROZEN.save(&mut self.0, id.as_str(), true)?;
+            } else {
+                dapp::FROZEN.remove(&mut self.0, id.as_str())?;
+            }
+
+            Ok(())
+        }
+
+        fn set_relayer(&mut self, id: &Id, relayer: Id) -> Result<(), Self::Error> {
+            dapp::RELAYER
+                .save(&mut self.0, id.as_str(), relayer.as_ref())
+                .map_err(Error::from)
+        }
+
+        fn remove_relayer(&mut self, id: &Id) -> Result<(), Self::Error> {
+            dapp::RELAYER
+                .remove(&mut self.0, id.as_str())
+                .map_err(Error::from)
+        }
+
+        fn reset_dapp_accounting(&mut self, id: &Id) -> Result<(), Self::Error> {
+            // a dApp is only indexed once activated, without an index there is nothing to reset
+            if !dapp::DAPP_REVERSE_INDEX.has_key(&self.0, id.as_str())? {
+                return Ok(());
+            }
+
+            for code in self.codes_for_dapp(id)? {
+                // the referral caps start afresh with the new activation
+                referral::INVOCATION_COUNTS.remove(&mut self.0, (id.as_str(), code.to_u64()))?;
+
+                let Some(earnings) = self.dapp_earnings(id.as_id_ref(), code)? else {
+                    continue;
+                };
+
+                let total = self
+                    .total_earnings(code)?
+                    .map_or(0, NonZeroU128::get)
+                    .saturating_sub(earnings.get());
+
+                match NonZeroU128::new(total) {
+                    Some(total) => {
+                        referral::CODE_TOTAL_EARNINGS.save(&mut self.0, code.to_u64(), total)?
+                    }
+                    None => referral::CODE_TOTAL_EARNINGS.remove(&mut self.0, code.to_u64())?,
+                }
+
+                referral::CODE_DAPP_EARNINGS.remove(&mut self.0, (id.as_str(), code.to_u64()))?;
+
+                self.remove_code_dapp(code, id)?;
+
+                let Some(collected) = self.referrer_dapp_collected(id, code)? else {
+                    continue;
+                };
+
+                let total = self
+                    .referrer_total_collected(code)?
+                    .map_or(0, NonZeroU128::get)
+                    .saturating_sub(collected.get());
+
+                match NonZeroU128::new(total) {
+                    Some(total) => {
+                        collect::REFERRER_TOTAL.save(&mut self.0, code.to_u64(), total)?
+                    }
+                    None => collect::REFERRER_TOTAL.remove(&mut self.0, code.to_u64())?,
+                }
+
+                collect::REFERRER_DAPP.remove(&mut self.0, (id.as_str(), code.to_u64()))?;
+            }
+
+            for index in 0..self.dapp_discrete_referrers(id)? {
+                referral::DAPP_CODE_LIST.remove(&mut self.0, (id.as_str(), index))?;
+            }
+
+            referral::DISCRETE_REFERRERS.remove(&mut self.0, id.as_str())?;
+            referral::TOTAL_INVOCATION_COUNTS.remove(&mut self.0, id.as_str())?;
+
+            // the rewards pot is kept across activations, so whatever it paid out before,
+            // to the referrers or the dApp, counts as already collected by the dApp
+            let paid_out = self
+                .dapp_contributions(id.as_id_ref())?
+                .map_or(0, NonZeroU128::get)
+                .checked_add(self.dapp_total_collected(id)?.map_or(0, NonZeroU128::get))
+                .ok_or(Error::Overflow)?;
+
+            // the hub totals are lifetime aggregates, so are left as is
+            referral::DAPP_CONTRIBUTIONS.remove(&mut self.0, id.as_str())?;
+
+            collect::DAPP_REFERRERS.remove(&mut self.0, id.as_str())?;
+
+            match NonZeroU128::new(paid_out) {
+                Some(paid_out) => collect::DAPP_TOTAL.save(&mut self.0, id.as_str(), paid_out),
+                None => collect::DAPP_TOTAL.remove(&mut self.0, id.as_str()),
+            }
+            .map_err(Error::from)
+        }
+    }
+
+    impl<T> Storage<T>
+    where
+        T: MutKvStorage,
+    {
+        /// Free the current name of a dApp, unless it resolves to another dApp.
+        fn release_name(&mut self, id: &Id) -> Result<(), Error<T::Error>> {
+            let Some(name) = dapp::DAPPS.may_load(&self.0, id.as_str())? else {
+                return Ok(());
+            };
+
+            if self.dapp_by_name(&name)?.as_ref() == Some(id) {
+                dapp::NAMES.remove(&mut self.0, &name)?;
+            }
+
+            Ok(())
+        }
+
+        /// Remove a dApp from a code's dApp list, moving the last dApp into it's place.
+        fn remove_code_dapp(&mut self, code: ReferralCode, id: &Id) -> Result<(), Error<T::Error>> {
+            let count = self.code_dapp_count(code)?;
+
+            for index in 0..count {
+                if self.code_dapp(code, index)?.as_ref() != Some(id) {
+                    continue;
+                }
+
+                let last = count - 1;
+
+                if index != last {
+                    let moved = referral::CODE_DAPP_LIST
+                        .may_load(&self.0, (code.to_u64(), last))?
+                        .ok_or(Error::NotFound)?;
+
+                    referral::CODE_DAPP_LIST.save(&mut self.0, (code.to_u64(), index), &moved)?;
+                }
+
+                referral::CODE_DAPP_LIST.remove(&mut self.0, (code.to_u64(), last))?;
+
+                return referral::CODE_DAPP_COUNT
+                    .save(&mut self.0, code.to_u64(), last)
+                    .map_err(Error::from);
+            }
+
+            Ok(())
+        }
     }
 
     mod referral {
-        use std::num::NonZeroU128;
+        use std::num::{NonZeroU128, NonZeroU64};
 
         use kv_storage::{item, map, Item, Map};
 
+        use referrals_core::hub::Activity;
+
+        use crate::{MAX_ID_KEY_LEN, MAX_INT_KEY_LEN};
+
         pub static CODES: Map<1024, u64, String> = map!("codes");
 
-        pub static CODE_OWNERS: Map<1024, &str, u64> = map!("code_owners");
+        pub static CODE_OWNERS: Map<1024, &str, NonZeroU64> = map!("code_owners");
 
-        pub static LATEST_CODE: Item<u64> = item!("latest_code");
+        pub static LATEST_CODE: Item<NonZeroU64> = item!("latest_code");
 
         pub static INVOCATION_COUNTS: Map<1024, (&str, u64), u64> = map!("invocation_counts");
 
         pub static TOTAL_INVOCATION_COUNTS: Map<1024, &str, u64> = map!("total_invocation_counts");
 
+        pub static DAPP_INVOCATIONS_BY_DAY: Map<1024, (&str, u64), u64> =
+            map!("dapp_invocations_by_day");
+
         pub static DISCRETE_REFERRERS: Map<1024, &str, u64> = map!("discrete_referrers");
 
+        pub static DAPP_CODE_LIST: Map<1024, (&str, u64), NonZeroU64> = map!("dapp_code_list");
+
         pub static CODE_TOTAL_EARNINGS: Map<1024, u64, NonZeroU128> = map!("code_total_earnings");
 
         pub static CODE_DAPP_EARNINGS: Map<1024, (&str, u64), NonZeroU128> =
             map!("code_dapp_earnings");
 
+        pub static CODE_DAPP_COUNT: Map<1024, u64, u64> = map!("code_dapp_count");
+
+        pub static CODE_DAPP_LIST: Map<1024, (u64, u64), String> = map!("code_dapp_list");
+
         pub static DAPP_CONTRIBUTIONS: Map<1024, &str, NonZeroU128> = map!("dapp_contributions");
+
+        pub static DAPP_LAST_ACTIVE: Map<1024, &str, Activity> = map!("dapp_last_active");
+
+        pub static CODE_LAST_ACTIVE: Map<1024, (&str, u64), Activity> = map!("code_last_active");
+
+        pub static BLOCKED_CODES: Map<1024, u64, bool> = map!("blocked_codes");
+
+        pub static BLOCK_SELF_REFERRAL: Item<bool> = item!("block_self_referral");
+
+        assert_key_fits!("codes": MAX_INT_KEY_LEN);
+        assert_key_fits!("code_owners": MAX_ID_KEY_LEN);
+        assert_key_fits!("invocation_counts": MAX_ID_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("total_invocation_counts": MAX_ID_KEY_LEN);
+        assert_key_fits!("dapp_invocations_by_day": MAX_ID_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("discrete_referrers": MAX_ID_KEY_LEN);
+        assert_key_fits!("dapp_code_list": MAX_ID_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("code_total_earnings": MAX_INT_KEY_LEN);
+        assert_key_fits!("code_dapp_earnings": MAX_ID_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("code_dapp_count": MAX_INT_KEY_LEN);
+        assert_key_fits!("code_dapp_list": MAX_INT_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("dapp_contributions": MAX_ID_KEY_LEN);
+        assert_key_fits!("dapp_last_active": MAX_ID_KEY_LEN);
+        assert_key_fits!("code_last_active": MAX_ID_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("blocked_codes": MAX_INT_KEY_LEN);
     }
 
     impl<T> ReadonlyReferralStore for Storage<T>
@@ -197,7 +665,7 @@ mod hub {
                 .map_err(Error::from)
         }
 
-        fn owner_exists(&self, owner: &Id) -> Result<bool, Self::Error> {
+        fn owner_exists(&self, owner: IdRef<'_>) -> Result<bool, Self::Error> {
             referral::CODE_OWNERS
                 .has_key(&self.0, owner.as_str())
                 .map_err(Error::from)
@@ -225,7 +693,7 @@ mod hub {
 
         fn dapp_earnings(
             &self,
-            dapp: &Id,
+            dapp: IdRef<'_>,
             code: ReferralCode,
         ) -> Result<Option<NonZeroU128>, Self::Error> {
             referral::CODE_DAPP_EARNINGS
@@ -233,11 +701,46 @@ mod hub {
                 .map_err(Error::from)
         }
 
-        fn dapp_contributions(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        fn dapp_contributions(&self, dapp: IdRef<'_>) -> Result<Option<NonZeroU128>, Self::Error> {
             referral::DAPP_CONTRIBUTIONS
                 .may_load(&self.0, dapp.as_str())
                 .map_err(Error::from)
         }
+
+        fn code_dapp_count(&self, code: ReferralCode) -> Result<u64, Self::Error> {
+            referral::CODE_DAPP_COUNT
+                .may_load(&self.0, code.to_u64())
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn code_dapp(&self, code: ReferralCode, index: u64) -> Result<Option<Id>, Self::Error> {
+            referral::CODE_DAPP_LIST
+                .may_load(&self.0, (code.to_u64(), index))
+                .map(|maybe| maybe.map(Id::from))
+                .map_err(Error::from)
+        }
+
+        fn code_blocked(&self, code: ReferralCode) -> Result<bool, Self::Error> {
+            referral::BLOCKED_CODES
+                .may_load(&self.0, code.to_u64())
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn invocations(&self, dapp: IdRef<'_>, code: ReferralCode) -> Result<u64, Self::Error> {
+            referral::INVOCATION_COUNTS
+                .may_load(&self.0, (dapp.as_str(), code.to_u64()))
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
+
+        fn self_referral_blocked(&self) -> Result<bool, Self::Error> {
+            referral::BLOCK_SELF_REFERRAL
+                .may_load(&self.0)
+                .map(Option::unwrap_or_default)
+                .map_err(Error::from)
+        }
     }
 
     impl<T> MutableReferralStore for Storage<T>
@@ -246,49 +749,107 @@ mod hub {
     {
         fn set_latest(&mut self, code: ReferralCode) -> Result<(), Self::Error> {
             referral::LATEST_CODE
-                .save(&mut self.0, code.to_u64())
+                .save(&mut self.0, NonZeroU64::from(code))
                 .map_err(Error::from)
         }
 
         fn set_code_owner(&mut self, code: ReferralCode, owner: Id) -> Result<(), Self::Error> {
+            // ownership transfers re-use the same code
+            if !self.code_exists(code)? {
+                let codes = self.total_referral_codes()?;
+                totals::REFERRAL_CODES
+                    .save(&mut self.0, codes.checked_add(1).ok_or(Error::Overflow)?)?;
+            }
+
             referral::CODES.save(&mut self.0, code.to_u64(), owner.as_ref())?;
-            referral::CODE_OWNERS.save(&mut self.0, owner.as_str(), code.to_u64())?;
+            referral::CODE_OWNERS.save(&mut self.0, owner.as_str(), NonZeroU64::from(code))?;
             Ok(())
         }
 
         fn increment_invocations(
             &mut self,
-            dapp: &Id,
+            dapp: IdRef<'_>,
             code: ReferralCode,
         ) -> Result<(), Self::Error> {
             let current_per_referrer = referral::INVOCATION_COUNTS
                 .may_load(&self.0, (dapp.as_str(), code.to_u64()))?
                 .unwrap_or(0);
 
+            let current_total = referral::TOTAL_INVOCATION_COUNTS
+                .may_load(&self.0, dapp.as_str())?
+                .unwrap_or_default();
+
+            let invocations = self.total_invocations()?;
+
+            // counts are checked before any are saved so an overflow leaves them all untouched
+            let (Some(per_referrer), Some(total), Some(invocations)) = (
+                current_per_referrer.checked_add(1),
+                current_total.checked_add(1),
+                invocations.checked_add(1),
+            ) else {
+                return Err(Error::Overflow);
+            };
+
+            // first referral of the dApp by this code, add it to the dApp's code list
             if current_per_referrer == 0 {
                 let discrete_referrers = referral::DISCRETE_REFERRERS
                     .may_load(&self.0, dapp.as_str())?
                     .unwrap_or(0);
 
+                referral::DAPP_CODE_LIST.save(
+                    &mut self.0,
+                    (dapp.as_str(), discrete_referrers),
+                    NonZeroU64::from(code),
+                )?;
+
                 referral::DISCRETE_REFERRERS.save(
                     &mut self.0,
                     dapp.as_str(),
-                    discrete_referrers + 1,
+                    discrete_referrers.checked_add(1).ok_or(Error::Overflow)?,
                 )?;
             }
 
-            let current_total = referral::TOTAL_INVOCATION_COUNTS
-                .may_load(&self.0, dapp.as_str())?
-                .unwrap_or_default();
-
             referral::INVOCATION_COUNTS.save(
                 &mut self.0,
                 (dapp.as_str(), code.to_u64()),
-                current_per_referrer + 1,
+                per_referrer,
             )?;
 
+            totals::INVOCATIONS.save(&mut self.0, invocations)?;
+
             referral::TOTAL_INVOCATION_COUNTS
-                .save(&mut self.0, dapp.as_str(), current_total + 1)
+                .save(&mut self.0, dapp.as_str(), total)
+                .map_err(Error::from)
+        }
+
+        fn increment_daily_invocations(
+            &mut self,
+            dapp: IdRef<'_>,
+            day: u64,
+        ) -> Result<(), Self::Error> {
+            let current = referral::DAPP_INVOCATIONS_BY_DAY
+                .may_load(&self.0, (dapp.as_str(), day))?
+                .unwrap_or(0);
+
+            referral::DAPP_INVOCATIONS_BY_DAY
+                .save(
+                    &mut self.0,
+                    (dapp.as_str(), day),
+                    current.checked_add(1).ok_or(Error::Overflow)?,
+                )
+                .map_err(Error::from)
+        }
+
+        fn touch(
+            &mut self,
+            dapp: IdRef<'_>,
+            code: ReferralCode,
+            activity: Activity,
+        ) -> Result<(), Self::Error> {
+            referral::DAPP_LAST_ACTIVE.save(&mut self.0, dapp.as_str(), activity)?;
+
+            referral::CODE_LAST_ACTIVE
+                .save(&mut self.0, (dapp.as_str(), code.to_u64()), activity)
                 .map_err(Error::from)
         }
 
@@ -304,10 +865,21 @@ mod hub {
 
         fn set_dapp_earnings(
             &mut self,
-            dapp: &Id,
+            dapp: IdRef<'_>,
             code: ReferralCode,
             total: NonZeroU128,
         ) -> Result<(), Self::Error> {
+            // first earnings with this dApp, add it to the code's dApp list
+            if self.dapp_earnings(dapp, code)?.is_none() {
+                let count = self.code_dapp_count(code)?;
+                referral::CODE_DAPP_LIST.save(
+                    &mut self.0,
+                    (code.to_u64(), count),
+                    &String::from(dapp),
+                )?;
+                referral::CODE_DAPP_COUNT.save(&mut self.0, code.to_u64(), count + 1)?;
+            }
+
             referral::CODE_DAPP_EARNINGS
                 .save(&mut self.0, (dapp.as_str(), code.to_u64()), total)
                 .map_err(Error::from)
@@ -315,13 +887,36 @@ mod hub {
 
         fn set_dapp_contributions(
             &mut self,
-            dapp: &Id,
+            dapp: IdRef<'_>,
             contributions: NonZeroU128,
         ) -> Result<(), Self::Error> {
+            let previous = self.dapp_contributions(dapp)?.map_or(0, NonZeroU128::get);
+            let total = self.total_contributions()?;
+            let total = total
+                .checked_add(contributions.get().saturating_sub(previous))
+                .ok_or(Error::Overflow)?;
+            totals::CONTRIBUTIONS.save(&mut self.0, total)?;
+
             referral::DAPP_CONTRIBUTIONS
                 .save(&mut self.0, dapp.as_str(), contributions)
                 .map_err(Error::from)
         }
+
+        fn set_code_blocked(
+            &mut self,
+            code: ReferralCode,
+            blocked: bool,
+        ) -> Result<(), Self::Error> {
+            referral::BLOCKED_CODES
+                .save(&mut self.0, code.to_u64(), blocked)
+                .map_err(Error::from)
+        }
+
+        fn set_self_referral_blocked(&mut self, blocked: bool) -> Result<(), Self::Error> {
+            referral::BLOCK_SELF_REFERRAL
+                .save(&mut self.0, blocked)
+                .map_err(Error::from)
+        }
     }
 
     // implementation requires stores from both `dapp` & `referral`
@@ -348,26 +943,34 @@ mod hub {
 
             let start = start.unwrap_or(0);
 
+            // paging past the end is an empty page, not an error
             if start > last_index {
-                return Err(Error::IndexOutOfBounds);
+                return Ok(vec![]);
             }
 
-            let limit = match limit {
-                Some(l) => (start + l).min(last_index),
-                None => last_index,
+            let end = match limit {
+                Some(l) => start.saturating_add(l).min(last_index + 1),
+                None => last_index + 1,
             };
 
-            (start..=limit)
-                .map(|idx| {
+            // skip any gaps in the index rather than failing the whole page
+            (start..end)
+                .filter_map(|idx| {
                     dapp::DAPP_INDEX
                         .may_load(&self.0, idx)
-                        .map_err(Error::from)?
-                        .ok_or(Error::NotFound)
-                        .map(Id::from)
+                        .map_err(Error::from)
+                        .transpose()
                 })
+                .map(|res| res.map(Id::from))
                 .collect()
         }
 
+        fn dapp_index(&self, dapp: &Id) -> Result<Option<u64>, Self::Error> {
+            dapp::DAPP_REVERSE_INDEX
+                .may_load(&self.0, dapp.as_str())
+                .map_err(Error::from)
+        }
+
         fn dapp_name(&self, dapp: &Id) -> Result<Option<String>, Self::Error> {
             dapp::DAPPS
                 .may_load(&self.0, dapp.as_str())
@@ -393,6 +996,53 @@ mod hub {
                 .map(|maybe_count| maybe_count.unwrap_or(0))
                 .map_err(Error::from)
         }
+
+        fn dapp_last_active(&self, dapp: &Id) -> Result<Option<Activity>, Self::Error> {
+            referral::DAPP_LAST_ACTIVE
+                .may_load(&self.0, dapp.as_str())
+                .map_err(Error::from)
+        }
+
+        fn dapp_daily_invocations(&self, dapp: &Id, day: u64) -> Result<u64, Self::Error> {
+            referral::DAPP_INVOCATIONS_BY_DAY
+                .may_load(&self.0, (dapp.as_str(), day))
+                .map(|maybe_count| maybe_count.unwrap_or(0))
+                .map_err(Error::from)
+        }
+    }
+
+    impl<T> Storage<T>
+    where
+        T: ReadonlyKvStorage,
+    {
+        /// The dApps a code has earned with, in the order it first earned with them.
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if there is a storage error.
+        pub fn dapps_for_code(&self, code: ReferralCode) -> Result<Vec<Id>, Error<T::Error>> {
+            (0..self.code_dapp_count(code)?)
+                .filter_map(|index| self.code_dapp(code, index).transpose())
+                .collect()
+        }
+
+        /// The codes that have referred a dApp, in the order they first referred it.
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if there is a storage error.
+        pub fn codes_for_dapp(&self, dapp: &Id) -> Result<Vec<ReferralCode>, Error<T::Error>> {
+            // codes that first referred the dApp before it's code list was kept are not listed
+            (0..self.dapp_discrete_referrers(dapp)?)
+                .filter_map(|index| {
+                    referral::DAPP_CODE_LIST
+                        .may_load(&self.0, (dapp.as_str(), index))
+                        .map(|maybe| maybe.map(ReferralCode::from))
+                        .map_err(Error::from)
+                        .transpose()
+                })
+                .collect()
+        }
     }
 
     impl<T> ReferrersQuery for Storage<T>
@@ -405,6 +1055,23 @@ mod hub {
                 .map(|maybe_code| maybe_code.map(ReferralCode::from))
                 .map_err(Error::from)
         }
+
+        fn referrer_invocations(&self, dapp: &Id, code: ReferralCode) -> Result<u64, Self::Error> {
+            referral::INVOCATION_COUNTS
+                .may_load(&self.0, (dapp.as_str(), code.to_u64()))
+                .map(|maybe_count| maybe_count.unwrap_or(0))
+                .map_err(Error::from)
+        }
+
+        fn referrer_last_active(
+            &self,
+            dapp: &Id,
+            code: ReferralCode,
+        ) -> Result<Option<Activity>, Self::Error> {
+            referral::CODE_LAST_ACTIVE
+                .may_load(&self.0, (dapp.as_str(), code.to_u64()))
+                .map_err(Error::from)
+        }
     }
 
     mod collect {
@@ -412,11 +1079,20 @@ mod hub {
 
         use kv_storage::{map, Map};
 
+        use crate::{MAX_ID_KEY_LEN, MAX_INT_KEY_LEN};
+
         pub static REFERRER_TOTAL: Map<1024, u64, NonZeroU128> = map!("referrer_total");
 
         pub static REFERRER_DAPP: Map<1024, (&str, u64), NonZeroU128> = map!("referrer_dapp");
 
         pub static DAPP_TOTAL: Map<1024, &str, NonZeroU128> = map!("dapp_total");
+
+        pub static DAPP_REFERRERS: Map<1024, &str, NonZeroU128> = map!("dapp_referrers");
+
+        assert_key_fits!("referrer_total": MAX_INT_KEY_LEN);
+        assert_key_fits!("referrer_dapp": MAX_ID_KEY_LEN, MAX_INT_KEY_LEN);
+        assert_key_fits!("dapp_total": MAX_ID_KEY_LEN);
+        assert_key_fits!("dapp_referrers": MAX_ID_KEY_LEN);
     }
 
     impl<T> ReadonlyCollectStore for Storage<T>
@@ -447,6 +1123,13 @@ mod hub {
                 .may_load(&self.0, dapp.as_str())
                 .map_err(Error::from)
         }
+
+        fn dapp_referrers_collected(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+            // collections made before the dApp's total was kept are not counted
+            collect::DAPP_REFERRERS
+                .may_load(&self.0, dapp.as_str())
+                .map_err(Error::from)
+        }
     }
 
     impl<T> MutableCollectStore for Storage<T>
@@ -458,6 +1141,15 @@ mod hub {
             code: ReferralCode,
             total: NonZeroU128,
         ) -> Result<(), Self::Error> {
+            let previous = self
+                .referrer_total_collected(code)?
+                .map_or(0, NonZeroU128::get);
+            let collected = self.total_collected()?;
+            let collected = collected
+                .checked_add(total.get().saturating_sub(previous))
+                .ok_or(Error::Overflow)?;
+            totals::COLLECTED.save(&mut self.0, collected)?;
+
             collect::REFERRER_TOTAL
                 .save(&mut self.0, code.to_u64(), total)
                 .map_err(Error::from)
@@ -469,6 +1161,21 @@ mod hub {
             code: ReferralCode,
             total: NonZeroU128,
         ) -> Result<(), Self::Error> {
+            let previous = self
+                .referrer_dapp_collected(dapp, code)?
+                .map_or(0, NonZeroU128::get);
+            let collected = self
+                .dapp_referrers_collected(dapp)?
+                .map_or(0, NonZeroU128::get);
+
+            let collected = collected
+                .checked_add(total.get().saturating_sub(previous))
+                .ok_or(Error::Overflow)?;
+
+            if let Some(collected) = NonZeroU128::new(collected) {
+                collect::DAPP_REFERRERS.save(&mut self.0, dapp.as_str(), collected)?;
+            }
+
             collect::REFERRER_DAPP
                 .save(&mut self.0, (dapp.as_str(), code.to_u64()), total)
                 .map_err(Error::from)
@@ -0,0 +1,627 @@
+//! An in-memory api for tests, composing the core storage with configurable external query
+//! responses so unit & integration tests exercise the same store implementation.
+
+use std::num::{NonZeroU128, NonZeroU64};
+
+use referrals_core::hub::{
+    Activity, CollectQuery, DappCampaign, DappExternalQuery, DappsQuery, Features,
+    MutableCollectStore, MutableConfigStore, MutableDappStore, MutableReferralStore,
+    NonZeroPercent, ReadonlyCollectStore, ReadonlyConfigStore, ReadonlyDappStore,
+    ReadonlyReferralStore, Recording, ReferralCode, ReferrersQuery, TotalsQuery,
+};
+use referrals_core::{FallibleApi, Id, IdRef};
+
+use kv_storage::{MutStorage as MutKvStorage, Storage as ReadonlyKvStorage};
+
+use crate::{Error, Storage};
+
+/// Core storage over `T` along with the responses to give external queries.
+/// Queries without a configured response fail with [`Error::NotFound`].
+pub struct MemApi<T> {
+    storage: Storage<T>,
+    self_id: Id,
+    rewards_admin: Option<Id>,
+    rewards_pot_admin: Option<Id>,
+    rewards_pot_dapp: Option<Id>,
+    current_fee: Option<NonZeroU128>,
+    dapp_total_rewards: Option<NonZeroU128>,
+    height: u64,
+    time: u64,
+    enabled_features: Features,
+    min_collect_amount: Option<NonZeroU128>,
+    self_dapp_allowed: bool,
+}
+
+impl<T> MemApi<T> {
+    pub fn new(storage: T, self_id: impl Into<Id>) -> Self {
+        Self {
+            storage: Storage::new(storage),
+            self_id: self_id.into(),
+            rewards_admin: None,
+            rewards_pot_admin: None,
+            rewards_pot_dapp: None,
+            current_fee: None,
+            dapp_total_rewards: None,
+            height: 0,
+            time: 0,
+            enabled_features: Features::default(),
+            min_collect_amount: None,
+            self_dapp_allowed: true,
+        }
+    }
+
+    #[must_use]
+    pub fn rewards_admin(mut self, id: impl Into<Id>) -> Self {
+        self.rewards_admin = Some(id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn rewards_pot_admin(mut self, id: impl Into<Id>) -> Self {
+        self.rewards_pot_admin = Some(id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn rewards_pot_dapp(mut self, id: impl Into<Id>) -> Self {
+        self.rewards_pot_dapp = Some(id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn current_fee(mut self, fee: NonZeroU128) -> Self {
+        self.current_fee = Some(fee);
+        self
+    }
+
+    #[must_use]
+    pub fn dapp_total_rewards(mut self, total: NonZeroU128) -> Self {
+        self.dapp_total_rewards = Some(total);
+        self
+    }
+
+    #[must_use]
+    pub fn block(mut self, height: u64, time: u64) -> Self {
+        self.height = height;
+        self.time = time;
+        self
+    }
+
+    pub fn set_current_fee(&mut self, fee: NonZeroU128) -> &mut Self {
+        self.current_fee = Some(fee);
+        self
+    }
+
+    pub fn set_dapp_total_rewards(&mut self, total: NonZeroU128) -> &mut Self {
+        self.dapp_total_rewards = Some(total);
+        self
+    }
+
+    pub fn storage(&self) -> &Storage<T> {
+        &self.storage
+    }
+
+    pub fn storage_mut(&mut self) -> &mut Storage<T> {
+        &mut self.storage
+    }
+}
+
+impl<T> Default for MemApi<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default(), "self")
+    }
+}
+
+impl<T> FallibleApi for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    type Error = Error<T::Error>;
+}
+
+impl<T> Recording for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+    Storage<T>: Recording<Error = Error<T::Error>>,
+{
+    type Write = <Storage<T> as Recording>::Write;
+
+    fn recorded_writes(&self) -> Vec<Self::Write> {
+        self.storage.recorded_writes()
+    }
+}
+
+impl<T> DappExternalQuery for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn self_id(&self) -> Result<Id, Self::Error> {
+        Ok(self.self_id.clone())
+    }
+
+    fn rewards_admin(&self, _id: &Id) -> Result<Id, Self::Error> {
+        self.rewards_admin.clone().ok_or(Error::NotFound)
+    }
+
+    fn rewards_pot_admin(&self, _id: &Id) -> Result<Id, Self::Error> {
+        self.rewards_pot_admin.clone().ok_or(Error::NotFound)
+    }
+
+    fn rewards_pot_dapp(&self, _id: &Id) -> Result<Id, Self::Error> {
+        self.rewards_pot_dapp.clone().ok_or(Error::NotFound)
+    }
+
+    fn current_fee(&self, _id: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self.current_fee)
+    }
+
+    fn current_height(&self) -> Result<u64, Self::Error> {
+        Ok(self.height)
+    }
+
+    fn current_time(&self) -> Result<u64, Self::Error> {
+        Ok(self.time)
+    }
+}
+
+impl<T> CollectQuery for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn dapp_total_rewards(&self, _pot: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self.dapp_total_rewards)
+    }
+}
+
+impl<T> ReadonlyConfigStore for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn enabled_features(&self) -> Result<Features, Self::Error> {
+        Ok(self.enabled_features)
+    }
+
+    fn min_collect_amount(&self) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self.min_collect_amount)
+    }
+
+    fn self_dapp_allowed(&self) -> Result<bool, Self::Error> {
+        Ok(self.self_dapp_allowed)
+    }
+}
+
+impl<T> MutableConfigStore for MemApi<T>
+where
+    T: MutKvStorage,
+{
+    fn set_enabled_features(&mut self, features: Features) -> Result<(), Self::Error> {
+        self.enabled_features = features;
+        Ok(())
+    }
+
+    fn set_min_collect_amount(&mut self, amount: Option<NonZeroU128>) -> Result<(), Self::Error> {
+        self.min_collect_amount = amount;
+        Ok(())
+    }
+
+    fn set_self_dapp_allowed(&mut self, allowed: bool) -> Result<(), Self::Error> {
+        self.self_dapp_allowed = allowed;
+        Ok(())
+    }
+}
+
+// Delegation to Storage boilerplate
+
+impl<T> ReadonlyDappStore for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn dapp_exists(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.storage.dapp_exists(id)
+    }
+
+    fn percent(&self, id: &Id) -> Result<Option<NonZeroPercent>, Self::Error> {
+        self.storage.percent(id)
+    }
+
+    fn collector(&self, id: &Id) -> Result<Id, Self::Error> {
+        self.storage.collector(id)
+    }
+
+    fn has_rewards_pot(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.storage.has_rewards_pot(id)
+    }
+
+    fn rewards_pot(&self, id: &Id) -> Result<Id, Self::Error> {
+        self.storage.rewards_pot(id)
+    }
+
+    fn self_referral_allowed(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.storage.self_referral_allowed(id)
+    }
+
+    fn pending_collector(&self, id: &Id) -> Result<Option<Id>, Self::Error> {
+        self.storage.pending_collector(id)
+    }
+
+    fn campaign(&self, id: &Id) -> Result<Option<DappCampaign>, Self::Error> {
+        self.storage.campaign(id)
+    }
+
+    fn max_invocations_per_code(&self, id: &Id) -> Result<Option<NonZeroU64>, Self::Error> {
+        self.storage.max_invocations_per_code(id)
+    }
+
+    fn dapp_frozen(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.storage.dapp_frozen(id)
+    }
+
+    fn dapp_by_name(&self, name: &str) -> Result<Option<Id>, Self::Error> {
+        self.storage.dapp_by_name(name)
+    }
+
+    fn relayer(&self, id: &Id) -> Result<Option<Id>, Self::Error> {
+        self.storage.relayer(id)
+    }
+}
+
+impl<T> MutableDappStore for MemApi<T>
+where
+    T: MutKvStorage,
+{
+    fn add_dapp(&mut self, id: &Id, name: String) -> Result<(), Self::Error> {
+        self.storage.add_dapp(id, name)
+    }
+
+    fn remove_dapp(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.remove_dapp(id)
+    }
+
+    fn set_percent(&mut self, id: &Id, percent: NonZeroPercent) -> Result<(), Self::Error> {
+        self.storage.set_percent(id, percent)
+    }
+
+    fn set_name(&mut self, id: &Id, name: String) -> Result<(), Self::Error> {
+        self.storage.set_name(id, name)
+    }
+
+    fn set_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
+        self.storage.set_collector(id, collector)
+    }
+
+    fn set_repo_url(&mut self, id: &Id, repo_url: String) -> Result<(), Self::Error> {
+        self.storage.set_repo_url(id, repo_url)
+    }
+
+    fn clear_repo_url(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.clear_repo_url(id)
+    }
+
+    fn set_rewards_pot(&mut self, id: &Id, rewards_pot: Id) -> Result<(), Self::Error> {
+        self.storage.set_rewards_pot(id, rewards_pot)
+    }
+
+    fn set_self_referral_allowed(&mut self, id: &Id, allowed: bool) -> Result<(), Self::Error> {
+        self.storage.set_self_referral_allowed(id, allowed)
+    }
+
+    fn set_pending_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
+        self.storage.set_pending_collector(id, collector)
+    }
+
+    fn remove_pending_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.remove_pending_collector(id)
+    }
+
+    fn remove_percent(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.remove_percent(id)
+    }
+
+    fn remove_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.remove_collector(id)
+    }
+
+    fn set_campaign(&mut self, id: &Id, campaign: DappCampaign) -> Result<(), Self::Error> {
+        self.storage.set_campaign(id, campaign)
+    }
+
+    fn set_max_invocations_per_code(
+        &mut self,
+        id: &Id,
+        max: NonZeroU64,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_max_invocations_per_code(id, max)
+    }
+
+    fn set_dapp_frozen(&mut self, id: &Id, frozen: bool) -> Result<(), Self::Error> {
+        self.storage.set_dapp_frozen(id, frozen)
+    }
+
+    fn set_relayer(&mut self, id: &Id, relayer: Id) -> Result<(), Self::Error> {
+        self.storage.set_relayer(id, relayer)
+    }
+
+    fn remove_relayer(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.remove_relayer(id)
+    }
+
+    fn reset_dapp_accounting(&mut self, id: &Id) -> Result<(), Self::Error> {
+        self.storage.reset_dapp_accounting(id)
+    }
+}
+
+impl<T> ReadonlyReferralStore for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn code_exists(&self, code: ReferralCode) -> Result<bool, Self::Error> {
+        self.storage.code_exists(code)
+    }
+
+    fn owner_exists(&self, owner: IdRef<'_>) -> Result<bool, Self::Error> {
+        self.storage.owner_exists(owner)
+    }
+
+    fn owner_of(&self, code: ReferralCode) -> Result<Option<Id>, Self::Error> {
+        self.storage.owner_of(code)
+    }
+
+    fn latest(&self) -> Result<Option<ReferralCode>, Self::Error> {
+        self.storage.latest()
+    }
+
+    fn total_earnings(&self, code: ReferralCode) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.total_earnings(code)
+    }
+
+    fn dapp_earnings(
+        &self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+    ) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.dapp_earnings(dapp, code)
+    }
+
+    fn dapp_contributions(&self, dapp: IdRef<'_>) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.dapp_contributions(dapp)
+    }
+
+    fn code_dapp_count(&self, code: ReferralCode) -> Result<u64, Self::Error> {
+        self.storage.code_dapp_count(code)
+    }
+
+    fn code_dapp(&self, code: ReferralCode, index: u64) -> Result<Option<Id>, Self::Error> {
+        self.storage.code_dapp(code, index)
+    }
+
+    fn code_blocked(&self, code: ReferralCode) -> Result<bool, Self::Error> {
+        self.storage.code_blocked(code)
+    }
+
+    fn invocations(&self, dapp: IdRef<'_>, code: ReferralCode) -> Result<u64, Self::Error> {
+        self.storage.invocations(dapp, code)
+    }
+
+    fn self_referral_blocked(&self) -> Result<bool, Self::Error> {
+        self.storage.self_referral_blocked()
+    }
+}
+
+impl<T> MutableReferralStore for MemApi<T>
+where
+    T: MutKvStorage,
+{
+    fn set_latest(&mut self, code: ReferralCode) -> Result<(), Self::Error> {
+        self.storage.set_latest(code)
+    }
+
+    fn set_code_owner(&mut self, code: ReferralCode, owner: Id) -> Result<(), Self::Error> {
+        self.storage.set_code_owner(code, owner)
+    }
+
+    fn increment_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+    ) -> Result<(), Self::Error> {
+        self.storage.increment_invocations(dapp, code)
+    }
+
+    fn increment_daily_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        day: u64,
+    ) -> Result<(), Self::Error> {
+        self.storage.increment_daily_invocations(dapp, day)
+    }
+
+    fn touch(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+        activity: Activity,
+    ) -> Result<(), Self::Error> {
+        self.storage.touch(dapp, code, activity)
+    }
+
+    fn set_total_earnings(
+        &mut self,
+        code: ReferralCode,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_total_earnings(code, total)
+    }
+
+    fn set_dapp_earnings(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_dapp_earnings(dapp, code, total)
+    }
+
+    fn set_dapp_contributions(
+        &mut self,
+        dapp: IdRef<'_>,
+        contributions: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_dapp_contributions(dapp, contributions)
+    }
+
+    fn set_code_blocked(&mut self, code: ReferralCode, blocked: bool) -> Result<(), Self::Error> {
+        self.storage.set_code_blocked(code, blocked)
+    }
+
+    fn set_self_referral_blocked(&mut self, blocked: bool) -> Result<(), Self::Error> {
+        self.storage.set_self_referral_blocked(blocked)
+    }
+}
+
+impl<T> ReadonlyCollectStore for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn referrer_total_collected(
+        &self,
+        code: ReferralCode,
+    ) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.referrer_total_collected(code)
+    }
+
+    fn referrer_dapp_collected(
+        &self,
+        dapp: &Id,
+        code: ReferralCode,
+    ) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.referrer_dapp_collected(dapp, code)
+    }
+
+    fn dapp_total_collected(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.dapp_total_collected(dapp)
+    }
+
+    fn dapp_referrers_collected(&self, dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        self.storage.dapp_referrers_collected(dapp)
+    }
+}
+
+impl<T> MutableCollectStore for MemApi<T>
+where
+    T: MutKvStorage,
+{
+    fn set_referrer_total_collected(
+        &mut self,
+        code: ReferralCode,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_referrer_total_collected(code, total)
+    }
+
+    fn set_referrer_dapp_collected(
+        &mut self,
+        dapp: &Id,
+        code: ReferralCode,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_referrer_dapp_collected(dapp, code, total)
+    }
+
+    fn set_dapp_total_collected(
+        &mut self,
+        dapp: &Id,
+        total: NonZeroU128,
+    ) -> Result<(), Self::Error> {
+        self.storage.set_dapp_total_collected(dapp, total)
+    }
+}
+
+impl<T> DappsQuery for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn total_dapp_count(&self) -> Result<u64, Self::Error> {
+        self.storage.total_dapp_count()
+    }
+
+    fn all_dapp_ids(&self, start: Option<u64>, limit: Option<u64>) -> Result<Vec<Id>, Self::Error> {
+        self.storage.all_dapp_ids(start, limit)
+    }
+
+    fn dapp_index(&self, dapp: &Id) -> Result<Option<u64>, Self::Error> {
+        self.storage.dapp_index(dapp)
+    }
+
+    fn dapp_name(&self, dapp: &Id) -> Result<Option<String>, Self::Error> {
+        self.storage.dapp_name(dapp)
+    }
+
+    fn dapp_repo_url(&self, dapp: &Id) -> Result<Option<String>, Self::Error> {
+        self.storage.dapp_repo_url(dapp)
+    }
+
+    fn dapp_total_invocations(&self, dapp: &Id) -> Result<u64, Self::Error> {
+        self.storage.dapp_total_invocations(dapp)
+    }
+
+    fn dapp_discrete_referrers(&self, dapp: &Id) -> Result<u64, Self::Error> {
+        self.storage.dapp_discrete_referrers(dapp)
+    }
+
+    fn dapp_last_active(&self, dapp: &Id) -> Result<Option<Activity>, Self::Error> {
+        self.storage.dapp_last_active(dapp)
+    }
+
+    fn dapp_daily_invocations(&self, dapp: &Id, day: u64) -> Result<u64, Self::Error> {
+        self.storage.dapp_daily_invocations(dapp, day)
+    }
+}
+
+impl<T> TotalsQuery for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn active_dapp_count(&self) -> Result<u64, Self::Error> {
+        self.storage.active_dapp_count()
+    }
+
+    fn total_referral_codes(&self) -> Result<u64, Self::Error> {
+        self.storage.total_referral_codes()
+    }
+
+    fn total_invocations(&self) -> Result<u64, Self::Error> {
+        self.storage.total_invocations()
+    }
+
+    fn total_contributions(&self) -> Result<u128, Self::Error> {
+        self.storage.total_contributions()
+    }
+
+    fn total_collected(&self) -> Result<u128, Self::Error> {
+        self.storage.total_collected()
+    }
+}
+
+impl<T> ReferrersQuery for MemApi<T>
+where
+    T: ReadonlyKvStorage,
+{
+    fn referral_code(&self, referrer: &Id) -> Result<Option<ReferralCode>, Self::Error> {
+        self.storage.referral_code(referrer)
+    }
+
+    fn referrer_invocations(&self, dapp: &Id, code: ReferralCode) -> Result<u64, Self::Error> {
+        self.storage.referrer_invocations(dapp, code)
+    }
+
+    fn referrer_last_active(
+        &self,
+        dapp: &Id,
+        code: ReferralCode,
+    ) -> Result<Option<Activity>, Self::Error> {
+        self.storage.referrer_last_active(dapp, code)
+    }
+}
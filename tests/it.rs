@@ -28,5 +28,11 @@ pub mod referrals_cw;
 #[cfg(test)]
 pub mod referrals_parse_cw;
 
+#[cfg(test)]
+pub mod referrals_archway;
+
 #[cfg(test)]
 pub mod referrals_archway_drivers;
+
+#[cfg(test)]
+pub mod referrals_archway_api;
@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+
+use archway_bindings::types::rewards::{
+    ContractMetadataResponse, FlatFeeResponse, RewardsRecord, RewardsRecordsResponse,
+    WithdrawRewardsResponse,
+};
+use archway_bindings::{ArchwayMsg, ArchwayQuery, PageResponse};
+use cosmwasm_std::{
+    coins, from_slice, to_binary, to_vec, Addr, Api, Binary, BlockInfo, CustomMsg, CustomQuery,
+    Empty, Querier, Storage,
+};
+use cw_multi_test::error::{bail, AnyResult};
+use cw_multi_test::{AppResponse, BankSudo, CosmosRouter, Module};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::suite::DENOM;
+
+const STATE_KEY: &[u8] = b"archway_rewards";
+
+/// Contract metadata, flat fees & rewards records of the archway rewards module.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Rewards {
+    pub owners: BTreeMap<String, String>,
+    pub rewards_addresses: BTreeMap<String, String>,
+    pub flat_fees: BTreeMap<String, u128>,
+    // records are kept once withdrawn, matching the rewards pot's accounting
+    pub records: Vec<(RewardsRecord, bool)>,
+}
+
+impl Rewards {
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage
+            .get(STATE_KEY)
+            .map(|bytes| from_slice(&bytes).unwrap())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) {
+        storage.set(STATE_KEY, &to_vec(self).unwrap());
+    }
+
+    fn authorize(&self, sender: &str, contract: &str) -> AnyResult<()> {
+        let owner = self.owners.get(contract).map(String::as_str);
+
+        if sender != contract && owner != Some(sender) {
+            bail!("{sender} is not the metadata owner of {contract}");
+        }
+
+        Ok(())
+    }
+
+    /// Mark up to `limit` of `rewards_address`'s records withdrawn, returning the count & total.
+    fn withdraw(&mut self, rewards_address: &str, limit: Option<u64>) -> (u64, u128) {
+        let limit = limit.map_or(usize::MAX, |n| usize::try_from(n).unwrap());
+
+        self.records
+            .iter_mut()
+            .filter(|(record, withdrawn)| !withdrawn && record.rewards_address == rewards_address)
+            .take(limit)
+            .fold((0, 0), |(num, total), (record, withdrawn)| {
+                *withdrawn = true;
+
+                let amount: u128 = record.rewards.iter().map(|coin| coin.amount.u128()).sum();
+
+                (num + 1, total + amount)
+            })
+    }
+}
+
+/// Stand-in for the archway rewards module, handling the custom messages & queries the contracts
+/// issue. State lives in the app's storage so failed transactions are rolled back with it.
+#[derive(Default)]
+pub struct ArchwayModule;
+
+impl ArchwayModule {
+    /// Transfer the metadata ownership of `contract` to `owner`.
+    pub fn set_owner(&self, storage: &mut dyn Storage, contract: &Addr, owner: &Addr) {
+        let mut rewards = Rewards::load(storage);
+
+        rewards
+            .owners
+            .insert(contract.to_string(), owner.to_string());
+
+        rewards.save(storage);
+    }
+
+    /// Create a rewards record of `amount` for `contract`, paid to its current rewards address.
+    pub fn accrue(
+        &self,
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        contract: &Addr,
+        amount: u128,
+    ) {
+        let mut rewards = Rewards::load(storage);
+
+        let rewards_address = rewards
+            .rewards_addresses
+            .get(contract.as_str())
+            .cloned()
+            .unwrap_or_else(|| contract.to_string());
+
+        let id = rewards.records.len() as u64 + 1;
+
+        rewards.records.push((
+            RewardsRecord {
+                id,
+                rewards_address,
+                rewards: coins(amount, DENOM),
+                calculated_height: block.height,
+                calculated_time: block.time.to_string(),
+            },
+            false,
+        ));
+
+        rewards.save(storage);
+    }
+}
+
+impl Module for ArchwayModule {
+    type ExecT = ArchwayMsg;
+    type QueryT = ArchwayQuery;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: ArchwayMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let mut rewards = Rewards::load(storage);
+
+        let response = match msg {
+            ArchwayMsg::UpdateContractMetadata {
+                contract_address,
+                owner_address,
+                rewards_address,
+            } => {
+                let contract = contract_address.unwrap_or_else(|| sender.to_string());
+
+                rewards.authorize(sender.as_str(), &contract)?;
+
+                if let Some(owner) = owner_address {
+                    rewards.owners.insert(contract.clone(), owner);
+                }
+
+                if let Some(rewards_address) = rewards_address {
+                    rewards.rewards_addresses.insert(contract, rewards_address);
+                }
+
+                AppResponse::default()
+            }
+
+            ArchwayMsg::SetFlatFee {
+                contract_address,
+                flat_fee_amount,
+            } => {
+                let contract = contract_address.unwrap_or_else(|| sender.to_string());
+
+                rewards.authorize(sender.as_str(), &contract)?;
+
+                rewards
+                    .flat_fees
+                    .insert(contract, flat_fee_amount.amount.u128());
+
+                AppResponse::default()
+            }
+
+            ArchwayMsg::WithdrawRewards {
+                records_limit,
+                record_ids,
+            } => {
+                if !record_ids.is_empty() {
+                    bail!("withdrawing by id is not supported");
+                }
+
+                let (records_num, total) = rewards.withdraw(sender.as_str(), records_limit);
+
+                if total > 0 {
+                    router.sudo(
+                        api,
+                        storage,
+                        block,
+                        BankSudo::Mint {
+                            to_address: sender.to_string(),
+                            amount: coins(total, DENOM),
+                        }
+                        .into(),
+                    )?;
+                }
+
+                AppResponse {
+                    events: vec![],
+                    data: Some(to_binary(&WithdrawRewardsResponse {
+                        records_num,
+                        total_rewards: coins(total, DENOM),
+                    })?),
+                }
+            }
+
+            msg => bail!("unhandled archway message: {msg:?}"),
+        };
+
+        rewards.save(storage);
+
+        Ok(response)
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        request: ArchwayQuery,
+    ) -> AnyResult<Binary> {
+        let rewards = Rewards::load(storage);
+
+        let response = match request {
+            ArchwayQuery::ContractMetadata { contract_address } => {
+                to_binary(&ContractMetadataResponse {
+                    owner_address: rewards
+                        .owners
+                        .get(&contract_address)
+                        .cloned()
+                        .unwrap_or_default(),
+                    rewards_address: rewards
+                        .rewards_addresses
+                        .get(&contract_address)
+                        .cloned()
+                        .unwrap_or_default(),
+                })?
+            }
+
+            ArchwayQuery::FlatFee { contract_address } => to_binary(&FlatFeeResponse {
+                flat_fee_amount: cosmwasm_std::coin(
+                    rewards
+                        .flat_fees
+                        .get(&contract_address)
+                        .copied()
+                        .unwrap_or_default(),
+                    DENOM,
+                ),
+            })?,
+
+            // every matching record is served in a single page
+            ArchwayQuery::RewardsRecords {
+                rewards_address, ..
+            } => {
+                let records: Vec<_> = rewards
+                    .records
+                    .into_iter()
+                    .filter(|(record, _)| record.rewards_address == rewards_address)
+                    .map(|(record, _)| record)
+                    .collect();
+
+                let total = records.len() as u64;
+
+                to_binary(&RewardsRecordsResponse {
+                    records,
+                    pagination: Some(PageResponse {
+                        next_key: None,
+                        total: Some(total),
+                    }),
+                })?
+            }
+
+            request => bail!("unhandled archway query: {request:?}"),
+        };
+
+        Ok(response)
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        _msg: Empty,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        bail!("sudo is not supported")
+    }
+}
@@ -0,0 +1,212 @@
+use cosmwasm_std::{Addr, Uint128};
+
+use referrals_cw::{
+    DappResponse, ExecuteMsg, QueryMsg, ReferralCodeResponse, RewardsPotAddressResponse,
+};
+
+use it::{check, expect};
+
+use crate::suite::{Suite, PREMIUM};
+
+struct Activated {
+    suite: Suite,
+    referrer: Addr,
+    code: u64,
+    dapp: Addr,
+    collector: Addr,
+}
+
+/// A referrer registered & a dApp activated with a 50% share of a 1000 fee.
+fn activated() -> Activated {
+    let mut suite = Suite::new();
+
+    let referrer = suite.addr("referrer");
+    let dapp = suite.addr("dapp");
+    let collector = suite.addr("collector");
+
+    suite
+        .execute(&referrer, ExecuteMsg::RegisterReferrer {})
+        .unwrap();
+
+    let ReferralCodeResponse { code } = suite.query(&QueryMsg::RefferalCode {
+        referrer: referrer.to_string(),
+    });
+
+    suite.transfer_rewards_ownership(&dapp);
+
+    suite
+        .execute(
+            &dapp,
+            ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 50,
+                collector: collector.to_string(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    suite
+        .execute(
+            &dapp,
+            ExecuteMsg::SetDappFee {
+                dapp: dapp.to_string(),
+                fee: Uint128::new(1000),
+            },
+        )
+        .unwrap();
+
+    Activated {
+        suite,
+        referrer,
+        code,
+        dapp,
+        collector,
+    }
+}
+
+#[test]
+fn referral_lifecycle_works() {
+    let Activated {
+        mut suite,
+        referrer,
+        code,
+        dapp,
+        collector,
+    } = activated();
+
+    // the pot the hub predicted the address of must be the one instantiated via reply
+    let RewardsPotAddressResponse { address } = suite.query(&QueryMsg::RewardsPotAddress {
+        dapp: dapp.to_string(),
+    });
+
+    let pot = Addr::unchecked(address);
+
+    suite.app.wrap().query_wasm_contract_info(&pot).unwrap();
+
+    assert_eq!(
+        suite.metadata(&dapp),
+        (Some(suite.hub.to_string()), Some(pot.to_string()))
+    );
+
+    for _ in 0..2 {
+        suite
+            .execute(&dapp, ExecuteMsg::RecordReferral { code, dapp: None })
+            .unwrap();
+    }
+
+    suite.accrue_rewards(&dapp, 3000);
+
+    suite
+        .collect(
+            &referrer,
+            ExecuteMsg::CollectReferrer {
+                code,
+                dapp: dapp.to_string(),
+                recipient: None,
+            },
+        )
+        .unwrap();
+
+    // 2 referrals at 500 each
+    assert_eq!(suite.balance(&referrer), 1000);
+    // the premium is passed on to the hub's collector
+    assert_eq!(suite.balance(&suite.hub), 0);
+    assert_eq!(suite.balance(&suite.addr("hub_owner")), PREMIUM);
+    assert_eq!(suite.balance(&pot), 2000);
+
+    suite.accrue_rewards(&dapp, 500);
+
+    suite
+        .execute(
+            &dapp,
+            ExecuteMsg::DeactivateDapp {
+                dapp: dapp.to_string(),
+                rewards_admin: dapp.to_string(),
+                rewards_recipient: collector.to_string(),
+                mode: None,
+            },
+        )
+        .unwrap();
+
+    // pending rewards are withdrawn before the rewards metadata is handed back
+    assert_eq!(suite.balance(&pot), 2500);
+
+    assert_eq!(
+        suite.metadata(&dapp),
+        (Some(dapp.to_string()), Some(collector.to_string()))
+    );
+
+    let response: DappResponse = suite.query(&QueryMsg::Dapp {
+        dapp: dapp.to_string(),
+    });
+
+    assert!(!response.active);
+    assert_eq!(response.total_invocations, 2);
+}
+
+#[test]
+fn unauthorized_collect_fails() {
+    let Activated {
+        mut suite,
+        code,
+        dapp,
+        ..
+    } = activated();
+
+    suite
+        .execute(&dapp, ExecuteMsg::RecordReferral { code, dapp: None })
+        .unwrap();
+
+    suite.accrue_rewards(&dapp, 1000);
+
+    let thief = suite.addr("thief");
+
+    let err = suite
+        .collect(
+            &thief,
+            ExecuteMsg::CollectReferrer {
+                code,
+                dapp: dapp.to_string(),
+                recipient: None,
+            },
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("unauthorised - {thief} is not the owner of referral code {code}")
+    );
+
+    // the failed collection is rolled back, premium included
+    assert_eq!(suite.balance(&thief), PREMIUM);
+}
+
+#[test]
+fn record_with_unknown_code_fails() {
+    let Activated {
+        mut suite, dapp, ..
+    } = activated();
+
+    let err = suite
+        .execute(
+            &dapp,
+            ExecuteMsg::RecordReferral {
+                code: 99,
+                dapp: None,
+            },
+        )
+        .unwrap_err();
+
+    check(
+        err.root_cause(),
+        expect!["referral code not registered - 99"],
+    );
+
+    let response: DappResponse = suite.query(&QueryMsg::Dapp {
+        dapp: dapp.to_string(),
+    });
+
+    assert_eq!(response.total_invocations, 0);
+}
@@ -0,0 +1,7 @@
+//! The hub & rewards pot contracts running on `cw-multi-test`, catching the serialization and
+//! addressing issues the driver mocks can't.
+
+pub mod archway;
+pub mod suite;
+
+mod lifecycle;
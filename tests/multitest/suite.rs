@@ -0,0 +1,244 @@
+use archway_bindings::{ArchwayMsg, ArchwayQuery};
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{coins, Addr, Decimal};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, BankSudo, Contract, ContractWrapper, Executor,
+    MockApiBech32, StakingInfo, SudoMsg, WasmKeeper,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use referrals_cw::{ExecuteMsg, InstantiateMsg, QueryMsg, WithReferralCode};
+
+use crate::archway::{ArchwayModule, Rewards};
+
+/// The bonded denom, used for flat fees & rewards.
+pub const DENOM: &str = "ucosm";
+
+/// The premium attached to collections.
+pub const PREMIUM: u128 = 1000;
+
+pub type ArchwayApp = App<
+    BankKeeper,
+    MockApiBech32,
+    MockStorage,
+    ArchwayModule,
+    WasmKeeper<ArchwayMsg, ArchwayQuery>,
+>;
+
+/// Adapt a contract crate's entry points to `ContractWrapper`, rendering errors as strings so
+/// failures can be asserted on by message.
+macro_rules! wrap_contract {
+    ($name:ident, $contract:ident, $driver:ident) => {
+        mod $name {
+            use archway_bindings::{ArchwayMsg, ArchwayQuery};
+            use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response};
+
+            use referrals_archway_drivers::$driver::{ExecuteMsg, InstantiateMsg, QueryMsg};
+
+            pub fn instantiate(
+                deps: DepsMut<ArchwayQuery>,
+                env: Env,
+                info: MessageInfo,
+                msg: InstantiateMsg,
+            ) -> Result<Response<ArchwayMsg>, String> {
+                $contract::instantiate(deps, env, info, msg).map_err(|err| err.to_string())
+            }
+
+            pub fn execute(
+                deps: DepsMut<ArchwayQuery>,
+                env: Env,
+                info: MessageInfo,
+                msg: ExecuteMsg,
+            ) -> Result<Response<ArchwayMsg>, String> {
+                $contract::execute(deps, env, info, msg).map_err(|err| err.to_string())
+            }
+
+            pub fn reply(
+                deps: DepsMut<ArchwayQuery>,
+                env: Env,
+                reply: Reply,
+            ) -> Result<Response<ArchwayMsg>, String> {
+                $contract::reply(deps, env, reply).map_err(|err| err.to_string())
+            }
+
+            pub fn query(
+                deps: Deps<ArchwayQuery>,
+                env: Env,
+                msg: QueryMsg,
+            ) -> Result<Binary, String> {
+                $contract::query(deps, env, msg).map_err(|err| err.to_string())
+            }
+        }
+    };
+}
+
+wrap_contract!(hub, archway_referrals_hub, hub);
+wrap_contract!(rewards_pot, archway_referrals_rewards_pot, rewards_pot);
+
+fn hub_contract() -> Box<dyn Contract<ArchwayMsg, ArchwayQuery>> {
+    Box::new(
+        ContractWrapper::new(hub::execute, hub::instantiate, hub::query).with_reply(hub::reply),
+    )
+}
+
+fn rewards_pot_contract() -> Box<dyn Contract<ArchwayMsg, ArchwayQuery>> {
+    Box::new(
+        ContractWrapper::new(
+            rewards_pot::execute,
+            rewards_pot::instantiate,
+            rewards_pot::query,
+        )
+        .with_reply(rewards_pot::reply),
+    )
+}
+
+/// A hub instantiated on an app with the archway rewards module stubbed.
+pub struct Suite {
+    pub app: ArchwayApp,
+    pub hub: Addr,
+}
+
+impl Suite {
+    /// Store both contracts and instantiate the hub, owned by `"hub_owner"`.
+    pub fn new() -> Self {
+        let mut app = AppBuilder::new_custom()
+            .with_api(MockApiBech32::new("archway"))
+            .with_custom(ArchwayModule)
+            .build(|router, _, storage| {
+                router
+                    .staking
+                    .setup(
+                        storage,
+                        StakingInfo {
+                            bonded_denom: DENOM.to_owned(),
+                            unbonding_time: 60,
+                            apr: Decimal::percent(10),
+                        },
+                    )
+                    .unwrap();
+            });
+
+        let rewards_pot_code_id = app.store_code(rewards_pot_contract());
+
+        let hub_code_id = app.store_code(hub_contract());
+
+        let hub_owner = app.api().addr_make("hub_owner");
+
+        let hub = app
+            .instantiate_contract(
+                hub_code_id,
+                hub_owner,
+                &InstantiateMsg {
+                    rewards_pot_code_id,
+                    contract_premium: PREMIUM.into(),
+                    rewards_denom: DENOM.to_owned(),
+                    self_activate: None,
+                    block_self_referral: None,
+                },
+                &[],
+                "referrals_hub",
+                None,
+            )
+            .unwrap();
+
+        Self { app, hub }
+    }
+
+    /// A valid address derived from `name`.
+    pub fn addr(&self, name: &str) -> Addr {
+        self.app.api().addr_make(name)
+    }
+
+    /// Execute `msg` on the hub on behalf of `sender`.
+    pub fn execute(&mut self, sender: &Addr, msg: ExecuteMsg) -> AnyResult<AppResponse> {
+        self.execute_with_funds(sender, msg, 0)
+    }
+
+    /// Execute `msg` on the hub on behalf of `sender`, attaching `funds` of the rewards denom.
+    pub fn execute_with_funds(
+        &mut self,
+        sender: &Addr,
+        msg: ExecuteMsg,
+        funds: u128,
+    ) -> AnyResult<AppResponse> {
+        let funds = if funds == 0 {
+            vec![]
+        } else {
+            coins(funds, DENOM)
+        };
+
+        self.app.execute_contract(
+            sender.clone(),
+            self.hub.clone(),
+            &WithReferralCode::from(msg),
+            &funds,
+        )
+    }
+
+    /// Mint the premium to `sender` and attach it to the collection `msg`.
+    pub fn collect(&mut self, sender: &Addr, msg: ExecuteMsg) -> AnyResult<AppResponse> {
+        self.mint(sender, PREMIUM);
+        self.execute_with_funds(sender, msg, PREMIUM)
+    }
+
+    /// Query the hub, panicking on failure.
+    pub fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> T {
+        self.query_contract(&self.hub, msg)
+    }
+
+    /// Query any contract, panicking on failure.
+    pub fn query_contract<M: Serialize, T: DeserializeOwned>(&self, contract: &Addr, msg: &M) -> T {
+        self.app.wrap().query_wasm_smart(contract, msg).unwrap()
+    }
+
+    /// The rewards denom balance of `address`.
+    pub fn balance(&self, address: &Addr) -> u128 {
+        self.app
+            .wrap()
+            .query_balance(address, DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    }
+
+    /// Credit `address` with `amount` of the rewards denom.
+    pub fn mint(&mut self, address: &Addr, amount: u128) {
+        self.app
+            .sudo(SudoMsg::Bank(BankSudo::Mint {
+                to_address: address.to_string(),
+                amount: coins(amount, DENOM),
+            }))
+            .unwrap();
+    }
+
+    /// Transfer the rewards ownership of `contract` to the hub, as the dApp would before activating.
+    pub fn transfer_rewards_ownership(&mut self, contract: &Addr) {
+        let hub = self.hub.clone();
+
+        self.app.init_modules(|router, _, storage| {
+            router.custom.set_owner(storage, contract, &hub);
+        });
+    }
+
+    /// Create a rewards record of `amount` for `contract`, paid to its current rewards address.
+    pub fn accrue_rewards(&mut self, contract: &Addr, amount: u128) {
+        let block = self.app.block_info();
+
+        self.app.init_modules(|router, _, storage| {
+            router.custom.accrue(storage, &block, contract, amount);
+        });
+    }
+
+    /// The `(owner, rewards_address)` archway metadata of `contract`.
+    pub fn metadata(&self, contract: &Addr) -> (Option<String>, Option<String>) {
+        self.app.read_module(|_, _, storage| {
+            let rewards = Rewards::load(storage);
+
+            (
+                rewards.owners.get(contract.as_str()).cloned(),
+                rewards.rewards_addresses.get(contract.as_str()).cloned(),
+            )
+        })
+    }
+}
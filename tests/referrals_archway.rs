@@ -0,0 +1,246 @@
+use archway_bindings::ArchwayMsg;
+use cosmwasm_std::testing::{mock_info, MockApi};
+use cosmwasm_std::{
+    coin, to_binary, Addr, CosmosMsg, DistributionMsg, Empty, Response, StdError, WasmMsg,
+};
+
+use referrals_archway::ResponseExt;
+use referrals_cw::ExecuteMsg;
+use referrals_parse_cw::parse_hub_exec;
+
+use crate::{check, expect};
+
+fn only_wasm_msg<T>(res: Response<T>) -> WasmMsg {
+    assert_eq!(res.messages.len(), 1);
+
+    match res.messages.into_iter().next().unwrap().msg {
+        CosmosMsg::Wasm(msg) => msg,
+        _ => panic!("expected a wasm message"),
+    }
+}
+
+#[test]
+fn collect_referrer_works() {
+    let res = Response::<Empty>::default()
+        .collect_referrer()
+        .referral_hub(Addr::unchecked("hub"))
+        .code(1)
+        .dapp(Addr::unchecked("dapp"))
+        .premium(coin(1000, "ucosm"))
+        .done()
+        .unwrap();
+
+    let expected = WasmMsg::Execute {
+        contract_addr: "hub".to_owned(),
+        msg: to_binary(&ExecuteMsg::CollectReferrer {
+            code: 1,
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        })
+        .unwrap(),
+        funds: vec![coin(1000, "ucosm")],
+    };
+
+    assert_eq!(only_wasm_msg(res), expected);
+}
+
+#[test]
+fn collect_dapp_works() {
+    // the builder steps can be given in any order
+    let res = Response::<ArchwayMsg>::default()
+        .collect_dapp()
+        .premium(coin(1000, "ucosm"))
+        .dapp(Addr::unchecked("dapp"))
+        .referral_hub(Addr::unchecked("hub"))
+        .done()
+        .unwrap();
+
+    let expected = WasmMsg::Execute {
+        contract_addr: "hub".to_owned(),
+        msg: to_binary(&ExecuteMsg::CollectDapp {
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        })
+        .unwrap(),
+        funds: vec![coin(1000, "ucosm")],
+    };
+
+    assert_eq!(only_wasm_msg(res), expected);
+}
+
+#[test]
+fn with_funds_works() {
+    let res = Response::<Empty>::default()
+        .record_referral()
+        .referral_hub(Addr::unchecked("hub"))
+        .referral_code(1)
+        .with_funds(vec![coin(100, "ucosm"), coin(5, "uatom")])
+        .done()
+        .unwrap();
+
+    let expected = WasmMsg::Execute {
+        contract_addr: "hub".to_owned(),
+        msg: to_binary(&ExecuteMsg::RecordReferral {
+            code: 1,
+            dapp: None,
+        })
+        .unwrap(),
+        funds: vec![coin(100, "ucosm"), coin(5, "uatom")],
+    };
+
+    assert_eq!(only_wasm_msg(res), expected);
+
+    let res = Response::<ArchwayMsg>::default()
+        .set_dapp_fee()
+        .with_funds(vec![coin(100, "ucosm")])
+        .referral_hub(Addr::unchecked("hub"))
+        .dapp(Addr::unchecked("dapp"))
+        .fee(1000u128)
+        .done()
+        .unwrap();
+
+    let expected = WasmMsg::Execute {
+        contract_addr: "hub".to_owned(),
+        msg: to_binary(&ExecuteMsg::SetDappFee {
+            dapp: "dapp".to_owned(),
+            fee: 1000u128.into(),
+        })
+        .unwrap(),
+        funds: vec![coin(100, "ucosm")],
+    };
+
+    assert_eq!(only_wasm_msg(res), expected);
+
+    // funds in the premium denom are added to the premium
+    let res = Response::<Empty>::default()
+        .collect_dapp()
+        .referral_hub(Addr::unchecked("hub"))
+        .dapp(Addr::unchecked("dapp"))
+        .with_funds(vec![coin(5, "uatom"), coin(100, "ucosm")])
+        .premium(coin(1000, "ucosm"))
+        .done()
+        .unwrap();
+
+    let expected = WasmMsg::Execute {
+        contract_addr: "hub".to_owned(),
+        msg: to_binary(&ExecuteMsg::CollectDapp {
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        })
+        .unwrap(),
+        funds: vec![coin(5, "uatom"), coin(1100, "ucosm")],
+    };
+
+    assert_eq!(only_wasm_msg(res), expected);
+}
+
+#[test]
+fn activate_converts_distribution_msg() {
+    let withdraw = DistributionMsg::SetWithdrawAddress {
+        address: "collector".to_owned(),
+    };
+
+    let res = Response::<Empty>::default()
+        .add_message(withdraw.clone())
+        .activate_dapp_referrals()
+        .referral_hub(Addr::unchecked("hub"))
+        .dapp_name("dapp")
+        .referrer_percent(50)
+        .collector(Addr::unchecked("collector"))
+        .done()
+        .unwrap();
+
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::<ArchwayMsg>::Distribution(withdraw)
+    );
+}
+
+#[test]
+fn activate_with_custom_msg_fails() {
+    let err = Response::<Empty>::default()
+        .add_message(CosmosMsg::Custom(Empty {}))
+        .activate_dapp_referrals()
+        .referral_hub(Addr::unchecked("hub"))
+        .dapp_name("dapp")
+        .referrer_percent(50)
+        .collector(Addr::unchecked("collector"))
+        .done()
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["Generic error: unhandled msg not converted to archway msg: Custom(Empty)"],
+    );
+}
+
+#[test]
+fn activate_rejects_percent_as_parse_does() {
+    for percent in [0, 101] {
+        let err = Response::<ArchwayMsg>::default()
+            .activate_dapp_referrals()
+            .referral_hub(Addr::unchecked("hub"))
+            .dapp_name("dapp")
+            .referrer_percent(percent)
+            .collector(Addr::unchecked("collector"))
+            .done()
+            .unwrap_err();
+
+        check(
+            &err,
+            expect!["Generic error: invalid percent - valid value is any integer between 1 & 100"],
+        );
+
+        let parse_err = parse_hub_exec(
+            &MockApi::default(),
+            mock_info("dapp", &[]),
+            ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(err, StdError::generic_err(parse_err.to_string()));
+    }
+}
+
+#[test]
+fn activate_requires_confirming_no_dapp_share() {
+    let activate = || {
+        Response::<ArchwayMsg>::default()
+            .activate_dapp_referrals()
+            .referral_hub(Addr::unchecked("hub"))
+            .dapp_name("dapp")
+            .referrer_percent(100)
+            .collector(Addr::unchecked("collector"))
+    };
+
+    let err = activate().done().unwrap_err();
+
+    check(
+        err,
+        expect!["Generic error: invalid percent - 100 leaves the dApp no share of rewards, set `confirm_no_dapp_share` to confirm"],
+    );
+
+    let res = activate().confirm_no_dapp_share().done().unwrap();
+
+    let expected = WasmMsg::Execute {
+        contract_addr: "hub".to_owned(),
+        msg: to_binary(&ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 100,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: Some(true),
+        })
+        .unwrap(),
+        funds: vec![],
+    };
+
+    assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(expected));
+}
@@ -0,0 +1,190 @@
+use std::num::NonZeroU128;
+
+use archway_bindings::types::rewards::RewardsRecord;
+use archway_bindings::ArchwayQuery;
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier};
+use cosmwasm_std::{coin, coins, Env, QuerierWrapper};
+use kv_storage::KvStore;
+use referrals_archway_api::hub::Hub;
+use referrals_archway_api::rewards_pot::RewardsPot;
+use referrals_archway_api::testing::MockRewardsMetadata;
+use referrals_archway_api::{cache, Api};
+use referrals_core::hub::{DappExternalQuery, Features};
+use referrals_core::Id;
+
+use crate::referrals_storage::{Repo, RonSerde};
+use crate::{check, expect, nz};
+
+// The keys below are live on-chain, any change here needs a migration.
+
+#[test]
+fn hub_cache_layout_is_stable() {
+    let mut store: KvStore<RonSerde, Repo> = KvStore::default();
+
+    cache::set_rewards_denom(&mut store, &"ucosm".to_owned()).unwrap();
+    cache::hub::set_reward_pot_code_id(&mut store, 1).unwrap();
+    cache::hub::increment_reward_pot_count(&mut store).unwrap();
+    cache::hub::set_contract_premium(&mut store, 1000).unwrap();
+    cache::hub::set_deferred_cmds(&mut store, &vec![]).unwrap();
+    cache::hub::cache_dapp_fee(&mut store, &Id::from("dapp"), nz!(500)).unwrap();
+    cache::hub::set_pending_pot_dapp(&mut store, &Id::from("dapp")).unwrap();
+    cache::hub::set_pending_credit_code(&mut store, 1).unwrap();
+    cache::hub::set_enabled_features(&mut store, Features::ALL.set(Features::RECORD, false))
+        .unwrap();
+    cache::hub::set_min_collect_amount(&mut store, NonZeroU128::new(100)).unwrap();
+    cache::hub::set_self_dapp_allowed(&mut store, false).unwrap();
+
+    check(
+        store.repo(),
+        expect![[r#"
+            {
+            	referrals_archway_api::cache::hub::allow_self_dapp => false
+            	referrals_archway_api::cache::hub::contract_premium => 1000
+            	referrals_archway_api::cache::hub::dapp_fees_cache::dapp => 500
+            	referrals_archway_api::cache::hub::deferred_cmds => []
+            	referrals_archway_api::cache::hub::enabled_features => 13
+            	referrals_archway_api::cache::hub::min_collect_amount => 100
+            	referrals_archway_api::cache::hub::pending_credit_code => 1
+            	referrals_archway_api::cache::hub::pending_pot_dapp => "dapp"
+            	referrals_archway_api::cache::hub::reward_pot_code_id => 1
+            	referrals_archway_api::cache::hub::reward_pot_count => 1
+            	referrals_archway_api::cache::rewards_denom => "ucosm"
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn rewards_pot_cache_layout_is_stable() {
+    let mut store: KvStore<RonSerde, Repo> = KvStore::default();
+
+    cache::rewards_pot::set_dapp(&mut store, &"dapp".to_owned()).unwrap();
+    cache::rewards_pot::set_creator(&mut store, &"hub".to_owned()).unwrap();
+    cache::rewards_pot::set_total_rewards_collected(&mut store, "ucosm", 100).unwrap();
+    cache::rewards_pot::set_total_rewards_distributed(&mut store, "ucosm", 50).unwrap();
+    cache::rewards_pot::set_rewards_records_collected(&mut store, 2).unwrap();
+    cache::rewards_pot::set_withdraw_records_limit(&mut store, 10).unwrap();
+    cache::rewards_pot::set_min_withdraw_interval(&mut store, 60).unwrap();
+    cache::rewards_pot::set_last_withdraw_time(&mut store, 1000).unwrap();
+
+    check(
+        store.repo(),
+        expect![[r#"
+            {
+            	referrals_archway_api::cache::rewards_pot::creator => "hub"
+            	referrals_archway_api::cache::rewards_pot::dapp => "dapp"
+            	referrals_archway_api::cache::rewards_pot::last_withdraw_time => 1000
+            	referrals_archway_api::cache::rewards_pot::min_withdraw_interval => 60
+            	referrals_archway_api::cache::rewards_pot::rewards_collected::ucosm => 100
+            	referrals_archway_api::cache::rewards_pot::rewards_distributed::ucosm => 50
+            	referrals_archway_api::cache::rewards_pot::rewards_records_collected => 2
+            	referrals_archway_api::cache::rewards_pot::withdraw_records_limit => 10
+            }
+        "#]],
+    );
+
+    // the dApp & creator are kept apart
+    check(
+        cache::rewards_pot::dapp(&store).unwrap().unwrap(),
+        expect!["dapp"],
+    );
+    check(
+        cache::rewards_pot::creator(&store).unwrap().unwrap(),
+        expect!["hub"],
+    );
+}
+
+type Store = KvStore<RonSerde, Repo>;
+
+// archway queries reaching the querier fail, so only the rewards metadata double answers them
+fn api<'a, Mode: Default>(
+    store: Store,
+    env: &'a Env,
+    cw_api: &'a MockApi,
+    querier: &'a MockQuerier<ArchwayQuery>,
+    rewards: MockRewardsMetadata,
+) -> Api<'a, Mode, Store> {
+    Api::new(store, env, cw_api, QuerierWrapper::new(querier)).with_rewards_metadata(rewards)
+}
+
+fn record(id: u64, amount: u128) -> RewardsRecord {
+    RewardsRecord {
+        id,
+        rewards_address: String::from("cosmos2contract"),
+        rewards: coins(amount, "ucosm"),
+        calculated_height: 12345,
+        calculated_time: String::from("2022-11-11T11:11:22"),
+    }
+}
+
+#[test]
+fn hub_rewards_admin_uses_contract_metadata() {
+    let (env, cw_api, querier) = (mock_env(), MockApi::default(), MockQuerier::new(&[]));
+
+    let rewards = MockRewardsMetadata::default().metadata("dapp", "owner", "rewards_pot");
+
+    let api: Api<Hub, Store> = api(Store::default(), &env, &cw_api, &querier, rewards);
+
+    check(
+        api.rewards_admin(&Id::from("dapp")).unwrap(),
+        expect!["owner"],
+    );
+
+    check(
+        api.rewards_admin(&Id::from("other")).unwrap_err(),
+        expect!["contract metadata of other not found"],
+    );
+}
+
+#[test]
+fn hub_current_fee_prefers_cached_fee() {
+    let (env, cw_api, querier) = (mock_env(), MockApi::default(), MockQuerier::new(&[]));
+
+    let mut store = Store::default();
+
+    cache::hub::cache_dapp_fee(&mut store, &Id::from("cached"), nz!(200)).unwrap();
+
+    let rewards = MockRewardsMetadata::default()
+        .fee("flat", coin(500, "ucosm"))
+        .fee("cached", coin(100, "ucosm"));
+
+    let api: Api<Hub, Store> = api(store, &env, &cw_api, &querier, rewards);
+
+    let fees: Vec<_> = ["flat", "cached", "no_fee"]
+        .into_iter()
+        .map(|dapp| api.current_fee(&Id::from(dapp)).unwrap())
+        .collect();
+
+    check(format!("{fees:?}"), expect!["[Some(500), Some(200), None]"]);
+}
+
+#[test]
+fn rewards_pot_totals_outstanding_records() {
+    let (env, cw_api, querier) = (mock_env(), MockApi::default(), MockQuerier::new(&[]));
+
+    let mut store = Store::default();
+
+    cache::set_rewards_denom(&mut store, &"ucosm".to_owned()).unwrap();
+    cache::rewards_pot::set_total_rewards_collected(&mut store, "ucosm", 300).unwrap();
+    cache::rewards_pot::set_rewards_records_collected(&mut store, 2).unwrap();
+
+    let records = (1..=5).map(|id| record(id, u128::from(id) * 100)).collect();
+
+    let rewards = MockRewardsMetadata::default().records("cosmos2contract", records);
+
+    let api: Api<RewardsPot, Store> = api(store, &env, &cw_api, &querier, rewards);
+
+    check(api.total_rewards_records().unwrap(), expect!["5"]);
+    check(api.outstanding_records().unwrap(), expect!["3"]);
+
+    // the newest three records are outstanding, queried two at a time
+    check(
+        api.total_rewards_paged(2, 2).unwrap().total,
+        expect!["1500"],
+    );
+
+    check(
+        api.total_rewards_paged(2, 1).unwrap_err(),
+        expect!["too many outstanding rewards records to total"],
+    );
+}
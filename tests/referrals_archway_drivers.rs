@@ -1,17 +1,28 @@
-use cosmwasm_std::{Attribute, CosmosMsg, Event, ReplyOn, Response as CwResponse, SubMsg, WasmMsg};
+use cosmwasm_std::{
+    Attribute, Binary, CosmosMsg, Event, ReplyOn, Response as CwResponse, SubMsg, WasmMsg,
+};
 use serde::{de::DeserializeOwned, Serialize};
 
 use referrals_archway_drivers::CustomMsg;
 
 type Response = CwResponse<CustomMsg>;
 
+pub mod harness;
 pub mod hub;
 pub mod rewards_pot;
 
 #[derive(Serialize)]
 pub enum DisplayWasmMsg<W = ()> {
-    Execute { contract_addr: String, msg: W },
-    Instantiate { code_id: u64, msg: W, label: String },
+    Execute {
+        contract_addr: String,
+        msg: W,
+    },
+    Instantiate2 {
+        code_id: u64,
+        msg: W,
+        label: String,
+        salt: Binary,
+    },
 }
 
 #[derive(Serialize)]
@@ -101,15 +112,17 @@ where
                 contract_addr,
                 msg: cosmwasm_std::from_binary(&msg).unwrap(),
             },
-            WasmMsg::Instantiate {
+            WasmMsg::Instantiate2 {
                 code_id,
                 msg,
                 label,
+                salt,
                 ..
-            } => DisplayWasmMsg::Instantiate {
+            } => DisplayWasmMsg::Instantiate2 {
                 code_id,
                 msg: cosmwasm_std::from_binary(&msg).unwrap(),
                 label,
+                salt,
             },
             m => panic!("not handling {m:?}"),
         }
@@ -0,0 +1,878 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use archway_bindings::types::rewards::{
+    ContractMetadataResponse, FlatFeeResponse, RewardsRecord, WithdrawRewardsResponse,
+};
+use archway_bindings::{ArchwayMsg, ArchwayQuery};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    coins, from_binary, instantiate2_address, to_binary, Addr, Api, BankMsg, Binary, CanonicalAddr,
+    Coin, ContractResult, CosmosMsg, Env, HexBinary, OwnedDeps, QuerierResult, QueryResponse,
+    RecoverPubkeyError, Reply, ReplyOn, StdResult, SubMsg, SubMsgResponse, SubMsgResult,
+    SystemError, SystemResult, VerificationError, WasmMsg, WasmQuery,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use referrals_archway_drivers::{hub, rewards_pot, CustomMsg};
+
+use super::Response;
+
+/// The bonded denom, used for flat fees & rewards.
+pub const DENOM: &str = "ucosm";
+
+/// Address the hub is instantiated at.
+pub const HUB: &str = "referrals_hub";
+
+/// Code ID the rewards pot is stored under.
+pub const REWARDS_POT_CODE_ID: u64 = 1;
+
+/// Seconds between blocks as the chain advances.
+pub const BLOCK_TIME: u64 = 5;
+
+/// Checksum of the rewards pot code, as reported by `WasmQuery::CodeInfo`.
+const REWARDS_POT_CHECKSUM: [u8; 32] = [0x26; 32];
+
+type MockDeps = OwnedDeps<MockStorage, ChainApi, MockQuerier<ArchwayQuery>, ArchwayQuery>;
+
+/// Canonical addresses derived for `Instantiate2`, in the order they were first humanized.
+type PotAddresses = RefCell<Vec<CanonicalAddr>>;
+
+/// A `MockApi` that humanizes the addresses derived for `Instantiate2` as `rewards_pot_{n}`, so the
+/// address the hub expects for a pot is the one the chain instantiates it at.
+struct ChainApi {
+    mock: MockApi,
+    pots: Rc<PotAddresses>,
+}
+
+impl ChainApi {
+    fn pot_address(pots: &PotAddresses, canonical: &CanonicalAddr) -> Addr {
+        let mut pots = pots.borrow_mut();
+
+        let index = pots.iter().position(|pot| pot == canonical);
+
+        let index = index.unwrap_or_else(|| {
+            pots.push(canonical.clone());
+            pots.len() - 1
+        });
+
+        Addr::unchecked(format!("rewards_pot_{index}"))
+    }
+}
+
+impl Api for ChainApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.mock.addr_validate(human)
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        self.mock.addr_canonicalize(human)
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        // instantiate2 addresses are a sha256 hash, shorter than any the mock api canonicalizes
+        if canonical.len() == 32 {
+            return Ok(Self::pot_address(&self.pots, canonical));
+        }
+
+        self.mock.addr_humanize(canonical)
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.mock
+            .secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.mock
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.mock.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.mock
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.mock.debug(message);
+    }
+}
+
+/// The code info of the rewards pot, the only code the hub queries.
+#[derive(Serialize)]
+struct CodeInfo {
+    code_id: u64,
+    creator: String,
+    checksum: HexBinary,
+}
+
+type Contracts = RefCell<BTreeMap<String, Rc<RefCell<Contract>>>>;
+
+#[derive(Clone, Copy)]
+enum Code {
+    Hub,
+    RewardsPot,
+}
+
+struct Contract {
+    code: Code,
+    deps: MockDeps,
+}
+
+fn parse<T: DeserializeOwned>(msg: &Binary) -> Result<T, String> {
+    from_binary(msg).map_err(|err| err.to_string())
+}
+
+/// The environment of `address`, `blocks` past the mock env's block.
+fn env_for(address: &str, blocks: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height += blocks;
+    env.block.time = env.block.time.plus_seconds(blocks * BLOCK_TIME);
+    env.contract.address = cosmwasm_std::Addr::unchecked(address);
+    env
+}
+
+/// Call the same entry point on whichever driver the contract's code belongs to.
+macro_rules! dispatch {
+    ($code:expr, $entry:ident($($arg:expr),*)) => {
+        match $code {
+            Code::Hub => hub::$entry($($arg),*).map_err(|err| err.to_string()),
+            Code::RewardsPot => rewards_pot::$entry($($arg),*).map_err(|err| err.to_string()),
+        }
+    };
+}
+
+impl Contract {
+    fn instantiate(&mut self, env: Env, sender: &str, msg: &Binary) -> Result<Response, String> {
+        let info = mock_info(sender, &[]);
+        dispatch!(self.code, init(self.deps.as_mut(), env, info, parse(msg)?))
+    }
+
+    fn execute(
+        &mut self,
+        env: Env,
+        sender: &str,
+        msg: &Binary,
+        funds: &[Coin],
+    ) -> Result<Response, String> {
+        let info = mock_info(sender, funds);
+        dispatch!(
+            self.code,
+            execute(self.deps.as_mut(), env, info, parse(msg)?)
+        )
+    }
+
+    fn reply(&mut self, env: Env, reply: Reply) -> Result<Response, String> {
+        dispatch!(self.code, reply(self.deps.as_mut(), env, reply))
+    }
+
+    fn query(&self, env: Env, msg: &Binary) -> Result<Binary, String> {
+        dispatch!(self.code, query(self.deps.as_ref(), env, parse(msg)?))
+    }
+}
+
+/// Archway module state shared by every contract's querier.
+#[derive(Default)]
+struct Ledger {
+    balances: BTreeMap<String, u128>,
+    // balances in any other denom, by address & denom
+    foreign: BTreeMap<(String, String), u128>,
+    owners: BTreeMap<String, String>,
+    rewards_addresses: BTreeMap<String, String>,
+    flat_fees: BTreeMap<String, u128>,
+    // records are kept once withdrawn, matching the rewards pot's accounting
+    records: Vec<(RewardsRecord, bool)>,
+}
+
+impl Ledger {
+    fn query(&self, query: &ArchwayQuery) -> ContractResult<QueryResponse> {
+        let response = match query {
+            ArchwayQuery::ContractMetadata { contract_address } => {
+                to_binary(&ContractMetadataResponse {
+                    owner_address: self
+                        .owners
+                        .get(contract_address)
+                        .cloned()
+                        .unwrap_or_default(),
+                    rewards_address: self
+                        .rewards_addresses
+                        .get(contract_address)
+                        .cloned()
+                        .unwrap_or_default(),
+                })
+            }
+
+            ArchwayQuery::FlatFee { contract_address } => to_binary(&FlatFeeResponse {
+                flat_fee_amount: Coin::new(
+                    self.flat_fees
+                        .get(contract_address)
+                        .copied()
+                        .unwrap_or_default(),
+                    DENOM,
+                ),
+            }),
+
+            ArchwayQuery::RewardsRecords {
+                rewards_address, ..
+            } => {
+                let records: Vec<_> = self
+                    .records
+                    .iter()
+                    .filter(|(record, _)| &record.rewards_address == rewards_address)
+                    .map(|(record, _)| record.clone())
+                    .collect();
+
+                return super::rewards_pot::archway_query_handler(query, &records);
+            }
+
+            _ => panic!("unhandled archway query: {query:?}"),
+        };
+
+        response.into()
+    }
+
+    fn update_metadata(
+        &mut self,
+        sender: &str,
+        contract: Option<String>,
+        owner: Option<String>,
+        rewards: Option<String>,
+    ) -> Result<(), String> {
+        let contract = contract.unwrap_or_else(|| sender.to_owned());
+
+        self.authorize(sender, &contract)?;
+
+        if let Some(owner) = owner {
+            self.owners.insert(contract.clone(), owner);
+        }
+
+        if let Some(rewards) = rewards {
+            self.rewards_addresses.insert(contract, rewards);
+        }
+
+        Ok(())
+    }
+
+    fn set_flat_fee(
+        &mut self,
+        sender: &str,
+        contract: Option<String>,
+        fee: &Coin,
+    ) -> Result<(), String> {
+        let contract = contract.unwrap_or_else(|| sender.to_owned());
+
+        self.authorize(sender, &contract)?;
+
+        self.flat_fees.insert(contract, fee.amount.u128());
+
+        Ok(())
+    }
+
+    fn authorize(&self, sender: &str, contract: &str) -> Result<(), String> {
+        let owner = self.owners.get(contract).map(String::as_str);
+
+        if sender != contract && owner != Some(sender) {
+            return Err(format!("{sender} is not the metadata owner of {contract}"));
+        }
+
+        Ok(())
+    }
+
+    fn withdraw_rewards(
+        &mut self,
+        rewards_address: &str,
+        limit: Option<u64>,
+    ) -> WithdrawRewardsResponse {
+        let limit = limit.map_or(usize::MAX, |n| usize::try_from(n).unwrap());
+
+        let mut records_num = 0;
+        let mut total = 0;
+
+        for (record, withdrawn) in self
+            .records
+            .iter_mut()
+            .filter(|(record, withdrawn)| !withdrawn && record.rewards_address == rewards_address)
+            .take(limit)
+        {
+            *withdrawn = true;
+            records_num += 1;
+            total += record
+                .rewards
+                .iter()
+                .map(|coin| coin.amount.u128())
+                .sum::<u128>();
+        }
+
+        *self.balances.entry(rewards_address.to_owned()).or_default() += total;
+
+        WithdrawRewardsResponse {
+            records_num,
+            total_rewards: coins(total, DENOM),
+        }
+    }
+
+    fn send(&mut self, from: &str, to: &str, amount: &[Coin]) -> Result<(), String> {
+        let (amount, foreign): (Vec<_>, Vec<_>) =
+            amount.iter().partition(|coin| coin.denom == DENOM);
+
+        for coin in foreign {
+            let balance = self
+                .foreign
+                .entry((from.to_owned(), coin.denom.clone()))
+                .or_default();
+            let available = *balance;
+
+            *balance = available
+                .checked_sub(coin.amount.u128())
+                .ok_or_else(|| format!("{from} has insufficient funds: {available} < {coin}"))?;
+
+            *self
+                .foreign
+                .entry((to.to_owned(), coin.denom.clone()))
+                .or_default() += coin.amount.u128();
+        }
+
+        let amount: u128 = amount.iter().map(|coin| coin.amount.u128()).sum();
+
+        let balance = self.balances.entry(from.to_owned()).or_default();
+        let available = *balance;
+
+        *balance = available
+            .checked_sub(amount)
+            .ok_or_else(|| format!("{from} has insufficient funds: {available} < {amount}"))?;
+
+        *self.balances.entry(to.to_owned()).or_default() += amount;
+
+        Ok(())
+    }
+}
+
+fn wasm_query(contracts: &Contracts, blocks: u64, query: &WasmQuery) -> QuerierResult {
+    if let WasmQuery::CodeInfo { code_id } = query {
+        assert_eq!(*code_id, REWARDS_POT_CODE_ID, "unknown code id");
+
+        let code_info = to_binary(&CodeInfo {
+            code_id: *code_id,
+            creator: "deployer".to_owned(),
+            checksum: HexBinary::from(REWARDS_POT_CHECKSUM.as_slice()),
+        });
+
+        return SystemResult::Ok(code_info.into());
+    }
+
+    let WasmQuery::Smart { contract_addr, msg } = query else {
+        panic!("unhandled wasm query: {query:?}");
+    };
+
+    let Some(contract) = contracts.borrow().get(contract_addr).cloned() else {
+        return SystemResult::Err(SystemError::NoSuchContract {
+            addr: contract_addr.clone(),
+        });
+    };
+
+    let result = contract.borrow().query(env_for(contract_addr, blocks), msg);
+
+    SystemResult::Ok(result.into())
+}
+
+/// Protobuf encode a length delimited field, omitting it if empty.
+fn encode_field(field: u8, bytes: &[u8], out: &mut Vec<u8>) {
+    if bytes.is_empty() {
+        return;
+    }
+
+    out.push((field << 3) | 2);
+
+    let mut len = bytes.len();
+
+    while len >= 0x80 {
+        out.push((len & 0x7f) as u8 | 0x80);
+        len >>= 7;
+    }
+
+    out.push(len as u8);
+    out.extend_from_slice(bytes);
+}
+
+/// An in-memory chain running the hub & any rewards pots it creates together, routing sub-messages
+/// between their entry points, delivering replies and tracking the balances moved by `BankMsg::Send`.
+///
+/// A failed message is not rolled back, so any error should be treated as the end of the scenario.
+#[derive(Default)]
+pub struct Chain {
+    contracts: Rc<Contracts>,
+    ledger: Rc<RefCell<Ledger>>,
+    blocks: Rc<Cell<u64>>,
+    pots: Rc<PotAddresses>,
+    next_pot_address: Option<String>,
+}
+
+impl Chain {
+    /// Instantiate the hub at `HUB`, processing the messages it issues to activate itself.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the hub or any message it issues fails.
+    pub fn instantiate_hub(
+        &mut self,
+        sender: &str,
+        msg: &hub::InstantiateMsg,
+    ) -> Result<Response, String> {
+        let msg = to_binary(msg).map_err(|err| err.to_string())?;
+
+        self.instantiate(Code::Hub, HUB, sender, &msg)
+    }
+
+    /// Execute `msg` on `contract` on behalf of `sender`, processing any messages issued.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the contract or any message it issues fails.
+    pub fn execute<M: Serialize>(
+        &mut self,
+        contract: &str,
+        sender: &str,
+        msg: &M,
+    ) -> Result<Response, String> {
+        self.execute_with_funds(contract, sender, msg, 0)
+    }
+
+    /// Execute `msg` on `contract` on behalf of `sender`, attaching `funds` of the rewards denom from
+    /// the sender's balance.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the sender can't cover the funds, or the contract or any
+    /// message it issues fails.
+    pub fn execute_with_funds<M: Serialize>(
+        &mut self,
+        contract: &str,
+        sender: &str,
+        msg: &M,
+        funds: u128,
+    ) -> Result<Response, String> {
+        let funds = if funds == 0 {
+            vec![]
+        } else {
+            coins(funds, DENOM)
+        };
+
+        self.execute_with_coins(contract, sender, msg, &funds)
+    }
+
+    /// Execute `msg` on `contract` on behalf of `sender`, attaching `funds` in any denom from the
+    /// sender's balances.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the sender can't cover the funds, or the contract or any
+    /// message it issues fails.
+    pub fn execute_with_coins<M: Serialize>(
+        &mut self,
+        contract: &str,
+        sender: &str,
+        msg: &M,
+        funds: &[Coin],
+    ) -> Result<Response, String> {
+        let msg = to_binary(msg).map_err(|err| err.to_string())?;
+
+        self.ledger.borrow_mut().send(sender, contract, funds)?;
+
+        self.sync_balance(contract);
+
+        self.execute_raw(contract, sender, &msg, funds)
+    }
+
+    /// Query `contract`, panicking on failure.
+    pub fn query<M: Serialize, T: DeserializeOwned>(&self, contract: &str, msg: &M) -> T {
+        let msg = to_binary(msg).unwrap();
+
+        let response = self
+            .contract(contract)
+            .borrow()
+            .query(self.env(contract), &msg)
+            .unwrap();
+
+        from_binary(&response).unwrap()
+    }
+
+    /// Query a contract expecting it to fail, returning the error message.
+    pub fn query_err<M: Serialize>(&self, contract: &str, msg: &M) -> String {
+        let msg = to_binary(msg).unwrap();
+
+        self.contract(contract)
+            .borrow()
+            .query(self.env(contract), &msg)
+            .unwrap_err()
+    }
+
+    /// The rewards denom balance of `address`.
+    pub fn balance(&self, address: &str) -> u128 {
+        self.ledger
+            .borrow()
+            .balances
+            .get(address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Credit `address` with `amount` of the rewards denom.
+    pub fn mint(&mut self, address: &str, amount: u128) {
+        *self
+            .ledger
+            .borrow_mut()
+            .balances
+            .entry(address.to_owned())
+            .or_default() += amount;
+
+        self.sync_balance(address);
+    }
+
+    /// The balance of `address` in a `denom` other than the rewards denom.
+    pub fn foreign_balance(&self, address: &str, denom: &str) -> u128 {
+        self.ledger
+            .borrow()
+            .foreign
+            .get(&(address.to_owned(), denom.to_owned()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Credit `address` with `amount` of a `denom` other than the rewards denom.
+    pub fn mint_foreign(&mut self, address: &str, denom: &str, amount: u128) {
+        *self
+            .ledger
+            .borrow_mut()
+            .foreign
+            .entry((address.to_owned(), denom.to_owned()))
+            .or_default() += amount;
+    }
+
+    /// Transfer the rewards ownership of `contract` to `owner`, as the dApp would before activating.
+    pub fn set_rewards_owner(&mut self, contract: &str, owner: &str) {
+        self.ledger
+            .borrow_mut()
+            .owners
+            .insert(contract.to_owned(), owner.to_owned());
+    }
+
+    /// Set the flat fee of `contract` directly on chain, bypassing the hub.
+    pub fn set_flat_fee(&mut self, contract: &str, amount: u128) {
+        self.ledger
+            .borrow_mut()
+            .flat_fees
+            .insert(contract.to_owned(), amount);
+    }
+
+    /// Create a rewards record of `amount` for `contract`, paid to its current rewards address.
+    pub fn accrue_rewards(&mut self, contract: &str, amount: u128) {
+        let mut ledger = self.ledger.borrow_mut();
+
+        let rewards_address = ledger
+            .rewards_addresses
+            .get(contract)
+            .cloned()
+            .unwrap_or_else(|| contract.to_owned());
+
+        let id = ledger.records.len() as u64 + 1;
+
+        ledger.records.push((
+            RewardsRecord {
+                id,
+                rewards_address,
+                rewards: coins(amount, DENOM),
+                calculated_height: 12345,
+                calculated_time: String::from("2022-11-11T11:11:22"),
+            },
+            false,
+        ));
+    }
+
+    /// Instantiate the next rewards pot at `address` rather than the address derived for it, as if
+    /// another contract had substituted itself.
+    pub fn substitute_next_pot(&mut self, address: &str) {
+        self.next_pot_address = Some(address.to_owned());
+    }
+
+    /// Remove the contract at `address`, as if it had been migrated away, so any query to it fails.
+    pub fn remove_contract(&mut self, address: &str) {
+        self.contracts.borrow_mut().remove(address);
+    }
+
+    /// Advance the chain by `blocks`, moving the height & time every contract sees.
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.blocks.set(self.blocks.get() + blocks);
+    }
+
+    fn env(&self, address: &str) -> Env {
+        env_for(address, self.blocks.get())
+    }
+
+    fn contract(&self, address: &str) -> Rc<RefCell<Contract>> {
+        self.contracts
+            .borrow()
+            .get(address)
+            .cloned()
+            .unwrap_or_else(|| panic!("no contract at {address}"))
+    }
+
+    fn execute_raw(
+        &mut self,
+        contract: &str,
+        sender: &str,
+        msg: &Binary,
+        funds: &[Coin],
+    ) -> Result<Response, String> {
+        let response =
+            self.contract(contract)
+                .borrow_mut()
+                .execute(self.env(contract), sender, msg, funds)?;
+
+        self.process(contract, response.messages.clone())?;
+
+        Ok(response)
+    }
+
+    fn mock_deps(&self) -> MockDeps {
+        let ledger = self.ledger.clone();
+        let contracts = self.contracts.clone();
+        let blocks = self.blocks.clone();
+
+        let mut querier = MockQuerier::<ArchwayQuery>::new(&[])
+            .with_custom_handler(move |query| SystemResult::Ok(ledger.borrow().query(query)));
+
+        querier.update_wasm(move |query| wasm_query(&contracts, blocks.get(), query));
+
+        querier.update_staking(DENOM, &[], &[]);
+
+        OwnedDeps {
+            storage: MockStorage::default(),
+            api: ChainApi {
+                mock: MockApi::default(),
+                pots: self.pots.clone(),
+            },
+            querier,
+            custom_query_type: PhantomData,
+        }
+    }
+
+    fn instantiate(
+        &mut self,
+        code: Code,
+        address: &str,
+        sender: &str,
+        msg: &Binary,
+    ) -> Result<Response, String> {
+        let contract = Rc::new(RefCell::new(Contract {
+            code,
+            deps: self.mock_deps(),
+        }));
+
+        self.contracts
+            .borrow_mut()
+            .insert(address.to_owned(), contract.clone());
+
+        let response = contract
+            .borrow_mut()
+            .instantiate(self.env(address), sender, msg);
+
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                self.contracts.borrow_mut().remove(address);
+                return Err(err);
+            }
+        };
+
+        self.process(address, response.messages.clone())?;
+
+        Ok(response)
+    }
+
+    /// Process the sub-messages issued by `sender` depth first, replying where requested.
+    fn process(&mut self, sender: &str, messages: Vec<SubMsg<CustomMsg>>) -> Result<(), String> {
+        for SubMsg {
+            id, msg, reply_on, ..
+        } in messages
+        {
+            let result = match (self.dispatch(sender, msg), reply_on) {
+                (Ok(data), ReplyOn::Success | ReplyOn::Always) => {
+                    SubMsgResult::Ok(SubMsgResponse {
+                        events: vec![],
+                        data,
+                    })
+                }
+                (Err(err), ReplyOn::Error | ReplyOn::Always) => SubMsgResult::Err(err),
+                (Ok(_), _) => continue,
+                (Err(err), _) => return Err(err),
+            };
+
+            let response = self
+                .contract(sender)
+                .borrow_mut()
+                .reply(self.env(sender), Reply { id, result })?;
+
+            self.process(sender, response.messages)?;
+        }
+
+        Ok(())
+    }
+
+    /// Execute a single message issued by `sender`, returning the data to reply with.
+    fn dispatch(
+        &mut self,
+        sender: &str,
+        msg: CosmosMsg<CustomMsg>,
+    ) -> Result<Option<Binary>, String> {
+        match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => {
+                assert!(funds.is_empty(), "funds are not supported");
+
+                let response = self.execute_raw(&contract_addr, sender, &msg, &[])?;
+
+                let mut data = vec![];
+                encode_field(1, response.data.unwrap_or_default().as_slice(), &mut data);
+
+                Ok(Some(Binary(data)))
+            }
+
+            CosmosMsg::Wasm(WasmMsg::Instantiate2 {
+                code_id,
+                msg,
+                funds,
+                salt,
+                ..
+            }) => {
+                assert_eq!(code_id, REWARDS_POT_CODE_ID, "unknown code id");
+                assert!(funds.is_empty(), "funds are not supported");
+
+                let creator = MockApi::default()
+                    .addr_canonicalize(sender)
+                    .map_err(|err| err.to_string())?;
+
+                let canonical = instantiate2_address(&REWARDS_POT_CHECKSUM, &creator, &salt)
+                    .map_err(|err| err.to_string())?;
+
+                let address = self
+                    .next_pot_address
+                    .take()
+                    .unwrap_or_else(|| ChainApi::pot_address(&self.pots, &canonical).into_string());
+
+                // as on chain, the same creator & salt can't instantiate twice
+                if self.contracts.borrow().contains_key(&address) {
+                    return Err(format!("contract {address} already exists"));
+                }
+
+                let response = self.instantiate(Code::RewardsPot, &address, sender, &msg)?;
+
+                let mut data = vec![];
+                encode_field(1, address.as_bytes(), &mut data);
+                encode_field(2, response.data.unwrap_or_default().as_slice(), &mut data);
+
+                Ok(Some(Binary(data)))
+            }
+
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                self.ledger
+                    .borrow_mut()
+                    .send(sender, &to_address, &amount)?;
+
+                self.sync_balance(sender);
+                self.sync_balance(&to_address);
+
+                Ok(None)
+            }
+
+            CosmosMsg::Custom(ArchwayMsg::UpdateContractMetadata {
+                contract_address,
+                owner_address,
+                rewards_address,
+            }) => {
+                self.ledger.borrow_mut().update_metadata(
+                    sender,
+                    contract_address,
+                    owner_address,
+                    rewards_address,
+                )?;
+
+                Ok(None)
+            }
+
+            CosmosMsg::Custom(ArchwayMsg::SetFlatFee {
+                contract_address,
+                flat_fee_amount,
+            }) => {
+                self.ledger.borrow_mut().set_flat_fee(
+                    sender,
+                    contract_address,
+                    &flat_fee_amount,
+                )?;
+
+                Ok(None)
+            }
+
+            CosmosMsg::Custom(ArchwayMsg::WithdrawRewards {
+                records_limit,
+                record_ids,
+            }) => {
+                assert!(record_ids.is_empty(), "withdrawing by id is not supported");
+
+                let response = self
+                    .ledger
+                    .borrow_mut()
+                    .withdraw_rewards(sender, records_limit);
+
+                self.sync_balance(sender);
+
+                to_binary(&response)
+                    .map(Some)
+                    .map_err(|err| err.to_string())
+            }
+
+            msg => panic!("unhandled message: {msg:?}"),
+        }
+    }
+
+    /// Update the bank querier of the contract at `address` (if any) with its ledger balance.
+    fn sync_balance(&self, address: &str) {
+        let Some(contract) = self.contracts.borrow().get(address).cloned() else {
+            return;
+        };
+
+        contract
+            .borrow_mut()
+            .deps
+            .querier
+            .update_balance(address, coins(self.balance(address), DENOM));
+    }
+}
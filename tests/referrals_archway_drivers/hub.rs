@@ -1,30 +1,52 @@
-use archway_bindings::testing::MockDepsExt;
+use std::cell::Cell;
+use std::num::NonZeroU128;
+use std::rc::Rc;
+
 use archway_bindings::types::rewards::{ContractMetadataResponse, FlatFeeResponse};
 use archway_bindings::ArchwayQuery;
 use cosmwasm_std::{
-    to_binary, Addr, ContractResult, QuerierResult, QueryResponse, Uint128, WasmQuery,
+    coin, to_binary, Addr, Binary, ContractResult, QuerierResult, QueryResponse, Reply, Storage,
+    SubMsgResponse, SubMsgResult, Uint128, WasmQuery,
 };
+use serde::de::DeserializeOwned;
+
 use referrals_archway_api::hub as api;
+use referrals_archway_api::ReplyId;
 use referrals_archway_drivers::hub;
 use referrals_archway_drivers::hub::InstantiateMsg;
 use referrals_archway_drivers::rewards_pot::{
     ExecuteMsg as PotExecuteMsg, InstantiateMsg as PotInitMsg, QueryMsg as PotQueryMsg,
 };
-use referrals_core::hub::{self as hub_core, Kind, Msg, Registration};
+use referrals_core::hub::{
+    self as hub_core, referral, DappExternalQuery, HandleReply, Kind, Msg, MutableDappStore,
+    ReadonlyDappStore, Registration,
+};
 use referrals_core::Id;
 use referrals_cw::rewards_pot::{
     AdminResponse, DappResponse as PotDappResponse, TotalRewardsResponse,
 };
 use referrals_cw::{
-    AllDappsResponse, DappResponse, ExecuteMsg, QueryMsg, ReferralCodeResponse, TotalDappsResponse,
-    WithReferralCode,
+    AllDappsResponse, CodeOwnerResponse, CollectReferrerAllResponse, ConfigureDappEntry,
+    DappInvocationsOverRangeResponse, DappResponse, DappRewardsResponse, DeactivationMode,
+    ExecuteMsg, ExportRecord, ExportSection, ExportStateResponse, HubConfigResponse,
+    HubInstantiateResponse, IsDappResponse, OwedResponse, QueryMsg, ReferralCodeResponse,
+    ReferrerStatsResponse, RewardsPotAddressResponse, SimulateResponse, TotalDappsResponse,
+    TotalsResponse, WithReferralCode, WithReferralCodeResponse,
 };
 
-use crate::{check, expect, pretty};
+use crate::{check, code, expect, nz, nzp, pretty};
 
+use super::harness::{Chain, BLOCK_TIME, DENOM, HUB, REWARDS_POT_CODE_ID};
 use super::DisplayResponse;
 
+const PREMIUM: u128 = 1000;
+
 pub fn wasm_query_handler(query: &WasmQuery) -> QuerierResult {
+    pot_query_handler(query, "dapp")
+}
+
+/// Handle queries to `rewards_pot_0`, a rewards pot created by the hub for `dapp`.
+pub fn pot_query_handler(query: &WasmQuery, dapp: &str) -> QuerierResult {
     match query {
         WasmQuery::Smart { contract_addr, msg } => {
             assert_eq!(contract_addr, "rewards_pot_0");
@@ -35,11 +57,12 @@ pub fn wasm_query_handler(query: &WasmQuery) -> QuerierResult {
                     total: Uint128::new(5000),
                 }),
                 PotQueryMsg::Dapp {} => cosmwasm_std::to_binary(&PotDappResponse {
-                    dapp: "dapp".to_owned(),
+                    dapp: dapp.to_owned(),
                 }),
                 PotQueryMsg::Admin {} => cosmwasm_std::to_binary(&AdminResponse {
                     admin: "referrals_hub".to_owned(),
                 }),
+                msg => panic!("unhandled rewards pot query: {msg:?}"),
             }
             .unwrap();
 
@@ -107,6 +130,18 @@ macro_rules! exec_ok {
     };
 }
 
+macro_rules! exec_err {
+    ($deps:ident, $from:literal, $msg:expr) => {
+        hub::execute(
+            $deps.as_mut(),
+            env!(),
+            info!($from),
+            WithReferralCode::from($msg),
+        )
+        .unwrap_err()
+    };
+}
+
 macro_rules! query_ok {
     ($deps:ident, $msg:expr) => {{
         let bin = hub::query($deps.as_ref(), env!(), $msg).unwrap();
@@ -115,21 +150,48 @@ macro_rules! query_ok {
     }};
 }
 
+fn chain_exec_ok<D, W>(chain: &mut Chain, sender: &str, msg: ExecuteMsg) -> DisplayResponse<D, W>
+where
+    D: DeserializeOwned,
+    W: DeserializeOwned,
+{
+    chain
+        .execute(HUB, sender, &msg)
+        .map(DisplayResponse::from)
+        .unwrap()
+}
+
+/// Collect on behalf of `sender`, minting them the contract premium to attach.
+fn chain_collect_ok<D, W>(chain: &mut Chain, sender: &str, msg: ExecuteMsg) -> DisplayResponse<D, W>
+where
+    D: DeserializeOwned,
+    W: DeserializeOwned,
+{
+    chain.mint(sender, PREMIUM);
+
+    chain
+        .execute_with_funds(HUB, sender, &msg, PREMIUM)
+        .map(DisplayResponse::from)
+        .unwrap()
+}
+
 #[test]
 fn plumbing_works() {
-    let mut deps =
-        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 0));
+    let mut chain = Chain::default();
 
-    deps.querier.update_wasm(wasm_query_handler);
-
-    let res: DisplayResponse<(), ExecuteMsg> = init_ok!(
-        deps,
-        "hub_owner",
-        InstantiateMsg {
-            contract_premium: 1000u128.into(),
-            rewards_pot_code_id: 1,
-        }
-    );
+    let res: DisplayResponse<(), ExecuteMsg> = chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .map(DisplayResponse::from)
+        .unwrap();
 
     check(
         pretty(&res),
@@ -154,6 +216,8 @@ fn plumbing_works() {
                       name: "referrals_hub",
                       percent: 100,
                       collector: "hub_owner",
+                      fee: None,
+                      confirm_no_dapp_share: Some(true),
                     ),
                   )),
                   reply_on: never,
@@ -176,7 +240,7 @@ fn plumbing_works() {
     );
 
     let res: DisplayResponse<ReferralCodeResponse> =
-        exec_ok!(deps, "referrer", ExecuteMsg::RegisterReferrer {});
+        chain_exec_ok(&mut chain, "referrer", ExecuteMsg::RegisterReferrer {});
 
     check(
         pretty(&res),
@@ -191,14 +255,18 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let res: DisplayResponse<(), PotInitMsg> = exec_ok!(
-        deps,
+    chain.set_rewards_owner("dapp", HUB);
+
+    let res: DisplayResponse<(), PotInitMsg> = chain_exec_ok(
+        &mut chain,
         "dapp",
         ExecuteMsg::ActivateDapp {
             name: "dapp".to_owned(),
             percent: 75,
             collector: "collector".to_owned(),
-        }
+            fee: None,
+            confirm_no_dapp_share: None,
+        },
     );
 
     check(
@@ -209,14 +277,18 @@ fn plumbing_works() {
               messages: [
                 (
                   id: 0,
-                  msg: Wasm(Instantiate(
+                  msg: Wasm(Instantiate2(
                     code_id: 1,
                     msg: (
                       dapp: "dapp",
+                      withdraw_records_limit: None,
+                      min_withdraw_interval_secs: None,
+                      rewards_denom: Some("ucosm"),
                     ),
-                    label: "referrals-reward-pot-0",
+                    label: "referrals-reward-pot-dapp-1",
+                    salt: "x+fZ9+9FZb+rNnrTdR05dym0hsWvKJQdq8gQuMyZp/c=",
                   )),
-                  reply_on: success,
+                  reply_on: always,
                 ),
               ],
               attributes: [],
@@ -224,31 +296,28 @@ fn plumbing_works() {
             )"#]],
     );
 
-    // Skip Instanitate Reply parsing and set rewards pot address directly
-    {
-        let env = env!();
-        let mut deps = deps.as_mut();
-        let mut api = api::from_deps_mut(&mut deps, &env);
-        hub_core::exec(
-            &mut api,
-            Msg {
-                sender: Id::from("referrals_hub"),
-                kind: Kind::Register(Registration::RewardsPot {
-                    dapp: Id::from("dapp"),
-                    rewards_pot: Id::from("rewards_pot_0"),
-                }),
-            },
-        )
-        .unwrap();
-    }
+    let res: RewardsPotAddressResponse = chain.query(
+        HUB,
+        &QueryMsg::RewardsPotAddress {
+            dapp: "dapp".to_owned(),
+        },
+    );
 
-    let res: DisplayResponse = exec_ok!(
-        deps,
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              address: "rewards_pot_1",
+            )"#]],
+    );
+
+    let res: DisplayResponse = chain_exec_ok(
+        &mut chain,
         "dapp",
         ExecuteMsg::SetDappFee {
             dapp: "dapp".to_owned(),
             fee: Uint128::new(1000),
-        }
+        },
     );
 
     check(
@@ -262,7 +331,7 @@ fn plumbing_works() {
                   msg: Std(custom(set_flat_fee(
                     contract_address: Some("dapp"),
                     flat_fee_amount: (
-                      denom: "",
+                      denom: "ucosm",
                       amount: "1000",
                     ),
                   ))),
@@ -274,9 +343,14 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let mut deps = deps.with_archway_query_handler(move |q| archway_query_handler(q, 1000));
-
-    let res: DisplayResponse = exec_ok!(deps, "dapp", ExecuteMsg::RecordReferral { code: 1 });
+    let res: DisplayResponse = chain_exec_ok(
+        &mut chain,
+        "dapp",
+        ExecuteMsg::RecordReferral {
+            code: 1,
+            dapp: None,
+        },
+    );
 
     check(
         pretty(&res),
@@ -289,13 +363,13 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let res: DisplayResponse = exec_ok!(
-        deps,
+    let res: DisplayResponse = chain_exec_ok(
+        &mut chain,
         "referrer",
         ExecuteMsg::TransferOwnership {
             code: 1,
             owner: "referrer_new".to_owned(),
-        }
+        },
     );
 
     check(
@@ -309,13 +383,16 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let res: DisplayResponse<(), PotExecuteMsg> = exec_ok!(
-        deps,
+    chain.accrue_rewards("dapp", 5000);
+
+    let res: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
         "referrer_new",
         ExecuteMsg::CollectReferrer {
             code: 1,
             dapp: "dapp".to_owned(),
-        }
+            recipient: None,
+        },
     );
 
     check(
@@ -324,13 +401,28 @@ fn plumbing_works() {
             (
               data: None,
               messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "hub_owner",
+                    amount: [
+                      (
+                        denom: "ucosm",
+                        amount: "1000",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
                 (
                   id: 0,
                   msg: Wasm(Execute(
-                    contract_addr: "rewards_pot_0",
+                    contract_addr: "rewards_pot_1",
                     msg: distribute_rewards(
                       recipient: "referrer_new",
                       amount: "750",
+                      clamp: None,
+                      denom: None,
                     ),
                   )),
                   reply_on: never,
@@ -341,15 +433,22 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let res: DisplayResponse = exec_ok!(
-        deps,
+    check(chain.balance("referrer_new"), expect!["750"]);
+
+    let res: DisplayResponse = chain_exec_ok(
+        &mut chain,
         "collector",
         ExecuteMsg::ConfigureDapp {
             dapp: "dapp".to_owned(),
+            name: None,
             percent: None,
             collector: Some("collector_new".to_owned()),
             repo_url: None,
-        }
+            clear_repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        },
     );
 
     check(
@@ -363,12 +462,32 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let res: DisplayResponse<(), PotExecuteMsg> = exec_ok!(
-        deps,
+    let res: DisplayResponse = chain_exec_ok(
+        &mut chain,
+        "collector_new",
+        ExecuteMsg::AcceptCollector {
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let res: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
         "collector_new",
         ExecuteMsg::CollectDapp {
             dapp: "dapp".to_owned(),
-        }
+            recipient: None,
+        },
     );
 
     check(
@@ -377,13 +496,28 @@ fn plumbing_works() {
             (
               data: None,
               messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "hub_owner",
+                    amount: [
+                      (
+                        denom: "ucosm",
+                        amount: "1000",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
                 (
                   id: 0,
                   msg: Wasm(Execute(
-                    contract_addr: "rewards_pot_0",
+                    contract_addr: "rewards_pot_1",
                     msg: distribute_rewards(
                       recipient: "collector_new",
                       amount: "4250",
+                      clamp: None,
+                      denom: None,
                     ),
                   )),
                   reply_on: never,
@@ -394,21 +528,27 @@ fn plumbing_works() {
             )"#]],
     );
 
-    let res: TotalDappsResponse = query_ok!(deps, QueryMsg::TotalDapps {});
+    check(chain.balance("collector_new"), expect!["4250"]);
+    check(chain.balance("rewards_pot_1"), expect!["0"]);
+    // the premiums are passed on to the hub's collector
+    check(chain.balance(HUB), expect!["0"]);
+    check(chain.balance("hub_owner"), expect!["2000"]);
+
+    let res: TotalDappsResponse = chain.query(HUB, &QueryMsg::TotalDapps {});
 
     check(
         pretty(&res),
         expect![[r#"
         (
-          total: 1,
+          total: 2,
         )"#]],
     );
 
-    let res: DappResponse = query_ok!(
-        deps,
-        QueryMsg::Dapp {
-            dapp: "dapp".to_owned()
-        }
+    let res: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::Dapp {
+            dapp: "dapp".to_owned(),
+        },
     );
 
     check(
@@ -419,21 +559,30 @@ fn plumbing_works() {
               active: true,
               name: Some("dapp"),
               percent: 75,
+              dapp_share_percent: 25,
               repo_url: None,
               fee: Some("1000"),
               total_invocations: 1,
               discrete_referrers: 1,
               total_contributions: "750",
               total_rewards: "5000",
+              last_active: Some((
+                height: 12345,
+                time: "1571797419000000000",
+              )),
+              campaign: None,
+              activation_index: 1,
             )"#]],
     );
 
-    let res: AllDappsResponse = query_ok!(
-        deps,
-        QueryMsg::AllDapps {
+    let res: AllDappsResponse = chain.query(
+        HUB,
+        &QueryMsg::AllDapps {
             start: None,
-            limit: None
-        }
+            after: None,
+            limit: None,
+            only_active: None,
+        },
     );
 
     check(
@@ -441,87 +590,4230 @@ fn plumbing_works() {
         expect![[r#"
             (
               dapps: [
+                (
+                  address: "referrals_hub",
+                  active: true,
+                  name: Some("referrals_hub"),
+                  percent: 100,
+                  dapp_share_percent: 0,
+                  repo_url: None,
+                  fee: Some("1000"),
+                  total_invocations: 0,
+                  discrete_referrers: 0,
+                  total_contributions: "0",
+                  total_rewards: "0",
+                  last_active: None,
+                  campaign: None,
+                  activation_index: 0,
+                ),
                 (
                   address: "dapp",
                   active: true,
                   name: Some("dapp"),
                   percent: 75,
+                  dapp_share_percent: 25,
                   repo_url: None,
                   fee: Some("1000"),
                   total_invocations: 1,
                   discrete_referrers: 1,
                   total_contributions: "750",
                   total_rewards: "5000",
+                  last_active: Some((
+                    height: 12345,
+                    time: "1571797419000000000",
+                  )),
+                  campaign: None,
+                  activation_index: 1,
                 ),
               ],
+              total: 2,
+              next_key: None,
+              errors: [],
+            )"#]],
+    );
+
+    let res: TotalsResponse = chain.query(HUB, &QueryMsg::Totals {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              total_dapps: 2,
+              active_dapps: 2,
+              referral_codes: 1,
+              total_invocations: 1,
+              total_contributions: "750",
+              total_collected: "750",
             )"#]],
     );
 }
 
 #[test]
-fn self_referral_forwarding_works() {
-    let mut deps =
-        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 0));
+fn two_dapps_three_referrers_collect_works() {
+    let mut chain = Chain::default();
 
-    deps.querier.update_wasm(wasm_query_handler);
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
 
-    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
-        deps,
-        "hub_owner",
-        InstantiateMsg {
-            contract_premium: 1000u128.into(),
-            rewards_pot_code_id: 1,
-        }
+    let exec = |chain: &mut Chain, sender: &str, msg: ExecuteMsg| {
+        chain.execute(HUB, sender, &msg).unwrap();
+    };
+
+    for referrer in ["r1", "r2", "r3"] {
+        exec(&mut chain, referrer, ExecuteMsg::RegisterReferrer {});
+    }
+
+    for (dapp, percent, fee) in [("dapp_a", 50, 1000), ("dapp_b", 20, 500)] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        exec(
+            &mut chain,
+            dapp,
+            ExecuteMsg::ActivateDapp {
+                name: dapp.to_owned(),
+                percent,
+                collector: dapp.replace("dapp", "collector"),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        );
+
+        exec(
+            &mut chain,
+            dapp,
+            ExecuteMsg::SetDappFee {
+                dapp: dapp.to_owned(),
+                fee: Uint128::new(fee),
+            },
+        );
+    }
+
+    let record = |chain: &mut Chain, dapp: &str, code: u64| {
+        exec(chain, dapp, ExecuteMsg::RecordReferral { code, dapp: None });
+    };
+
+    let collect = |chain: &mut Chain, sender: &str, msg: ExecuteMsg| {
+        let _: DisplayResponse<CollectReferrerAllResponse, PotExecuteMsg> =
+            chain_collect_ok(chain, sender, msg);
+    };
+
+    // dApp A shares 500 per referral, dApp B 100
+    record(&mut chain, "dapp_a", 1);
+    record(&mut chain, "dapp_a", 1);
+    record(&mut chain, "dapp_a", 2);
+    record(&mut chain, "dapp_b", 2);
+    record(&mut chain, "dapp_b", 3);
+    record(&mut chain, "dapp_b", 3);
+
+    chain.accrue_rewards("dapp_a", 3000);
+    chain.accrue_rewards("dapp_b", 1500);
+
+    collect(
+        &mut chain,
+        "r1",
+        ExecuteMsg::CollectReferrer {
+            code: 1,
+            dapp: "dapp_a".to_owned(),
+            recipient: None,
+        },
     );
 
-    let _: DisplayResponse<ReferralCodeResponse> =
-        exec_ok!(deps, "referrer", ExecuteMsg::RegisterReferrer {});
+    // rewards recorded & accrued after dApp A's pot has withdrawn
+    record(&mut chain, "dapp_a", 3);
 
-    let res: DisplayResponse<ReferralCodeResponse, ExecuteMsg> = exec_ok!(
-        deps,
-        "another_referrer",
-        WithReferralCode {
-            referral_code: Some(1),
-            msg: ExecuteMsg::RegisterReferrer {}
-        }
+    chain.accrue_rewards("dapp_a", 1000);
+
+    collect(
+        &mut chain,
+        "collector_b",
+        ExecuteMsg::CollectDapp {
+            dapp: "dapp_b".to_owned(),
+            recipient: None,
+        },
+    );
+
+    collect(
+        &mut chain,
+        "r2",
+        ExecuteMsg::CollectReferrerAll {
+            code: 2,
+            start: None,
+            limit: None,
+        },
+    );
+
+    collect(
+        &mut chain,
+        "r3",
+        ExecuteMsg::CollectReferrer {
+            code: 3,
+            dapp: "dapp_b".to_owned(),
+            recipient: None,
+        },
+    );
+
+    collect(
+        &mut chain,
+        "collector_a",
+        ExecuteMsg::CollectDapp {
+            dapp: "dapp_a".to_owned(),
+            recipient: None,
+        },
+    );
+
+    collect(
+        &mut chain,
+        "r3",
+        ExecuteMsg::CollectReferrer {
+            code: 3,
+            dapp: "dapp_a".to_owned(),
+            recipient: None,
+        },
+    );
+
+    chain.mint("r1", PREMIUM);
+
+    let err = chain
+        .execute_with_funds(
+            HUB,
+            "r1",
+            &ExecuteMsg::CollectReferrer {
+                code: 1,
+                dapp: "dapp_a".to_owned(),
+                recipient: None,
+            },
+            PREMIUM,
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0011] nothing to collect - referral code 1 with dApp dapp_a"],
     );
 
+    let balances: Vec<_> = [
+        "r1",
+        "r2",
+        "r3",
+        "collector_a",
+        "collector_b",
+        "rewards_pot_1",
+        "rewards_pot_2",
+    ]
+    .into_iter()
+    .map(|address| (address, chain.balance(address)))
+    .collect();
+
     check(
-        pretty(&res),
+        pretty(&balances),
         expect![[r#"
-            (
-              data: Some((
-                code: 2,
-              )),
-              messages: [
-                (
-                  id: 0,
-                  msg: Wasm(Execute(
-                    contract_addr: "referrals_hub",
-                    msg: record_referral(
-                      code: 1,
-                    ),
-                  )),
-                  reply_on: never,
-                ),
-              ],
-              attributes: [],
-              events: [],
-            )"#]],
+            [
+              ("r1", 1000),
+              ("r2", 600),
+              ("r3", 700),
+              ("collector_a", 2000),
+              ("collector_b", 1200),
+              ("rewards_pot_1", 0),
+              ("rewards_pot_2", 0),
+            ]"#]],
     );
 
-    let res: ReferralCodeResponse = query_ok!(
-        deps,
-        QueryMsg::RefferalCode {
-            referrer: "another_referrer".to_owned()
-        }
-    );
+    let res: TotalsResponse = chain.query(HUB, &QueryMsg::Totals {});
 
     check(
         pretty(&res),
         expect![[r#"
-        (
-          code: 2,
-        )"#]],
+            (
+              total_dapps: 3,
+              active_dapps: 3,
+              referral_codes: 3,
+              total_invocations: 7,
+              total_contributions: "2300",
+              total_collected: "2300",
+            )"#]],
     );
 }
+
+/// A hub with a dApp whose pot holds rewards & a referrer with earnings from it.
+fn chain_with_earnings() -> Chain {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    for (sender, msg) in [
+        ("referrer", ExecuteMsg::RegisterReferrer {}),
+        (
+            "dapp",
+            ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        ),
+        (
+            "dapp",
+            ExecuteMsg::SetDappFee {
+                dapp: "dapp".to_owned(),
+                fee: Uint128::new(1000),
+            },
+        ),
+        (
+            "dapp",
+            ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: None,
+            },
+        ),
+    ] {
+        chain.execute(HUB, sender, &msg).unwrap();
+    }
+
+    chain.accrue_rewards("dapp", 5000);
+
+    chain
+}
+
+#[test]
+fn collect_with_premium_works() {
+    let mut chain = chain_with_earnings();
+
+    chain.mint("referrer", 1500);
+
+    chain
+        .execute_with_funds(
+            HUB,
+            "referrer",
+            &ExecuteMsg::CollectReferrer {
+                code: 1,
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            1500,
+        )
+        .unwrap();
+
+    chain.mint("collector", PREMIUM);
+
+    chain
+        .execute_with_funds(
+            HUB,
+            "collector",
+            &ExecuteMsg::CollectDapp {
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            PREMIUM,
+        )
+        .unwrap();
+
+    // the referrer's overpaid premium is refunded
+    check(chain.balance("referrer"), expect!["1250"]);
+    check(chain.balance("collector"), expect!["4250"]);
+    check(chain.balance(HUB), expect!["0"]);
+    check(chain.balance("hub_owner"), expect!["2000"]);
+}
+
+#[test]
+fn collect_refunds_excess_funds() {
+    let mut chain = chain_with_earnings();
+
+    chain.mint("collector", PREMIUM + 500);
+    chain.mint_foreign("collector", "uatom", 300);
+
+    chain
+        .execute_with_coins(
+            HUB,
+            "collector",
+            &ExecuteMsg::CollectDapp {
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            &[coin(PREMIUM + 500, DENOM), coin(300, "uatom")],
+        )
+        .unwrap();
+
+    // only the premium is passed on, the rest returns to the collector
+    check(chain.balance("collector"), expect!["4750"]);
+    check(chain.foreign_balance("collector", "uatom"), expect!["300"]);
+    check(chain.balance("hub_owner"), expect!["1000"]);
+    check(chain.foreign_balance("hub_owner", "uatom"), expect!["0"]);
+    check(chain.balance(HUB), expect!["0"]);
+    check(chain.foreign_balance(HUB, "uatom"), expect!["0"]);
+}
+
+#[test]
+fn collect_to_recipient_works() {
+    let mut chain = chain_with_earnings();
+
+    let res: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "referrer",
+        ExecuteMsg::CollectReferrer {
+            code: 1,
+            dapp: "dapp".to_owned(),
+            recipient: Some("referrer_wallet".to_owned()),
+        },
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "hub_owner",
+                    amount: [
+                      (
+                        denom: "ucosm",
+                        amount: "1000",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+                (
+                  id: 0,
+                  msg: Wasm(Execute(
+                    contract_addr: "rewards_pot_1",
+                    msg: distribute_rewards(
+                      recipient: "referrer_wallet",
+                      amount: "750",
+                      clamp: None,
+                      denom: None,
+                    ),
+                  )),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let res: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "collector",
+        ExecuteMsg::CollectDapp {
+            dapp: "dapp".to_owned(),
+            recipient: Some("treasury".to_owned()),
+        },
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "hub_owner",
+                    amount: [
+                      (
+                        denom: "ucosm",
+                        amount: "1000",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+                (
+                  id: 0,
+                  msg: Wasm(Execute(
+                    contract_addr: "rewards_pot_1",
+                    msg: distribute_rewards(
+                      recipient: "treasury",
+                      amount: "4250",
+                      clamp: None,
+                      denom: None,
+                    ),
+                  )),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    check(chain.balance("referrer_wallet"), expect!["750"]);
+    check(chain.balance("treasury"), expect!["4250"]);
+    check(chain.balance("rewards_pot_1"), expect!["0"]);
+}
+
+#[test]
+fn collect_without_premium_fails() {
+    let mut chain = chain_with_earnings();
+
+    let collect_referrer = ExecuteMsg::CollectReferrer {
+        code: 1,
+        dapp: "dapp".to_owned(),
+        recipient: None,
+    };
+
+    let err = chain
+        .execute(HUB, "referrer", &collect_referrer)
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["insufficient premium - required 1000, attached 0"],
+    );
+
+    chain.mint("referrer", 999);
+
+    let err = chain
+        .execute_with_funds(HUB, "referrer", &collect_referrer, 999)
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["insufficient premium - required 1000, attached 999"],
+    );
+
+    let err = chain
+        .execute(
+            HUB,
+            "collector",
+            &ExecuteMsg::CollectDapp {
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["insufficient premium - required 1000, attached 0"],
+    );
+
+    let err = chain
+        .execute(
+            HUB,
+            "referrer",
+            &ExecuteMsg::CollectReferrerAll {
+                code: 1,
+                start: None,
+                limit: None,
+            },
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["insufficient premium - required 1000, attached 0"],
+    );
+
+    check(chain.balance("rewards_pot_1"), expect!["0"]);
+}
+
+#[test]
+fn collect_below_minimum_fails() {
+    let mut chain = chain_with_earnings();
+
+    let err = chain
+        .execute(
+            HUB,
+            "referrer",
+            &ExecuteMsg::SetMinCollectAmount {
+                amount: Some(Uint128::new(1000)),
+            },
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    chain
+        .execute(
+            HUB,
+            "hub_owner",
+            &ExecuteMsg::SetMinCollectAmount {
+                amount: Some(Uint128::new(1000)),
+            },
+        )
+        .unwrap();
+
+    let res: HubConfigResponse = chain.query(HUB, &QueryMsg::HubConfig {});
+
+    check(
+        pretty(&res.min_collect_amount),
+        expect![[r#"Some("1000")"#]],
+    );
+
+    chain.mint("referrer", PREMIUM);
+
+    let err = chain
+        .execute_with_funds(
+            HUB,
+            "referrer",
+            &ExecuteMsg::CollectReferrer {
+                code: 1,
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            PREMIUM,
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0018] below minimum collect - 750 is less than 1000"],
+    );
+
+    // batching is exempt, collecting the dust along with any other earnings
+    let _: DisplayResponse<CollectReferrerAllResponse, PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "referrer",
+        ExecuteMsg::CollectReferrerAll {
+            code: 1,
+            start: None,
+            limit: None,
+        },
+    );
+
+    // the premium attached to the rejected collection was returned
+    check(chain.balance("referrer"), expect!["1750"]);
+}
+
+#[test]
+fn owed_queries_match_collections() {
+    let mut chain = chain_with_earnings();
+
+    let owed_to_referrer = QueryMsg::OwedToReferrer {
+        code: 1,
+        dapp: "dapp".to_owned(),
+    };
+
+    let owed_to_dapp = QueryMsg::OwedToDapp {
+        dapp: "dapp".to_owned(),
+    };
+
+    let owed: OwedResponse = chain.query(HUB, &owed_to_referrer);
+
+    check(
+        pretty(&owed),
+        expect![[r#"
+            (
+              owed: "750",
+              already_collected: "0",
+            )"#]],
+    );
+
+    let _: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "referrer",
+        ExecuteMsg::CollectReferrer {
+            code: 1,
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        },
+    );
+
+    assert_eq!(chain.balance("referrer"), owed.owed.u128());
+
+    let owed: OwedResponse = chain.query(HUB, &owed_to_referrer);
+
+    check(
+        pretty(&owed),
+        expect![[r#"
+            (
+              owed: "0",
+              already_collected: "750",
+            )"#]],
+    );
+
+    let owed: OwedResponse = chain.query(HUB, &owed_to_dapp);
+
+    check(
+        pretty(&owed),
+        expect![[r#"
+            (
+              owed: "4250",
+              already_collected: "0",
+            )"#]],
+    );
+
+    let _: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "collector",
+        ExecuteMsg::CollectDapp {
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        },
+    );
+
+    assert_eq!(chain.balance("collector"), owed.owed.u128());
+
+    let owed: OwedResponse = chain.query(HUB, &owed_to_dapp);
+
+    check(
+        pretty(&owed),
+        expect![[r#"
+            (
+              owed: "0",
+              already_collected: "4250",
+            )"#]],
+    );
+}
+
+#[test]
+fn dapp_rewards_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    let dapp_rewards = || QueryMsg::DappRewards {
+        dapp: "dapp".to_owned(),
+    };
+
+    let err = hub::query(deps.as_ref(), env!(), dapp_rewards()).unwrap_err();
+
+    check(err, expect!["[E0016] rewards pot not set - dapp"]);
+
+    // Skip Instanitate Reply parsing and set rewards pot address directly
+    {
+        let env = env!();
+        let mut deps = deps.as_mut();
+        let mut api = api::from_deps_mut(&mut deps, &env);
+        hub_core::exec(
+            &mut api,
+            Msg {
+                sender: Id::from("referrals_hub"),
+                kind: Kind::Register(Registration::RewardsPot {
+                    dapp: Id::from("dapp"),
+                    rewards_pot: Id::from("rewards_pot_0"),
+                }),
+            },
+        )
+        .unwrap();
+    }
+
+    let res: DappRewardsResponse = query_ok!(deps, dapp_rewards());
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              rewards_pot: "rewards_pot_0",
+              total_rewards: "5000",
+              contributions: "0",
+              referrers_collected: "0",
+              dapp_collected: "0",
+              undistributed: "5000",
+            )"#]],
+    );
+}
+
+#[test]
+fn dapp_rewards_tracks_collections() {
+    let mut chain = chain_with_earnings();
+
+    let dapp_rewards = QueryMsg::DappRewards {
+        dapp: "dapp".to_owned(),
+    };
+
+    let _: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "referrer",
+        ExecuteMsg::CollectReferrer {
+            code: 1,
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        },
+    );
+
+    let res: DappRewardsResponse = chain.query(HUB, &dapp_rewards);
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              rewards_pot: "rewards_pot_1",
+              total_rewards: "5000",
+              contributions: "750",
+              referrers_collected: "750",
+              dapp_collected: "0",
+              undistributed: "4250",
+            )"#]],
+    );
+
+    let _: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "collector",
+        ExecuteMsg::CollectDapp {
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        },
+    );
+
+    let res: DappRewardsResponse = chain.query(HUB, &dapp_rewards);
+
+    check(res.dapp_collected, expect!["4250"]);
+    check(res.undistributed, expect!["0"]);
+}
+
+#[test]
+fn last_active_tracks_latest_referral() {
+    let mut chain = chain_with_earnings();
+
+    chain
+        .execute(HUB, "referrer_2", &ExecuteMsg::RegisterReferrer {})
+        .unwrap();
+
+    chain.advance_blocks(10);
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::RecordReferral {
+                code: 2,
+                dapp: None,
+            },
+        )
+        .unwrap();
+
+    let stats: ReferrerStatsResponse = chain.query(
+        HUB,
+        &QueryMsg::ReferrerStats {
+            code: 1,
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(
+        pretty(&stats),
+        expect![[r#"
+            (
+              invocations: 1,
+              earnings: "750",
+              last_active: Some((
+                height: 12345,
+                time: "1571797419000000000",
+              )),
+              blocked: false,
+            )"#]],
+    );
+
+    let stats: ReferrerStatsResponse = chain.query(
+        HUB,
+        &QueryMsg::ReferrerStats {
+            code: 2,
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(
+        pretty(&stats),
+        expect![[r#"
+            (
+              invocations: 1,
+              earnings: "750",
+              last_active: Some((
+                height: 12355,
+                time: "1571797469000000000",
+              )),
+              blocked: false,
+            )"#]],
+    );
+
+    let res: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::Dapp {
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(
+        pretty(&res.last_active),
+        expect![[r#"
+            Some((
+              height: 12355,
+              time: "1571797469000000000",
+            ))"#]],
+    );
+}
+
+#[test]
+fn dapp_invocations_over_range_works() {
+    let mut chain = chain_with_earnings();
+
+    // one referral recorded on day 18192 by `chain_with_earnings`, two more on the next day
+    chain.advance_blocks(86_400 / BLOCK_TIME);
+
+    for _ in 0..2 {
+        chain
+            .execute(
+                HUB,
+                "dapp",
+                &ExecuteMsg::RecordReferral {
+                    code: 1,
+                    dapp: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let res: DappInvocationsOverRangeResponse = chain.query(
+        HUB,
+        &QueryMsg::DappInvocationsOverRange {
+            dapp: "dapp".to_owned(),
+            from_day: 18191,
+            to_day: 18194,
+        },
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              days: [
+                (
+                  day: 18191,
+                  invocations: 0,
+                ),
+                (
+                  day: 18192,
+                  invocations: 1,
+                ),
+                (
+                  day: 18193,
+                  invocations: 2,
+                ),
+                (
+                  day: 18194,
+                  invocations: 0,
+                ),
+              ],
+            )"#]],
+    );
+
+    let err = chain.query_err(
+        HUB,
+        &QueryMsg::DappInvocationsOverRange {
+            dapp: "dapp".to_owned(),
+            from_day: 18193,
+            to_day: 18192,
+        },
+    );
+
+    check(err, expect!["[E0017] invalid day range"]);
+}
+
+#[test]
+fn code_owner_works() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    let _: DisplayResponse<ReferralCodeResponse> =
+        chain_exec_ok(&mut chain, "referrer", ExecuteMsg::RegisterReferrer {});
+
+    let res: CodeOwnerResponse = chain.query(HUB, &QueryMsg::CodeOwner { code: 1 });
+
+    check(pretty(&res.owner), expect![[r#"Some("referrer")"#]]);
+
+    let res: CodeOwnerResponse = chain.query(HUB, &QueryMsg::CodeOwner { code: 99 });
+
+    check(pretty(&res.owner), expect!["None"]);
+}
+
+#[test]
+fn campaign_multiplies_referrer_share_in_window() {
+    let mut chain = chain_with_earnings();
+
+    let set_campaign = ExecuteMsg::SetCampaign {
+        dapp: "dapp".to_owned(),
+        multiplier_num: 6,
+        multiplier_den: 5,
+        start_height: 12346,
+        end_height: 12350,
+    };
+
+    let err = chain.execute(HUB, "referrer", &set_campaign).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - referrer is not dApp dapp or it's collector"],
+    );
+
+    chain.execute(HUB, "collector", &set_campaign).unwrap();
+
+    let dapp_query = QueryMsg::Dapp {
+        dapp: "dapp".to_owned(),
+    };
+
+    // not yet started
+    let res: DappResponse = chain.query(HUB, &dapp_query);
+
+    check(pretty(&res.campaign), expect!["None"]);
+
+    let record = ExecuteMsg::RecordReferral {
+        code: 1,
+        dapp: None,
+    };
+
+    chain.advance_blocks(1);
+
+    chain.execute(HUB, "dapp", &record).unwrap();
+
+    let res: DappResponse = chain.query(HUB, &dapp_query);
+
+    check(
+        pretty(&res.campaign),
+        expect![[r#"
+            Some((
+              multiplier_num: 6,
+              multiplier_den: 5,
+              start_height: 12346,
+              end_height: 12350,
+            ))"#]],
+    );
+
+    chain.advance_blocks(10);
+
+    chain.execute(HUB, "dapp", &record).unwrap();
+
+    let stats: ReferrerStatsResponse = chain.query(
+        HUB,
+        &QueryMsg::ReferrerStats {
+            code: 1,
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    // 750 before, 900 during & 750 after the campaign
+    check(stats.earnings, expect!["2400"]);
+    check(stats.invocations, expect!["3"]);
+}
+
+#[test]
+fn blocked_code_cannot_record_or_collect() {
+    let mut chain = chain_with_earnings();
+
+    let block = |blocked| ExecuteMsg::SetCodeStatus { code: 1, blocked };
+
+    let err = chain.execute(HUB, "referrer", &block(true)).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    chain.execute(HUB, "hub_owner", &block(true)).unwrap();
+
+    let record = ExecuteMsg::RecordReferral {
+        code: 1,
+        dapp: None,
+    };
+
+    let err = chain.execute(HUB, "dapp", &record).unwrap_err();
+
+    check(err, expect!["[E0013] referral code blocked - 1"]);
+
+    let collect = ExecuteMsg::CollectReferrer {
+        code: 1,
+        dapp: "dapp".to_owned(),
+        recipient: None,
+    };
+
+    chain.mint("referrer", PREMIUM);
+
+    let err = chain
+        .execute_with_funds(HUB, "referrer", &collect, PREMIUM)
+        .unwrap_err();
+
+    check(err, expect!["[E0013] referral code blocked - 1"]);
+
+    let stats_query = QueryMsg::ReferrerStats {
+        code: 1,
+        dapp: "dapp".to_owned(),
+    };
+
+    let stats: ReferrerStatsResponse = chain.query(HUB, &stats_query);
+
+    check(stats.blocked, expect!["true"]);
+    check(stats.invocations, expect!["1"]);
+
+    chain.execute(HUB, "hub_owner", &block(false)).unwrap();
+
+    chain.execute(HUB, "dapp", &record).unwrap();
+
+    let stats: ReferrerStatsResponse = chain.query(HUB, &stats_query);
+
+    check(stats.blocked, expect!["false"]);
+    check(stats.invocations, expect!["2"]);
+
+    // the premium attached to the failed collection is not refunded by the harness
+    chain.mint("referrer", PREMIUM);
+
+    chain
+        .execute_with_funds(HUB, "referrer", &collect, PREMIUM)
+        .unwrap();
+
+    check(chain.balance("referrer"), expect!["1500"]);
+}
+
+#[test]
+fn relayer_records_for_authorising_dapp_only() {
+    let mut chain = chain_with_earnings();
+
+    chain.set_rewards_owner("dapp_b", HUB);
+
+    chain
+        .execute(
+            HUB,
+            "dapp_b",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp_b".to_owned(),
+                percent: 50,
+                collector: "collector_b".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    let record = |dapp: &str| ExecuteMsg::RecordReferral {
+        code: 1,
+        dapp: Some(dapp.to_owned()),
+    };
+
+    let set_relayer = |relayer: Option<&str>| ExecuteMsg::SetReferralRelayer {
+        dapp: "dapp".to_owned(),
+        relayer: relayer.map(ToOwned::to_owned),
+    };
+
+    let err = chain.execute(HUB, "relayer", &record("dapp")).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - relayer is not dApp dapp or it's referral relayer"],
+    );
+
+    // only the dApp or it's collector may authorise a relayer
+    let err = chain
+        .execute(HUB, "relayer", &set_relayer(Some("relayer")))
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - relayer is not dApp dapp or it's collector"],
+    );
+
+    chain
+        .execute(HUB, "collector", &set_relayer(Some("relayer")))
+        .unwrap();
+
+    chain.execute(HUB, "relayer", &record("dapp")).unwrap();
+
+    let stats_query = |dapp: &str| QueryMsg::ReferrerStats {
+        code: 1,
+        dapp: dapp.to_owned(),
+    };
+
+    let stats: ReferrerStatsResponse = chain.query(HUB, &stats_query("dapp"));
+
+    check(stats.invocations, expect!["2"]);
+
+    // the relayer of one dApp cannot record for another
+    let err = chain
+        .execute(HUB, "relayer", &record("dapp_b"))
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - relayer is not dApp dapp_b or it's referral relayer"],
+    );
+
+    let stats: ReferrerStatsResponse = chain.query(HUB, &stats_query("dapp_b"));
+
+    check(stats.invocations, expect!["0"]);
+
+    chain.execute(HUB, "dapp", &set_relayer(None)).unwrap();
+
+    let err = chain.execute(HUB, "relayer", &record("dapp")).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - relayer is not dApp dapp or it's referral relayer"],
+    );
+}
+
+#[test]
+fn dapp_by_name_works() {
+    let mut chain = chain_with_earnings();
+
+    let res: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::DappByName {
+            name: "dapp".to_owned(),
+        },
+    );
+
+    check(res.address, expect!["dapp"]);
+
+    let err = chain.query_err(
+        HUB,
+        &QueryMsg::DappByName {
+            name: "unknown".to_owned(),
+        },
+    );
+
+    check(err, expect!["[E0023] dapp name not registered - unknown"]);
+
+    let err = chain
+        .execute(
+            HUB,
+            "dapp_2",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+    check(err, expect!["[E0022] name taken - dapp"]);
+}
+
+#[test]
+fn rename_dapp_works() {
+    let mut chain = chain_with_earnings();
+
+    let query_dapp = |chain: &Chain| -> DappResponse {
+        chain.query(
+            HUB,
+            &QueryMsg::Dapp {
+                dapp: "dapp".to_owned(),
+            },
+        )
+    };
+
+    let before = query_dapp(&chain);
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: Some("dapp_v2".to_owned()),
+                percent: None,
+                collector: None,
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    let after = query_dapp(&chain);
+
+    check(pretty(&after.name), expect![[r#"Some("dapp_v2")"#]]);
+
+    // everything but the name persists
+    assert_eq!(
+        DappResponse {
+            name: before.name.clone(),
+            ..after
+        },
+        before
+    );
+
+    let res: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::DappByName {
+            name: "dapp_v2".to_owned(),
+        },
+    );
+
+    check(res.address, expect!["dapp"]);
+
+    // the old name is released
+    let err = chain.query_err(
+        HUB,
+        &QueryMsg::DappByName {
+            name: "dapp".to_owned(),
+        },
+    );
+
+    check(err, expect!["[E0023] dapp name not registered - dapp"]);
+}
+
+#[test]
+fn dapp_without_fee_query_works() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    let res: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::Dapp {
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    // a dApp is inactive until it's fee is set
+    check(
+        format!("active: {}, fee: {:?}", res.active, res.fee),
+        expect!["active: false, fee: None"],
+    );
+}
+
+#[test]
+fn is_dapp_works() {
+    let mut chain = chain_with_earnings();
+
+    let is_dapp = |address: &str| QueryMsg::IsDapp {
+        address: address.to_owned(),
+    };
+
+    let res: IsDappResponse = chain.query(HUB, &is_dapp("dapp"));
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              registered: true,
+              active: true,
+            )"#]],
+    );
+
+    let res: IsDappResponse = chain.query(HUB, &is_dapp("unknown"));
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              registered: false,
+              active: false,
+            )"#]],
+    );
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::DeactivateDapp {
+                dapp: "dapp".to_owned(),
+                rewards_admin: "rewards_admin".to_owned(),
+                rewards_recipient: "rewards_recipient".to_owned(),
+                mode: None,
+            },
+        )
+        .unwrap();
+
+    let res: IsDappResponse = chain.query(HUB, &is_dapp("dapp"));
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              registered: true,
+              active: false,
+            )"#]],
+    );
+}
+
+#[test]
+fn frozen_dapp_cannot_record_until_purged() {
+    let mut chain = chain_with_earnings();
+
+    let deactivate = |mode| ExecuteMsg::DeactivateDapp {
+        dapp: "dapp".to_owned(),
+        rewards_admin: "rewards_admin".to_owned(),
+        rewards_recipient: "rewards_recipient".to_owned(),
+        mode,
+    };
+
+    chain.execute(HUB, "dapp", &deactivate(None)).unwrap();
+
+    let err = chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: None,
+            },
+        )
+        .unwrap_err();
+
+    check(err, expect!["[E0020] dapp deactivated - dapp"]);
+
+    let dapp: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::Dapp {
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(dapp.active, expect!["false"]);
+
+    let purge = deactivate(Some(DeactivationMode::Purge));
+
+    let err = chain.execute(HUB, "dapp", &purge).unwrap_err();
+
+    check(
+        err,
+        expect![
+            "[E0021] uncollected earnings - referrers of dApp dapp have earnings yet to collect"
+        ],
+    );
+
+    chain.mint("referrer", PREMIUM);
+
+    chain
+        .execute_with_funds(
+            HUB,
+            "referrer",
+            &ExecuteMsg::CollectReferrer {
+                code: 1,
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            PREMIUM,
+        )
+        .unwrap();
+
+    chain.execute(HUB, "dapp", &purge).unwrap();
+
+    let totals: TotalsResponse = chain.query(HUB, &QueryMsg::Totals {});
+
+    check(totals.total_dapps, expect!["1"]);
+    check(totals.active_dapps, expect!["0"]);
+}
+
+#[test]
+fn reactivation_after_purge_works() {
+    let mut chain = chain_with_earnings();
+
+    chain.mint("referrer", PREMIUM);
+
+    chain
+        .execute_with_funds(
+            HUB,
+            "referrer",
+            &ExecuteMsg::CollectReferrer {
+                code: 1,
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            PREMIUM,
+        )
+        .unwrap();
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::DeactivateDapp {
+                dapp: "dapp".to_owned(),
+                rewards_admin: "dapp".to_owned(),
+                rewards_recipient: "dapp".to_owned(),
+                mode: Some(DeactivationMode::Purge),
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    // the rewards pot instantiated by the first activation is reused
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    let owed_to_dapp = QueryMsg::OwedToDapp {
+        dapp: "dapp".to_owned(),
+    };
+
+    let owed: OwedResponse = chain.query(HUB, &owed_to_dapp);
+
+    check(
+        pretty(&owed),
+        expect![[r#"
+            (
+              owed: "4250",
+              already_collected: "750",
+            )"#]],
+    );
+
+    chain.accrue_rewards("dapp", 2000);
+
+    let owed: OwedResponse = chain.query(HUB, &owed_to_dapp);
+
+    check(owed.owed, expect!["6250"]);
+
+    let _: DisplayResponse<(), PotExecuteMsg> = chain_collect_ok(
+        &mut chain,
+        "collector",
+        ExecuteMsg::CollectDapp {
+            dapp: "dapp".to_owned(),
+            recipient: None,
+        },
+    );
+
+    check(chain.balance("collector"), expect!["6250"]);
+}
+
+#[test]
+fn disabled_features_are_rejected() {
+    let mut chain = chain_with_earnings();
+
+    let features = |record, collect| ExecuteMsg::SetEnabledFeatures {
+        register: true,
+        record,
+        collect,
+        configure: true,
+    };
+
+    let err = chain
+        .execute(HUB, "referrer", &features(false, false))
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    chain
+        .execute(HUB, "hub_owner", &features(false, false))
+        .unwrap();
+
+    let config: HubConfigResponse = chain.query(HUB, &QueryMsg::HubConfig {});
+
+    check(
+        pretty(&config.enabled_features),
+        expect![[r#"
+            (
+              register: true,
+              record: false,
+              collect: false,
+              configure: true,
+            )"#]],
+    );
+
+    let record = ExecuteMsg::RecordReferral {
+        code: 1,
+        dapp: None,
+    };
+
+    let err = chain.execute(HUB, "dapp", &record).unwrap_err();
+
+    check(err, expect!["[E0014] feature disabled - record"]);
+
+    let collect = ExecuteMsg::CollectReferrer {
+        code: 1,
+        dapp: "dapp".to_owned(),
+        recipient: None,
+    };
+
+    chain.mint("referrer", PREMIUM);
+
+    let err = chain
+        .execute_with_funds(HUB, "referrer", &collect, PREMIUM)
+        .unwrap_err();
+
+    check(err, expect!["[E0014] feature disabled - collect"]);
+
+    chain
+        .execute(HUB, "hub_owner", &features(true, true))
+        .unwrap();
+
+    chain.execute(HUB, "dapp", &record).unwrap();
+
+    // the premium attached to the failed collection is not refunded by the harness
+    chain.mint("referrer", PREMIUM);
+
+    chain
+        .execute_with_funds(HUB, "referrer", &collect, PREMIUM)
+        .unwrap();
+
+    check(chain.balance("referrer"), expect!["1500"]);
+}
+
+#[test]
+fn register_referrer_for_assigns_nominee() {
+    let mut chain = chain_with_earnings();
+
+    let register = ExecuteMsg::RegisterReferrerFor {
+        owner: "nominee".to_owned(),
+    };
+
+    let err = chain.execute(HUB, "referrer", &register).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    chain.execute(HUB, "hub_owner", &register).unwrap();
+
+    let ReferralCodeResponse { code } = chain.query(
+        HUB,
+        &QueryMsg::RefferalCode {
+            referrer: "nominee".to_owned(),
+        },
+    );
+
+    check(code, expect!["2"]);
+
+    let err = chain.execute(HUB, "hub_owner", &register).unwrap_err();
+
+    check(err, expect!["[E0002] already registered"]);
+
+    // the hub owner issued the registration, but does not own a code
+    chain
+        .execute(HUB, "hub_owner", &ExecuteMsg::RegisterReferrer {})
+        .unwrap();
+
+    let ReferralCodeResponse { code } = chain.query(
+        HUB,
+        &QueryMsg::RefferalCode {
+            referrer: "hub_owner".to_owned(),
+        },
+    );
+
+    check(code, expect!["3"]);
+}
+
+#[test]
+fn repo_url_set_update_clear_works() {
+    let mut chain = chain_with_earnings();
+
+    let configure = |repo_url: Option<&str>, clear_repo_url| ExecuteMsg::ConfigureDapp {
+        dapp: "dapp".to_owned(),
+        name: None,
+        percent: None,
+        collector: None,
+        repo_url: repo_url.map(str::to_owned),
+        clear_repo_url,
+        allow_self_referral: None,
+        max_invocations_per_code: None,
+        confirm_no_dapp_share: None,
+    };
+
+    let repo_url = |chain: &Chain| {
+        let res: DappResponse = chain.query(
+            HUB,
+            &QueryMsg::Dapp {
+                dapp: "dapp".to_owned(),
+            },
+        );
+
+        res.repo_url
+    };
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &configure(Some("https://github.com/org/repo"), None),
+        )
+        .unwrap();
+
+    check(
+        pretty(&repo_url(&chain)),
+        expect![[r#"Some("https://github.com/org/repo")"#]],
+    );
+
+    chain
+        .execute(
+            HUB,
+            "collector",
+            &configure(Some("https://gitlab.com/org/repo"), None),
+        )
+        .unwrap();
+
+    check(
+        pretty(&repo_url(&chain)),
+        expect![[r#"Some("https://gitlab.com/org/repo")"#]],
+    );
+
+    let err = chain
+        .execute(
+            HUB,
+            "dapp",
+            &configure(Some("ftp://github.com/org/repo"), None),
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["invalid repo url - expected an http(s) url with a host"],
+    );
+
+    chain
+        .execute(HUB, "dapp", &configure(None, Some(true)))
+        .unwrap();
+
+    check(pretty(&repo_url(&chain)), expect!["None"]);
+}
+
+#[test]
+fn configure_dapps_is_all_or_nothing() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for (dapp, collector) in [
+        ("dapp_a", "collector"),
+        ("dapp_b", "collector"),
+        ("dapp_c", "collector_c"),
+    ] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.to_owned(),
+                    percent: 50,
+                    collector: collector.to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let update = |dapp: &str, percent| ConfigureDappEntry {
+        dapp: dapp.to_owned(),
+        name: None,
+        percent: Some(percent),
+        collector: None,
+        repo_url: None,
+        clear_repo_url: None,
+        allow_self_referral: None,
+        max_invocations_per_code: None,
+        confirm_no_dapp_share: None,
+    };
+
+    let percents = |chain: &Chain| {
+        ["dapp_a", "dapp_b", "dapp_c"]
+            .into_iter()
+            .map(|dapp| {
+                let res: DappResponse = chain.query(
+                    HUB,
+                    &QueryMsg::Dapp {
+                        dapp: dapp.to_owned(),
+                    },
+                );
+
+                res.percent
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // the collector of dApps a & b cannot configure dApp c, so neither are configured
+    let err = chain
+        .execute(
+            HUB,
+            "collector",
+            &ExecuteMsg::ConfigureDapps {
+                updates: vec![update("dapp_a", 20), update("dapp_c", 30)],
+            },
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - collector is not dApp dapp_c or it's collector"],
+    );
+
+    check(format!("{:?}", percents(&chain)), expect!["[50, 50, 50]"]);
+
+    chain
+        .execute(
+            HUB,
+            "collector",
+            &ExecuteMsg::ConfigureDapps {
+                updates: vec![update("dapp_a", 20), update("dapp_b", 30)],
+            },
+        )
+        .unwrap();
+
+    check(format!("{:?}", percents(&chain)), expect!["[20, 30, 50]"]);
+}
+
+#[test]
+fn current_fee_prefers_cached_fee() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    let fee = |chain: &Chain| {
+        let res: DappResponse = chain.query(
+            HUB,
+            &QueryMsg::Dapp {
+                dapp: "dapp".to_owned(),
+            },
+        );
+
+        pretty(&res.fee)
+    };
+
+    check(fee(&chain), expect!["None"]);
+
+    // without a fee set through the hub, the chain's flat fee is used
+    chain.set_flat_fee("dapp", 800);
+
+    check(fee(&chain), expect![[r#"Some("800")"#]]);
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::SetDappFee {
+                dapp: "dapp".to_owned(),
+                fee: Uint128::new(1000),
+            },
+        )
+        .unwrap();
+
+    chain.set_flat_fee("dapp", 300);
+
+    check(fee(&chain), expect![[r#"Some("1000")"#]]);
+
+    chain
+        .execute(HUB, "referrer", &ExecuteMsg::RegisterReferrer {})
+        .unwrap();
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: None,
+            },
+        )
+        .unwrap();
+
+    let res: ReferrerStatsResponse = chain.query(
+        HUB,
+        &QueryMsg::ReferrerStats {
+            code: 1,
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    // 75% of the cached fee
+    check(res.earnings, expect!["750"]);
+}
+
+#[test]
+fn repeated_reads_are_memoized_within_an_execution() {
+    let flat_fee_queries = Rc::new(Cell::new(0));
+
+    let counter = flat_fee_queries.clone();
+    let mut deps = archway_bindings::testing::mock_dependencies(move |q| {
+        if matches!(q, ArchwayQuery::FlatFee { .. }) {
+            counter.set(counter.get() + 1);
+        }
+
+        archway_query_handler(q, 1000)
+    });
+
+    deps.querier.update_wasm(wasm_query_handler);
+    deps.querier.update_staking("test", &[], &[]);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(deps, "referrer", ExecuteMsg::RegisterReferrer {});
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    let env = env!();
+    let mut deps = deps.as_mut();
+    let mut api = api::from_deps_mut(&mut deps, &env);
+    let dapp = Id::from("dapp");
+
+    api.set_rewards_pot(&dapp, Id::from("rewards_pot_0"))
+        .unwrap();
+
+    let before = flat_fee_queries.get();
+
+    for _ in 0..5 {
+        referral::record(&mut api, &dapp, None, code!(1)).unwrap();
+    }
+
+    assert_eq!(flat_fee_queries.get() - before, 1);
+
+    // values changed within the same execution replace the memoized ones
+    api.set_dapp_fee(dapp.clone(), nz!(2000)).unwrap();
+    api.set_percent(&dapp, nzp!(50)).unwrap();
+
+    assert_eq!(api.current_fee(&dapp).unwrap(), NonZeroU128::new(2000));
+    check(api.percent(&dapp).unwrap().unwrap().to_u8(), expect!["50"]);
+    assert_eq!(flat_fee_queries.get() - before, 1);
+}
+
+#[test]
+fn activate_with_fee_works() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    let res: DisplayResponse<(), PotInitMsg> = chain_exec_ok(
+        &mut chain,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: Some(Uint128::new(1000)),
+            confirm_no_dapp_share: None,
+        },
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Wasm(Instantiate2(
+                    code_id: 1,
+                    msg: (
+                      dapp: "dapp",
+                      withdraw_records_limit: None,
+                      min_withdraw_interval_secs: None,
+                      rewards_denom: Some("ucosm"),
+                    ),
+                    label: "referrals-reward-pot-dapp-1",
+                    salt: "x+fZ9+9FZb+rNnrTdR05dym0hsWvKJQdq8gQuMyZp/c=",
+                  )),
+                  reply_on: always,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let res: DappResponse = chain.query(
+        HUB,
+        &QueryMsg::Dapp {
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    // the fee is set on chain once the rewards pot is in place
+    check(pretty(&res.fee), expect![[r#"Some("1000")"#]]);
+}
+
+#[test]
+fn activate_with_zero_fee_fails() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    let err = chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: Some(Uint128::zero()),
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+    check(err, expect!["invalid fee - expected non-zero value"]);
+}
+
+#[test]
+fn invocation_cap_stops_earnings() {
+    let mut chain = chain_with_earnings();
+
+    chain
+        .execute(
+            HUB,
+            "collector",
+            &ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: None,
+                collector: None,
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: Some(2),
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    let record = ExecuteMsg::RecordReferral {
+        code: 1,
+        dapp: None,
+    };
+
+    // the second invocation reaches the cap & still earns
+    let res: DisplayResponse = chain_exec_ok(&mut chain, "dapp", record.clone());
+
+    check(pretty(&res.attributes), expect!["[]"]);
+
+    let res: DisplayResponse = chain_exec_ok(&mut chain, "dapp", record);
+
+    check(
+        pretty(&res.attributes),
+        expect![[r#"
+            [
+              (
+                key: "invocation_cap_reached",
+                value: "1",
+              ),
+            ]"#]],
+    );
+
+    let stats: ReferrerStatsResponse = chain.query(
+        HUB,
+        &QueryMsg::ReferrerStats {
+            code: 1,
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(stats.invocations, expect!["3"]);
+    check(stats.earnings, expect!["1500"]);
+}
+
+#[test]
+fn self_referral_forwarding_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 0));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse<ReferralCodeResponse> =
+        exec_ok!(deps, "referrer", ExecuteMsg::RegisterReferrer {});
+
+    let res: DisplayResponse<WithReferralCodeResponse<ReferralCodeResponse>, ExecuteMsg> = exec_ok!(
+        deps,
+        "another_referrer",
+        WithReferralCode {
+            referral_code: Some(1),
+            msg: ExecuteMsg::RegisterReferrer {}
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: Some((
+                referral_code: 1,
+                data: (
+                  code: 2,
+                ),
+              )),
+              messages: [
+                (
+                  id: 2,
+                  msg: Wasm(Execute(
+                    contract_addr: "referrals_hub",
+                    msg: record_referral(
+                      code: 1,
+                    ),
+                  )),
+                  reply_on: error,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let res: ReferralCodeResponse = query_ok!(
+        deps,
+        QueryMsg::RefferalCode {
+            referrer: "another_referrer".to_owned()
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+        (
+          code: 2,
+        )"#]],
+    );
+}
+
+#[test]
+fn wrapped_registration_credits_referral() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    let _: DisplayResponse<ReferralCodeResponse> =
+        chain_exec_ok(&mut chain, "referrer", ExecuteMsg::RegisterReferrer {});
+
+    let res: DisplayResponse<WithReferralCodeResponse<ReferralCodeResponse>, ExecuteMsg> = chain
+        .execute(
+            HUB,
+            "another_referrer",
+            &WithReferralCode {
+                referral_code: Some(1),
+                msg: ExecuteMsg::RegisterReferrer {},
+            },
+        )
+        .map(DisplayResponse::from)
+        .unwrap();
+
+    check(
+        pretty(&res.data),
+        expect![[r#"
+            Some((
+              referral_code: 1,
+              data: (
+                code: 2,
+              ),
+            ))"#]],
+    );
+
+    let stats: ReferrerStatsResponse = chain.query(
+        HUB,
+        &QueryMsg::ReferrerStats {
+            code: 1,
+            dapp: HUB.to_owned(),
+        },
+    );
+
+    check(stats.invocations, expect!["1"]);
+}
+
+#[test]
+fn failed_referral_credit_keeps_registration() {
+    let mut chain = Chain::default();
+
+    // without self-activation the hub can't record referrals of it's own
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: Some(false),
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    let _: DisplayResponse<ReferralCodeResponse> =
+        chain_exec_ok(&mut chain, "referrer", ExecuteMsg::RegisterReferrer {});
+
+    chain
+        .execute(
+            HUB,
+            "another_referrer",
+            &WithReferralCode {
+                referral_code: Some(1),
+                msg: ExecuteMsg::RegisterReferrer {},
+            },
+        )
+        .unwrap();
+
+    let res: ReferralCodeResponse = chain.query(
+        HUB,
+        &QueryMsg::RefferalCode {
+            referrer: "another_referrer".to_owned(),
+        },
+    );
+
+    check(res.code, expect!["2"]);
+}
+
+#[test]
+fn failed_referral_credit_is_reported() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 0));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse<ReferralCodeResponse> =
+        exec_ok!(deps, "referrer", ExecuteMsg::RegisterReferrer {});
+
+    let _: DisplayResponse<WithReferralCodeResponse<ReferralCodeResponse>, ExecuteMsg> = exec_ok!(
+        deps,
+        "another_referrer",
+        WithReferralCode {
+            referral_code: Some(1),
+            msg: ExecuteMsg::RegisterReferrer {}
+        }
+    );
+
+    let res: DisplayResponse = hub::reply(
+        deps.as_mut(),
+        env!(),
+        Reply {
+            id: ReplyId::CreditReferral.as_u64(),
+            result: SubMsgResult::Err("record failed".to_owned()),
+        },
+    )
+    .map(DisplayResponse::from)
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [],
+              attributes: [],
+              events: [
+                (
+                  type: "referral_credit_failed",
+                  attributes: [
+                    (
+                      key: "code",
+                      value: "1",
+                    ),
+                    (
+                      key: "error",
+                      value: "record failed",
+                    ),
+                  ],
+                ),
+              ],
+            )"#]],
+    );
+}
+
+#[test]
+fn self_referral_forwarding_to_own_code_fails() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 0));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: Some(true),
+        }
+    );
+
+    let res = exec_err!(
+        deps,
+        "referrer",
+        WithReferralCode {
+            referral_code: Some(1),
+            msg: ExecuteMsg::RegisterReferrer {}
+        }
+    );
+
+    check(res, expect!["[E0012] self referral"]);
+}
+
+#[test]
+fn all_dapps_only_active_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    for dapp in ["dapp_1", "dapp_2", "dapp_3"] {
+        deps.querier
+            .update_wasm(move |query| pot_query_handler(query, dapp));
+
+        let mut deps = deps.as_mut();
+        let env = env!();
+        let info = cosmwasm_std::testing::mock_info(dapp, &[]);
+
+        hub::execute(
+            deps.branch(),
+            env.clone(),
+            info,
+            WithReferralCode::from(ExecuteMsg::ActivateDapp {
+                name: dapp.to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            }),
+        )
+        .unwrap();
+
+        // Skip Instanitate Reply parsing and set rewards pot address directly
+        let mut api = api::from_deps_mut(&mut deps, &env);
+        hub_core::exec(
+            &mut api,
+            Msg {
+                sender: Id::from("referrals_hub"),
+                kind: Kind::Register(Registration::RewardsPot {
+                    dapp: Id::from(dapp),
+                    rewards_pot: Id::from("rewards_pot_0"),
+                }),
+            },
+        )
+        .unwrap();
+    }
+
+    let _: DisplayResponse = exec_ok!(
+        deps,
+        "dapp_2",
+        ExecuteMsg::DeactivateDapp {
+            dapp: "dapp_2".to_owned(),
+            rewards_admin: "rewards_admin".to_owned(),
+            rewards_recipient: "rewards_recipient".to_owned(),
+            mode: None,
+        }
+    );
+
+    let addresses = |res: AllDappsResponse| -> Vec<String> {
+        res.dapps.into_iter().map(|dapp| dapp.address).collect()
+    };
+
+    let res: AllDappsResponse = query_ok!(
+        deps,
+        QueryMsg::AllDapps {
+            start: None,
+            after: None,
+            limit: None,
+            only_active: None,
+        }
+    );
+
+    check(
+        pretty(&addresses(res)),
+        expect![[r#"
+            [
+              "dapp_1",
+              "dapp_2",
+              "dapp_3",
+            ]"#]],
+    );
+
+    let res: AllDappsResponse = query_ok!(
+        deps,
+        QueryMsg::AllDapps {
+            start: None,
+            after: None,
+            limit: None,
+            only_active: Some(true),
+        }
+    );
+
+    check(
+        pretty(&addresses(res)),
+        expect![[r#"
+            [
+              "dapp_1",
+              "dapp_3",
+            ]"#]],
+    );
+
+    let res: AllDappsResponse = query_ok!(
+        deps,
+        QueryMsg::AllDapps {
+            start: None,
+            after: None,
+            limit: Some(1),
+            only_active: Some(true),
+        }
+    );
+
+    check(
+        pretty(&addresses(res)),
+        expect![[r#"
+            [
+              "dapp_1",
+            ]"#]],
+    );
+
+    let res: AllDappsResponse = query_ok!(
+        deps,
+        QueryMsg::AllDapps {
+            start: Some(1),
+            after: None,
+            limit: Some(1),
+            only_active: Some(true),
+        }
+    );
+
+    check(
+        pretty(&addresses(res)),
+        expect![[r#"
+            [
+              "dapp_3",
+            ]"#]],
+    );
+}
+
+#[test]
+fn equal_names_sort_by_activation_index() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for (dapp, name) in [("dapp_1", "twin"), ("dapp_2", "alpha"), ("dapp_3", "twin")] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: name.to_owned(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let mut res: AllDappsResponse = chain.query(
+        HUB,
+        &QueryMsg::AllDapps {
+            start: None,
+            after: None,
+            limit: None,
+            only_active: None,
+        },
+    );
+
+    // reversed first so only the tie-break can restore the activation order of the twins
+    res.dapps.reverse();
+    res.dapps
+        .sort_by(|a, b| (&a.name, a.activation_index).cmp(&(&b.name, b.activation_index)));
+
+    let sorted: Vec<_> = res
+        .dapps
+        .into_iter()
+        .map(|dapp| format!("{} {}", dapp.activation_index, dapp.address))
+        .collect();
+
+    check(
+        pretty(&sorted),
+        expect![[r#"
+            [
+              "2 dapp_2",
+              "0 referrals_hub",
+              "1 dapp_1",
+              "3 dapp_3",
+            ]"#]],
+    );
+}
+
+#[test]
+fn instantiate_response_data_works() {
+    let mut chain = Chain::default();
+
+    let res = chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    let data: HubInstantiateResponse = cosmwasm_std::from_binary(&res.data.unwrap()).unwrap();
+
+    check(
+        pretty(&data),
+        expect![[r#"
+            (
+              address: "referrals_hub",
+              rewards_pot_code_id: 1,
+            )"#]],
+    );
+}
+
+#[test]
+fn all_dapps_total_ignores_limit() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for dapp in ["dapp_1", "dapp_2", "dapp_3"] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.to_owned(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let res: AllDappsResponse = chain.query(
+        HUB,
+        &QueryMsg::AllDapps {
+            start: Some(2),
+            after: None,
+            limit: Some(1),
+            only_active: None,
+        },
+    );
+
+    check(res.total, expect!["4"]);
+
+    let addresses: Vec<_> = res.dapps.into_iter().map(|dapp| dapp.address).collect();
+
+    check(
+        pretty(&addresses),
+        expect![[r#"
+            [
+              "dapp_2",
+            ]"#]],
+    );
+}
+
+#[test]
+fn all_dapps_cursor_pagination_works() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for dapp in ["dapp_1", "dapp_2", "dapp_3", "dapp_4", "dapp_5"] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.to_owned(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let mut after = None;
+    let mut pages = vec![];
+
+    loop {
+        let res: AllDappsResponse = chain.query(
+            HUB,
+            &QueryMsg::AllDapps {
+                start: None,
+                after: after.take(),
+                limit: Some(2),
+                only_active: None,
+            },
+        );
+
+        let addresses: Vec<_> = res.dapps.into_iter().map(|dapp| dapp.address).collect();
+
+        pages.push(addresses.join(","));
+
+        let Some(next_key) = res.next_key else {
+            break;
+        };
+
+        after = Some(next_key);
+    }
+
+    // the hub activates itself as the first dApp
+    check(
+        pretty(&pages),
+        expect![[r#"
+            [
+              "referrals_hub,dapp_1",
+              "dapp_2,dapp_3",
+              "dapp_4,dapp_5",
+              "",
+            ]"#]],
+    );
+}
+
+#[test]
+fn all_dapps_limit_is_capped() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for n in 1..=50 {
+        let dapp = format!("dapp_{n}");
+
+        chain.set_rewards_owner(&dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                &dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.clone(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    for limit in [None, Some(100)] {
+        let res: AllDappsResponse = chain.query(
+            HUB,
+            &QueryMsg::AllDapps {
+                start: None,
+                after: None,
+                limit,
+                only_active: None,
+            },
+        );
+
+        check(
+            format!("{} of {}", res.dapps.len(), res.total),
+            expect!["30 of 51"],
+        );
+
+        // the hub activates itself as the first dApp
+        check(pretty(&res.next_key), expect![[r#"Some("dapp_29")"#]]);
+    }
+}
+
+#[test]
+fn all_dapps_paging_boundaries() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for dapp in ["dapp_1", "dapp_2", "dapp_3"] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.to_owned(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let page = |start, limit| {
+        let res: AllDappsResponse = chain.query(
+            HUB,
+            &QueryMsg::AllDapps {
+                start,
+                after: None,
+                limit,
+                only_active: None,
+            },
+        );
+
+        let addresses: Vec<_> = res.dapps.into_iter().map(|dapp| dapp.address).collect();
+
+        format!("{addresses:?} next: {:?}", res.next_key)
+    };
+
+    // `limit` is the number of dApps returned, the hub being the first at index 0
+    check(
+        page(None, Some(2)),
+        expect![[r#"["referrals_hub", "dapp_1"] next: Some("dapp_1")"#]],
+    );
+    check(page(None, Some(0)), expect!["[] next: None"]);
+
+    // the last dApp ends a partial page
+    check(
+        page(Some(3), Some(10)),
+        expect![[r#"["dapp_3"] next: None"#]],
+    );
+
+    // past the last dApp is an empty page rather than an error
+    check(page(Some(4), Some(10)), expect!["[] next: None"]);
+    check(page(Some(u64::MAX), None), expect!["[] next: None"]);
+}
+
+#[test]
+fn all_dapps_only_active_scan_is_capped() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    let dapps: Vec<_> = (1..=100)
+        .map(|n| format!("dapp_{n}"))
+        .chain(["dapp_active".to_owned()])
+        .collect();
+
+    for dapp in &dapps {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.clone(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    for dapp in &dapps[..100] {
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::DeactivateDapp {
+                    dapp: dapp.clone(),
+                    rewards_admin: "rewards_admin".to_owned(),
+                    rewards_recipient: "rewards_recipient".to_owned(),
+                    mode: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let page = |after| {
+        let res: AllDappsResponse = chain.query(
+            HUB,
+            &QueryMsg::AllDapps {
+                start: None,
+                after,
+                limit: Some(2),
+                only_active: Some(true),
+            },
+        );
+
+        let addresses: Vec<_> = res.dapps.into_iter().map(|dapp| dapp.address).collect();
+
+        (
+            format!("{addresses:?} next: {:?}", res.next_key),
+            res.next_key,
+        )
+    };
+
+    // the scan stops at the cap with the page short, the hub being the only active dApp read
+    let (res, next_key) = page(None);
+
+    check(res, expect![[r#"["referrals_hub"] next: Some("dapp_99")"#]]);
+
+    let (res, _) = page(next_key);
+
+    check(res, expect![[r#"["dapp_active"] next: None"#]]);
+}
+
+#[test]
+fn all_dapps_reports_unreadable_dapps() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for dapp in ["dapp_1", "dapp_2", "dapp_3"] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.to_owned(),
+                    percent: 75,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let pot: RewardsPotAddressResponse = chain.query(
+        HUB,
+        &QueryMsg::RewardsPotAddress {
+            dapp: "dapp_2".to_owned(),
+        },
+    );
+
+    chain.remove_contract(&pot.address);
+
+    let all_dapps = |limit| QueryMsg::AllDapps {
+        start: None,
+        after: None,
+        limit,
+        only_active: None,
+    };
+
+    let res: AllDappsResponse = chain.query(HUB, &all_dapps(None));
+
+    let addresses: Vec<_> = res.dapps.iter().map(|dapp| dapp.address.as_str()).collect();
+
+    check(
+        pretty(&addresses),
+        expect![[r#"
+            [
+              "referrals_hub",
+              "dapp_1",
+              "dapp_3",
+            ]"#]],
+    );
+
+    check(
+        pretty(&res.errors),
+        expect![[r#"
+            [
+              (
+                dapp: "dapp_2",
+                error: "Generic error: Querier system error: No such contract: rewards_pot_2",
+              ),
+            ]"#]],
+    );
+
+    // the unreadable dApp still fills it's place in a page
+    let res: AllDappsResponse = chain.query(HUB, &all_dapps(Some(3)));
+
+    check(pretty(&res.next_key), expect![[r#"Some("dapp_2")"#]]);
+}
+
+#[test]
+fn rewards_pot_init_failure_rolls_back_activation() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let activate = |fee: Option<Uint128>| ExecuteMsg::ActivateDapp {
+        name: "dapp".to_owned(),
+        percent: 75,
+        collector: "collector".to_owned(),
+        fee,
+        confirm_no_dapp_share: None,
+    };
+
+    let res: DisplayResponse<(), PotInitMsg> =
+        exec_ok!(deps, "dapp", activate(Some(Uint128::new(500))));
+
+    // the fee waits on the rewards pot, only it's instantiation is sent
+    check(res.messages.len(), expect!["1"]);
+
+    let res: DisplayResponse = hub::reply(
+        deps.as_mut(),
+        env!(),
+        Reply {
+            id: 0,
+            result: SubMsgResult::Err("instantiate failed".to_owned()),
+        },
+    )
+    .map(DisplayResponse::from)
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [],
+              attributes: [],
+              events: [
+                (
+                  type: "rewards_pot_init_failed",
+                  attributes: [
+                    (
+                      key: "dapp",
+                      value: "dapp",
+                    ),
+                    (
+                      key: "error",
+                      value: "instantiate failed",
+                    ),
+                  ],
+                ),
+              ],
+            )"#]],
+    );
+
+    let res: TotalsResponse = query_ok!(deps, QueryMsg::Totals {});
+
+    check(res.active_dapps, expect!["0"]);
+
+    // the fee of the rolled back activation is dropped, leaving the chain's flat fee
+    let env = env!();
+    let api = api::from_deps(deps.as_ref(), &env);
+
+    check(
+        api.current_fee(&Id::from("dapp")).unwrap().unwrap(),
+        expect!["1000"],
+    );
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(deps, "dapp", activate(None));
+
+    let res: TotalsResponse = query_ok!(deps, QueryMsg::Totals {});
+
+    check(res.active_dapps, expect!["1"]);
+}
+
+#[test]
+fn init_with_blank_rewards_denom_fails() {
+    let init = |denom: &str| {
+        let mut deps =
+            archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+        hub::init(
+            deps.as_mut(),
+            env!(),
+            info!("hub_owner"),
+            InstantiateMsg {
+                contract_premium: 1000u128.into(),
+                rewards_pot_code_id: 1,
+                rewards_denom: denom.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap_err()
+    };
+
+    check(
+        init(""),
+        expect![[r#"invalid rewards denom - "" is blank"#]],
+    );
+    check(
+        init("  "),
+        expect![[r#"invalid rewards denom - "  " is blank"#]],
+    );
+}
+
+#[test]
+fn set_dapp_fee_without_rewards_denom_fails() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    // a hub instantiated before it's rewards denom was configured
+    deps.storage
+        .remove(b"referrals_archway_api::cache::rewards_denom");
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    let res = exec_err!(
+        deps,
+        "dapp",
+        ExecuteMsg::SetDappFee {
+            dapp: "dapp".to_owned(),
+            fee: Uint128::new(1000),
+        }
+    );
+
+    check(res, expect!["rewards denom has not been configured"]);
+}
+
+#[test]
+fn set_dapp_fee_uses_configured_rewards_denom() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: "uarch".to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    // a fee is only set once the rewards pot is in place, so register it as the reply would
+    {
+        let env = env!();
+        let mut deps = deps.as_mut();
+        let mut api = api::from_deps_mut(&mut deps, &env);
+        hub_core::exec(
+            &mut api,
+            Msg {
+                sender: Id::from("referrals_hub"),
+                kind: Kind::Register(Registration::RewardsPot {
+                    dapp: Id::from("dapp"),
+                    rewards_pot: Id::from("rewards_pot_0"),
+                }),
+            },
+        )
+        .unwrap();
+    }
+
+    let res: DisplayResponse = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::SetDappFee {
+            dapp: "dapp".to_owned(),
+            fee: Uint128::new(1000),
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(custom(set_flat_fee(
+                    contract_address: Some("dapp"),
+                    flat_fee_amount: (
+                      denom: "uarch",
+                      amount: "1000",
+                    ),
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn substituted_rewards_pot_rejected() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner("dapp", HUB);
+
+    let res: RewardsPotAddressResponse = chain.query(
+        HUB,
+        &QueryMsg::RewardsPotAddress {
+            dapp: "dapp".to_owned(),
+        },
+    );
+
+    check(res.address, expect!["rewards_pot_1"]);
+
+    chain.substitute_next_pot("malicious_pot");
+
+    let err = chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 75,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+    check(
+        err,
+        expect![
+            "invalid reply - rewards pot instantiated at malicious_pot, expected rewards_pot_1"
+        ],
+    );
+}
+
+#[test]
+fn rewards_pot_for_other_dapp_rejected() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier
+        .update_wasm(|query| pot_query_handler(query, "other_dapp"));
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    let env = env!();
+    let mut deps = deps.as_mut();
+    let mut api = api::from_deps_mut(&mut deps, &env);
+
+    let err = hub_core::exec(
+        &mut api,
+        Msg {
+            sender: Id::from("referrals_hub"),
+            kind: Kind::Register(Registration::RewardsPot {
+                dapp: Id::from("dapp"),
+                rewards_pot: Id::from("rewards_pot_0"),
+            }),
+        },
+    )
+    .unwrap_err();
+
+    check(err, expect!["[E0009] rewards pot belongs to another dApp"]);
+
+    // the dApp is left without a rewards pot rather than one that answers for another dApp
+    assert!(!api.has_rewards_pot(&Id::from("dapp")).unwrap());
+}
+
+#[test]
+fn handover_pots_works() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    for dapp in ["dapp_a", "dapp_b"] {
+        chain.set_rewards_owner(dapp, HUB);
+
+        chain
+            .execute(
+                HUB,
+                dapp,
+                &ExecuteMsg::ActivateDapp {
+                    name: dapp.to_owned(),
+                    percent: 50,
+                    collector: dapp.replace("dapp", "collector"),
+                    fee: None,
+                    confirm_no_dapp_share: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let handover = ExecuteMsg::HandoverPots {
+        dapps: vec!["dapp_a".to_owned(), "dapp_b".to_owned()],
+        new_admin: "new_hub".to_owned(),
+    };
+
+    let err = chain.execute(HUB, "dapp_a", &handover).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - dapp_a is not the hub or it's collector"],
+    );
+
+    let res = chain.execute(HUB, "hub_owner", &handover).unwrap();
+
+    // one update per listed dApp, the hub's own pot is left as is
+    check(res.messages.len(), expect!["3"]);
+
+    for (dapp, expected_admin) in [("dapp_a", "new_hub"), ("dapp_b", "new_hub"), (HUB, HUB)] {
+        let RewardsPotAddressResponse { address } = chain.query(
+            HUB,
+            &QueryMsg::RewardsPotAddress {
+                dapp: dapp.to_owned(),
+            },
+        );
+
+        let AdminResponse { admin } = chain.query(&address, &PotQueryMsg::Admin {});
+
+        assert_eq!(admin, expected_admin, "{dapp} pot admin");
+    }
+
+    // the pots no longer answer to the previous hub
+    let err = chain.execute(HUB, "hub_owner", &handover).unwrap_err();
+
+    check(err, expect!["unauthorized"]);
+}
+
+#[test]
+fn handover_pots_without_rewards_pot_fails() {
+    let mut chain = chain_with_earnings();
+
+    let err = chain
+        .execute(
+            HUB,
+            "hub_owner",
+            &ExecuteMsg::HandoverPots {
+                dapps: vec!["dapp".to_owned(), "unknown".to_owned()],
+                new_admin: "new_hub".to_owned(),
+            },
+        )
+        .unwrap_err();
+
+    check(err, expect!["[E0016] rewards pot not set - unknown"]);
+}
+
+#[test]
+fn hub_config_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let res: HubConfigResponse = query_ok!(deps, QueryMsg::HubConfig {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              rewards_pot_code_id: 1,
+              rewards_pot_count: 0,
+              contract_premium: "1000",
+              collector: None,
+              enabled_features: (
+                register: true,
+                record: true,
+                collect: true,
+                configure: true,
+              ),
+              min_collect_amount: None,
+              allow_self_dapp: true,
+            )"#]],
+    );
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    // Skip Instanitate Reply parsing and set rewards pot address directly
+    {
+        let env = env!();
+        let mut deps = deps.as_mut();
+        let mut api = api::from_deps_mut(&mut deps, &env);
+        hub_core::exec(
+            &mut api,
+            Msg {
+                sender: Id::from("referrals_hub"),
+                kind: Kind::Register(Registration::RewardsPot {
+                    dapp: Id::from("dapp"),
+                    rewards_pot: Id::from("rewards_pot_0"),
+                }),
+            },
+        )
+        .unwrap();
+    }
+
+    let res: HubConfigResponse = query_ok!(deps, QueryMsg::HubConfig {});
+
+    check(res.rewards_pot_count, expect!["1"]);
+}
+
+#[test]
+fn hub_config_after_instantiation_works() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: None,
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    let res: HubConfigResponse = chain.query(HUB, &QueryMsg::HubConfig {});
+
+    // the hub's own rewards pot is created as it activates itself
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              rewards_pot_code_id: 1,
+              rewards_pot_count: 1,
+              contract_premium: "1000",
+              collector: Some("hub_owner"),
+              enabled_features: (
+                register: true,
+                record: true,
+                collect: true,
+                configure: true,
+              ),
+              min_collect_amount: None,
+              allow_self_dapp: true,
+            )"#]],
+    );
+}
+
+#[test]
+fn instantiate_without_self_activation_works() {
+    let mut chain = Chain::default();
+
+    let res = chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: Some(false),
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    assert!(res.messages.is_empty());
+
+    let res: TotalDappsResponse = chain.query(HUB, &QueryMsg::TotalDapps {});
+
+    check(res.total, expect!["0"]);
+
+    // the sender is still the hub's collector, able to administer it
+    chain
+        .execute(
+            HUB,
+            "hub_owner",
+            &ExecuteMsg::SetEnabledFeatures {
+                register: true,
+                record: true,
+                collect: true,
+                configure: false,
+            },
+        )
+        .unwrap();
+
+    let res: HubConfigResponse = chain.query(HUB, &QueryMsg::HubConfig {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              rewards_pot_code_id: 1,
+              rewards_pot_count: 0,
+              contract_premium: "1000",
+              collector: Some("hub_owner"),
+              enabled_features: (
+                register: true,
+                record: true,
+                collect: true,
+                configure: false,
+              ),
+              min_collect_amount: None,
+              allow_self_dapp: true,
+            )"#]],
+    );
+}
+
+#[test]
+fn init_skips_activation_when_already_activated() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let msg = InstantiateMsg {
+        contract_premium: 1000u128.into(),
+        rewards_pot_code_id: 1,
+        rewards_denom: DENOM.to_owned(),
+        self_activate: None,
+        block_self_referral: None,
+    };
+
+    let res: DisplayResponse<(), ExecuteMsg> = init_ok!(deps, "hub_owner", msg.clone());
+
+    check(res.messages.len(), expect!["3"]);
+
+    // apply the self-activation issued by init
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::ActivateDapp {
+            name: "referrals_hub".to_owned(),
+            percent: 100,
+            collector: "hub_owner".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: Some(true),
+        }
+    );
+
+    let res: DisplayResponse<(), ExecuteMsg> = init_ok!(deps, "hub_owner", msg);
+
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn deactivate_relinquishes_rewards_admin_after_withdrawal() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let _: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    // Skip Instanitate Reply parsing and set rewards pot address directly
+    {
+        let env = env!();
+        let mut deps = deps.as_mut();
+        let mut api = api::from_deps_mut(&mut deps, &env);
+        hub_core::exec(
+            &mut api,
+            Msg {
+                sender: Id::from("referrals_hub"),
+                kind: Kind::Register(Registration::RewardsPot {
+                    dapp: Id::from("dapp"),
+                    rewards_pot: Id::from("rewards_pot_0"),
+                }),
+            },
+        )
+        .unwrap();
+    }
+
+    let res: DisplayResponse<(), PotExecuteMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::DeactivateDapp {
+            dapp: "dapp".to_owned(),
+            rewards_admin: "rewards_admin".to_owned(),
+            rewards_recipient: "rewards_recipient".to_owned(),
+            mode: None,
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 1,
+                  msg: Wasm(Execute(
+                    contract_addr: "rewards_pot_0",
+                    msg: withdraw_rewards(),
+                  )),
+                  reply_on: success,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let res: DisplayResponse = hub::reply(
+        deps.as_mut(),
+        env!(),
+        Reply {
+            id: ReplyId::WithdrawRewards.as_u64(),
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .map(DisplayResponse::from)
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(custom(update_contract_metadata(
+                    contract_address: Some("dapp"),
+                    owner_address: None,
+                    rewards_address: Some("rewards_recipient"),
+                  ))),
+                  reply_on: never,
+                ),
+                (
+                  id: 0,
+                  msg: Std(custom(update_contract_metadata(
+                    contract_address: Some("dapp"),
+                    owner_address: Some("rewards_admin"),
+                    rewards_address: None,
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn rewards_pot_label_sanitizes_dapp_name() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    deps.querier.update_wasm(wasm_query_handler);
+
+    let _: DisplayResponse<(), ExecuteMsg> = init_ok!(
+        deps,
+        "hub_owner",
+        InstantiateMsg {
+            contract_premium: 1000u128.into(),
+            rewards_pot_code_id: 1,
+            rewards_denom: DENOM.to_owned(),
+            self_activate: None,
+            block_self_referral: None,
+        }
+    );
+
+    let res: DisplayResponse<(), PotInitMsg> = exec_ok!(
+        deps,
+        "dapp",
+        ExecuteMsg::ActivateDapp {
+            name: "My dApp".to_owned(),
+            percent: 75,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Wasm(Instantiate2(
+                    code_id: 1,
+                    msg: (
+                      dapp: "dapp",
+                      withdraw_records_limit: None,
+                      min_withdraw_interval_secs: None,
+                      rewards_denom: Some("ucosm"),
+                    ),
+                    label: "referrals-reward-pot-My-dApp-0",
+                    salt: "x+fZ9+9FZb+rNnrTdR05dym0hsWvKJQdq8gQuMyZp/c=",
+                  )),
+                  reply_on: always,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn unknown_reply_id_fails() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, 1000));
+
+    let err = hub::reply(
+        deps.as_mut(),
+        env!(),
+        Reply {
+            id: 7,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap_err();
+
+    check(err, expect!["unknown reply id - 7"]);
+}
+
+#[test]
+fn simulate_record_referral_commits_nothing() {
+    let chain = chain_with_earnings();
+
+    let stats_query = QueryMsg::ReferrerStats {
+        code: 1,
+        dapp: "dapp".to_owned(),
+    };
+
+    let before: ReferrerStatsResponse = chain.query(HUB, &stats_query);
+
+    let res: SimulateResponse = chain.query(
+        HUB,
+        &QueryMsg::Simulate {
+            msg: ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: None,
+            },
+            sender: "dapp".to_owned(),
+        },
+    );
+
+    check(
+        String::from_utf8(res.reply.to_vec()).unwrap(),
+        expect![[r#""Empty""#]],
+    );
+
+    assert!(res.writes.iter().any(|write| write.value.is_some()));
+
+    let after: ReferrerStatsResponse = chain.query(HUB, &stats_query);
+
+    check(after.invocations, expect!["1"]);
+    assert_eq!(after, before);
+}
+
+fn export_pages(chain: &Chain, section: ExportSection) -> Vec<String> {
+    let mut start_after = None;
+    let mut pages = vec![];
+
+    loop {
+        let res: ExportStateResponse = chain.query(
+            HUB,
+            &QueryMsg::ExportState {
+                section,
+                start_after: start_after.take(),
+                limit: 2,
+            },
+        );
+
+        let records: Vec<_> = res
+            .records
+            .into_iter()
+            .map(|record| match record {
+                ExportRecord::Dapp(d) => format!("{}#{}", d.address, d.activation_index),
+                ExportRecord::Code(c) => {
+                    format!("{}:{}={}", c.code, c.owner.unwrap(), c.total_earnings)
+                }
+                ExportRecord::Earnings(e) => {
+                    format!("{}:{}={}/{}", e.code, e.dapp, e.earnings, e.collected)
+                }
+                ExportRecord::Collections(c) => {
+                    format!("{}={}/{}", c.dapp, c.contributions, c.referrers_collected)
+                }
+            })
+            .collect();
+
+        pages.push(records.join(","));
+
+        let Some(next_key) = res.next_key else {
+            break;
+        };
+
+        start_after = Some(next_key);
+    }
+
+    pages
+}
+
+#[test]
+fn export_state_pages_through_sections() {
+    let mut chain = chain_with_earnings();
+
+    for referrer in ["referrer_2", "referrer_3"] {
+        chain
+            .execute(HUB, referrer, &ExecuteMsg::RegisterReferrer {})
+            .unwrap();
+    }
+
+    for code in [2, 3] {
+        chain
+            .execute(
+                HUB,
+                "dapp",
+                &ExecuteMsg::RecordReferral { code, dapp: None },
+            )
+            .unwrap();
+    }
+
+    chain.mint("referrer", 1500);
+
+    chain
+        .execute_with_funds(
+            HUB,
+            "referrer",
+            &ExecuteMsg::CollectReferrer {
+                code: 1,
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+            1500,
+        )
+        .unwrap();
+
+    let sections = [
+        ExportSection::Dapps,
+        ExportSection::Codes,
+        ExportSection::Earnings,
+        ExportSection::Collections,
+    ];
+
+    let pages: Vec<_> = sections
+        .into_iter()
+        .map(|section| export_pages(&chain, section))
+        .collect();
+
+    // the hub activates itself as the first dApp
+    check(
+        pretty(&pages),
+        expect![[r#"
+            [
+              [
+                "referrals_hub#0,dapp#1",
+                "",
+              ],
+              [
+                "1:referrer=750,2:referrer_2=750",
+                "3:referrer_3=750",
+              ],
+              [
+                "1:dapp=750/750,2:dapp=750/0",
+                "3:dapp=750/0",
+              ],
+              [
+                "referrals_hub=0/0,dapp=2250/750",
+                "",
+              ],
+            ]"#]],
+    );
+}
+
+#[test]
+fn export_state_invalid_key_fails() {
+    let chain = chain_with_earnings();
+
+    let err = chain.query_err(
+        HUB,
+        &QueryMsg::ExportState {
+            section: ExportSection::Earnings,
+            start_after: Some(Binary::from(1u64.to_be_bytes())),
+            limit: 10,
+        },
+    );
+
+    check(err, expect!["[E0015] invalid pagination cursor"]);
+}
+
+#[test]
+fn hub_activates_itself_only_when_allowed() {
+    let mut chain = Chain::default();
+
+    chain
+        .instantiate_hub(
+            "hub_owner",
+            &InstantiateMsg {
+                contract_premium: PREMIUM.into(),
+                rewards_pot_code_id: REWARDS_POT_CODE_ID,
+                rewards_denom: DENOM.to_owned(),
+                self_activate: Some(false),
+                block_self_referral: None,
+            },
+        )
+        .unwrap();
+
+    chain.set_rewards_owner(HUB, HUB);
+
+    let allow = |allowed| ExecuteMsg::SetAllowSelfDapp { allowed };
+
+    let activate = ExecuteMsg::ActivateDapp {
+        name: "hub".to_owned(),
+        percent: 50,
+        collector: "hub_owner".to_owned(),
+        fee: None,
+        confirm_no_dapp_share: None,
+    };
+
+    let err = chain.execute(HUB, "referrer", &allow(false)).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0001] unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    chain.execute(HUB, "hub_owner", &allow(false)).unwrap();
+
+    let res: HubConfigResponse = chain.query(HUB, &QueryMsg::HubConfig {});
+
+    check(res.allow_self_dapp, expect!["false"]);
+
+    let err = chain.execute(HUB, HUB, &activate).unwrap_err();
+
+    check(
+        err,
+        expect!["[E0019] self registration - the hub may not activate itself as a dApp"],
+    );
+
+    // other dApps are unaffected
+    chain.set_rewards_owner("dapp", HUB);
+
+    chain
+        .execute(
+            HUB,
+            "dapp",
+            &ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 50,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+
+    chain.execute(HUB, "hub_owner", &allow(true)).unwrap();
+
+    chain.execute(HUB, HUB, &activate).unwrap();
+
+    let res: TotalDappsResponse = chain.query(HUB, &QueryMsg::TotalDapps {});
+
+    check(res.total, expect!["2"]);
+}
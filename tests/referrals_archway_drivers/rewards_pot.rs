@@ -3,12 +3,16 @@ use archway_bindings::types::rewards::{
 };
 use archway_bindings::{testing::MockDepsExt, ArchwayQuery, PageResponse};
 use cosmwasm_std::{
-    coins, to_binary, Addr, ContractResult, QueryResponse, SubMsgResponse, SubMsgResult, Uint128,
+    coins, to_binary, Addr, Binary, Coin, ContractResult, QueryResponse, SubMsgResponse,
+    SubMsgResult, Uint128,
 };
+use referrals_archway_api::rewards_pot as api;
+use referrals_archway_api::ReplyId;
 use referrals_archway_drivers::rewards_pot;
 use referrals_archway_drivers::rewards_pot::{ExecuteMsg, InstantiateMsg, QueryMsg};
 use referrals_cw::rewards_pot::{
-    AdminResponse, DappResponse, InstantiateResponse, TotalRewardsResponse,
+    AdminResponse, DappResponse, InstantiateResponse, OutstandingRecordsResponse,
+    RewardsBreakdownResponse, TotalRewardsResponse,
 };
 
 use crate::{check, expect, pretty};
@@ -21,9 +25,14 @@ pub fn archway_query_handler(
 ) -> ContractResult<QueryResponse> {
     let response = match query {
         ArchwayQuery::RewardsRecords { pagination, .. } => {
-            let records = pagination.clone().map_or(records.to_vec(), |p| {
-                let iter = records.iter().cloned();
+            let total = records.len();
+
+            // the page key is the big-endian index of the next record to serve
+            let (records, next_key) = pagination.clone().map_or((records.to_vec(), None), |p| {
                 let reverse = matches!(p.reverse, Some(true));
+                let start = p.key.map_or(0, |key| {
+                    usize::try_from(u64::from_be_bytes(key.to_vec().try_into().unwrap())).unwrap()
+                });
                 let limit = p
                     .limit
                     .and_then(|n| (n > 0).then_some(n))
@@ -31,22 +40,25 @@ pub fn archway_query_handler(
                     .transpose()
                     .unwrap();
 
-                match (reverse, limit) {
-                    (true, None) => iter.rev().collect(),
-                    (true, Some(n)) => iter.rev().take(n).collect(),
+                let ordered: Vec<_> = if reverse {
+                    records.iter().rev().cloned().collect()
+                } else {
+                    records.to_vec()
+                };
 
-                    (false, None) => iter.collect(),
-                    (false, Some(n)) => iter.take(n).collect(),
-                }
-            });
+                let end = limit.map_or(total, |n| (start + n).min(total));
 
-            let len = records.len();
+                let next_key =
+                    (end < total).then(|| Binary::from((end as u64).to_be_bytes().to_vec()));
+
+                (ordered[start..end].to_vec(), next_key)
+            });
 
             to_binary(&RewardsRecordsResponse {
                 records,
                 pagination: Some(PageResponse {
-                    next_key: None,
-                    total: Some(len as _),
+                    next_key,
+                    total: Some(total as _),
                 }),
             })
         }
@@ -102,7 +114,7 @@ macro_rules! exec_err {
 macro_rules! _reply {
     ($deps:ident, $msg:expr) => {{
         let reply = cosmwasm_std::Reply {
-            id: 0,
+            id: ReplyId::WithdrawRewards.as_u64(),
             result: SubMsgResult::Ok(SubMsgResponse {
                 events: vec![],
                 data: Some(cosmwasm_std::to_binary(&$msg).unwrap()),
@@ -156,11 +168,17 @@ fn plumbing_works() {
 
     deps.querier.update_staking("ucosm", &[], &[]);
 
+    deps.querier
+        .update_balance("rewards_pot", coins(3000, "ucosm"));
+
     let res: DisplayResponse<InstantiateResponse> = init_ok!(
         deps,
         "referrals_hub",
         InstantiateMsg {
-            dapp: "dapp".to_owned()
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
         }
     );
 
@@ -213,7 +231,7 @@ fn plumbing_works() {
               data: None,
               messages: [
                 (
-                  id: 0,
+                  id: 1,
                   msg: Std(custom(withdraw_rewards(
                     records_limit: Some(3),
                     record_ids: [],
@@ -251,6 +269,8 @@ fn plumbing_works() {
         ExecuteMsg::DistributeRewards {
             recipient: "collector".to_owned(),
             amount: Uint128::new(1000),
+            clamp: None,
+            denom: None,
         }
     );
 
@@ -341,7 +361,10 @@ fn non_admin_exec_fails() {
         deps,
         "referrals_hub",
         InstantiateMsg {
-            dapp: "dapp".to_owned()
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
         }
     );
 
@@ -355,8 +378,942 @@ fn non_admin_exec_fails() {
         ExecuteMsg::DistributeRewards {
             recipient: "collector".to_owned(),
             amount: Uint128::new(1000),
+            clamp: None,
+            denom: None,
+        }
+    );
+
+    check(res, expect!["unauthorized"]);
+}
+
+#[test]
+fn distribute_exceeding_balance_fails() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    deps.querier.update_staking("ucosm", &[], &[]);
+
+    deps.querier
+        .update_balance("rewards_pot", coins(500, "ucosm"));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let res = exec_err!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::DistributeRewards {
+            recipient: "collector".to_owned(),
+            amount: Uint128::new(1000),
+            clamp: None,
+            denom: None,
+        }
+    );
+
+    check(
+        res,
+        expect!["insufficient rewards pot balance - requested 1000, available 500"],
+    );
+}
+
+#[test]
+fn distribute_clamped_to_balance_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    deps.querier.update_staking("ucosm", &[], &[]);
+
+    deps.querier
+        .update_balance("rewards_pot", coins(500, "ucosm"));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let res: DisplayResponse = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::DistributeRewards {
+            recipient: "collector".to_owned(),
+            amount: Uint128::new(1000),
+            clamp: Some(true),
+            denom: None,
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "collector",
+                    amount: [
+                      (
+                        denom: "ucosm",
+                        amount: "500",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn distribute_defaults_to_configured_denom() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    deps.querier
+        .update_balance("rewards_pot", coins(500, "uarch"));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: Some("uarch".to_owned()),
+        }
+    );
+
+    let res: DisplayResponse = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::DistributeRewards {
+            recipient: "collector".to_owned(),
+            amount: Uint128::new(500),
+            clamp: None,
+            denom: None,
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "collector",
+                    amount: [
+                      (
+                        denom: "uarch",
+                        amount: "500",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn sweep_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    deps.querier.update_staking("ucosm", &[], &[]);
+
+    deps.querier.update_balance(
+        "rewards_pot",
+        vec![Coin::new(3000, "ucosm"), Coin::new(700, "uatom")],
+    );
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let res = exec_err!(
+        deps,
+        "bob",
+        ExecuteMsg::Sweep {
+            denom: "uatom".to_owned(),
+            recipient: "bob".to_owned(),
+        }
+    );
+
+    check(res, expect!["unauthorized"]);
+
+    let res: DisplayResponse = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::Sweep {
+            denom: "uatom".to_owned(),
+            recipient: "recovery".to_owned(),
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "recovery",
+                    amount: [
+                      (
+                        denom: "uatom",
+                        amount: "700",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn update_admin_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
         }
     );
 
+    let update_admin = || ExecuteMsg::UpdateAdmin {
+        new_admin: "new_hub".to_owned(),
+    };
+
+    let res = exec_err!(deps, "bob", update_admin());
+
+    check(res, expect!["unauthorized"]);
+
+    let res: DisplayResponse = exec_ok!(deps, "referrals_hub", update_admin());
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [],
+              attributes: [],
+              events: [
+                (
+                  type: "update_admin",
+                  attributes: [
+                    (
+                      key: "new_admin",
+                      value: "new_hub",
+                    ),
+                  ],
+                ),
+              ],
+            )"#]],
+    );
+
+    let res: AdminResponse = query_ok!(deps, QueryMsg::Admin {});
+
+    check(res.admin, expect!["new_hub"]);
+
+    // the previous admin has relinquished all rights over the pot
+    let res = exec_err!(deps, "referrals_hub", update_admin());
+
     check(res, expect!["unauthorized"]);
+
+    let _: DisplayResponse = exec_ok!(deps, "new_hub", ExecuteMsg::WithdrawRewards {});
+}
+
+#[test]
+fn sweep_protects_undistributed_rewards() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    deps.querier.update_staking("ucosm", &[], &[]);
+
+    deps.querier
+        .update_balance("rewards_pot", coins(3000, "ucosm"));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 3,
+            total_rewards: cosmwasm_std::coins(3000, "ucosm")
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::DistributeRewards {
+            recipient: "collector".to_owned(),
+            amount: Uint128::new(1000),
+            clamp: None,
+            denom: None,
+        }
+    );
+
+    // 2000 undistributed plus 500 sent directly to the pot
+    deps.querier
+        .update_balance("rewards_pot", coins(2500, "ucosm"));
+
+    let res: DisplayResponse = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::Sweep {
+            denom: "ucosm".to_owned(),
+            recipient: "recovery".to_owned(),
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "recovery",
+                    amount: [
+                      (
+                        denom: "ucosm",
+                        amount: "500",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    deps.querier
+        .update_balance("rewards_pot", coins(2000, "ucosm"));
+
+    let res = exec_err!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::Sweep {
+            denom: "ucosm".to_owned(),
+            recipient: "recovery".to_owned(),
+        }
+    );
+
+    check(res, expect!["nothing to sweep"]);
+}
+
+#[test]
+fn distribute_other_denom_works() {
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &[]));
+
+    deps.querier.update_staking("ucosm", &[], &[]);
+
+    deps.querier.update_balance(
+        "rewards_pot",
+        vec![Coin::new(3000, "ucosm"), Coin::new(700, "uatom")],
+    );
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 2,
+            total_rewards: vec![Coin::new(3000, "ucosm"), Coin::new(700, "uatom")],
+        }
+    );
+
+    let distribute = |amount: u128| ExecuteMsg::DistributeRewards {
+        recipient: "collector".to_owned(),
+        amount: Uint128::new(amount),
+        clamp: None,
+        denom: Some("uatom".to_owned()),
+    };
+
+    let res = exec_err!(deps, "referrals_hub", distribute(1000));
+
+    check(
+        res,
+        expect!["insufficient rewards pot balance - requested 1000, available 700"],
+    );
+
+    let res: DisplayResponse = exec_ok!(deps, "referrals_hub", distribute(500));
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "collector",
+                    amount: [
+                      (
+                        denom: "uatom",
+                        amount: "500",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    // 200 undistributed plus 300 sent directly to the pot
+    deps.querier.update_balance(
+        "rewards_pot",
+        vec![Coin::new(3000, "ucosm"), Coin::new(500, "uatom")],
+    );
+
+    let res: DisplayResponse = exec_ok!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::Sweep {
+            denom: "uatom".to_owned(),
+            recipient: "recovery".to_owned(),
+        }
+    );
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 0,
+                  msg: Std(bank(send(
+                    to_address: "recovery",
+                    amount: [
+                      (
+                        denom: "uatom",
+                        amount: "300",
+                      ),
+                    ],
+                  ))),
+                  reply_on: never,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    // the rewards denom's undistributed rewards are untouched
+    let res = exec_err!(
+        deps,
+        "referrals_hub",
+        ExecuteMsg::Sweep {
+            denom: "ucosm".to_owned(),
+            recipient: "recovery".to_owned(),
+        }
+    );
+
+    check(res, expect!["nothing to sweep"]);
+}
+
+fn many_records(count: u64) -> Vec<RewardsRecord> {
+    (1..=count)
+        .map(|id| RewardsRecord {
+            id,
+            rewards_address: String::from("rewards_pot"),
+            rewards: coins(10, "ucosm"),
+            calculated_height: 12345,
+            calculated_time: String::from("2022-11-11T11:11:22"),
+        })
+        .collect()
+}
+
+#[test]
+fn total_rewards_pages_through_records() {
+    let records = many_records(250);
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let res: TotalRewardsResponse = query_ok!(deps, QueryMsg::TotalRewards {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              total: "2500",
+            )"#]],
+    );
+}
+
+#[test]
+fn total_rewards_exceeding_max_pages_fails() {
+    let records = many_records(250);
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let env = env!();
+
+    let res = api::from_deps(deps.as_ref(), &env)
+        .total_rewards_paged(100, 2)
+        .unwrap_err();
+
+    check(
+        res,
+        expect!["too many outstanding rewards records to total"],
+    );
+}
+
+#[test]
+fn capped_withdrawal_resumes() {
+    let records = many_records(3);
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: Some(2),
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let res: OutstandingRecordsResponse = query_ok!(deps, QueryMsg::OutstandingRecords {});
+
+    check(res.outstanding, expect!["3"]);
+
+    let res: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 1,
+                  msg: Std(custom(withdraw_rewards(
+                    records_limit: Some(2),
+                    record_ids: [],
+                  ))),
+                  reply_on: success,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 2,
+            total_rewards: cosmwasm_std::coins(20, "ucosm")
+        }
+    );
+
+    let res: OutstandingRecordsResponse = query_ok!(deps, QueryMsg::OutstandingRecords {});
+
+    check(res.outstanding, expect!["1"]);
+
+    let res: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 1,
+                  msg: Std(custom(withdraw_rewards(
+                    records_limit: Some(1),
+                    record_ids: [],
+                  ))),
+                  reply_on: success,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 1,
+            total_rewards: cosmwasm_std::coins(10, "ucosm")
+        }
+    );
+
+    let res: OutstandingRecordsResponse = query_ok!(deps, QueryMsg::OutstandingRecords {});
+
+    check(res.outstanding, expect!["0"]);
+
+    let res: TotalRewardsResponse = query_ok!(deps, QueryMsg::TotalRewards {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              total: "30",
+            )"#]],
+    );
+}
+
+#[test]
+fn empty_withdrawal_still_advances_records_collected() {
+    let records = many_records(2);
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    // the records were withdrawn, even though they held nothing
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 2,
+            total_rewards: vec![],
+        }
+    );
+
+    let res: OutstandingRecordsResponse = query_ok!(deps, QueryMsg::OutstandingRecords {});
+
+    check(res.outstanding, expect!["0"]);
+}
+
+#[test]
+fn outstanding_records_query_works() {
+    let records = many_records(5);
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: Some(2),
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 2,
+            total_rewards: cosmwasm_std::coins(20, "ucosm")
+        }
+    );
+
+    let res: OutstandingRecordsResponse = query_ok!(deps, QueryMsg::OutstandingRecords {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              outstanding: 3,
+              collected: 2,
+              total: 5,
+            )"#]],
+    );
+}
+
+#[test]
+fn withdraw_cooldown_works() {
+    let records = many_records(3);
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: Some(1),
+            min_withdraw_interval_secs: Some(60),
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    let err = exec_err!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    check(err, expect!["withdraw cooldown - 60 seconds remaining"]);
+
+    let mut env = env!();
+
+    env.block.time = env.block.time.plus_seconds(59);
+
+    let err = rewards_pot::execute(
+        deps.as_mut(),
+        env.clone(),
+        info!("referrals_hub"),
+        ExecuteMsg::WithdrawRewards {},
+    )
+    .unwrap_err();
+
+    check(err, expect!["withdraw cooldown - 1 seconds remaining"]);
+
+    env.block.time = env.block.time.plus_seconds(1);
+
+    let res: DisplayResponse = rewards_pot::execute(
+        deps.as_mut(),
+        env,
+        info!("referrals_hub"),
+        ExecuteMsg::WithdrawRewards {},
+    )
+    .map(DisplayResponse::from)
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              data: None,
+              messages: [
+                (
+                  id: 1,
+                  msg: Std(custom(withdraw_rewards(
+                    records_limit: Some(1),
+                    record_ids: [],
+                  ))),
+                  reply_on: success,
+                ),
+              ],
+              attributes: [],
+              events: [],
+            )"#]],
+    );
+}
+
+#[test]
+fn withdrawal_reply_keeps_records_created_after_withdraw() {
+    let records = std::sync::Arc::new(std::sync::Mutex::new(many_records(2)));
+
+    let mut deps = archway_bindings::testing::mock_dependencies({
+        let records = records.clone();
+        move |q| archway_query_handler(q, &records.lock().unwrap())
+    });
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: None,
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    // a new record is created before the withdrawal reply is handled
+    *records.lock().unwrap() = many_records(3);
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 2,
+            total_rewards: cosmwasm_std::coins(20, "ucosm")
+        }
+    );
+
+    let res: OutstandingRecordsResponse = query_ok!(deps, QueryMsg::OutstandingRecords {});
+
+    check(res.outstanding, expect!["1"]);
+
+    let res: TotalRewardsResponse = query_ok!(deps, QueryMsg::TotalRewards {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              total: "30",
+            )"#]],
+    );
+}
+
+#[test]
+fn rewards_breakdown_works() {
+    let records: Vec<_> = many_records(5)
+        .into_iter()
+        .map(|record| RewardsRecord {
+            rewards: coins(1000, "ucosm"),
+            ..record
+        })
+        .collect();
+
+    let mut deps =
+        archway_bindings::testing::mock_dependencies(move |q| archway_query_handler(q, &records));
+
+    let _: DisplayResponse<InstantiateResponse> = init_ok!(
+        deps,
+        "referrals_hub",
+        InstantiateMsg {
+            dapp: "dapp".to_owned(),
+            withdraw_records_limit: Some(3),
+            min_withdraw_interval_secs: None,
+            rewards_denom: None,
+        }
+    );
+
+    let _: DisplayResponse = exec_ok!(deps, "referrals_hub", ExecuteMsg::WithdrawRewards {});
+
+    let _: DisplayResponse = reply_ok!(
+        deps,
+        WithdrawRewardsResponse {
+            records_num: 3,
+            total_rewards: cosmwasm_std::coins(3000, "ucosm")
+        }
+    );
+
+    let res: RewardsBreakdownResponse = query_ok!(deps, QueryMsg::RewardsBreakdown {});
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              collected: "3000",
+              pending: "2000",
+              total: "5000",
+            )"#]],
+    );
+}
+
+#[test]
+fn unexpected_reply_id_fails() {
+    let mut deps = archway_bindings::testing::mock_dependencies(move |q| {
+        archway_query_handler(q, &many_records(1))
+    });
+
+    let err = rewards_pot::reply(
+        deps.as_mut(),
+        env!(),
+        cosmwasm_std::Reply {
+            id: ReplyId::CreateRewardsPot.as_u64(),
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap_err();
+
+    check(err, expect!["unknown reply id - 0"]);
 }
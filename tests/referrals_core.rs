@@ -1,8 +1,9 @@
-use std::num::NonZeroU128;
+use std::collections::BTreeMap;
+use std::num::{NonZeroU128, NonZeroU64};
 
 use serde::Serialize;
 
-use referrals_core::hub::NonZeroPercent;
+use referrals_core::hub::{Activity, DappCampaign, Features, NonZeroPercent};
 use referrals_core::{FallibleApi, Id};
 
 #[derive(Serialize, Default)]
@@ -25,6 +26,34 @@ pub struct MockApi {
     code_dapp_collected: u128,
     dapp_total_collected: u128,
     dapp_total_rewards: u128,
+    self_referral_allowed: bool,
+    pending_collector: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    campaign: Option<DappCampaign>,
+    #[serde(skip)]
+    last_active: Option<Activity>,
+    #[serde(skip)]
+    daily_invocations: BTreeMap<u64, u64>,
+    #[serde(skip)]
+    code_blocked: bool,
+    #[serde(skip)]
+    self_referral_blocked: bool,
+    #[serde(skip)]
+    max_invocations_per_code: Option<NonZeroU64>,
+    #[serde(skip)]
+    enabled_features: Features,
+    #[serde(skip)]
+    min_collect_amount: Option<NonZeroU128>,
+    #[serde(skip)]
+    allow_self_dapp: Option<bool>,
+    #[serde(skip)]
+    dapp_frozen: bool,
+    #[serde(skip)]
+    rewards_pot_dapp: Option<String>,
+    #[serde(skip)]
+    repo_url: Option<String>,
+    #[serde(skip)]
+    relayer: Option<String>,
 }
 
 #[macro_export]
@@ -41,6 +70,13 @@ macro_rules! nz {
     };
 }
 
+#[macro_export]
+macro_rules! code {
+    ($c:literal) => {
+        referrals_core::hub::ReferralCode::try_from($c).unwrap()
+    };
+}
+
 impl MockApi {
     pub fn dapp(mut self, id: &str) -> Self {
         self.dapp = Some((id.to_owned(), id.to_owned()));
@@ -67,6 +103,11 @@ impl MockApi {
         self
     }
 
+    pub fn rewards_pot_dapp(mut self, id: &str) -> Self {
+        self.rewards_pot_dapp = Some(id.into());
+        self
+    }
+
     pub fn current_fee(mut self, fee: NonZeroU128) -> Self {
         self.current_fee = Some(fee);
         self
@@ -82,6 +123,16 @@ impl MockApi {
         self
     }
 
+    pub fn referral_relayer(mut self, id: &str) -> Self {
+        self.relayer = Some(id.into());
+        self
+    }
+
+    pub fn block_self_referral(mut self) -> Self {
+        self.self_referral_blocked = true;
+        self
+    }
+
     pub fn dapp_total_rewards(mut self, total: u128) -> Self {
         self.dapp_total_rewards = total;
         self
@@ -92,6 +143,36 @@ impl MockApi {
         self
     }
 
+    pub fn code_blocked(mut self, blocked: bool) -> Self {
+        self.code_blocked = blocked;
+        self
+    }
+
+    pub fn max_invocations_per_code(mut self, max: u64) -> Self {
+        self.max_invocations_per_code = NonZeroU64::new(max);
+        self
+    }
+
+    pub fn min_collect_amount(mut self, amount: NonZeroU128) -> Self {
+        self.min_collect_amount = Some(amount);
+        self
+    }
+
+    pub fn allow_self_dapp(mut self, allowed: bool) -> Self {
+        self.allow_self_dapp = Some(allowed);
+        self
+    }
+
+    pub fn frozen(mut self, frozen: bool) -> Self {
+        self.dapp_frozen = frozen;
+        self
+    }
+
+    pub fn campaign(mut self, campaign: DappCampaign) -> Self {
+        self.campaign = Some(campaign);
+        self
+    }
+
     pub fn set_current_fee(&mut self, fee: NonZeroU128) -> &mut Self {
         self.current_fee = Some(fee);
         self
@@ -106,9 +187,42 @@ impl FallibleApi for MockApi {
 pub mod hub {
     use super::*;
 
+    #[cfg(test)]
+    pub mod audit;
     pub mod collect;
+    pub mod config;
     pub mod dapp;
     #[cfg(test)]
+    pub mod error;
+    #[cfg(test)]
     pub mod exec;
     pub mod referral;
+    #[cfg(test)]
+    pub mod simulate;
+}
+
+#[cfg(test)]
+mod id {
+    use referrals_core::{Id, IdError};
+
+    #[test]
+    fn try_new_works() {
+        assert_eq!(Id::try_new("dapp").unwrap(), Id::from("dapp"));
+        assert_eq!(
+            Id::try_new("d".repeat(128)).unwrap(),
+            Id::from("d".repeat(128))
+        );
+    }
+
+    #[test]
+    fn try_new_invalid_fails() {
+        assert_eq!(Id::try_new(""), Err(IdError::Empty));
+        assert_eq!(Id::try_new(" \t"), Err(IdError::Empty));
+        assert_eq!(
+            Id::try_new("d".repeat(129)),
+            Err(IdError::TooLong { len: 129, max: 128 })
+        );
+        assert_eq!(Id::try_new("da\0pp"), Err(IdError::ControlCharacter));
+        assert_eq!(Id::try_new("dapp\n"), Err(IdError::ControlCharacter));
+    }
 }
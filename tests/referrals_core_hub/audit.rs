@@ -0,0 +1,62 @@
+use referrals_core::hub::audit::check_invariants;
+use referrals_core::hub::{referral, MutableCollectStore, MutableDappStore, MutableReferralStore};
+use referrals_core::IdRef;
+
+use crate::{check, expect};
+
+use super::*;
+
+fn recorded() -> MockApi {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+    api.set_latest(code!(1)).unwrap();
+
+    for _ in 0..2 {
+        referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+    }
+
+    api
+}
+
+#[test]
+fn works() {
+    check_invariants(&MockApi::default()).unwrap();
+
+    check_invariants(&recorded()).unwrap();
+}
+
+#[test]
+fn corrupt_dapp_contributions_fails() {
+    let mut api = recorded();
+
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(1500))
+        .unwrap();
+
+    let res = check_invariants(&api).unwrap_err();
+
+    check(
+        res,
+        expect!["dApp dapp contributions of 1500 differ from the 1000 earned by codes"],
+    );
+}
+
+#[test]
+fn overcollected_fails() {
+    let mut api = recorded();
+
+    api.set_referrer_dapp_collected(&Id::from("dapp"), code!(1), nz!(1001))
+        .unwrap();
+
+    let res = check_invariants(&api).unwrap_err();
+
+    check(
+        res,
+        expect!["code 1 collected 1001 from dApp dapp, more than the 1000 earned"],
+    );
+}
@@ -25,6 +25,11 @@ impl ReadonlyCollectStore for MockApi {
     fn dapp_total_collected(&self, _dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
         Ok(NonZeroU128::new(self.dapp_total_collected))
     }
+
+    // a single referrer is mocked, so it's collections are the dApp's referrer collections
+    fn dapp_referrers_collected(&self, _dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(NonZeroU128::new(self.code_dapp_collected))
+    }
 }
 
 impl MutableCollectStore for MockApi {
@@ -69,5 +74,11 @@ impl CollectQuery for MockApi {
 #[cfg(test)]
 pub mod dapp;
 
+#[cfg(test)]
+pub mod invariants;
+
 #[cfg(test)]
 pub mod referrer;
+
+#[cfg(test)]
+pub mod referrer_all;
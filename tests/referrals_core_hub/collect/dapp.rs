@@ -1,5 +1,6 @@
 use referrals_core::hub::collect;
 use referrals_core::hub::MutableReferralStore;
+use referrals_core::IdRef;
 
 use crate::{check, expect, pretty};
 
@@ -14,10 +15,10 @@ fn works() {
         .referral_code(1)
         .dapp_total_rewards(11_000);
 
-    api.set_dapp_contributions(&Id::from("dapp"), nz!(5000))
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(5000))
         .unwrap();
 
-    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp")).unwrap();
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap();
 
     check(
         pretty(&res),
@@ -51,15 +52,17 @@ fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 6000,
               dapp_total_rewards: 11000,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 
-    api.set_dapp_contributions(&Id::from("dapp"), nz!(10_000))
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(10_000))
         .unwrap();
 
     api.set_dapp_total_rewards(22_000);
 
-    let res = collect::dapp(&mut api, Id::from("dapp"), &Id::from("dapp")).unwrap();
+    let res = collect::dapp(&mut api, Id::from("dapp"), &Id::from("dapp"), None).unwrap();
 
     check(
         pretty(&res),
@@ -93,6 +96,8 @@ fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 12000,
               dapp_total_rewards: 22000,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
@@ -101,9 +106,12 @@ fn works() {
 fn sender_not_dapp_or_collector_fails() {
     let mut api = MockApi::default().dapp("dapp").collector("collector");
 
-    let res = collect::dapp(&mut api, Id::from("bob"), &Id::from("dapp")).unwrap_err();
+    let res = collect::dapp(&mut api, Id::from("bob"), &Id::from("dapp"), None).unwrap_err();
 
-    check(res, expect!["unauthorised"]);
+    check(
+        res,
+        expect!["unauthorised - bob is not dApp dapp or it's collector"],
+    );
 }
 
 #[test]
@@ -114,27 +122,106 @@ fn no_earnings_to_collect_fails() {
         .collector("collector")
         .referral_code(1);
 
-    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp")).unwrap_err();
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap_err();
 
-    check(res, expect!["nothing to collect"]);
+    check(res, expect!["nothing to collect - dApp dapp"]);
 
-    api.set_dapp_contributions(&Id::from("dapp"), nz!(5000))
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(5000))
         .unwrap();
 
     api.set_dapp_total_rewards(5000);
 
-    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp")).unwrap_err();
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap_err();
 
-    check(res, expect!["nothing to collect"]);
+    check(res, expect!["nothing to collect - dApp dapp"]);
 
-    api.set_dapp_contributions(&Id::from("dapp"), nz!(5000))
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(5000))
         .unwrap();
 
     api.set_dapp_total_rewards(11_000);
 
-    collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp")).unwrap();
+    collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap();
+
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap_err();
+
+    check(res, expect!["nothing to collect - dApp dapp"]);
+}
+
+#[test]
+fn contributions_exceeding_rewards_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .collector("collector")
+        .referral_code(1)
+        .dapp_total_rewards(4000);
+
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(5000))
+        .unwrap();
+
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap_err();
+
+    check(res, expect!["nothing to collect - dApp dapp"]);
+}
+
+#[test]
+fn tracking_only_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .collector("collector")
+        .dapp_total_rewards(3000);
+
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            RedistributeRewards(
+              amount: 3000,
+              pot: ("rewards_pot"),
+              receiver: ("collector"),
+            )"#]],
+    );
+
+    check(
+        format!("{:?}", collect::dapp_owed(&api, &Id::from("dapp")).unwrap()),
+        expect!["Owed { owed: None, already_collected: Some(3000) }"],
+    );
+}
+
+#[test]
+fn paid_to_recipient_when_given() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .collector("collector")
+        .referral_code(1)
+        .dapp_total_rewards(11_000);
+
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(5000))
+        .unwrap();
+
+    let res = collect::dapp(
+        &mut api,
+        Id::from("collector"),
+        &Id::from("dapp"),
+        Some(Id::from("treasury")),
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            RedistributeRewards(
+              amount: 6000,
+              pot: ("rewards_pot"),
+              receiver: ("treasury"),
+            )"#]],
+    );
 
-    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp")).unwrap_err();
+    // collected rewards are still accounted against the dApp
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap_err();
 
-    check(res, expect!["nothing to collect"]);
+    check(res, expect!["nothing to collect - dApp dapp"]);
 }
@@ -0,0 +1,326 @@
+use kv_storage::KvStore;
+use proptest::prelude::*;
+use referrals_core::hub::{
+    audit, collect, dapp, referral, Command, DappExternalQuery, Error, MutableDappStore,
+    MutableReferralStore, ReadonlyConfigStore, ReadonlyDappStore,
+};
+use referrals_storage::testing::MemApi;
+
+use crate::referrals_core::hub::dapp::SELF_ID;
+use crate::referrals_storage::{Repo, RonSerde};
+
+use super::*;
+
+const DAPP: &str = "dapp";
+const COLLECTOR: &str = "collector";
+const POT: &str = "rewards_pot";
+
+/// A step taken against the hub, codes are given as an index into the registered owners.
+#[derive(Debug, Clone)]
+enum Op {
+    Record(usize),
+    AccrueRewards(u128),
+    ChangeFee(u128),
+    CollectReferrer(usize),
+    CollectDapp,
+}
+
+fn op(codes: usize) -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (0..codes).prop_map(Op::Record),
+        2 => (1..=5000u128).prop_map(Op::AccrueRewards),
+        1 => (1..=2000u128).prop_map(Op::ChangeFee),
+        2 => (0..codes).prop_map(Op::CollectReferrer),
+        2 => Just(Op::CollectDapp),
+    ]
+}
+
+/// The parts of the environment outside of the hub's control.
+trait Env {
+    fn accrue_rewards(&mut self, amount: u128);
+
+    fn change_fee(&mut self, fee: NonZeroU128);
+}
+
+impl Env for MockApi {
+    fn accrue_rewards(&mut self, amount: u128) {
+        let total = total_rewards(self);
+        self.set_dapp_total_rewards(total + amount);
+    }
+
+    fn change_fee(&mut self, fee: NonZeroU128) {
+        self.set_current_fee(fee);
+    }
+}
+
+impl Env for MemApi<KvStore<RonSerde, Repo>> {
+    fn accrue_rewards(&mut self, amount: u128) {
+        let total = total_rewards(self) + amount;
+        self.set_dapp_total_rewards(NonZeroU128::new(total).unwrap());
+    }
+
+    fn change_fee(&mut self, fee: NonZeroU128) {
+        self.set_current_fee(fee);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct CodeAccounting {
+    earnings: u128,
+    total_earnings: u128,
+    collected: u128,
+    total_collected: u128,
+}
+
+/// The collect accounting of the dApp & every code, as stored.
+#[derive(Debug, PartialEq, Eq)]
+struct Accounting {
+    contributions: u128,
+    collected: u128,
+    codes: Vec<CodeAccounting>,
+}
+
+fn get(value: Option<NonZeroU128>) -> u128 {
+    value.map_or(0, NonZeroU128::get)
+}
+
+fn total_rewards<Api: CollectQuery>(api: &Api) -> u128 {
+    get(CollectQuery::dapp_total_rewards(api, &Id::from(POT)).unwrap())
+}
+
+fn accounting<Api>(api: &Api, codes: &[ReferralCode]) -> Accounting
+where
+    Api: ReadonlyReferralStore + ReadonlyCollectStore,
+{
+    let dapp = Id::from(DAPP);
+
+    let codes = codes
+        .iter()
+        .map(|&code| CodeAccounting {
+            earnings: get(api.dapp_earnings(dapp.as_id_ref(), code).unwrap()),
+            total_earnings: get(api.total_earnings(code).unwrap()),
+            collected: get(api.referrer_dapp_collected(&dapp, code).unwrap()),
+            total_collected: get(api.referrer_total_collected(code).unwrap()),
+        })
+        .collect();
+
+    Accounting {
+        contributions: get(api.dapp_contributions(dapp.as_id_ref()).unwrap()),
+        collected: get(api.dapp_total_collected(&dapp).unwrap()),
+        codes,
+    }
+}
+
+/// Run a collection, returning the amount paid out if any. Finding nothing to collect must leave
+/// the accounting untouched, as must collecting again straight after a successful collection.
+fn collected<Api, F>(
+    api: &mut Api,
+    codes: &[ReferralCode],
+    mut collect: F,
+) -> Result<Option<u128>, TestCaseError>
+where
+    Api: ReadonlyReferralStore + ReadonlyCollectStore,
+    F: FnMut(&mut Api) -> Result<Command, Error<Api::Error>>,
+{
+    let before = accounting(api, codes);
+
+    let amount = match collect(api) {
+        Ok(Command::RedistributeRewards { amount, .. }) => amount.get(),
+        Err(Error::NothingToCollect(_)) => {
+            prop_assert_eq!(accounting(api, codes), before);
+            return Ok(None);
+        }
+        res => {
+            return Err(TestCaseError::fail(format!(
+                "unexpected collection: {res:?}"
+            )))
+        }
+    };
+
+    let after = accounting(api, codes);
+
+    let res = collect(api);
+
+    prop_assert!(
+        matches!(res, Err(Error::NothingToCollect(_))),
+        "collected twice: {res:?}"
+    );
+    prop_assert_eq!(accounting(api, codes), after);
+
+    Ok(Some(amount))
+}
+
+/// Apply `ops` to an api with an activated dApp & the given referral codes, checking the collect
+/// accounting invariants hold after every step.
+fn run<Api>(api: &mut Api, owners: &[(Id, ReferralCode)], ops: Vec<Op>) -> Result<(), TestCaseError>
+where
+    Api: Env
+        + DappExternalQuery
+        + ReadonlyDappStore
+        + ReadonlyReferralStore
+        + MutableReferralStore
+        + ReadonlyCollectStore
+        + MutableCollectStore
+        + CollectQuery
+        + ReadonlyConfigStore,
+{
+    let dapp = Id::from(DAPP);
+    let collector = Id::from(COLLECTOR);
+    let codes: Vec<_> = owners.iter().map(|(_, code)| *code).collect();
+
+    let mut referrers_paid = vec![0u128; owners.len()];
+    let mut dapp_paid = 0u128;
+
+    for op in ops {
+        match op {
+            Op::Record(index) => {
+                referral::record(api, &dapp, None, owners[index].1).unwrap();
+            }
+
+            Op::AccrueRewards(amount) => api.accrue_rewards(amount),
+
+            Op::ChangeFee(fee) => api.change_fee(NonZeroU128::new(fee).unwrap()),
+
+            Op::CollectReferrer(index) => {
+                let (owner, code) = &owners[index];
+
+                if let Some(amount) = collected(api, &codes, |api| {
+                    collect::referrer(api, owner.clone(), &dapp, *code, None)
+                })? {
+                    referrers_paid[index] += amount;
+                }
+            }
+
+            Op::CollectDapp => {
+                if let Some(amount) = collected(api, &codes, |api| {
+                    collect::dapp(api, collector.clone(), &dapp, None)
+                })? {
+                    dapp_paid += amount;
+
+                    // the dApp is paid everything not contributed to referrers, no more
+                    let Accounting {
+                        contributions,
+                        collected,
+                        ..
+                    } = accounting(api, &codes);
+
+                    prop_assert_eq!(contributions + collected, total_rewards(api));
+                }
+            }
+        }
+
+        if let Err(err) = audit::check_invariants(api) {
+            return Err(TestCaseError::fail(format!("{err:?}")));
+        }
+
+        let state = accounting(api, &codes);
+
+        prop_assert_eq!(state.collected, dapp_paid);
+        prop_assert!(state.collected <= total_rewards(api));
+
+        for (code, paid) in state.codes.iter().zip(&referrers_paid) {
+            prop_assert_eq!(code.collected, *paid);
+            prop_assert_eq!(code.total_collected, *paid);
+            prop_assert!(code.collected <= code.earnings);
+            prop_assert_eq!(code.earnings, code.total_earnings);
+        }
+
+        let earnings: u128 = state.codes.iter().map(|code| code.earnings).sum();
+
+        prop_assert_eq!(state.contributions, earnings);
+    }
+
+    Ok(())
+}
+
+fn mock_api() -> (MockApi, Vec<(Id, ReferralCode)>) {
+    let mut api = MockApi::default()
+        .dapp(DAPP)
+        .collector(COLLECTOR)
+        .rewards_pot(POT)
+        .current_fee(nz!(1000))
+        .referral_code(1)
+        .referral_code_owner("referrer");
+
+    api.set_percent(&Id::from(DAPP), nzp!(50)).unwrap();
+    api.set_latest(code!(1)).unwrap();
+
+    (api, vec![(Id::from("referrer"), code!(1))])
+}
+
+fn mem_api(codes: usize) -> (MemApi<KvStore<RonSerde, Repo>>, Vec<(Id, ReferralCode)>) {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default()
+        .rewards_admin(SELF_ID)
+        .current_fee(nz!(1000));
+
+    let owners = (0..codes)
+        .map(|index| {
+            let owner = Id::from(format!("referrer{index}"));
+            let code = referral::register(&mut api, owner.clone()).unwrap();
+            (owner, code)
+        })
+        .collect();
+
+    dapp::activate(
+        &mut api,
+        Id::from(DAPP),
+        DAPP.to_owned(),
+        nzp!(50),
+        Id::from(COLLECTOR),
+        None,
+    )
+    .unwrap();
+
+    api.set_rewards_pot(&Id::from(DAPP), Id::from(POT)).unwrap();
+
+    (api, owners)
+}
+
+proptest! {
+    #[test]
+    fn mock_api_invariants_hold(ops in prop::collection::vec(op(1), 1..64)) {
+        let (mut api, owners) = mock_api();
+
+        run(&mut api, &owners, ops)?;
+    }
+
+    #[test]
+    fn storage_invariants_hold(ops in prop::collection::vec(op(3), 1..64)) {
+        let (mut api, owners) = mem_api(3);
+
+        run(&mut api, &owners, ops)?;
+    }
+}
+
+#[test]
+fn lagging_rewards_leave_nothing_to_collect() {
+    let (mut api, owners) = mem_api(1);
+
+    // contributions of 1500 against 1000 of rewards
+    let ops = vec![
+        Op::Record(0),
+        Op::Record(0),
+        Op::Record(0),
+        Op::AccrueRewards(1000),
+        Op::CollectDapp,
+        Op::CollectReferrer(0),
+        Op::AccrueRewards(1000),
+        Op::CollectDapp,
+    ];
+
+    run(&mut api, &owners, ops).unwrap();
+
+    assert_eq!(
+        accounting(&api, &[owners[0].1]),
+        Accounting {
+            contributions: 1500,
+            collected: 500,
+            codes: vec![CodeAccounting {
+                earnings: 1500,
+                total_earnings: 1500,
+                collected: 1500,
+                total_collected: 1500,
+            }],
+        }
+    );
+}
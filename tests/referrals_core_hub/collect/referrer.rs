@@ -1,5 +1,6 @@
 use referrals_core::hub::collect;
 use referrals_core::hub::{MutableDappStore, MutableReferralStore};
+use referrals_core::IdRef;
 
 use crate::{check, expect, pretty};
 
@@ -14,17 +15,17 @@ fn works() {
         .referral_code_owner("referrer")
         .dapp_total_rewards(11_000);
 
-    api.set_total_earnings(ReferralCode::from(1), nz!(5000))
-        .unwrap();
+    api.set_total_earnings(code!(1), nz!(5000)).unwrap();
 
-    api.set_dapp_earnings(&Id::from("dapp"), ReferralCode::from(1), nz!(5000))
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5000))
         .unwrap();
 
     let res = collect::referrer(
         &mut api,
         Id::from("referrer"),
         &Id::from("dapp"),
-        ReferralCode::from(1),
+        code!(1),
+        None,
     )
     .unwrap();
 
@@ -60,20 +61,22 @@ fn works() {
               code_dapp_collected: 5000,
               dapp_total_collected: 0,
               dapp_total_rewards: 11000,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 
-    api.set_total_earnings(ReferralCode::from(1), nz!(7000))
-        .unwrap();
+    api.set_total_earnings(code!(1), nz!(7000)).unwrap();
 
-    api.set_dapp_earnings(&Id::from("dapp"), ReferralCode::from(1), nz!(7000))
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(7000))
         .unwrap();
 
     let res = collect::referrer(
         &mut api,
         Id::from("referrer"),
         &Id::from("dapp"),
-        ReferralCode::from(1),
+        code!(1),
+        None,
     )
     .unwrap();
 
@@ -109,6 +112,8 @@ fn works() {
               code_dapp_collected: 7000,
               dapp_total_collected: 0,
               dapp_total_rewards: 11000,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
@@ -126,11 +131,12 @@ fn code_not_registered_fails() {
         &mut api,
         Id::from("referrer"),
         &Id::from("dapp"),
-        ReferralCode::from(1),
+        code!(1),
+        None,
     )
     .unwrap_err();
 
-    check(res, expect!["referral code not registered"]);
+    check(res, expect!["referral code not registered - 1"]);
 }
 
 #[test]
@@ -143,15 +149,13 @@ fn sender_not_code_owner_fails() {
         .current_fee(nz!(1000))
         .dapp_total_rewards(11_000);
 
-    let res = collect::referrer(
-        &mut api,
-        Id::from("bob"),
-        &Id::from("dapp"),
-        ReferralCode::from(1),
-    )
-    .unwrap_err();
+    let res = collect::referrer(&mut api, Id::from("bob"), &Id::from("dapp"), code!(1), None)
+        .unwrap_err();
 
-    check(res, expect!["unauthorised"]);
+    check(
+        res,
+        expect!["unauthorised - bob is not the owner of referral code 1"],
+    );
 }
 
 #[test]
@@ -168,35 +172,130 @@ fn no_earnings_to_collect_fails() {
         &mut api,
         Id::from("referrer"),
         &Id::from("dapp"),
-        ReferralCode::from(1),
+        code!(1),
+        None,
     )
     .unwrap_err();
 
-    check(res, expect!["nothing to collect"]);
+    check(
+        res,
+        expect!["nothing to collect - referral code 1 with dApp dapp"],
+    );
 
     api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
 
-    api.set_total_earnings(ReferralCode::from(1), nz!(5000))
+    api.set_total_earnings(code!(1), nz!(5000)).unwrap();
+
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5000))
         .unwrap();
 
-    api.set_dapp_earnings(&Id::from("dapp"), ReferralCode::from(1), nz!(5000))
+    collect::referrer(
+        &mut api,
+        Id::from("referrer"),
+        &Id::from("dapp"),
+        code!(1),
+        None,
+    )
+    .unwrap();
+
+    let res = collect::referrer(
+        &mut api,
+        Id::from("referrer"),
+        &Id::from("dapp"),
+        code!(1),
+        None,
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["nothing to collect - referral code 1 with dApp dapp"],
+    );
+}
+
+#[test]
+fn code_blocked_fails_until_unblocked() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .referral_code(1)
+        .rewards_pot("rewards_pot")
+        .referral_code_owner("referrer")
+        .dapp_total_rewards(11_000)
+        .code_blocked(true);
+
+    api.set_total_earnings(code!(1), nz!(5000)).unwrap();
+
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5000))
         .unwrap();
 
+    let res = collect::referrer(
+        &mut api,
+        Id::from("referrer"),
+        &Id::from("dapp"),
+        code!(1),
+        None,
+    )
+    .unwrap_err();
+
+    check(res, expect!["referral code blocked - 1"]);
+
+    let mut api = api.code_blocked(false);
+
     collect::referrer(
         &mut api,
         Id::from("referrer"),
         &Id::from("dapp"),
-        ReferralCode::from(1),
+        code!(1),
+        None,
     )
     .unwrap();
+}
+
+#[test]
+fn paid_to_recipient_when_given() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .referral_code(1)
+        .referral_code_owner("referrer")
+        .dapp_total_rewards(11_000);
+
+    api.set_total_earnings(code!(1), nz!(5000)).unwrap();
+
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5000))
+        .unwrap();
 
     let res = collect::referrer(
         &mut api,
         Id::from("referrer"),
         &Id::from("dapp"),
-        ReferralCode::from(1),
+        code!(1),
+        Some(Id::from("treasury")),
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            RedistributeRewards(
+              amount: 5000,
+              pot: ("rewards_pot"),
+              receiver: ("treasury"),
+            )"#]],
+    );
+
+    // the recipient does not need to own the code, but the sender still does
+    let res = collect::referrer(
+        &mut api,
+        Id::from("treasury"),
+        &Id::from("dapp"),
+        code!(1),
+        Some(Id::from("treasury")),
     )
     .unwrap_err();
 
-    check(res, expect!["nothing to collect"]);
+    check(
+        res,
+        expect!["unauthorised - treasury is not the owner of referral code 1"],
+    );
 }
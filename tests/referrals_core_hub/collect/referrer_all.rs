@@ -0,0 +1,147 @@
+use kv_storage::KvStore;
+use referrals_core::hub::collect;
+use referrals_core::hub::{MutableDappStore, MutableReferralStore};
+use referrals_core::IdRef;
+use referrals_storage::testing::MemApi;
+
+use crate::referrals_storage::{Repo, RonSerde};
+use crate::{check, expect, pretty};
+
+use super::*;
+
+#[test]
+fn works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .referral_code(1)
+        .referral_code_owner("referrer");
+
+    api.set_total_earnings(code!(1), nz!(5000)).unwrap();
+
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5000))
+        .unwrap();
+
+    let res = collect::referrer_all(&mut api, &Id::from("referrer"), code!(1), None, None).unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            ([
+              WithdrawPending(("rewards_pot")),
+              RedistributeRewards(
+                amount: 5000,
+                pot: ("rewards_pot"),
+                receiver: ("referrer"),
+              ),
+            ], None)"#]],
+    );
+
+    let res =
+        collect::referrer_all(&mut api, &Id::from("referrer"), code!(1), None, None).unwrap_err();
+
+    check(res, expect!["nothing to collect - referral code 1"]);
+}
+
+#[test]
+fn three_dapps_works() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default();
+
+    let code = code!(1);
+
+    api.set_code_owner(code, Id::from("referrer")).unwrap();
+
+    for (dapp, earnings) in [
+        ("dapp1", nz!(1000)),
+        ("dapp2", nz!(2000)),
+        ("dapp3", nz!(3000)),
+    ] {
+        let dapp = Id::from(dapp);
+        api.add_dapp(&dapp, dapp.as_str().to_owned()).unwrap();
+        api.set_rewards_pot(&dapp, Id::from(format!("{}_pot", dapp.as_str())))
+            .unwrap();
+        api.set_dapp_earnings(dapp.as_id_ref(), code, earnings)
+            .unwrap();
+    }
+
+    let res = collect::referrer_all(&mut api, &Id::from("referrer"), code, None, None).unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            ([
+              WithdrawPending(("dapp1_pot")),
+              RedistributeRewards(
+                amount: 1000,
+                pot: ("dapp1_pot"),
+                receiver: ("referrer"),
+              ),
+              WithdrawPending(("dapp2_pot")),
+              RedistributeRewards(
+                amount: 2000,
+                pot: ("dapp2_pot"),
+                receiver: ("referrer"),
+              ),
+              WithdrawPending(("dapp3_pot")),
+              RedistributeRewards(
+                amount: 3000,
+                pot: ("dapp3_pot"),
+                receiver: ("referrer"),
+              ),
+            ], None)"#]],
+    );
+
+    check(
+        api.referrer_total_collected(code).unwrap().unwrap(),
+        expect!["6000"],
+    );
+
+    api.set_dapp_earnings(IdRef::from("dapp2"), code, nz!(2500))
+        .unwrap();
+
+    let res = collect::referrer_all(&mut api, &Id::from("referrer"), code, None, Some(2)).unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            ([
+              WithdrawPending(("dapp2_pot")),
+              RedistributeRewards(
+                amount: 500,
+                pot: ("dapp2_pot"),
+                receiver: ("referrer"),
+              ),
+            ], Some(2))"#]],
+    );
+
+    let res =
+        collect::referrer_all(&mut api, &Id::from("referrer"), code, Some(2), Some(2)).unwrap_err();
+
+    check(res, expect!["nothing to collect - referral code 1"]);
+}
+
+#[test]
+fn sender_not_owner_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .referral_code(1)
+        .referral_code_owner("referrer");
+
+    let res = collect::referrer_all(&mut api, &Id::from("bob"), code!(1), None, None).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - bob is not the owner of referral code 1"],
+    );
+}
+
+#[test]
+fn not_registered_fails() {
+    let mut api = MockApi::default();
+
+    let res =
+        collect::referrer_all(&mut api, &Id::from("referrer"), code!(1), None, None).unwrap_err();
+
+    check(res, expect!["referral code not registered - 1"]);
+}
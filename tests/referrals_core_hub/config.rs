@@ -0,0 +1,41 @@
+use referrals_core::hub::{Features, MutableConfigStore, ReadonlyConfigStore};
+
+use super::*;
+
+impl ReadonlyConfigStore for MockApi {
+    fn enabled_features(&self) -> Result<Features, Self::Error> {
+        Ok(self.enabled_features)
+    }
+
+    fn min_collect_amount(&self) -> Result<Option<NonZeroU128>, Self::Error> {
+        Ok(self.min_collect_amount)
+    }
+
+    fn self_dapp_allowed(&self) -> Result<bool, Self::Error> {
+        Ok(self.allow_self_dapp.unwrap_or(true))
+    }
+}
+
+impl MutableConfigStore for MockApi {
+    fn set_enabled_features(&mut self, features: Features) -> Result<(), Self::Error> {
+        self.enabled_features = features;
+        Ok(())
+    }
+
+    fn set_min_collect_amount(&mut self, amount: Option<NonZeroU128>) -> Result<(), Self::Error> {
+        self.min_collect_amount = amount;
+        Ok(())
+    }
+
+    fn set_self_dapp_allowed(&mut self, allowed: bool) -> Result<(), Self::Error> {
+        self.allow_self_dapp = Some(allowed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod set_enabled_features;
+#[cfg(test)]
+pub mod set_min_collect_amount;
+#[cfg(test)]
+pub mod set_self_dapp_allowed;
@@ -0,0 +1,88 @@
+use referrals_core::hub::{config, exec, Configure, Features, Kind, Msg, ReferralCode};
+
+use crate::{check, expect};
+
+use super::*;
+
+fn record(code: u64) -> Msg {
+    Msg {
+        sender: Id::from("dapp"),
+        kind: Kind::Referral {
+            code: ReferralCode::try_from(code).unwrap(),
+            dapp: None,
+        },
+    }
+}
+
+#[test]
+pub fn disable_then_enable_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    let features = Features::ALL.set(Features::RECORD, false);
+
+    config::set_enabled_features(&mut api, &Id::from("collector"), features).unwrap();
+
+    assert_eq!(api.enabled_features, features);
+
+    let res = exec(&mut api, record(1)).unwrap_err();
+
+    check(res, expect!["feature disabled - record"]);
+
+    // the features can be set with configuration disabled
+    exec(
+        &mut api,
+        Msg {
+            sender: Id::from(dapp::SELF_ID),
+            kind: Kind::Config(Configure::EnabledFeatures {
+                features: Features::NONE,
+            }),
+        },
+    )
+    .unwrap();
+
+    exec(
+        &mut api,
+        Msg {
+            sender: Id::from(dapp::SELF_ID),
+            kind: Kind::Config(Configure::EnabledFeatures {
+                features: Features::ALL,
+            }),
+        },
+    )
+    .unwrap();
+
+    exec(&mut api, record(1)).unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 1);
+}
+
+#[test]
+pub fn sender_not_hub_or_collector_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res =
+        config::set_enabled_features(&mut api, &Id::from("dapp"), Features::NONE).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - dapp is not the hub or it's collector"],
+    );
+
+    assert_eq!(api.enabled_features, Features::ALL);
+}
+
+#[test]
+pub fn features_mask_works() {
+    let features = Features::NONE
+        .set(Features::REGISTER, true)
+        .set(Features::CONFIGURE, true);
+
+    assert!(features.contains(Features::REGISTER));
+    assert!(!features.contains(Features::COLLECT));
+    assert_eq!(features.bits(), 0b1001);
+    assert_eq!(Features::from_bits(0xff), Features::ALL);
+}
@@ -0,0 +1,116 @@
+use referrals_core::hub::{collect, config, MutableReferralStore};
+use referrals_core::IdRef;
+
+use crate::{check, expect, pretty};
+
+use super::*;
+
+#[test]
+pub fn dapp_below_minimum_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .collector("collector")
+        .dapp_total_rewards(11_000);
+
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(5000))
+        .unwrap();
+
+    config::set_min_collect_amount(&mut api, &Id::from(dapp::SELF_ID), Some(nz!(10_000))).unwrap();
+
+    let before = pretty(&api);
+
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap_err();
+
+    check(
+        res,
+        expect!["below minimum collect - 6000 is less than 10000"],
+    );
+
+    // the rewards are left to accrue
+    assert_eq!(pretty(&api), before);
+
+    config::set_min_collect_amount(&mut api, &Id::from(dapp::SELF_ID), None).unwrap();
+
+    let res = collect::dapp(&mut api, Id::from("collector"), &Id::from("dapp"), None).unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            RedistributeRewards(
+              amount: 6000,
+              pot: ("rewards_pot"),
+              receiver: ("collector"),
+            )"#]],
+    );
+}
+
+#[test]
+pub fn referrer_below_minimum_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot("rewards_pot")
+        .referral_code(1)
+        .referral_code_owner("referrer")
+        .min_collect_amount(nz!(5001));
+
+    api.set_total_earnings(code!(1), nz!(5000)).unwrap();
+
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5000))
+        .unwrap();
+
+    let res = collect::referrer(
+        &mut api,
+        Id::from("referrer"),
+        &Id::from("dapp"),
+        code!(1),
+        None,
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["below minimum collect - 5000 is less than 5001"],
+    );
+
+    assert_eq!(api.code_dapp_collected, 0);
+    assert_eq!(api.code_total_collected, 0);
+
+    // exactly the minimum is enough
+    api.set_dapp_earnings(IdRef::from("dapp"), code!(1), nz!(5001))
+        .unwrap();
+
+    let res = collect::referrer(
+        &mut api,
+        Id::from("referrer"),
+        &Id::from("dapp"),
+        code!(1),
+        None,
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            RedistributeRewards(
+              amount: 5001,
+              pot: ("rewards_pot"),
+              receiver: ("referrer"),
+            )"#]],
+    );
+}
+
+#[test]
+pub fn sender_not_hub_or_collector_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res =
+        config::set_min_collect_amount(&mut api, &Id::from("dapp"), Some(nz!(1000))).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - dapp is not the hub or it's collector"],
+    );
+
+    assert_eq!(api.min_collect_amount, None);
+}
@@ -0,0 +1,105 @@
+use referrals_core::hub::{config, dapp, ReadonlyConfigStore};
+
+use crate::referrals_core::hub::dapp::SELF_ID;
+use crate::{check, expect, pretty};
+
+use super::*;
+
+fn activate_hub(api: &mut MockApi) -> Result<String, String> {
+    dapp::activate(
+        api,
+        Id::from(SELF_ID),
+        "hub".to_owned(),
+        nzp!(50),
+        Id::from("collector"),
+        None,
+    )
+    .map(|reply| pretty(&reply))
+    .map_err(|err| err.to_string())
+}
+
+#[test]
+pub fn hub_activation_only_when_allowed() {
+    let mut api = MockApi::default().rewards_admin(SELF_ID);
+
+    // allowed unless set otherwise
+    assert!(api.self_dapp_allowed().unwrap());
+
+    config::set_self_dapp_allowed(&mut api, &Id::from(SELF_ID), false).unwrap();
+
+    let before = pretty(&api);
+
+    check(
+        activate_hub(&mut api).unwrap_err(),
+        expect!["self registration - the hub may not activate itself as a dApp"],
+    );
+
+    assert_eq!(pretty(&api), before);
+
+    config::set_self_dapp_allowed(&mut api, &Id::from(SELF_ID), true).unwrap();
+
+    check(
+        activate_hub(&mut api).unwrap(),
+        expect![[r#"Cmd(CreateRewardsPot(("self")))"#]],
+    );
+}
+
+#[test]
+pub fn hub_tracking_only_activation_only_when_allowed() {
+    let mut api = MockApi::default()
+        .rewards_admin(SELF_ID)
+        .allow_self_dapp(false);
+
+    let before = pretty(&api);
+
+    let res = dapp::activate_tracking_only(
+        &mut api,
+        Id::from(SELF_ID),
+        "hub".to_owned(),
+        Id::from("collector"),
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["self registration - the hub may not activate itself as a dApp"],
+    );
+
+    assert_eq!(pretty(&api), before);
+}
+
+#[test]
+pub fn other_dapps_unaffected() {
+    let mut api = MockApi::default()
+        .rewards_admin(SELF_ID)
+        .allow_self_dapp(false);
+
+    let res = dapp::activate(
+        &mut api,
+        Id::from("dapp"),
+        "dapp".to_owned(),
+        nzp!(50),
+        Id::from("collector"),
+        None,
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"Cmd(CreateRewardsPot(("dapp")))"#]],
+    );
+}
+
+#[test]
+pub fn sender_not_hub_or_collector_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res = config::set_self_dapp_allowed(&mut api, &Id::from("dapp"), false).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - dapp is not the hub or it's collector"],
+    );
+
+    assert_eq!(api.allow_self_dapp, None);
+}
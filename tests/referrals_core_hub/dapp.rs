@@ -1,4 +1,4 @@
-use referrals_core::hub::{DappExternalQuery, MutableDappStore, ReadonlyDappStore};
+use referrals_core::hub::{DappCampaign, DappExternalQuery, MutableDappStore, ReadonlyDappStore};
 
 use super::*;
 
@@ -10,8 +10,8 @@ impl ReadonlyDappStore for MockApi {
             .map_or(false, |(dapp, _)| dapp == id.as_str()))
     }
 
-    fn percent(&self, _id: &Id) -> Result<NonZeroPercent, Self::Error> {
-        Ok(self.percent.and_then(NonZeroPercent::new).unwrap())
+    fn percent(&self, _id: &Id) -> Result<Option<NonZeroPercent>, Self::Error> {
+        Ok(self.percent.and_then(NonZeroPercent::new))
     }
 
     fn collector(&self, _id: &Id) -> Result<Id, Self::Error> {
@@ -25,6 +25,38 @@ impl ReadonlyDappStore for MockApi {
     fn rewards_pot(&self, _id: &Id) -> Result<Id, Self::Error> {
         Ok(self.rewards_pot.as_ref().map(Id::from).unwrap())
     }
+
+    fn self_referral_allowed(&self, _id: &Id) -> Result<bool, Self::Error> {
+        Ok(self.self_referral_allowed)
+    }
+
+    fn pending_collector(&self, _id: &Id) -> Result<Option<Id>, Self::Error> {
+        Ok(self.pending_collector.as_ref().map(Id::from))
+    }
+
+    fn campaign(&self, _id: &Id) -> Result<Option<DappCampaign>, Self::Error> {
+        Ok(self.campaign)
+    }
+
+    fn max_invocations_per_code(&self, _id: &Id) -> Result<Option<NonZeroU64>, Self::Error> {
+        Ok(self.max_invocations_per_code)
+    }
+
+    fn dapp_frozen(&self, _id: &Id) -> Result<bool, Self::Error> {
+        Ok(self.dapp_frozen)
+    }
+
+    fn dapp_by_name(&self, name: &str) -> Result<Option<Id>, Self::Error> {
+        Ok(self
+            .dapp
+            .as_ref()
+            .filter(|(_, dapp_name)| dapp_name == name)
+            .map(|(dapp, _)| Id::from(dapp.as_str())))
+    }
+
+    fn relayer(&self, _id: &Id) -> Result<Option<Id>, Self::Error> {
+        Ok(self.relayer.as_ref().map(Id::from))
+    }
 }
 
 impl MutableDappStore for MockApi {
@@ -47,14 +79,27 @@ impl MutableDappStore for MockApi {
         Ok(())
     }
 
+    fn set_name(&mut self, id: &Id, name: String) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.dapp = Some((id.clone().into_string(), name));
+        Ok(())
+    }
+
     fn set_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
         assert!(self.dapp_exists(id)?);
         self.collector = Some(collector.into_string());
         Ok(())
     }
 
-    fn set_repo_url(&mut self, id: &Id, _repo_url: String) -> Result<(), Self::Error> {
+    fn set_repo_url(&mut self, id: &Id, repo_url: String) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.repo_url = Some(repo_url);
+        Ok(())
+    }
+
+    fn clear_repo_url(&mut self, id: &Id) -> Result<(), Self::Error> {
         assert!(self.dapp_exists(id)?);
+        self.repo_url = None;
         Ok(())
     }
 
@@ -63,9 +108,87 @@ impl MutableDappStore for MockApi {
         self.rewards_pot = Some(rewards_pot.into_string());
         Ok(())
     }
+
+    fn set_self_referral_allowed(&mut self, id: &Id, allowed: bool) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.self_referral_allowed = allowed;
+        Ok(())
+    }
+
+    fn set_pending_collector(&mut self, id: &Id, collector: Id) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.pending_collector = Some(collector.into_string());
+        Ok(())
+    }
+
+    fn remove_pending_collector(&mut self, id: &Id) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.pending_collector = None;
+        Ok(())
+    }
+
+    fn remove_percent(&mut self, _id: &Id) -> Result<(), Self::Error> {
+        self.percent = None;
+        Ok(())
+    }
+
+    fn remove_collector(&mut self, _id: &Id) -> Result<(), Self::Error> {
+        self.collector = None;
+        Ok(())
+    }
+
+    fn set_campaign(&mut self, id: &Id, campaign: DappCampaign) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.campaign = Some(campaign);
+        Ok(())
+    }
+
+    fn set_max_invocations_per_code(
+        &mut self,
+        id: &Id,
+        max: NonZeroU64,
+    ) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.max_invocations_per_code = Some(max);
+        Ok(())
+    }
+
+    fn set_dapp_frozen(&mut self, id: &Id, frozen: bool) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.dapp_frozen = frozen;
+        Ok(())
+    }
+
+    fn set_relayer(&mut self, id: &Id, relayer: Id) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(id)?);
+        self.relayer = Some(relayer.into_string());
+        Ok(())
+    }
+
+    fn remove_relayer(&mut self, _id: &Id) -> Result<(), Self::Error> {
+        self.relayer = None;
+        Ok(())
+    }
+
+    fn reset_dapp_accounting(&mut self, _id: &Id) -> Result<(), Self::Error> {
+        self.code_total_earnings = self
+            .code_total_earnings
+            .saturating_sub(self.code_dapp_earnings);
+        self.code_total_collected = self
+            .code_total_collected
+            .saturating_sub(self.code_dapp_collected);
+        self.code_dapp_earnings = 0;
+        self.code_dapp_collected = 0;
+        self.dapp_reffered_invocations = 0;
+        self.dapp_contributions = 0;
+        self.dapp_total_collected = 0;
+        Ok(())
+    }
 }
 
 pub const SELF_ID: &str = "self";
+pub const BLOCK_HEIGHT: u64 = 12345;
+pub const BLOCK_TIME: u64 = 1_571_797_419;
 
 impl DappExternalQuery for MockApi {
     fn self_id(&self) -> Result<Id, Self::Error> {
@@ -83,18 +206,49 @@ impl DappExternalQuery for MockApi {
             .map_or_else(|| Id::from(SELF_ID), Id::from))
     }
 
+    fn rewards_pot_dapp(&self, _id: &Id) -> Result<Id, Self::Error> {
+        // defaults to the pot being created for the activated dApp
+        let dapp = self
+            .rewards_pot_dapp
+            .as_deref()
+            .or(self.dapp.as_ref().map(|(id, _)| id.as_str()))
+            .unwrap_or_default();
+
+        Ok(Id::from(dapp))
+    }
+
     fn current_fee(&self, _id: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
         Ok(self.current_fee)
     }
+
+    fn current_height(&self) -> Result<u64, Self::Error> {
+        Ok(BLOCK_HEIGHT)
+    }
+
+    fn current_time(&self) -> Result<u64, Self::Error> {
+        Ok(BLOCK_TIME)
+    }
 }
 
+#[cfg(test)]
+pub mod accept_collector;
 #[cfg(test)]
 pub mod activate;
 #[cfg(test)]
 pub mod configure;
 #[cfg(test)]
+pub mod configure_all;
+#[cfg(test)]
 pub mod deactivate;
 #[cfg(test)]
+pub mod handover_pots;
+#[cfg(test)]
+pub mod rollback_activation;
+#[cfg(test)]
+pub mod set_campaign;
+#[cfg(test)]
 pub mod set_fee;
 #[cfg(test)]
+pub mod set_relayer;
+#[cfg(test)]
 pub mod set_rewards_pot;
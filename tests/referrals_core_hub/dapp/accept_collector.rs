@@ -0,0 +1,52 @@
+use referrals_core::hub::{dapp, MutableDappStore};
+
+use crate::check;
+use crate::expect;
+
+use super::*;
+
+#[test]
+pub fn works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    api.set_pending_collector(&Id::from("dapp"), Id::from("new_collector"))
+        .unwrap();
+
+    dapp::accept_collector(&mut api, Id::from("new_collector"), &Id::from("dapp")).unwrap();
+
+    assert_eq!(api.collector, Some("new_collector".to_owned()));
+    assert_eq!(api.pending_collector, None);
+}
+
+#[test]
+pub fn not_registered_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res =
+        dapp::accept_collector(&mut api, Id::from("new_collector"), &Id::from("dapp")).unwrap_err();
+
+    check(res, expect!["dapp not activated - dapp"]);
+}
+
+#[test]
+pub fn sender_not_nominated_fails() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    let res =
+        dapp::accept_collector(&mut api, Id::from("new_collector"), &Id::from("dapp")).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - new_collector is not the pending collector of dApp dapp"],
+    );
+
+    api.set_pending_collector(&Id::from("dapp"), Id::from("new_collector"))
+        .unwrap();
+
+    let res = dapp::accept_collector(&mut api, Id::from("bob"), &Id::from("dapp")).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - bob is not the pending collector of dApp dapp"],
+    );
+}
@@ -1,5 +1,12 @@
-use referrals_core::hub::dapp;
+use kv_storage::KvStore;
+use referrals_core::hub::{
+    audit, collect, dapp, referral, DappsQuery, DeactivationMode, MutableDappStore,
+    ReadonlyCollectStore, ReadonlyReferralStore,
+};
+use referrals_core::IdRef;
+use referrals_storage::testing::MemApi;
 
+use crate::referrals_storage::{Repo, RonSerde};
 use crate::{check, expect, pretty};
 
 use super::*;
@@ -14,10 +21,14 @@ pub fn works() {
         "dapp".to_owned(),
         nzp!(100),
         Id::from("collector"),
+        None,
     )
     .unwrap();
 
-    check(pretty(&res), expect![[r#"CreateRewardsPot(("dapp"))"#]]);
+    check(
+        pretty(&res),
+        expect![[r#"Cmd(CreateRewardsPot(("dapp")))"#]],
+    );
 
     check(
         pretty(&api),
@@ -41,10 +52,39 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
 
+#[test]
+pub fn with_fee_works() {
+    let mut api = MockApi::default().rewards_admin(SELF_ID);
+
+    let res = dapp::activate(
+        &mut api,
+        Id::from("dapp"),
+        "dapp".to_owned(),
+        nzp!(100),
+        Id::from("collector"),
+        Some(nz!(1000)),
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            MultiCmd([
+              CreateRewardsPot(("dapp")),
+              SetDappFee(
+                dapp: ("dapp"),
+                amount: 1000,
+              ),
+            ])"#]],
+    );
+}
+
 #[test]
 pub fn already_registered_fails() {
     let mut api = MockApi::default().dapp("dapp");
@@ -55,12 +95,32 @@ pub fn already_registered_fails() {
         "dapp".to_owned(),
         nzp!(100),
         Id::from("collector"),
+        None,
     )
     .unwrap_err();
 
     check(res, expect!["already registered"]);
 }
 
+#[test]
+pub fn name_taken_fails() {
+    let mut api = MockApi::default().dapp("dapp").rewards_admin(SELF_ID);
+
+    let res = dapp::activate(
+        &mut api,
+        Id::from("other"),
+        "dapp".to_owned(),
+        nzp!(100),
+        Id::from("collector"),
+        None,
+    )
+    .unwrap_err();
+
+    check(res, expect!["name taken - dapp"]);
+
+    check(pretty(&api.dapp), expect![[r#"Some(("dapp", "dapp"))"#]]);
+}
+
 #[test]
 pub fn not_referrals_admin_fails() {
     let mut api = MockApi::default().rewards_admin("bob");
@@ -71,8 +131,282 @@ pub fn not_referrals_admin_fails() {
         "dapp".to_owned(),
         nzp!(100),
         Id::from("collector"),
+        None,
     )
     .unwrap_err();
 
     check(res, expect!["invalid rewards admin"]);
 }
+
+#[test]
+pub fn tracking_only_works() {
+    let mut api = MockApi::default().rewards_admin(SELF_ID);
+
+    let res = dapp::activate_tracking_only(
+        &mut api,
+        Id::from("dapp"),
+        "dapp".to_owned(),
+        Id::from("collector"),
+    )
+    .unwrap();
+
+    check(pretty(&res), expect![[r#"CreateRewardsPot(("dapp"))"#]]);
+
+    check(
+        pretty(&api),
+        expect![[r#"
+            (
+              dapp: Some(("dapp", "dapp")),
+              percent: None,
+              collector: Some("collector"),
+              rewards_pot: None,
+              rewards_pot_admin: None,
+              rewards_admin: Some("self"),
+              current_fee: None,
+              referral_code: None,
+              referral_code_owner: None,
+              latest_referral_code: None,
+              dapp_reffered_invocations: 0,
+              code_total_earnings: 0,
+              code_dapp_earnings: 0,
+              dapp_contributions: 0,
+              code_total_collected: 0,
+              code_dapp_collected: 0,
+              dapp_total_collected: 0,
+              dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
+            )"#]],
+    );
+}
+
+#[test]
+pub fn reactivation_resets_earnings_works() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default()
+        .rewards_admin(SELF_ID)
+        .rewards_pot_admin(SELF_ID)
+        .rewards_pot_dapp("dapp")
+        .current_fee(nz!(1000))
+        .dapp_total_rewards(nz!(2000));
+
+    let dapp = Id::from("dapp");
+
+    let code = referral::register(&mut api, Id::from("referrer")).unwrap();
+
+    let activate = |api: &mut MemApi<KvStore<RonSerde, Repo>>| {
+        dapp::activate(
+            api,
+            dapp.clone(),
+            "dapp".to_owned(),
+            nzp!(50),
+            Id::from("collector"),
+            None,
+        )
+        .unwrap()
+    };
+
+    activate(&mut api);
+
+    dapp::set_rewards_pot(&mut api, dapp.clone(), Id::from("rewards_pot")).unwrap();
+
+    for _ in 0..2 {
+        referral::record(&mut api, &dapp, None, code).unwrap();
+    }
+
+    check(
+        api.dapp_earnings(dapp.as_id_ref(), code).unwrap().unwrap(),
+        expect!["1000"],
+    );
+
+    // a dApp may only be purged once it's referrers have collected
+    collect::referrer(&mut api, Id::from("referrer"), &dapp, code, None).unwrap();
+
+    dapp::deactivate(
+        &mut api,
+        &dapp,
+        dapp.clone(),
+        dapp.clone(),
+        dapp.clone(),
+        DeactivationMode::Purge,
+    )
+    .unwrap();
+
+    // the pot from the first activation is taken up again rather than instantiated anew
+    check(
+        pretty(&activate(&mut api)),
+        expect![[r#"
+            Cmd(SetRewardsRecipient(
+              dapp: ("dapp"),
+              recipient: ("rewards_pot"),
+            ))"#]],
+    );
+
+    assert!(api.dapp_earnings(dapp.as_id_ref(), code).unwrap().is_none());
+    assert!(api
+        .dapp_contributions(IdRef::from("dapp"))
+        .unwrap()
+        .is_none());
+    assert!(api.total_earnings(code).unwrap().is_none());
+
+    referral::record(&mut api, &dapp, None, code).unwrap();
+
+    // only the referral since re-activation earns
+    check(
+        api.dapp_earnings(dapp.as_id_ref(), code).unwrap().unwrap(),
+        expect!["500"],
+    );
+    check(api.total_earnings(code).unwrap().unwrap(), expect!["500"]);
+    check(api.code_dapp_count(code).unwrap(), expect!["1"]);
+
+    // the pot's rewards paid out before re-activation are not owed to the dApp again
+    check(
+        format!("{:?}", collect::dapp_owed(&api, &dapp).unwrap()),
+        expect!["Owed { owed: Some(500), already_collected: Some(1000) }"],
+    );
+
+    audit::check_invariants(&api).unwrap();
+}
+
+#[test]
+pub fn reactivation_resets_invocation_caps_works() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default()
+        .rewards_admin(SELF_ID)
+        .rewards_pot_admin(SELF_ID)
+        .rewards_pot_dapp("dapp")
+        .current_fee(nz!(1000))
+        .dapp_total_rewards(nz!(2000));
+
+    let dapp = Id::from("dapp");
+
+    let code = referral::register(&mut api, Id::from("referrer")).unwrap();
+
+    let activate = |api: &mut MemApi<KvStore<RonSerde, Repo>>| {
+        dapp::activate(
+            api,
+            dapp.clone(),
+            "dapp".to_owned(),
+            nzp!(50),
+            Id::from("collector"),
+            None,
+        )
+        .unwrap();
+
+        api.set_max_invocations_per_code(&dapp, nz!(2)).unwrap();
+    };
+
+    activate(&mut api);
+
+    dapp::set_rewards_pot(&mut api, dapp.clone(), Id::from("rewards_pot")).unwrap();
+
+    let capped: Vec<bool> = (0..3)
+        .map(|_| referral::record(&mut api, &dapp, None, code).unwrap())
+        .collect();
+
+    check(format!("{capped:?}"), expect!["[false, false, true]"]);
+
+    collect::referrer(&mut api, Id::from("referrer"), &dapp, code, None).unwrap();
+
+    dapp::deactivate(
+        &mut api,
+        &dapp,
+        dapp.clone(),
+        dapp.clone(),
+        dapp.clone(),
+        DeactivationMode::Purge,
+    )
+    .unwrap();
+
+    activate(&mut api);
+
+    check(
+        api.invocations(dapp.as_id_ref(), code).unwrap(),
+        expect!["0"],
+    );
+    check(api.dapp_discrete_referrers(&dapp).unwrap(), expect!["0"]);
+
+    // the code may earn up to the cap once more
+    let capped: Vec<bool> = (0..3)
+        .map(|_| referral::record(&mut api, &dapp, None, code).unwrap())
+        .collect();
+
+    check(format!("{capped:?}"), expect!["[false, false, true]"]);
+    check(
+        api.dapp_earnings(dapp.as_id_ref(), code).unwrap().unwrap(),
+        expect!["1000"],
+    );
+    check(api.dapp_discrete_referrers(&dapp).unwrap(), expect!["1"]);
+
+    audit::check_invariants(&api).unwrap();
+}
+
+#[test]
+pub fn reactivation_then_purge_works() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default()
+        .rewards_admin(SELF_ID)
+        .rewards_pot_admin(SELF_ID)
+        .rewards_pot_dapp("dapp")
+        .current_fee(nz!(1000))
+        .dapp_total_rewards(nz!(2000));
+
+    let dapp = Id::from("dapp");
+
+    let code = referral::register(&mut api, Id::from("referrer")).unwrap();
+
+    let activate = |api: &mut MemApi<KvStore<RonSerde, Repo>>| {
+        dapp::activate(
+            api,
+            dapp.clone(),
+            "dapp".to_owned(),
+            nzp!(50),
+            Id::from("collector"),
+            None,
+        )
+        .unwrap()
+    };
+
+    let purge = |api: &mut MemApi<KvStore<RonSerde, Repo>>| {
+        dapp::deactivate(
+            api,
+            &dapp,
+            dapp.clone(),
+            dapp.clone(),
+            dapp.clone(),
+            DeactivationMode::Purge,
+        )
+    };
+
+    activate(&mut api);
+
+    dapp::set_rewards_pot(&mut api, dapp.clone(), Id::from("rewards_pot")).unwrap();
+
+    for _ in 0..2 {
+        referral::record(&mut api, &dapp, None, code).unwrap();
+    }
+
+    collect::referrer(&mut api, Id::from("referrer"), &dapp, code, None).unwrap();
+
+    purge(&mut api).unwrap();
+
+    activate(&mut api);
+
+    assert!(api.dapp_referrers_collected(&dapp).unwrap().is_none());
+
+    referral::record(&mut api, &dapp, None, code).unwrap();
+
+    // collections from the previous activation do not cover the new earnings
+    check(
+        purge(&mut api).unwrap_err(),
+        expect!["uncollected earnings - referrers of dApp dapp have earnings yet to collect"],
+    );
+
+    collect::referrer(&mut api, Id::from("referrer"), &dapp, code, None).unwrap();
+
+    check(
+        api.dapp_referrers_collected(&dapp).unwrap().unwrap(),
+        expect!["500"],
+    );
+
+    purge(&mut api).unwrap();
+
+    audit::check_invariants(&api).unwrap();
+}
@@ -1,6 +1,9 @@
+use kv_storage::KvStore;
 use referrals_core::hub::dapp;
-use referrals_core::hub::DappMetadata;
+use referrals_core::hub::{DappMetadata, DappRepoUrl, MutableDappStore, ReadonlyDappStore};
+use referrals_storage::testing::MemApi;
 
+use crate::referrals_storage::{Repo, RonSerde};
 use crate::{check, expect, pretty};
 
 use super::*;
@@ -14,9 +17,12 @@ pub fn works() {
         &Id::from("collector"),
         &Id::from("dapp"),
         DappMetadata {
+            name: None,
             percent: Some(nzp!(50)),
             collector: Some(Id::from("new_collector")),
-            repo_url: Some("repo_url".to_owned()),
+            repo_url: Some(DappRepoUrl::Set("repo_url".to_owned())),
+            allow_self_referral: None,
+            max_invocations_per_code: None,
         },
     )
     .unwrap();
@@ -26,9 +32,12 @@ pub fn works() {
         &Id::from("dapp"),
         &Id::from("dapp"),
         DappMetadata {
+            name: None,
             percent: Some(nzp!(50)),
             collector: Some(Id::from("new_collector")),
-            repo_url: Some("repo_url".to_owned()),
+            repo_url: Some(DappRepoUrl::Set("repo_url".to_owned())),
+            allow_self_referral: None,
+            max_invocations_per_code: None,
         },
     )
     .unwrap();
@@ -39,7 +48,7 @@ pub fn works() {
             (
               dapp: Some(("dapp", "dapp")),
               percent: Some(50),
-              collector: Some("new_collector"),
+              collector: Some("collector"),
               rewards_pot: None,
               rewards_pot_admin: None,
               rewards_admin: None,
@@ -55,6 +64,8 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: Some("new_collector"),
             )"#]],
     );
 }
@@ -68,14 +79,17 @@ pub fn not_registered_fails() {
         &Id::from("dapp"),
         &Id::from("dapp"),
         DappMetadata {
+            name: None,
             percent: Some(nzp!(50)),
             collector: Some(Id::from("new_collector")),
-            repo_url: Some("repo_url".to_owned()),
+            repo_url: Some(DappRepoUrl::Set("repo_url".to_owned())),
+            allow_self_referral: None,
+            max_invocations_per_code: None,
         },
     )
     .unwrap_err();
 
-    check(res, expect!["dapp not activated"]);
+    check(res, expect!["dapp not activated - dapp"]);
 }
 
 #[test]
@@ -87,12 +101,154 @@ pub fn sender_not_dapp_or_collector_fails() {
         &Id::from("bob"),
         &Id::from("dapp"),
         DappMetadata {
+            name: None,
             percent: Some(nzp!(50)),
             collector: Some(Id::from("new_collector")),
-            repo_url: Some("repo_url".to_owned()),
+            repo_url: Some(DappRepoUrl::Set("repo_url".to_owned())),
+            allow_self_referral: None,
+            max_invocations_per_code: None,
         },
     )
     .unwrap_err();
 
-    check(res, expect!["unauthorised"]);
+    check(
+        res,
+        expect!["unauthorised - bob is not dApp dapp or it's collector"],
+    );
+}
+
+#[test]
+pub fn max_invocations_per_code_works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    dapp::configure(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp"),
+        DappMetadata {
+            name: None,
+            percent: None,
+            collector: None,
+            repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: NonZeroU64::new(10),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(api.max_invocations_per_code, NonZeroU64::new(10));
+}
+
+#[test]
+pub fn repo_url_works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    let metadata = |repo_url| DappMetadata {
+        name: None,
+        percent: None,
+        collector: None,
+        repo_url,
+        allow_self_referral: None,
+        max_invocations_per_code: None,
+    };
+
+    dapp::configure(
+        &mut api,
+        &Id::from("dapp"),
+        &Id::from("dapp"),
+        metadata(Some(DappRepoUrl::Set("https://repo.com".to_owned()))),
+    )
+    .unwrap();
+
+    assert_eq!(api.repo_url.as_deref(), Some("https://repo.com"));
+
+    // leaving the repo url unset keeps the current one
+    dapp::configure(
+        &mut api,
+        &Id::from("dapp"),
+        &Id::from("dapp"),
+        metadata(None),
+    )
+    .unwrap();
+
+    assert_eq!(api.repo_url.as_deref(), Some("https://repo.com"));
+
+    dapp::configure(
+        &mut api,
+        &Id::from("dapp"),
+        &Id::from("dapp"),
+        metadata(Some(DappRepoUrl::Clear)),
+    )
+    .unwrap();
+
+    assert_eq!(api.repo_url, None);
+}
+
+#[test]
+pub fn name_works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    dapp::configure(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp"),
+        DappMetadata {
+            name: Some("new_name".to_owned()),
+            percent: None,
+            collector: None,
+            repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+        },
+    )
+    .unwrap();
+
+    check(
+        pretty(&api.dapp),
+        expect![[r#"Some(("dapp", "new_name"))"#]],
+    );
+}
+
+#[test]
+pub fn name_taken_fails() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default();
+
+    for dapp in ["dapp1", "dapp2"] {
+        api.add_dapp(&Id::from(dapp), dapp.to_owned()).unwrap();
+        api.set_collector(&Id::from(dapp), Id::from("collector"))
+            .unwrap();
+    }
+
+    let rename = |name: &str| DappMetadata {
+        name: Some(name.to_owned()),
+        percent: None,
+        collector: None,
+        repo_url: None,
+        allow_self_referral: None,
+        max_invocations_per_code: None,
+    };
+
+    let res = dapp::configure(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp2"),
+        rename("dapp1"),
+    )
+    .unwrap_err();
+
+    check(res, expect!["name taken - dapp1"]);
+
+    // keeping it's own name is not taking it
+    dapp::configure(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp1"),
+        rename("dapp1"),
+    )
+    .unwrap();
+
+    check(
+        format!("{:?}", api.dapp_by_name("dapp1").unwrap()),
+        expect![[r#"Some(Id("dapp1"))"#]],
+    );
 }
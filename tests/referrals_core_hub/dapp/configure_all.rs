@@ -0,0 +1,154 @@
+use kv_storage::KvStore;
+use referrals_core::hub::dapp;
+use referrals_core::hub::{DappMetadata, MutableDappStore, ReadonlyDappStore};
+use referrals_storage::testing::MemApi;
+
+use crate::referrals_storage::{Repo, RonSerde};
+use crate::{check, expect, pretty};
+
+use super::*;
+
+fn percent(percent: u8) -> DappMetadata {
+    DappMetadata {
+        name: None,
+        percent: NonZeroPercent::new(percent),
+        collector: None,
+        repo_url: None,
+        allow_self_referral: None,
+        max_invocations_per_code: None,
+    }
+}
+
+#[test]
+pub fn works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    dapp::configure_all(
+        &mut api,
+        &Id::from("collector"),
+        vec![(Id::from("dapp"), percent(50))],
+    )
+    .unwrap();
+
+    check(
+        api.percent(&Id::from("dapp")).unwrap().unwrap().to_u8(),
+        expect!["50"],
+    );
+}
+
+#[test]
+pub fn several_dapps_works() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default();
+
+    for dapp in ["dapp1", "dapp2"] {
+        let dapp = Id::from(dapp);
+        api.add_dapp(&dapp, dapp.as_str().to_owned()).unwrap();
+        api.set_percent(&dapp, nzp!(10)).unwrap();
+        api.set_collector(&dapp, Id::from("collector")).unwrap();
+    }
+
+    dapp::configure_all(
+        &mut api,
+        &Id::from("collector"),
+        vec![
+            (Id::from("dapp1"), percent(20)),
+            (Id::from("dapp2"), percent(30)),
+        ],
+    )
+    .unwrap();
+
+    let percents: Vec<_> = ["dapp1", "dapp2"]
+        .into_iter()
+        .map(|dapp| api.percent(&Id::from(dapp)).unwrap().unwrap().to_u8())
+        .collect();
+
+    check(format!("{percents:?}"), expect!["[20, 30]"]);
+}
+
+#[test]
+pub fn not_registered_entry_rejects_batch() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    let before = pretty(&api);
+
+    let res = dapp::configure_all(
+        &mut api,
+        &Id::from("collector"),
+        vec![
+            (Id::from("dapp"), percent(50)),
+            (Id::from("other"), percent(50)),
+        ],
+    )
+    .unwrap_err();
+
+    check(res, expect!["dapp not activated - other"]);
+
+    // the valid entry ahead of the invalid one is not applied
+    assert_eq!(pretty(&api), before);
+}
+
+#[test]
+pub fn unauthorized_entry_rejects_batch() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default();
+
+    for (dapp, collector) in [("dapp1", "collector"), ("dapp2", "someone_else")] {
+        let dapp = Id::from(dapp);
+        api.add_dapp(&dapp, dapp.as_str().to_owned()).unwrap();
+        api.set_percent(&dapp, nzp!(10)).unwrap();
+        api.set_collector(&dapp, Id::from(collector)).unwrap();
+    }
+
+    let res = dapp::configure_all(
+        &mut api,
+        &Id::from("collector"),
+        vec![
+            (Id::from("dapp1"), percent(20)),
+            (Id::from("dapp2"), percent(30)),
+        ],
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - collector is not dApp dapp2 or it's collector"],
+    );
+
+    check(
+        api.percent(&Id::from("dapp1")).unwrap().unwrap().to_u8(),
+        expect!["10"],
+    );
+}
+
+#[test]
+pub fn same_name_in_batch_rejects_batch() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default();
+
+    for dapp in ["dapp1", "dapp2"] {
+        let dapp = Id::from(dapp);
+        api.add_dapp(&dapp, dapp.as_str().to_owned()).unwrap();
+        api.set_collector(&dapp, Id::from("collector")).unwrap();
+    }
+
+    let rename = |name: &str| DappMetadata {
+        name: Some(name.to_owned()),
+        percent: None,
+        collector: None,
+        repo_url: None,
+        allow_self_referral: None,
+        max_invocations_per_code: None,
+    };
+
+    let res = dapp::configure_all(
+        &mut api,
+        &Id::from("collector"),
+        vec![
+            (Id::from("dapp1"), rename("same")),
+            (Id::from("dapp2"), rename("same")),
+        ],
+    )
+    .unwrap_err();
+
+    check(res, expect!["name taken - same"]);
+
+    assert_eq!(api.dapp_by_name("same").unwrap(), None);
+}
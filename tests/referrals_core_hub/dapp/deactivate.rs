@@ -1,4 +1,5 @@
-use referrals_core::hub::dapp;
+use referrals_core::hub::{dapp, DeactivationMode, MutableReferralStore, ReadonlyDappStore};
+use referrals_core::IdRef;
 
 use crate::{check, expect, pretty};
 
@@ -18,18 +19,25 @@ pub fn works() {
         Id::from("dapp"),
         Id::from("new_admin"),
         Id::from("new_recipient"),
+        DeactivationMode::Purge,
     )
     .unwrap();
 
     check(
         pretty(&res),
         expect![[r#"
-            (WithdrawPending(("rewards_pot")), SetRewardsRecipient(
-              dapp: ("dapp"),
-              recipient: ("new_recipient"),
-            ), SetRewardsAdmin(
-              dapp: ("dapp"),
-              admin: ("new_admin"),
+            Cmd(WithdrawPendingThen(
+              pot: ("rewards_pot"),
+              then: [
+                SetRewardsRecipient(
+                  dapp: ("dapp"),
+                  recipient: ("new_recipient"),
+                ),
+                SetRewardsAdmin(
+                  dapp: ("dapp"),
+                  admin: ("new_admin"),
+                ),
+              ],
             ))"#]],
     );
 
@@ -42,10 +50,11 @@ pub fn works() {
         Id::from("dapp"),
         Id::from("new_admin"),
         Id::from("new_recipient"),
+        DeactivationMode::Purge,
     )
     .unwrap();
 
-    assert_eq!(res, from_dapp_res);
+    assert_eq!(pretty(&res), pretty(&from_dapp_res));
 
     check(
         pretty(&api),
@@ -69,6 +78,8 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
@@ -85,10 +96,11 @@ pub fn not_registered_fails() {
         Id::from("dapp"),
         Id::from("new_admin"),
         Id::from("new_recipient"),
+        DeactivationMode::Freeze,
     )
     .unwrap_err();
 
-    check(res, expect!["dapp not activated"]);
+    check(res, expect!["dapp not activated - dapp"]);
 }
 
 #[test]
@@ -104,8 +116,120 @@ pub fn sender_not_dapp_or_collector_fails() {
         Id::from("dapp"),
         Id::from("new_admin"),
         Id::from("new_recipient"),
+        DeactivationMode::Freeze,
     )
     .unwrap_err();
 
-    check(res, expect!["unauthorised"]);
+    check(
+        res,
+        expect!["unauthorised - bob is not dApp dapp or it's collector"],
+    );
+}
+
+#[test]
+pub fn freeze_keeps_dapp() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot");
+
+    let res = dapp::deactivate(
+        &mut api,
+        &Id::from("collector"),
+        Id::from("dapp"),
+        Id::from("new_admin"),
+        Id::from("new_recipient"),
+        DeactivationMode::Freeze,
+    )
+    .unwrap();
+
+    // rewards admin rights are relinquished as when purged
+    check(
+        pretty(&res),
+        expect![[r#"
+            Cmd(WithdrawPendingThen(
+              pot: ("rewards_pot"),
+              then: [
+                SetRewardsRecipient(
+                  dapp: ("dapp"),
+                  recipient: ("new_recipient"),
+                ),
+                SetRewardsAdmin(
+                  dapp: ("dapp"),
+                  admin: ("new_admin"),
+                ),
+              ],
+            ))"#]],
+    );
+
+    assert!(api.dapp_exists(&Id::from("dapp")).unwrap());
+    assert!(api.dapp_frozen(&Id::from("dapp")).unwrap());
+
+    let res = dapp::deactivate(
+        &mut api,
+        &Id::from("collector"),
+        Id::from("dapp"),
+        Id::from("new_admin"),
+        Id::from("new_recipient"),
+        DeactivationMode::Freeze,
+    )
+    .unwrap_err();
+
+    check(res, expect!["dapp deactivated - dapp"]);
+}
+
+#[test]
+pub fn purge_frozen_dapp_only_removes() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot")
+        .referral_relayer("relayer")
+        .frozen(true);
+
+    let res = dapp::deactivate(
+        &mut api,
+        &Id::from("collector"),
+        Id::from("dapp"),
+        Id::from("new_admin"),
+        Id::from("new_recipient"),
+        DeactivationMode::Purge,
+    )
+    .unwrap();
+
+    check(pretty(&res), expect!["Empty"]);
+
+    assert!(!api.dapp_exists(&Id::from("dapp")).unwrap());
+    assert!(!api.dapp_frozen(&Id::from("dapp")).unwrap());
+    assert_eq!(api.relayer(&Id::from("dapp")).unwrap(), None);
+}
+
+#[test]
+pub fn purge_with_uncollected_earnings_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot");
+
+    api.set_dapp_contributions(IdRef::from("dapp"), nz!(500))
+        .unwrap();
+
+    let before = pretty(&api);
+
+    let res = dapp::deactivate(
+        &mut api,
+        &Id::from("collector"),
+        Id::from("dapp"),
+        Id::from("new_admin"),
+        Id::from("new_recipient"),
+        DeactivationMode::Purge,
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["uncollected earnings - referrers of dApp dapp have earnings yet to collect"],
+    );
+
+    assert_eq!(pretty(&api), before);
 }
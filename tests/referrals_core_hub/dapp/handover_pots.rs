@@ -0,0 +1,81 @@
+use referrals_core::hub::dapp;
+
+use crate::{check, expect, pretty};
+
+use super::*;
+
+#[test]
+pub fn works() {
+    let api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot");
+
+    let res = dapp::handover_pots(
+        &api,
+        &Id::from("collector"),
+        vec![Id::from("dapp")],
+        &Id::from("new_hub"),
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            [
+              UpdateRewardsPotAdmin(
+                pot: ("rewards_pot"),
+                admin: ("new_hub"),
+              ),
+            ]"#]],
+    );
+
+    let from_hub_res = dapp::handover_pots(
+        &api,
+        &Id::from(SELF_ID),
+        vec![Id::from("dapp")],
+        &Id::from("new_hub"),
+    )
+    .unwrap();
+
+    assert_eq!(res, from_hub_res);
+}
+
+#[test]
+pub fn sender_not_hub_or_collector_fails() {
+    let api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot");
+
+    let res = dapp::handover_pots(
+        &api,
+        &Id::from("dapp"),
+        vec![Id::from("dapp")],
+        &Id::from("new_hub"),
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - dapp is not the hub or it's collector"],
+    );
+}
+
+#[test]
+pub fn rewards_pot_not_set_fails() {
+    let api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot");
+
+    let res = dapp::handover_pots(
+        &api,
+        &Id::from("collector"),
+        vec![Id::from("dapp"), Id::from("unknown")],
+        &Id::from("new_hub"),
+    )
+    .unwrap_err();
+
+    check(res, expect!["rewards pot not set - unknown"]);
+}
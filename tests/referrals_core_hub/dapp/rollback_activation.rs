@@ -0,0 +1,74 @@
+use referrals_core::hub::dapp;
+
+use crate::{check, expect, pretty};
+
+use super::*;
+
+#[test]
+pub fn works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    api.percent = Some(50);
+
+    dapp::rollback_activation(&mut api, &Id::from(SELF_ID), &Id::from("dapp")).unwrap();
+
+    check(
+        pretty(&api),
+        expect![[r#"
+            (
+              dapp: None,
+              percent: None,
+              collector: None,
+              rewards_pot: None,
+              rewards_pot_admin: None,
+              rewards_admin: None,
+              current_fee: None,
+              referral_code: None,
+              referral_code_owner: None,
+              latest_referral_code: None,
+              dapp_reffered_invocations: 0,
+              code_total_earnings: 0,
+              code_dapp_earnings: 0,
+              dapp_contributions: 0,
+              code_total_collected: 0,
+              code_dapp_collected: 0,
+              dapp_total_collected: 0,
+              dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
+            )"#]],
+    );
+}
+
+#[test]
+pub fn sender_not_self_fails() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    let res =
+        dapp::rollback_activation(&mut api, &Id::from("dapp"), &Id::from("dapp")).unwrap_err();
+
+    check(res, expect!["unauthorised - dapp is not the hub"]);
+}
+
+#[test]
+pub fn not_registered_fails() {
+    let mut api = MockApi::default();
+
+    let res =
+        dapp::rollback_activation(&mut api, &Id::from(SELF_ID), &Id::from("dapp")).unwrap_err();
+
+    check(res, expect!["dapp not activated - dapp"]);
+}
+
+#[test]
+pub fn rewards_pot_set_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .rewards_pot("rewards_pot");
+
+    let res =
+        dapp::rollback_activation(&mut api, &Id::from(SELF_ID), &Id::from("dapp")).unwrap_err();
+
+    check(res, expect!["rewards pot already set"]);
+}
@@ -0,0 +1,142 @@
+use std::num::NonZeroU64;
+
+use referrals_core::hub::{dapp, DappCampaign};
+
+use crate::{check, expect, pretty};
+
+use super::*;
+
+fn campaign() -> DappCampaign {
+    DappCampaign {
+        multiplier_num: NonZeroU64::new(2).unwrap(),
+        multiplier_den: NonZeroU64::new(1).unwrap(),
+        start_height: 12340,
+        end_height: 12350,
+    }
+}
+
+#[test]
+pub fn works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    dapp::set_campaign(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp"),
+        campaign(),
+    )
+    .unwrap();
+
+    check(
+        pretty(&api),
+        expect![[r#"
+            (
+              dapp: Some(("dapp", "dapp")),
+              percent: None,
+              collector: Some("collector"),
+              rewards_pot: None,
+              rewards_pot_admin: None,
+              rewards_admin: None,
+              current_fee: None,
+              referral_code: None,
+              referral_code_owner: None,
+              latest_referral_code: None,
+              dapp_reffered_invocations: 0,
+              code_total_earnings: 0,
+              code_dapp_earnings: 0,
+              dapp_contributions: 0,
+              code_total_collected: 0,
+              code_dapp_collected: 0,
+              dapp_total_collected: 0,
+              dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
+              campaign: Some((
+                multiplier_num: 2,
+                multiplier_den: 1,
+                start_height: 12340,
+                end_height: 12350,
+              )),
+            )"#]],
+    );
+
+    dapp::set_campaign(&mut api, &Id::from("dapp"), &Id::from("dapp"), campaign()).unwrap();
+}
+
+#[test]
+pub fn not_registered_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res = dapp::set_campaign(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp"),
+        campaign(),
+    )
+    .unwrap_err();
+
+    check(res, expect!["dapp not activated - dapp"]);
+}
+
+#[test]
+pub fn sender_not_dapp_or_collector_fails() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    let res =
+        dapp::set_campaign(&mut api, &Id::from("bob"), &Id::from("dapp"), campaign()).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - bob is not dApp dapp or it's collector"],
+    );
+}
+
+#[test]
+pub fn apply_to_caps_at_fee() {
+    let campaign = DappCampaign {
+        multiplier_num: NonZeroU64::new(3).unwrap(),
+        ..campaign()
+    };
+
+    check(
+        campaign.apply_to(nz!(300), nz!(1000)).unwrap(),
+        expect!["900"],
+    );
+    check(
+        campaign.apply_to(nz!(500), nz!(1000)).unwrap(),
+        expect!["1000"],
+    );
+
+    let campaign = DappCampaign {
+        multiplier_num: NonZeroU64::new(u64::MAX).unwrap(),
+        ..campaign
+    };
+
+    let share = NonZeroU128::new(u128::MAX / 2).unwrap();
+    let fee = NonZeroU128::new(u128::MAX).unwrap();
+
+    // the multiplied share overflows 128-bits, clamping to the fee
+    assert_eq!(campaign.apply_to(share, fee), Some(fee));
+}
+
+#[test]
+pub fn apply_to_fractional_multiplier_works() {
+    let campaign = DappCampaign {
+        multiplier_num: NonZeroU64::new(3).unwrap(),
+        multiplier_den: NonZeroU64::new(2).unwrap(),
+        ..campaign()
+    };
+
+    check(
+        campaign.apply_to(nz!(501), nz!(1000)).unwrap(),
+        expect!["751"],
+    );
+
+    let campaign = DappCampaign {
+        multiplier_num: NonZeroU64::new(1).unwrap(),
+        multiplier_den: NonZeroU64::new(1000).unwrap(),
+        ..campaign
+    };
+
+    assert!(campaign.apply_to(nz!(999), nz!(1000)).is_none());
+}
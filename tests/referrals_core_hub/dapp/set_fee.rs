@@ -52,6 +52,8 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
@@ -68,7 +70,7 @@ pub fn not_registered_fails() {
     )
     .unwrap_err();
 
-    check(res, expect!["dapp not activated"]);
+    check(res, expect!["dapp not activated - dapp"]);
 }
 
 #[test]
@@ -77,5 +79,8 @@ pub fn sender_not_dapp_or_collector_fails() {
 
     let res = dapp::set_fee(&mut api, &Id::from("bob"), Id::from("dapp"), nz!(1000)).unwrap_err();
 
-    check(res, expect!["unauthorised"]);
+    check(
+        res,
+        expect!["unauthorised - bob is not dApp dapp or it's collector"],
+    );
 }
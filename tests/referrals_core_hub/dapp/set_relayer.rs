@@ -0,0 +1,91 @@
+use referrals_core::hub::{dapp, ReadonlyDappStore};
+
+use crate::{check, expect};
+
+use super::*;
+
+#[test]
+pub fn works() {
+    let mut api = MockApi::default().dapp("dapp").collector("collector");
+
+    dapp::set_relayer(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp"),
+        Some(Id::from("relayer")),
+    )
+    .unwrap();
+
+    assert_eq!(
+        api.relayer(&Id::from("dapp")).unwrap(),
+        Some(Id::from("relayer"))
+    );
+
+    // the dApp itself may replace the relayer
+    dapp::set_relayer(
+        &mut api,
+        &Id::from("dapp"),
+        &Id::from("dapp"),
+        Some(Id::from("other_relayer")),
+    )
+    .unwrap();
+
+    assert_eq!(
+        api.relayer(&Id::from("dapp")).unwrap(),
+        Some(Id::from("other_relayer"))
+    );
+}
+
+#[test]
+pub fn remove_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .referral_relayer("relayer");
+
+    dapp::set_relayer(&mut api, &Id::from("collector"), &Id::from("dapp"), None).unwrap();
+
+    assert_eq!(api.relayer(&Id::from("dapp")).unwrap(), None);
+}
+
+#[test]
+pub fn not_registered_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res = dapp::set_relayer(
+        &mut api,
+        &Id::from("collector"),
+        &Id::from("dapp"),
+        Some(Id::from("relayer")),
+    )
+    .unwrap_err();
+
+    check(res, expect!["dapp not activated - dapp"]);
+}
+
+#[test]
+pub fn sender_not_dapp_or_collector_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .referral_relayer("relayer");
+
+    // a relayer cannot nominate another
+    let res = dapp::set_relayer(
+        &mut api,
+        &Id::from("relayer"),
+        &Id::from("dapp"),
+        Some(Id::from("bob")),
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - relayer is not dApp dapp or it's collector"],
+    );
+
+    assert_eq!(
+        api.relayer(&Id::from("dapp")).unwrap(),
+        Some(Id::from("relayer"))
+    );
+}
@@ -41,6 +41,8 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
@@ -52,7 +54,7 @@ pub fn not_registered_fails() {
     let res =
         dapp::set_rewards_pot(&mut api, Id::from("dapp"), Id::from("rewards_pot")).unwrap_err();
 
-    check(res, expect!["dapp not activated"]);
+    check(res, expect!["dapp not activated - dapp"]);
 }
 
 #[test]
@@ -74,3 +76,15 @@ pub fn not_rewards_pot_admin_fails() {
 
     check(res, expect!["invalid rewards pot admin"]);
 }
+
+#[test]
+pub fn rewards_pot_for_other_dapp_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .rewards_pot_dapp("other_dapp");
+
+    let res =
+        dapp::set_rewards_pot(&mut api, Id::from("dapp"), Id::from("rewards_pot")).unwrap_err();
+
+    check(res, expect!["rewards pot belongs to another dApp"]);
+}
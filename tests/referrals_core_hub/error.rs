@@ -0,0 +1,106 @@
+use std::convert::Infallible;
+
+use referrals_core::hub::{AuthRequirement, Earner, Error};
+use referrals_core::Id;
+
+use crate::{check, expect};
+
+#[test]
+fn code_works() {
+    let codes: Vec<_> = [
+        Error::<Infallible>::Unauthorized {
+            sender: Id::from("sender"),
+            required: AuthRequirement::Hub,
+        },
+        Error::AlreadyRegistered,
+        Error::ReferralCodeNotRegistered(code!(1)),
+        Error::NothingToCollect(Earner::Dapp(Id::from("dapp"))),
+        Error::FeatureDisabled("record"),
+        Error::RewardsPotNotSet(Id::from("dapp")),
+        Error::BelowMinimumCollect {
+            amount: nz!(1),
+            minimum: nz!(2),
+        },
+        Error::SelfRegistration,
+        Error::DappDeactivated(Id::from("dapp")),
+        Error::UncollectedEarnings(Id::from("dapp")),
+        Error::NameTaken("dapp".to_owned()),
+        Error::DappNameNotRegistered("dapp".to_owned()),
+    ]
+    .iter()
+    .map(Error::code)
+    .collect();
+
+    check(
+        format!("{codes:?}"),
+        expect![
+            "[Some(1), Some(2), Some(4), Some(11), Some(14), Some(16), Some(18), Some(19), Some(20), Some(21), Some(22), Some(23)]"
+        ],
+    );
+}
+
+#[test]
+fn coded_works() {
+    check(
+        Error::<Infallible>::Unauthorized {
+            sender: Id::from("sender"),
+            required: AuthRequirement::DappOrCollector(Id::from("dapp")),
+        }
+        .coded(),
+        expect!["[E0001] unauthorised - sender is not dApp dapp or it's collector"],
+    );
+    check(
+        Error::<Infallible>::FeatureDisabled("collect").coded(),
+        expect!["[E0014] feature disabled - collect"],
+    );
+}
+
+#[test]
+fn display_carries_ids_and_codes() {
+    let errors = [
+        Error::<Infallible>::Unauthorized {
+            sender: Id::from("thief"),
+            required: AuthRequirement::CodeOwner(code!(1)),
+        },
+        Error::Unauthorized {
+            sender: Id::from("thief"),
+            required: AuthRequirement::PendingCollector(Id::from("dapp")),
+        },
+        Error::DappNotActivated(Id::from("dapp")),
+        Error::ReferralCodeNotRegistered(code!(99)),
+        Error::CodeBlocked(code!(1)),
+        Error::NothingToCollect(Earner::Referrer {
+            code: code!(1),
+            dapp: Some(Id::from("dapp")),
+        }),
+        Error::NothingToCollect(Earner::Referrer {
+            code: code!(1),
+            dapp: None,
+        }),
+    ];
+
+    let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+
+    check(
+        messages.join("\n"),
+        expect![[r#"
+            unauthorised - thief is not the owner of referral code 1
+            unauthorised - thief is not the pending collector of dApp dapp
+            dapp not activated - dapp
+            referral code not registered - 99
+            referral code blocked - 1
+            nothing to collect - referral code 1 with dApp dapp
+            nothing to collect - referral code 1"#]],
+    );
+}
+
+#[test]
+fn api_error_has_no_code() {
+    let err = Error::Api(std::fmt::Error);
+
+    assert_eq!(err.code(), None);
+    check(
+        err.coded(),
+        expect!["an error occurred when formatting an argument"],
+    );
+}
@@ -1,5 +1,6 @@
 use referrals_core::hub::{
-    exec, Collection, Configure, DappMetadata, Kind, Msg, ReferralCode, Registration, Reply,
+    exec, Collection, Configure, DappMetadata, DappRepoUrl, DeactivationMode, Kind, Msg,
+    Registration, Reply,
 };
 
 use crate::{check, expect, pretty};
@@ -22,6 +23,19 @@ macro_rules! exec_msg_ok {
     };
 }
 
+macro_rules! exec_msg_err {
+    ($api:ident, $sender:literal, $kind:expr) => {
+        exec(
+            &mut $api,
+            Msg {
+                sender: Id::from($sender),
+                kind: $kind.into(),
+            },
+        )
+        .unwrap_err()
+    };
+}
+
 #[test]
 fn collect_earnings_post_deregister() {
     let mut api = MockApi::default()
@@ -35,6 +49,7 @@ fn collect_earnings_post_deregister() {
             name: "dapp".to_owned(),
             percent: nzp!(50),
             collector: Id::from("collector"),
+            fee: None,
         }
     );
 
@@ -62,7 +77,8 @@ fn collect_earnings_post_deregister() {
         api,
         "dapp",
         Kind::Referral {
-            code: ReferralCode::from(1),
+            code: code!(1),
+            dapp: None
         }
     );
 
@@ -74,16 +90,30 @@ fn collect_earnings_post_deregister() {
         Registration::DeactivateDapp {
             dapp: Id::from("dapp"),
             rewards_admin: Id::from("collector"),
-            rewards_recipient: Id::from("collector")
+            rewards_recipient: Id::from("collector"),
+            mode: DeactivationMode::Freeze,
+        }
+    );
+
+    // a frozen dApp takes no more referrals
+    let err = exec_msg_err!(
+        api,
+        "dapp",
+        Kind::Referral {
+            code: code!(1),
+            dapp: None
         }
     );
 
+    check(err, expect!["dapp deactivated - dapp"]);
+
     let res = exec_msg_ok!(
         api,
         "referrer",
         Collection::Referrer {
-            code: ReferralCode::from(1),
-            dapp: Id::from("dapp")
+            code: code!(1),
+            dapp: Id::from("dapp"),
+            recipient: None
         }
     );
 
@@ -98,7 +128,8 @@ fn collect_earnings_post_deregister() {
         api,
         "collector",
         Collection::Dapp {
-            dapp: Id::from("dapp")
+            dapp: Id::from("dapp"),
+            recipient: None
         }
     );
 
@@ -110,6 +141,367 @@ fn collect_earnings_post_deregister() {
     );
 }
 
+#[test]
+fn purge_requires_collected_earnings() {
+    let mut api = MockApi::default()
+        .rewards_admin(dapp::SELF_ID)
+        .current_fee(nz!(1000));
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Registration::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: nzp!(50),
+            collector: Id::from("collector"),
+            fee: None,
+        }
+    );
+
+    exec_msg_ok!(
+        api,
+        "self",
+        Registration::RewardsPot {
+            dapp: Id::from("dapp"),
+            rewards_pot: Id::from("rewards_pot")
+        }
+    );
+
+    exec_msg_ok!(api, "referrer", Registration::Referrer);
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Kind::Referral {
+            code: code!(1),
+            dapp: None
+        }
+    );
+
+    api.set_dapp_total_rewards(1000);
+
+    let purge = || Registration::DeactivateDapp {
+        dapp: Id::from("dapp"),
+        rewards_admin: Id::from("collector"),
+        rewards_recipient: Id::from("collector"),
+        mode: DeactivationMode::Purge,
+    };
+
+    let err = exec_msg_err!(api, "collector", purge());
+
+    check(
+        err,
+        expect!["uncollected earnings - referrers of dApp dapp have earnings yet to collect"],
+    );
+
+    assert!(api.dapp.is_some());
+
+    exec_msg_ok!(
+        api,
+        "referrer",
+        Collection::Referrer {
+            code: code!(1),
+            dapp: Id::from("dapp"),
+            recipient: None
+        }
+    );
+
+    let res = exec_msg_ok!(api, "collector", purge());
+
+    check(
+        res,
+        expect![[
+            r#"WithdrawPendingThen { pot: Id("rewards_pot"), then: [SetRewardsRecipient { dapp: Id("dapp"), recipient: Id("collector") }, SetRewardsAdmin { dapp: Id("dapp"), admin: Id("collector") }] }"#
+        ]],
+    );
+
+    assert!(api.dapp.is_none());
+}
+
+#[test]
+fn self_referral_only_when_allowed() {
+    let mut api = MockApi::default()
+        .rewards_admin(dapp::SELF_ID)
+        .current_fee(nz!(1000))
+        .block_self_referral();
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Registration::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: nzp!(50),
+            collector: Id::from("collector"),
+            fee: None,
+        }
+    );
+
+    exec_msg_ok!(api, "collector", Registration::Referrer);
+
+    let res = exec_msg_err!(
+        api,
+        "dapp",
+        Kind::Referral {
+            code: code!(1),
+            dapp: None
+        }
+    );
+
+    check(res, expect!["self referral"]);
+
+    exec_msg_ok!(
+        api,
+        "collector",
+        Configure::DappMetadata {
+            dapp: Id::from("dapp"),
+            metadata: DappMetadata {
+                name: None,
+                percent: None,
+                collector: None,
+                repo_url: None,
+                allow_self_referral: Some(true),
+                max_invocations_per_code: None,
+            }
+        }
+    );
+
+    let res = exec_msg_ok!(
+        api,
+        "dapp",
+        Kind::Referral {
+            code: code!(1),
+            dapp: None
+        }
+    );
+
+    check(res, expect!["empty"]);
+
+    assert_eq!(api.code_dapp_earnings, 500);
+}
+
+#[test]
+fn collector_transfer_requires_acceptance() {
+    let mut api = MockApi::default()
+        .rewards_admin(dapp::SELF_ID)
+        .current_fee(nz!(1000));
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Registration::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: nzp!(50),
+            collector: Id::from("collector"),
+            fee: None,
+        }
+    );
+
+    exec_msg_ok!(
+        api,
+        "self",
+        Registration::RewardsPot {
+            dapp: Id::from("dapp"),
+            rewards_pot: Id::from("rewards_pot")
+        }
+    );
+
+    exec_msg_ok!(api, "referrer", Registration::Referrer);
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Kind::Referral {
+            code: code!(1),
+            dapp: None
+        }
+    );
+
+    exec_msg_ok!(
+        api,
+        "collector",
+        Configure::DappMetadata {
+            dapp: Id::from("dapp"),
+            metadata: DappMetadata {
+                name: None,
+                percent: None,
+                collector: Some(Id::from("new_collector")),
+                repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+            }
+        }
+    );
+
+    api.set_dapp_total_rewards(1000);
+
+    let res = exec_msg_ok!(
+        api,
+        "collector",
+        Collection::Dapp {
+            dapp: Id::from("dapp"),
+            recipient: None
+        }
+    );
+
+    check(
+        res,
+        expect![[
+            r#"RedistributeRewards { amount: 500, pot: Id("rewards_pot"), receiver: Id("collector") }"#
+        ]],
+    );
+
+    let res = exec_msg_err!(
+        api,
+        "bob",
+        Configure::AcceptCollector {
+            dapp: Id::from("dapp")
+        }
+    );
+
+    check(
+        res,
+        expect!["unauthorised - bob is not the pending collector of dApp dapp"],
+    );
+
+    exec_msg_ok!(
+        api,
+        "new_collector",
+        Configure::AcceptCollector {
+            dapp: Id::from("dapp")
+        }
+    );
+
+    api.set_dapp_total_rewards(2000);
+
+    let res = exec_msg_err!(
+        api,
+        "collector",
+        Collection::Dapp {
+            dapp: Id::from("dapp"),
+            recipient: None
+        }
+    );
+
+    check(
+        res,
+        expect!["unauthorised - collector is not dApp dapp or it's collector"],
+    );
+
+    let res = exec_msg_ok!(
+        api,
+        "new_collector",
+        Collection::Dapp {
+            dapp: Id::from("dapp"),
+            recipient: None
+        }
+    );
+
+    check(
+        res,
+        expect![[
+            r#"RedistributeRewards { amount: 1000, pot: Id("rewards_pot"), receiver: Id("new_collector") }"#
+        ]],
+    );
+}
+
+#[test]
+fn collect_to_recipient() {
+    let mut api = MockApi::default()
+        .rewards_admin(dapp::SELF_ID)
+        .current_fee(nz!(1000));
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Registration::ActivateDapp {
+            name: "dapp".to_owned(),
+            percent: nzp!(50),
+            collector: Id::from("collector"),
+            fee: None,
+        }
+    );
+
+    exec_msg_ok!(
+        api,
+        "self",
+        Registration::RewardsPot {
+            dapp: Id::from("dapp"),
+            rewards_pot: Id::from("rewards_pot")
+        }
+    );
+
+    exec_msg_ok!(api, "referrer", Registration::Referrer);
+
+    exec_msg_ok!(
+        api,
+        "dapp",
+        Kind::Referral {
+            code: code!(1),
+            dapp: None
+        }
+    );
+
+    api.set_dapp_total_rewards(1000);
+
+    let res = exec_msg_ok!(
+        api,
+        "referrer",
+        Collection::Referrer {
+            code: code!(1),
+            dapp: Id::from("dapp"),
+            recipient: Some(Id::from("referrer_wallet"))
+        }
+    );
+
+    check(
+        res,
+        expect![[
+            r#"RedistributeRewards { amount: 500, pot: Id("rewards_pot"), receiver: Id("referrer_wallet") }"#
+        ]],
+    );
+
+    let res = exec_msg_ok!(
+        api,
+        "collector",
+        Collection::Dapp {
+            dapp: Id::from("dapp"),
+            recipient: Some(Id::from("treasury"))
+        }
+    );
+
+    check(
+        res,
+        expect![[
+            r#"RedistributeRewards { amount: 500, pot: Id("rewards_pot"), receiver: Id("treasury") }"#
+        ]],
+    );
+
+    let res = exec_msg_err!(
+        api,
+        "treasury",
+        Collection::Dapp {
+            dapp: Id::from("dapp"),
+            recipient: None
+        }
+    );
+
+    check(
+        res,
+        expect!["unauthorised - treasury is not dApp dapp or it's collector"],
+    );
+
+    let res = exec_msg_err!(
+        api,
+        "collector",
+        Collection::Dapp {
+            dapp: Id::from("dapp"),
+            recipient: None
+        }
+    );
+
+    check(res, expect!["nothing to collect - dApp dapp"]);
+}
+
 #[test]
 fn msg_routing() {
     let mut api = MockApi::default().rewards_admin(dapp::SELF_ID);
@@ -121,6 +513,7 @@ fn msg_routing() {
             name: "dapp".to_owned(),
             percent: nzp!(50),
             collector: Id::from("collector"),
+            fee: None,
         }
     );
 
@@ -168,9 +561,12 @@ fn msg_routing() {
         Configure::DappMetadata {
             dapp: Id::from("dapp"),
             metadata: DappMetadata {
+                name: None,
                 percent: Some(nzp!(75)),
                 collector: None,
-                repo_url: Some("some_repo".to_owned()),
+                repo_url: Some(DappRepoUrl::Set("some_repo".to_owned())),
+                allow_self_referral: None,
+                max_invocations_per_code: None,
             }
         }
     );
@@ -185,7 +581,7 @@ fn msg_routing() {
         api,
         "referrer1",
         Configure::TransferReferralCodeOwnership {
-            code: ReferralCode::from(1),
+            code: code!(1),
             owner: Id::from("referrer2")
         }
     );
@@ -200,7 +596,8 @@ fn msg_routing() {
         api,
         "dapp",
         Kind::Referral {
-            code: ReferralCode::from(1),
+            code: code!(1),
+            dapp: None
         }
     );
 
@@ -212,8 +609,9 @@ fn msg_routing() {
         api,
         "referrer2",
         Collection::Referrer {
-            code: ReferralCode::from(1),
-            dapp: Id::from("dapp")
+            code: code!(1),
+            dapp: Id::from("dapp"),
+            recipient: None
         }
     );
 
@@ -248,6 +646,8 @@ fn msg_routing() {
               code_dapp_collected: 750,
               dapp_total_collected: 0,
               dapp_total_rewards: 1333,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 
@@ -255,7 +655,8 @@ fn msg_routing() {
         api,
         "collector",
         Collection::Dapp {
-            dapp: Id::from("dapp")
+            dapp: Id::from("dapp"),
+            recipient: None
         }
     );
 
@@ -272,24 +673,16 @@ fn msg_routing() {
         Registration::DeactivateDapp {
             dapp: Id::from("dapp"),
             rewards_admin: Id::from("collector"),
-            rewards_recipient: Id::from("collector")
+            rewards_recipient: Id::from("collector"),
+            mode: DeactivationMode::Purge,
         }
     );
 
     check(
         res,
-        expect![[r#"
-            [
-              WithdrawPending(("rewards_pot")),
-              SetRewardsRecipient(
-                dapp: ("dapp"),
-                recipient: ("collector"),
-              ),
-              SetRewardsAdmin(
-                dapp: ("dapp"),
-                admin: ("collector"),
-              ),
-            ]"#]],
+        expect![[
+            r#"WithdrawPendingThen { pot: Id("rewards_pot"), then: [SetRewardsRecipient { dapp: Id("dapp"), recipient: Id("collector") }, SetRewardsAdmin { dapp: Id("dapp"), admin: Id("collector") }] }"#
+        ]],
     );
 }
 
@@ -302,6 +695,12 @@ impl std::fmt::Display for DisplayReply {
             Reply::MultiCmd(cmds) => {
                 write!(f, "{}", pretty(cmds))
             }
+            Reply::CollectAll { cmds, next } => {
+                write!(f, "{}, next: {next:?}", pretty(cmds))
+            }
+            Reply::InvocationCapReached(code) => {
+                write!(f, "invocation cap reached: {}", code.to_u64())
+            }
         }
     }
 }
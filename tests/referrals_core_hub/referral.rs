@@ -1,7 +1,9 @@
 use referrals_core::hub::{
-    MutableReferralStore, ReadonlyDappStore, ReadonlyReferralStore, ReferralCode,
+    Activity, MutableReferralStore, ReadonlyDappStore, ReadonlyReferralStore, ReferralCode,
 };
 
+use referrals_core::IdRef;
+
 use super::*;
 
 impl ReadonlyReferralStore for MockApi {
@@ -9,7 +11,7 @@ impl ReadonlyReferralStore for MockApi {
         Ok(self.referral_code.map_or(false, |c| c == code.to_u64()))
     }
 
-    fn owner_exists(&self, owner: &Id) -> Result<bool, Self::Error> {
+    fn owner_exists(&self, owner: IdRef<'_>) -> Result<bool, Self::Error> {
         Ok(self
             .referral_code_owner
             .as_ref()
@@ -25,7 +27,9 @@ impl ReadonlyReferralStore for MockApi {
     }
 
     fn latest(&self) -> Result<Option<ReferralCode>, Self::Error> {
-        Ok(self.latest_referral_code.map(ReferralCode::from))
+        Ok(self
+            .latest_referral_code
+            .map(|code| ReferralCode::try_from(code).unwrap()))
     }
 
     fn total_earnings(&self, code: ReferralCode) -> Result<Option<NonZeroU128>, Self::Error> {
@@ -35,16 +39,44 @@ impl ReadonlyReferralStore for MockApi {
 
     fn dapp_earnings(
         &self,
-        _dapp: &Id,
+        _dapp: IdRef<'_>,
         code: ReferralCode,
     ) -> Result<Option<NonZeroU128>, Self::Error> {
         assert!(self.code_exists(code)?);
         Ok(NonZeroU128::new(self.code_dapp_earnings))
     }
 
-    fn dapp_contributions(&self, _dapp: &Id) -> Result<Option<NonZeroU128>, Self::Error> {
+    fn dapp_contributions(&self, _dapp: IdRef<'_>) -> Result<Option<NonZeroU128>, Self::Error> {
         Ok(NonZeroU128::new(self.dapp_contributions))
     }
+
+    fn code_dapp_count(&self, code: ReferralCode) -> Result<u64, Self::Error> {
+        assert!(self.code_exists(code)?);
+        Ok(u64::from(self.code_dapp_earnings != 0))
+    }
+
+    fn code_dapp(&self, code: ReferralCode, index: u64) -> Result<Option<Id>, Self::Error> {
+        if index >= self.code_dapp_count(code)? {
+            return Ok(None);
+        }
+
+        Ok(self.dapp.as_ref().map(|(id, _)| Id::from(id.clone())))
+    }
+
+    fn code_blocked(&self, code: ReferralCode) -> Result<bool, Self::Error> {
+        assert!(self.code_exists(code)?);
+        Ok(self.code_blocked)
+    }
+
+    fn invocations(&self, dapp: IdRef<'_>, code: ReferralCode) -> Result<u64, Self::Error> {
+        assert!(self.dapp_exists(&dapp.to_id())?);
+        assert!(self.code_exists(code)?);
+        Ok(self.dapp_reffered_invocations)
+    }
+
+    fn self_referral_blocked(&self) -> Result<bool, Self::Error> {
+        Ok(self.self_referral_blocked)
+    }
 }
 
 impl MutableReferralStore for MockApi {
@@ -59,13 +91,39 @@ impl MutableReferralStore for MockApi {
         Ok(())
     }
 
-    fn increment_invocations(&mut self, dapp: &Id, code: ReferralCode) -> Result<(), Self::Error> {
-        assert!(self.dapp_exists(dapp)?);
+    fn increment_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+    ) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(&dapp.to_id())?);
         assert!(self.code_exists(code)?);
         self.dapp_reffered_invocations += 1;
         Ok(())
     }
 
+    fn increment_daily_invocations(
+        &mut self,
+        dapp: IdRef<'_>,
+        day: u64,
+    ) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(&dapp.to_id())?);
+        *self.daily_invocations.entry(day).or_default() += 1;
+        Ok(())
+    }
+
+    fn touch(
+        &mut self,
+        dapp: IdRef<'_>,
+        code: ReferralCode,
+        activity: Activity,
+    ) -> Result<(), Self::Error> {
+        assert!(self.dapp_exists(&dapp.to_id())?);
+        assert!(self.code_exists(code)?);
+        self.last_active = Some(activity);
+        Ok(())
+    }
+
     fn set_total_earnings(
         &mut self,
         code: ReferralCode,
@@ -78,11 +136,11 @@ impl MutableReferralStore for MockApi {
 
     fn set_dapp_earnings(
         &mut self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         code: ReferralCode,
         total: NonZeroU128,
     ) -> Result<(), Self::Error> {
-        assert!(self.dapp_exists(dapp)?);
+        assert!(self.dapp_exists(&dapp.to_id())?);
         assert!(self.code_exists(code)?);
         self.code_dapp_earnings = total.get();
         Ok(())
@@ -90,13 +148,24 @@ impl MutableReferralStore for MockApi {
 
     fn set_dapp_contributions(
         &mut self,
-        dapp: &Id,
+        dapp: IdRef<'_>,
         contributions: NonZeroU128,
     ) -> Result<(), Self::Error> {
-        assert!(self.dapp_exists(dapp)?);
+        assert!(self.dapp_exists(&dapp.to_id())?);
         self.dapp_contributions = contributions.get();
         Ok(())
     }
+
+    fn set_code_blocked(&mut self, code: ReferralCode, blocked: bool) -> Result<(), Self::Error> {
+        assert!(self.code_exists(code)?);
+        self.code_blocked = blocked;
+        Ok(())
+    }
+
+    fn set_self_referral_blocked(&mut self, blocked: bool) -> Result<(), Self::Error> {
+        self.self_referral_blocked = blocked;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -104,4 +173,8 @@ pub mod record;
 #[cfg(test)]
 pub mod register;
 #[cfg(test)]
+pub mod register_for;
+#[cfg(test)]
+pub mod set_code_status;
+#[cfg(test)]
 pub mod transfer_ownership;
@@ -1,4 +1,8 @@
-use referrals_core::hub::{referral, MutableDappStore};
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+
+use referrals_core::hub::referral::{self, SECONDS_PER_DAY};
+use referrals_core::hub::{DappCampaign, MutableDappStore};
 
 use crate::{check, expect, pretty};
 
@@ -8,13 +12,14 @@ use super::*;
 pub fn works() {
     let mut api = MockApi::default()
         .dapp("dapp")
+        .collector("collector")
         .current_fee(nz!(1000))
         .referral_code_owner("referrer")
         .referral_code(1);
 
     api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
 
-    referral::record(&mut api, &Id::from("dapp"), ReferralCode::from(1)).unwrap();
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
 
     check(
         pretty(&api),
@@ -22,7 +27,7 @@ pub fn works() {
             (
               dapp: Some(("dapp", "dapp")),
               percent: Some(50),
-              collector: None,
+              collector: Some("collector"),
               rewards_pot: None,
               rewards_pot_admin: None,
               rewards_admin: None,
@@ -38,41 +43,357 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
 
+#[test]
+#[tracing_test::traced_test]
+pub fn works_traced() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert!(logs_contain(
+        r#"record{sender=Id("dapp") dapp=None code=Code(1)}"#
+    ));
+    assert!(logs_contain("referrer_share=500"));
+}
+
+#[test]
+pub fn tracking_only_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 1);
+    assert_eq!(api.code_total_earnings, 0);
+    assert_eq!(api.code_dapp_earnings, 0);
+    assert_eq!(api.dapp_contributions, 0);
+}
+
+#[test]
+pub fn records_activity() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(
+        api.last_active,
+        Some(Activity {
+            height: dapp::BLOCK_HEIGHT,
+            time: dapp::BLOCK_TIME,
+        })
+    );
+
+    // the invocation is counted on the day of the block
+    assert_eq!(
+        api.daily_invocations,
+        BTreeMap::from([(dapp::BLOCK_TIME / SECONDS_PER_DAY, 1)])
+    );
+}
+
+fn double_rewards(start_height: u64, end_height: u64) -> DappCampaign {
+    DappCampaign {
+        multiplier_num: NonZeroU64::new(2).unwrap(),
+        multiplier_den: NonZeroU64::new(1).unwrap(),
+        start_height,
+        end_height,
+    }
+}
+
+#[test]
+pub fn campaign_in_window_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1)
+        .campaign(double_rewards(dapp::BLOCK_HEIGHT, dapp::BLOCK_HEIGHT + 10));
+
+    api.set_percent(&Id::from("dapp"), nzp!(25)).unwrap();
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.code_total_earnings, 500);
+    assert_eq!(api.code_dapp_earnings, 500);
+    assert_eq!(api.dapp_contributions, 500);
+}
+
+#[test]
+pub fn campaign_out_of_window_ignored() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1)
+        .campaign(double_rewards(
+            dapp::BLOCK_HEIGHT + 1,
+            dapp::BLOCK_HEIGHT + 10,
+        ));
+
+    api.set_percent(&Id::from("dapp"), nzp!(25)).unwrap();
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.code_total_earnings, 250);
+
+    let mut api = api.campaign(double_rewards(0, dapp::BLOCK_HEIGHT - 1));
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.code_total_earnings, 500);
+}
+
+#[test]
+pub fn campaign_share_clamped_to_fee() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1)
+        .campaign(DappCampaign {
+            multiplier_num: NonZeroU64::new(u64::MAX).unwrap(),
+            ..double_rewards(dapp::BLOCK_HEIGHT, dapp::BLOCK_HEIGHT)
+        });
+
+    api.set_percent(&Id::from("dapp"), nzp!(75)).unwrap();
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.code_total_earnings, 1000);
+    assert_eq!(api.dapp_contributions, 1000);
+}
+
+#[test]
+pub fn relayed_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1)
+        .referral_relayer("relayer");
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    referral::record(
+        &mut api,
+        &Id::from("relayer"),
+        Some(&Id::from("dapp")),
+        code!(1),
+    )
+    .unwrap();
+
+    // recorded for the dApp, not the relayer
+    assert_eq!(api.dapp_reffered_invocations, 1);
+    assert_eq!(api.code_dapp_earnings, 500);
+    assert_eq!(api.dapp_contributions, 500);
+
+    // a dApp may still name itself
+    referral::record(
+        &mut api,
+        &Id::from("dapp"),
+        Some(&Id::from("dapp")),
+        code!(1),
+    )
+    .unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 2);
+}
+
+#[test]
+pub fn relayed_by_unauthorised_sender_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    let before = pretty(&api);
+
+    let res = referral::record(
+        &mut api,
+        &Id::from("relayer"),
+        Some(&Id::from("dapp")),
+        code!(1),
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - relayer is not dApp dapp or it's referral relayer"],
+    );
+
+    // nor can the dApp's collector record for it
+    let res = referral::record(
+        &mut api,
+        &Id::from("collector"),
+        Some(&Id::from("dapp")),
+        code!(1),
+    )
+    .unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - collector is not dApp dapp or it's referral relayer"],
+    );
+
+    assert_eq!(pretty(&api), before);
+}
+
 #[test]
 pub fn dapp_not_registered_fails() {
     let mut api = MockApi::default()
         .referral_code_owner("referrer")
         .referral_code(1);
 
-    let res = referral::record(&mut api, &Id::from("dapp"), ReferralCode::from(1)).unwrap_err();
+    let res = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap_err();
 
-    check(res, expect!["dapp not activated"]);
+    check(res, expect!["dapp not activated - dapp"]);
 }
 
 #[test]
 pub fn code_not_registered_fails() {
     let mut api = MockApi::default().dapp("dapp");
 
-    let res = referral::record(&mut api, &Id::from("dapp"), ReferralCode::from(1)).unwrap_err();
+    let res = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap_err();
 
-    check(res, expect!["referral code not registered"]);
+    check(res, expect!["referral code not registered - 1"]);
 }
 
 #[test]
 pub fn calculation_overflow_fails() {
     let mut api = MockApi::default()
         .dapp("dapp")
+        .collector("collector")
         .current_fee(NonZeroU128::new(u128::max_value()).unwrap())
         .referral_code_owner("referrer")
         .referral_code(1);
 
     api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
 
-    let res = referral::record(&mut api, &Id::from("dapp"), ReferralCode::from(1)).unwrap_err();
+    let res = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap_err();
 
     check(res, expect!["math overflow"]);
 }
+
+#[test]
+pub fn self_referral_blocked_fails() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("collector")
+        .referral_code(1)
+        .block_self_referral();
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    let res = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap_err();
+
+    check(res, expect!["self referral"]);
+
+    let mut api = api.referral_code_owner("dapp");
+
+    let res = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap_err();
+
+    check(res, expect!["self referral"]);
+
+    let mut api = api.referral_code_owner("referrer");
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 1);
+}
+
+#[test]
+pub fn self_referral_not_blocked_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("collector")
+        .referral_code(1);
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 1);
+    assert_eq!(api.code_dapp_earnings, 500);
+}
+
+#[test]
+pub fn self_referral_blocked_allowed_by_dapp_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("collector")
+        .referral_code(1)
+        .block_self_referral();
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+    api.set_self_referral_allowed(&Id::from("dapp"), true)
+        .unwrap();
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 1);
+    assert_eq!(api.code_dapp_earnings, 500);
+}
+
+#[test]
+pub fn invocation_cap_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1)
+        .max_invocations_per_code(2);
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    for _ in 0..2 {
+        let capped = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+        assert!(!capped);
+    }
+
+    assert_eq!(api.dapp_reffered_invocations, 2);
+    assert_eq!(api.code_total_earnings, 1000);
+
+    // the invocation past the cap is counted, but earns nothing
+    let capped = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert!(capped);
+    assert_eq!(api.dapp_reffered_invocations, 3);
+    assert_eq!(api.code_total_earnings, 1000);
+    assert_eq!(api.code_dapp_earnings, 1000);
+    assert_eq!(api.dapp_contributions, 1000);
+}
@@ -0,0 +1,54 @@
+use referrals_core::hub::referral;
+
+use crate::{check, expect, pretty};
+
+use super::*;
+
+#[test]
+pub fn works() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res =
+        referral::register_for(&mut api, &Id::from("collector"), Id::from("referrer")).unwrap();
+
+    check(pretty(&res), expect!["(1)"]);
+
+    assert_eq!(api.referral_code_owner, Some("referrer".to_owned()));
+    assert_eq!(api.latest_referral_code, Some(1));
+}
+
+#[test]
+pub fn hub_works() {
+    let mut api = MockApi::default();
+
+    referral::register_for(&mut api, &Id::from(dapp::SELF_ID), Id::from("referrer")).unwrap();
+
+    assert_eq!(api.referral_code_owner, Some("referrer".to_owned()));
+}
+
+#[test]
+pub fn sender_not_hub_or_collector_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res =
+        referral::register_for(&mut api, &Id::from("referrer"), Id::from("referrer")).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    assert_eq!(api.referral_code_owner, None);
+}
+
+#[test]
+pub fn already_a_referral_code_owner_fails() {
+    let mut api = MockApi::default()
+        .collector("collector")
+        .referral_code_owner("referrer");
+
+    let res =
+        referral::register_for(&mut api, &Id::from("collector"), Id::from("referrer")).unwrap_err();
+
+    check(res, expect!["already registered"]);
+}
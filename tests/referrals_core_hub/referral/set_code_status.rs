@@ -0,0 +1,64 @@
+use referrals_core::hub::{referral, MutableDappStore};
+
+use crate::{check, expect};
+
+use super::*;
+
+#[test]
+pub fn block_then_unblock_works() {
+    let mut api = MockApi::default()
+        .dapp("dapp")
+        .collector("collector")
+        .current_fee(nz!(1000))
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    api.set_percent(&Id::from("dapp"), nzp!(50)).unwrap();
+
+    referral::set_code_status(&mut api, &Id::from("collector"), code!(1), true).unwrap();
+
+    assert!(api.code_blocked);
+
+    let res = referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap_err();
+
+    check(res, expect!["referral code blocked - 1"]);
+
+    assert_eq!(api.dapp_reffered_invocations, 0);
+
+    referral::set_code_status(&mut api, &Id::from(dapp::SELF_ID), code!(1), false).unwrap();
+
+    assert!(!api.code_blocked);
+
+    referral::record(&mut api, &Id::from("dapp"), None, code!(1)).unwrap();
+
+    assert_eq!(api.dapp_reffered_invocations, 1);
+    assert_eq!(api.code_total_earnings, 500);
+}
+
+#[test]
+pub fn code_not_registered_fails() {
+    let mut api = MockApi::default().collector("collector");
+
+    let res =
+        referral::set_code_status(&mut api, &Id::from("collector"), code!(1), true).unwrap_err();
+
+    check(res, expect!["referral code not registered - 1"]);
+}
+
+#[test]
+pub fn sender_not_hub_or_collector_fails() {
+    let mut api = MockApi::default()
+        .collector("collector")
+        .referral_code_owner("referrer")
+        .referral_code(1);
+
+    let res =
+        referral::set_code_status(&mut api, &Id::from("referrer"), code!(1), true).unwrap_err();
+
+    check(
+        res,
+        expect!["unauthorised - referrer is not the hub or it's collector"],
+    );
+
+    assert!(!api.code_blocked);
+}
@@ -13,7 +13,7 @@ pub fn works() {
     referral::transfer_ownership(
         &mut api,
         &Id::from("referrer"),
-        ReferralCode::from(1),
+        code!(1),
         Id::from("new_owner"),
     )
     .unwrap();
@@ -40,6 +40,8 @@ pub fn works() {
               code_dapp_collected: 0,
               dapp_total_collected: 0,
               dapp_total_rewards: 0,
+              self_referral_allowed: false,
+              pending_collector: None,
             )"#]],
     );
 }
@@ -53,12 +55,12 @@ pub fn code_not_registered_fails() {
     let res = referral::transfer_ownership(
         &mut api,
         &Id::from("referrer"),
-        ReferralCode::from(2),
+        code!(2),
         Id::from("new_owner"),
     )
     .unwrap_err();
 
-    check(res, expect!["referral code not registered"]);
+    check(res, expect!["referral code not registered - 2"]);
 }
 
 #[test]
@@ -67,13 +69,12 @@ pub fn sender_not_code_owner_fails() {
         .referral_code_owner("referrer")
         .referral_code(1);
 
-    let res = referral::transfer_ownership(
-        &mut api,
-        &Id::from("bob"),
-        ReferralCode::from(1),
-        Id::from("new_owner"),
-    )
-    .unwrap_err();
+    let res =
+        referral::transfer_ownership(&mut api, &Id::from("bob"), code!(1), Id::from("new_owner"))
+            .unwrap_err();
 
-    check(res, expect!["unauthorised"]);
+    check(
+        res,
+        expect!["unauthorised - bob is not the owner of referral code 1"],
+    );
 }
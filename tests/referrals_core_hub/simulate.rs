@@ -0,0 +1,66 @@
+use kv_storage::KvStore;
+use referrals_core::hub::{dapp, referral, simulate, Kind, Msg, MutableDappStore, TotalsQuery};
+use referrals_storage::testing::MemApi;
+use referrals_storage::RecordingStore;
+
+use crate::referrals_core::hub::dapp::SELF_ID;
+use crate::referrals_storage::{Repo, RonSerde};
+use crate::{check, expect, pretty};
+
+use super::*;
+
+#[test]
+fn record_referral_commits_nothing() {
+    let mut api: MemApi<KvStore<RonSerde, Repo>> = MemApi::default()
+        .rewards_admin(SELF_ID)
+        .current_fee(nz!(1000));
+
+    let code = referral::register(&mut api, Id::from("referrer")).unwrap();
+
+    dapp::activate(
+        &mut api,
+        Id::from("dapp"),
+        "dapp".to_owned(),
+        nzp!(50),
+        Id::from("collector"),
+        None,
+    )
+    .unwrap();
+
+    api.set_rewards_pot(&Id::from("dapp"), Id::from("rewards_pot"))
+        .unwrap();
+
+    let before = api.storage().inner().repo().to_string();
+
+    let store: KvStore<RonSerde, RecordingStore<&Repo>> =
+        KvStore::from_repo(RecordingStore::new(api.storage().inner().repo()));
+
+    let mut recording = MemApi::new(store, SELF_ID).current_fee(nz!(1000));
+
+    let simulation = simulate(
+        &mut recording,
+        Msg {
+            sender: Id::from("dapp"),
+            kind: Kind::Referral { code, dapp: None },
+        },
+    )
+    .unwrap();
+
+    check(pretty(&simulation.reply), expect!["Empty"]);
+
+    let invocations = simulation
+        .writes
+        .iter()
+        .find(|write| write.key == b"referrals_storage::hub::totals::invocations")
+        .and_then(|write| write.value.clone())
+        .map(String::from_utf8)
+        .unwrap()
+        .unwrap();
+
+    check(invocations, expect!["1"]);
+
+    // the recording api reads it's own writes, leaving the snapshot untouched
+    check(recording.total_invocations().unwrap(), expect!["1"]);
+    check(api.total_invocations().unwrap(), expect!["0"]);
+    assert_eq!(api.storage().inner().repo().to_string(), before);
+}
@@ -1,4 +1,5 @@
-use referrals_cw::{ExecuteMsg, WithReferralCode};
+use cosmwasm_std::Uint128;
+use referrals_cw::{DeactivationMode, ExecuteMsg, InstantiateMsg, QueryMsg, WithReferralCode};
 
 use serde_json_wasm::{from_str, to_string};
 
@@ -60,14 +61,19 @@ pub fn with_referral_code_json_serde() {
             referral_code: None,
             msg: ExecuteMsg::ConfigureDapp {
                 dapp: "dapp".to_owned(),
+                name: None,
                 percent: Some(89),
                 collector: Some("collector".to_string()),
                 repo_url: Some("repo.com".to_owned()),
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: None,
             },
         })
         .unwrap(),
         expect![[
-            r#"{"referral_code":null,"configure_dapp":{"dapp":"dapp","percent":89,"collector":"collector","repo_url":"repo.com"}}"#
+            r#"{"referral_code":null,"configure_dapp":{"dapp":"dapp","name":null,"percent":89,"collector":"collector","repo_url":"repo.com","clear_repo_url":null,"allow_self_referral":null,"max_invocations_per_code":null,"confirm_no_dapp_share":null}}"#
         ]],
     );
 
@@ -118,3 +124,431 @@ pub fn with_referral_code_json_serde() {
         }
     ))
 }
+
+#[test]
+pub fn camel_compat_execute_msg_deserialize() {
+    let expected = WithReferralCode {
+        referral_code: Some(69),
+        msg: ExecuteMsg::ConfigureDapp {
+            dapp: "dapp".to_owned(),
+            name: None,
+            percent: None,
+            collector: None,
+            repo_url: Some("repo.com".to_owned()),
+            clear_repo_url: None,
+            allow_self_referral: Some(true),
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        },
+    };
+
+    let snake: WithReferralCode<ExecuteMsg> = from_str(
+        r#"{
+            "referral_code": 69,
+            "configure_dapp": {
+                "dapp": "dapp",
+                "repo_url": "repo.com",
+                "allow_self_referral": true
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(snake, expected);
+
+    let camel: WithReferralCode<ExecuteMsg> = from_str(
+        r#"{
+            "referralCode": 69,
+            "configureDapp": {
+                "dapp": "dapp",
+                "repoUrl": "repo.com",
+                "allowSelfReferral": true
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(camel, expected);
+
+    // always serialized as snake_case
+    check(
+        to_string(&camel).unwrap(),
+        expect![[
+            r#"{"referral_code":69,"configure_dapp":{"dapp":"dapp","name":null,"percent":null,"collector":null,"repo_url":"repo.com","clear_repo_url":null,"allow_self_referral":true,"max_invocations_per_code":null,"confirm_no_dapp_share":null}}"#
+        ]],
+    );
+}
+
+#[test]
+pub fn camel_compat_instantiate_and_query_msg_deserialize() {
+    let expected = InstantiateMsg {
+        rewards_pot_code_id: 1,
+        contract_premium: Uint128::new(1000),
+        rewards_denom: "ucosm".to_owned(),
+        self_activate: None,
+        block_self_referral: None,
+    };
+
+    let snake: InstantiateMsg =
+        from_str(r#"{"rewards_pot_code_id":1,"contract_premium":"1000","rewards_denom":"ucosm"}"#)
+            .unwrap();
+
+    assert_eq!(snake, expected);
+
+    let camel: InstantiateMsg =
+        from_str(r#"{"rewardsPotCodeId":1,"contractPremium":"1000","rewardsDenom":"ucosm"}"#)
+            .unwrap();
+
+    assert_eq!(camel, expected);
+
+    let expected = QueryMsg::AllDapps {
+        start: Some(2),
+        after: None,
+        limit: None,
+        only_active: Some(true),
+    };
+
+    let snake: QueryMsg = from_str(r#"{"all_dapps":{"start":2,"only_active":true}}"#).unwrap();
+
+    assert_eq!(snake, expected);
+
+    let camel: QueryMsg = from_str(r#"{"allDapps":{"start":2,"onlyActive":true}}"#).unwrap();
+
+    assert_eq!(camel, expected);
+}
+
+mod address {
+    use cosmwasm_std::HexBinary;
+    use referrals_cw::address::{label_salt, predict_instantiate2_address, rewards_pot_salt};
+
+    use crate::{check, expect};
+
+    const CODE_HASH: &str = "13a1fc994cc6d1c81b746ee0c0ff6f90043875e0bf1d9be6b7d779fc978dc2a5";
+
+    #[test]
+    fn predict_instantiate2_address_works() {
+        // creator 9999999999aaaaaaaaaabbbbbbbbbbcccccccccc & salt 61 from the wasmd fixtures
+        check(
+            predict_instantiate2_address(
+                CODE_HASH,
+                "archway1nxvenxve42424242hwamhwamenxvenxvv2hmpz",
+                "61",
+            )
+            .unwrap(),
+            expect!["archway1t6r960j945lfv8mhl4mage2rg97w63xeynwrupum2s2l7em4lprs688tm3"],
+        );
+
+        // the code hash as reported by archwayd is upper case
+        check(
+            predict_instantiate2_address(
+                &CODE_HASH.to_uppercase(),
+                "archway1nxvenxve42424242hwamhwamenxvenxvv2hmpz",
+                "61",
+            )
+            .unwrap(),
+            expect!["archway1t6r960j945lfv8mhl4mage2rg97w63xeynwrupum2s2l7em4lprs688tm3"],
+        );
+    }
+
+    #[test]
+    fn label_salt_works() {
+        let salt = label_salt("rewards_pot:1700000000");
+
+        check(
+            &salt,
+            expect!["726577617264735f706f743a31373030303030303030"],
+        );
+
+        check(
+            predict_instantiate2_address(
+                CODE_HASH,
+                "archway1nxvenxve42424242hwamhwamenxvenxvv2hmpz",
+                &salt,
+            )
+            .unwrap(),
+            expect!["archway1lesyg9ygjpzvtav5r2eph3m5js63qxnvmxwac9ug05xt9l8tr0zsw9sgky"],
+        );
+    }
+
+    #[test]
+    fn rewards_pot_address_works() {
+        let salt = rewards_pot_salt("archway1qurswpc8qurswpc8qurswpc8qurswpc8xcm72k");
+
+        let salt = HexBinary::from(salt.as_slice()).to_hex();
+
+        check(
+            &salt,
+            expect!["ed2d4fce97eddd80893f807fc7b0d3c98c3fcd0076c8c63f54010d251786d7d8"],
+        );
+
+        // a hub with a 32 byte contract address
+        check(
+            predict_instantiate2_address(
+                CODE_HASH,
+                "archway1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0shd8x93",
+                &salt,
+            )
+            .unwrap(),
+            expect!["archway1vztwkv2ewzpmwpyx7n4kytn0c8x07caf63yyqmgdwml4y5uh97rsrtsamw"],
+        );
+    }
+
+    #[test]
+    fn invalid_input_fails() {
+        let creator = "archway1nxvenxve42424242hwamhwamenxvenxvv2hmpz";
+
+        assert!(predict_instantiate2_address("zz", creator, "61").is_err());
+        assert!(predict_instantiate2_address(CODE_HASH, "not_an_address", "61").is_err());
+        assert!(predict_instantiate2_address(CODE_HASH, creator, "").is_err());
+    }
+}
+
+mod with_referral_code {
+    use cosmwasm_std::Uint128;
+    use proptest::prelude::*;
+    use referrals_cw::{ConfigureDappEntry, ExecuteMsg, WithReferralCode};
+    use serde_json_wasm::{from_str, to_string};
+
+    use crate::{check, expect};
+
+    /// Strings that may collide with the wrapper's `referral_code` key.
+    fn text() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("referral_code".to_owned()),
+            Just("referralCode".to_owned()),
+            "[a-zA-Z0-9_ \"{}:,]{0,24}",
+        ]
+    }
+
+    fn amount() -> impl Strategy<Value = Uint128> {
+        any::<u128>().prop_map(Uint128::new)
+    }
+
+    fn configure_dapp_entry() -> impl Strategy<Value = ConfigureDappEntry> {
+        (
+            text(),
+            prop::option::of(text()),
+            any::<Option<u8>>(),
+            prop::option::of(text()),
+            prop::option::of(text()),
+            any::<Option<bool>>(),
+            any::<Option<bool>>(),
+            any::<Option<u64>>(),
+            any::<Option<bool>>(),
+        )
+            .prop_map(
+                |(
+                    dapp,
+                    name,
+                    percent,
+                    collector,
+                    repo_url,
+                    clear_repo_url,
+                    allow_self_referral,
+                    max_invocations_per_code,
+                    confirm_no_dapp_share,
+                )| ConfigureDappEntry {
+                    dapp,
+                    name,
+                    percent,
+                    collector,
+                    repo_url,
+                    clear_repo_url,
+                    allow_self_referral,
+                    max_invocations_per_code,
+                    confirm_no_dapp_share,
+                },
+            )
+    }
+
+    fn registration_msg() -> impl Strategy<Value = ExecuteMsg> {
+        prop_oneof![
+            Just(ExecuteMsg::RegisterReferrer {}),
+            text().prop_map(|owner| ExecuteMsg::RegisterReferrerFor { owner }),
+            (
+                text(),
+                any::<u8>(),
+                text(),
+                prop::option::of(amount()),
+                any::<Option<bool>>(),
+            )
+                .prop_map(|(name, percent, collector, fee, confirm_no_dapp_share)| {
+                    ExecuteMsg::ActivateDapp {
+                        name,
+                        percent,
+                        collector,
+                        fee,
+                        confirm_no_dapp_share,
+                    }
+                }),
+            (text(), text()).prop_map(|(name, collector)| ExecuteMsg::ActivateDappTrackingOnly {
+                name,
+                collector
+            }),
+            (
+                text(),
+                text(),
+                text(),
+                prop::option::of(prop_oneof![
+                    Just(DeactivationMode::Freeze),
+                    Just(DeactivationMode::Purge)
+                ]),
+            )
+                .prop_map(|(dapp, rewards_admin, rewards_recipient, mode)| {
+                    ExecuteMsg::DeactivateDapp {
+                        dapp,
+                        rewards_admin,
+                        rewards_recipient,
+                        mode,
+                    }
+                }),
+            (text(), amount()).prop_map(|(dapp, fee)| ExecuteMsg::SetDappFee { dapp, fee }),
+            (any::<u64>(), prop::option::of(text()))
+                .prop_map(|(code, dapp)| ExecuteMsg::RecordReferral { code, dapp }),
+        ]
+    }
+
+    fn collection_msg() -> impl Strategy<Value = ExecuteMsg> {
+        prop_oneof![
+            (any::<u64>(), text(), prop::option::of(text())).prop_map(|(code, dapp, recipient)| {
+                ExecuteMsg::CollectReferrer {
+                    code,
+                    dapp,
+                    recipient,
+                }
+            }),
+            (any::<u64>(), any::<Option<u64>>(), any::<Option<u64>>()).prop_map(
+                |(code, start, limit)| ExecuteMsg::CollectReferrerAll { code, start, limit }
+            ),
+            (text(), prop::option::of(text()))
+                .prop_map(|(dapp, recipient)| ExecuteMsg::CollectDapp { dapp, recipient }),
+            (any::<u64>(), text())
+                .prop_map(|(code, owner)| ExecuteMsg::TransferOwnership { code, owner }),
+        ]
+    }
+
+    fn configuration_msg() -> impl Strategy<Value = ExecuteMsg> {
+        prop_oneof![
+            configure_dapp_entry().prop_map(|entry| ExecuteMsg::ConfigureDapp {
+                dapp: entry.dapp,
+                name: entry.name,
+                percent: entry.percent,
+                collector: entry.collector,
+                repo_url: entry.repo_url,
+                clear_repo_url: entry.clear_repo_url,
+                allow_self_referral: entry.allow_self_referral,
+                max_invocations_per_code: entry.max_invocations_per_code,
+                confirm_no_dapp_share: entry.confirm_no_dapp_share,
+            }),
+            prop::collection::vec(configure_dapp_entry(), 0..4)
+                .prop_map(|updates| ExecuteMsg::ConfigureDapps { updates }),
+            text().prop_map(|dapp| ExecuteMsg::AcceptCollector { dapp }),
+            (
+                text(),
+                any::<u64>(),
+                any::<u64>(),
+                any::<u64>(),
+                any::<u64>()
+            )
+                .prop_map(
+                    |(dapp, multiplier_num, multiplier_den, start_height, end_height)| {
+                        ExecuteMsg::SetCampaign {
+                            dapp,
+                            multiplier_num,
+                            multiplier_den,
+                            start_height,
+                            end_height,
+                        }
+                    }
+                ),
+            (text(), prop::option::of(text()))
+                .prop_map(|(dapp, relayer)| ExecuteMsg::SetReferralRelayer { dapp, relayer }),
+            (any::<u64>(), any::<bool>())
+                .prop_map(|(code, blocked)| ExecuteMsg::SetCodeStatus { code, blocked }),
+            any::<[bool; 4]>().prop_map(|[register, record, collect, configure]| {
+                ExecuteMsg::SetEnabledFeatures {
+                    register,
+                    record,
+                    collect,
+                    configure,
+                }
+            }),
+            prop::option::of(amount())
+                .prop_map(|amount| ExecuteMsg::SetMinCollectAmount { amount }),
+            any::<bool>().prop_map(|allowed| ExecuteMsg::SetAllowSelfDapp { allowed }),
+            (prop::collection::vec(text(), 0..4), text())
+                .prop_map(|(dapps, new_admin)| ExecuteMsg::HandoverPots { dapps, new_admin }),
+        ]
+    }
+
+    fn execute_msg() -> impl Strategy<Value = ExecuteMsg> {
+        prop_oneof![registration_msg(), collection_msg(), configuration_msg()]
+    }
+
+    proptest! {
+        #[test]
+        fn json_round_trips(referral_code in any::<Option<u64>>(), msg in execute_msg()) {
+            let wrapped = WithReferralCode { referral_code, msg };
+
+            let json = to_string(&wrapped).unwrap();
+
+            let parsed: WithReferralCode<ExecuteMsg> = from_str(&json).unwrap();
+
+            prop_assert_eq!(parsed, wrapped);
+        }
+
+        #[test]
+        fn referral_code_after_msg_works(referral_code in any::<u64>(), msg in execute_msg()) {
+            // the msg is serialized alone, then the code appended after it
+            let json = to_string(&msg).unwrap();
+            let json = format!("{},\"referral_code\":{referral_code}}}", &json[..json.len() - 1]);
+
+            let parsed: WithReferralCode<ExecuteMsg> = from_str(&json).unwrap();
+
+            prop_assert_eq!(parsed, WithReferralCode { referral_code: Some(referral_code), msg });
+        }
+    }
+
+    #[test]
+    fn nested_referral_code_is_not_the_wrapper_code() {
+        let parsed: WithReferralCode<ExecuteMsg> =
+            from_str(r#"{"record_referral":{"code":1,"referral_code":2}}"#).unwrap();
+
+        assert_eq!(
+            parsed,
+            WithReferralCode {
+                referral_code: None,
+                msg: ExecuteMsg::RecordReferral {
+                    code: 1,
+                    dapp: None
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_referral_code_fails() {
+        let err = from_str::<WithReferralCode<ExecuteMsg>>(
+            r#"{"referral_code":1,"referral_code":2,"register_referrer":{}}"#,
+        )
+        .unwrap_err();
+
+        check(err, expect!["duplicate field `referral_code`"]);
+
+        // the camel case alias is the same field
+        let err = from_str::<WithReferralCode<ExecuteMsg>>(
+            r#"{"referralCode":1,"referral_code":2,"register_referrer":{}}"#,
+        )
+        .unwrap_err();
+
+        check(err, expect!["duplicate field `referral_code`"]);
+    }
+
+    #[test]
+    fn null_referral_code_works() {
+        let parsed: WithReferralCode<ExecuteMsg> =
+            from_str(r#"{"referral_code":null,"record_referral":{"code":1}}"#).unwrap();
+
+        assert_eq!(parsed.referral_code, None);
+    }
+}
@@ -24,7 +24,65 @@ fn register_referrer() {
     );
 }
 
+mod register_referrer_for {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::RegisterReferrerFor {
+                owner: "owner".to_owned(),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Register(ReferrerFor(
+                    owner: ("owner"),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_owner_address_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::RegisterReferrerFor {
+                owner: "0".to_owned(),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+}
+
 mod register_dapp {
+    use cosmwasm_std::Uint128;
+
     use super::*;
 
     #[test]
@@ -42,6 +100,44 @@ mod register_dapp {
                 name: "dapp".to_owned(),
                 percent: 100,
                 collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: Some(true),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Register(ActivateDapp(
+                    name: "dapp",
+                    percent: (100),
+                    collector: ("collector"),
+                    fee: None,
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn with_fee_works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 100,
+                collector: "collector".to_owned(),
+                fee: Some(Uint128::new(1000)),
+                confirm_no_dapp_share: Some(true),
             },
         )
         .unwrap();
@@ -55,11 +151,36 @@ mod register_dapp {
                     name: "dapp",
                     percent: (100),
                     collector: ("collector"),
+                    fee: Some(1000),
                   )),
                 )"#]],
         );
     }
 
+    #[test]
+    fn zero_fee_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 100,
+                collector: "collector".to_owned(),
+                fee: Some(Uint128::zero()),
+                confirm_no_dapp_share: Some(true),
+            },
+        )
+        .unwrap_err();
+
+        check(res, expect!["invalid fee - expected non-zero value"]);
+    }
+
     #[test]
     fn invalid_percent_fails() {
         let mock_api = MockApi::default();
@@ -75,6 +196,8 @@ mod register_dapp {
                 name: "dapp".to_owned(),
                 percent: 101,
                 collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
             },
         )
         .unwrap_err();
@@ -91,6 +214,8 @@ mod register_dapp {
                 name: "dapp".to_owned(),
                 percent: 0,
                 collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
             },
         )
         .unwrap_err();
@@ -101,6 +226,91 @@ mod register_dapp {
         );
     }
 
+    #[test]
+    fn unconfirmed_no_dapp_share_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        for confirm_no_dapp_share in [None, Some(false)] {
+            let res = parse_hub_exec(
+                &mock_api,
+                msg_info.clone(),
+                ExecuteMsg::ActivateDapp {
+                    name: "dapp".to_owned(),
+                    percent: 100,
+                    collector: "collector".to_owned(),
+                    fee: None,
+                    confirm_no_dapp_share,
+                },
+            )
+            .unwrap_err();
+
+            check(
+                res,
+                expect!["invalid percent - 100 leaves the dApp no share of rewards, set `confirm_no_dapp_share` to confirm"],
+            );
+        }
+
+        // confirmation is only needed when the dApp is left no share
+        parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ActivateDapp {
+                name: "dapp".to_owned(),
+                percent: 99,
+                collector: "collector".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn name_length_is_bounded() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let activate = |name: String| ExecuteMsg::ActivateDapp {
+            name,
+            percent: 50,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        };
+
+        for name in ["", "  "] {
+            let res =
+                parse_hub_exec(&mock_api, msg_info.clone(), activate(name.to_owned())).unwrap_err();
+
+            check(res, expect!["invalid name - expected a non-empty name"]);
+        }
+
+        parse_hub_exec(&mock_api, msg_info.clone(), activate("n".repeat(128))).unwrap();
+
+        let res =
+            parse_hub_exec(&mock_api, msg_info.clone(), activate("n".repeat(129))).unwrap_err();
+
+        check(
+            res,
+            expect!["invalid name - 129 bytes exceeds the maximum of 128"],
+        );
+
+        // the maximum is in bytes, not characters
+        let res = parse_hub_exec(&mock_api, msg_info, activate("é".repeat(65))).unwrap_err();
+
+        check(
+            res,
+            expect!["invalid name - 130 bytes exceeds the maximum of 128"],
+        );
+    }
+
     #[test]
     fn invalid_collector_fails() {
         let mock_api = MockApi::default();
@@ -116,6 +326,67 @@ mod register_dapp {
                 name: "dapp".to_owned(),
                 percent: 100,
                 collector: "0".to_owned(),
+                fee: None,
+                confirm_no_dapp_share: Some(true),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+}
+
+mod register_dapp_tracking_only {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ActivateDappTrackingOnly {
+                name: "dapp".to_owned(),
+                collector: "collector".to_owned(),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Register(ActivateDappTrackingOnly(
+                    name: "dapp",
+                    collector: ("collector"),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_collector_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ActivateDappTrackingOnly {
+                name: "dapp".to_owned(),
+                collector: "0".to_owned(),
             },
         )
         .unwrap_err();
@@ -128,6 +399,8 @@ mod register_dapp {
 }
 
 mod deregister_dapp {
+    use referrals_cw::DeactivationMode;
+
     use super::*;
 
     #[test]
@@ -145,6 +418,42 @@ mod deregister_dapp {
                 dapp: "dapp".to_owned(),
                 rewards_admin: "rewards_admin".to_owned(),
                 rewards_recipient: "new_recipient".to_owned(),
+                mode: None,
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Register(DeactivateDapp(
+                    dapp: ("dapp"),
+                    rewards_admin: ("rewards_admin"),
+                    rewards_recipient: ("new_recipient"),
+                    mode: Freeze,
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn purge_mode_works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::DeactivateDapp {
+                dapp: "dapp".to_owned(),
+                rewards_admin: "rewards_admin".to_owned(),
+                rewards_recipient: "new_recipient".to_owned(),
+                mode: Some(DeactivationMode::Purge),
             },
         )
         .unwrap();
@@ -158,6 +467,7 @@ mod deregister_dapp {
                     dapp: ("dapp"),
                     rewards_admin: ("rewards_admin"),
                     rewards_recipient: ("new_recipient"),
+                    mode: Purge,
                   )),
                 )"#]],
         );
@@ -178,6 +488,7 @@ mod deregister_dapp {
                 dapp: "0".to_owned(),
                 rewards_admin: "rewards_admin".to_owned(),
                 rewards_recipient: "new_recipient".to_owned(),
+                mode: None,
             },
         )
         .unwrap_err();
@@ -203,6 +514,7 @@ mod deregister_dapp {
                 dapp: "dapp".to_owned(),
                 rewards_admin: "0".to_owned(),
                 rewards_recipient: "new_recipient".to_owned(),
+                mode: None,
             },
         )
         .unwrap_err();
@@ -228,6 +540,7 @@ mod deregister_dapp {
                 dapp: "dapp".to_owned(),
                 rewards_admin: "new_admin".to_owned(),
                 rewards_recipient: "0".to_owned(),
+                mode: None,
             },
         )
         .unwrap_err();
@@ -321,29 +634,7 @@ mod set_dapp_fee {
     }
 }
 
-#[test]
-fn record_referral() {
-    let mock_api = MockApi::default();
-    let msg_info = MessageInfo {
-        sender: Addr::unchecked("sender"),
-        funds: vec![],
-    };
-
-    let res = parse_hub_exec(&mock_api, msg_info, ExecuteMsg::RecordReferral { code: 1 }).unwrap();
-
-    check(
-        pretty(&res),
-        expect![[r#"
-            (
-              sender: ("sender"),
-              kind: Referral(
-                code: (1),
-              ),
-            )"#]],
-    );
-}
-
-mod collect_referrer {
+mod record_referral {
     use super::*;
 
     #[test]
@@ -357,9 +648,9 @@ mod collect_referrer {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::CollectReferrer {
+            ExecuteMsg::RecordReferral {
                 code: 1,
-                dapp: "dapp".to_owned(),
+                dapp: None,
             },
         )
         .unwrap();
@@ -369,10 +660,41 @@ mod collect_referrer {
             expect![[r#"
                 (
                   sender: ("sender"),
-                  kind: Collect(Referrer(
-                    dapp: ("dapp"),
+                  kind: Referral(
                     code: (1),
-                  )),
+                    dapp: None,
+                  ),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn relayed_works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("relayer"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: Some("dapp".to_owned()),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("relayer"),
+                  kind: Referral(
+                    code: (1),
+                    dapp: Some(("dapp")),
+                  ),
                 )"#]],
         );
     }
@@ -381,16 +703,16 @@ mod collect_referrer {
     fn invalid_dapp_address_fails() {
         let mock_api = MockApi::default();
         let msg_info = MessageInfo {
-            sender: Addr::unchecked("sender"),
+            sender: Addr::unchecked("relayer"),
             funds: vec![],
         };
 
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::CollectReferrer {
+            ExecuteMsg::RecordReferral {
                 code: 1,
-                dapp: "0".to_owned(),
+                dapp: Some("0".to_owned()),
             },
         )
         .unwrap_err();
@@ -400,9 +722,33 @@ mod collect_referrer {
             expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
         );
     }
+
+    #[test]
+    fn zero_code_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::RecordReferral {
+                code: 0,
+                dapp: None,
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid referral code - expected non-zero value"],
+        );
+    }
 }
 
-mod collect_dapp {
+mod collect_referrer {
     use super::*;
 
     #[test]
@@ -416,8 +762,10 @@ mod collect_dapp {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::CollectDapp {
+            ExecuteMsg::CollectReferrer {
+                code: 1,
                 dapp: "dapp".to_owned(),
+                recipient: None,
             },
         )
         .unwrap();
@@ -427,8 +775,10 @@ mod collect_dapp {
             expect![[r#"
                 (
                   sender: ("sender"),
-                  kind: Collect(Dapp(
+                  kind: Collect(Referrer(
                     dapp: ("dapp"),
+                    code: (1),
+                    recipient: None,
                   )),
                 )"#]],
         );
@@ -445,8 +795,10 @@ mod collect_dapp {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::CollectDapp {
+            ExecuteMsg::CollectReferrer {
+                code: 1,
                 dapp: "0".to_owned(),
+                recipient: None,
             },
         )
         .unwrap_err();
@@ -456,13 +808,34 @@ mod collect_dapp {
             expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
         );
     }
-}
 
-mod transfer_ownership {
-    use super::*;
+    #[test]
+    fn zero_code_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::CollectReferrer {
+                code: 0,
+                dapp: "dapp".to_owned(),
+                recipient: None,
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid referral code - expected non-zero value"],
+        );
+    }
 
     #[test]
-    fn works() {
+    fn recipient_works() {
         let mock_api = MockApi::default();
         let msg_info = MessageInfo {
             sender: Addr::unchecked("sender"),
@@ -472,9 +845,10 @@ mod transfer_ownership {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::TransferOwnership {
+            ExecuteMsg::CollectReferrer {
                 code: 1,
-                owner: "new_owner".to_owned(),
+                dapp: "dapp".to_owned(),
+                recipient: Some("cold_wallet".to_owned()),
             },
         )
         .unwrap();
@@ -484,16 +858,17 @@ mod transfer_ownership {
             expect![[r#"
                 (
                   sender: ("sender"),
-                  kind: Config(TransferReferralCodeOwnership(
+                  kind: Collect(Referrer(
+                    dapp: ("dapp"),
                     code: (1),
-                    owner: ("new_owner"),
+                    recipient: Some(("cold_wallet")),
                   )),
                 )"#]],
         );
     }
 
     #[test]
-    fn invalid_owner_address_fails() {
+    fn invalid_recipient_address_fails() {
         let mock_api = MockApi::default();
         let msg_info = MessageInfo {
             sender: Addr::unchecked("sender"),
@@ -503,9 +878,10 @@ mod transfer_ownership {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::TransferOwnership {
+            ExecuteMsg::CollectReferrer {
                 code: 1,
-                owner: "0".to_owned(),
+                dapp: "dapp".to_owned(),
+                recipient: Some("0".to_owned()),
             },
         )
         .unwrap_err();
@@ -517,7 +893,7 @@ mod transfer_ownership {
     }
 }
 
-mod configure_dapp {
+mod collect_referrer_all {
     use super::*;
 
     #[test]
@@ -531,11 +907,46 @@ mod configure_dapp {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::ConfigureDapp {
+            ExecuteMsg::CollectReferrerAll {
+                code: 1,
+                start: Some(2),
+                limit: None,
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Collect(ReferrerAll(
+                    code: (1),
+                    start: Some(2),
+                    limit: None,
+                  )),
+                )"#]],
+        );
+    }
+}
+
+mod collect_dapp {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::CollectDapp {
                 dapp: "dapp".to_owned(),
-                percent: Some(100),
-                collector: Some("new_collector".to_owned()),
-                repo_url: None,
+                recipient: None,
             },
         )
         .unwrap();
@@ -545,20 +956,16 @@ mod configure_dapp {
             expect![[r#"
                 (
                   sender: ("sender"),
-                  kind: Config(DappMetadata(
+                  kind: Collect(Dapp(
                     dapp: ("dapp"),
-                    metadata: (
-                      percent: Some((100)),
-                      collector: Some(("new_collector")),
-                      repo_url: None,
-                    ),
+                    recipient: None,
                   )),
                 )"#]],
         );
     }
 
     #[test]
-    fn invalid_dapp_fails() {
+    fn invalid_dapp_address_fails() {
         let mock_api = MockApi::default();
         let msg_info = MessageInfo {
             sender: Addr::unchecked("sender"),
@@ -568,11 +975,9 @@ mod configure_dapp {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::ConfigureDapp {
+            ExecuteMsg::CollectDapp {
                 dapp: "0".to_owned(),
-                percent: Some(100),
-                collector: Some("new_collector".to_owned()),
-                repo_url: None,
+                recipient: None,
             },
         )
         .unwrap_err();
@@ -584,7 +989,7 @@ mod configure_dapp {
     }
 
     #[test]
-    fn invalid_percent_fails() {
+    fn recipient_works() {
         let mock_api = MockApi::default();
         let msg_info = MessageInfo {
             sender: Addr::unchecked("sender"),
@@ -593,41 +998,57 @@ mod configure_dapp {
 
         let res = parse_hub_exec(
             &mock_api,
-            msg_info.clone(),
-            ExecuteMsg::ConfigureDapp {
+            msg_info,
+            ExecuteMsg::CollectDapp {
                 dapp: "dapp".to_owned(),
-                percent: Some(0),
-                collector: Some("new_collector".to_owned()),
-                repo_url: None,
+                recipient: Some("treasury".to_owned()),
             },
         )
-        .unwrap_err();
+        .unwrap();
 
         check(
-            res,
-            expect!["invalid percent - valid value is any integer between 1 & 100"],
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Collect(Dapp(
+                    dapp: ("dapp"),
+                    recipient: Some(("treasury")),
+                  )),
+                )"#]],
         );
+    }
+
+    #[test]
+    fn invalid_recipient_address_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
 
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::ConfigureDapp {
+            ExecuteMsg::CollectDapp {
                 dapp: "dapp".to_owned(),
-                percent: Some(101),
-                collector: Some("new_collector".to_owned()),
-                repo_url: None,
+                recipient: Some("0".to_owned()),
             },
         )
         .unwrap_err();
 
         check(
             res,
-            expect!["invalid percent - valid value is any integer between 1 & 100"],
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
         );
     }
+}
+
+mod transfer_ownership {
+    use super::*;
 
     #[test]
-    fn invalid_collector_fails() {
+    fn works() {
         let mock_api = MockApi::default();
         let msg_info = MessageInfo {
             sender: Addr::unchecked("sender"),
@@ -637,11 +1058,40 @@ mod configure_dapp {
         let res = parse_hub_exec(
             &mock_api,
             msg_info,
-            ExecuteMsg::ConfigureDapp {
-                dapp: "dapp".to_owned(),
-                percent: Some(100),
-                collector: Some("0".to_owned()),
-                repo_url: None,
+            ExecuteMsg::TransferOwnership {
+                code: 1,
+                owner: "new_owner".to_owned(),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(TransferReferralCodeOwnership(
+                    code: (1),
+                    owner: ("new_owner"),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_owner_address_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::TransferOwnership {
+                code: 1,
+                owner: "0".to_owned(),
             },
         )
         .unwrap_err();
@@ -651,4 +1101,1199 @@ mod configure_dapp {
             expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
         );
     }
+
+    #[test]
+    fn zero_code_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::TransferOwnership {
+                code: 0,
+                owner: "new_owner".to_owned(),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid referral code - expected non-zero value"],
+        );
+    }
+}
+
+mod configure_dapp {
+    use referrals_core::hub::{Configure, DappRepoUrl, Kind, Msg};
+    use referrals_parse_cw::MAX_REPO_URL_LEN;
+
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: Some(100),
+                collector: Some("new_collector".to_owned()),
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: Some(true),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(DappMetadata(
+                    dapp: ("dapp"),
+                    metadata: (
+                      name: None,
+                      percent: Some((100)),
+                      collector: Some(("new_collector")),
+                      repo_url: None,
+                      allow_self_referral: None,
+                      max_invocations_per_code: None,
+                    ),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_dapp_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapp {
+                dapp: "0".to_owned(),
+                name: None,
+                percent: Some(100),
+                collector: Some("new_collector".to_owned()),
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: Some(true),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+
+    #[test]
+    fn invalid_percent_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info.clone(),
+            ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: Some(0),
+                collector: Some("new_collector".to_owned()),
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid percent - valid value is any integer between 1 & 100"],
+        );
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: Some(101),
+                collector: Some("new_collector".to_owned()),
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid percent - valid value is any integer between 1 & 100"],
+        );
+    }
+
+    #[test]
+    fn unconfirmed_no_dapp_share_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: Some(100),
+                collector: None,
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: Some(false),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid percent - 100 leaves the dApp no share of rewards, set `confirm_no_dapp_share` to confirm"],
+        );
+    }
+
+    #[test]
+    fn invalid_collector_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: Some(100),
+                collector: Some("0".to_owned()),
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: None,
+                confirm_no_dapp_share: Some(true),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+
+    #[test]
+    fn zero_invocation_cap_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapp {
+                dapp: "dapp".to_owned(),
+                name: None,
+                percent: None,
+                collector: None,
+                repo_url: None,
+                clear_repo_url: None,
+                allow_self_referral: None,
+                max_invocations_per_code: Some(0),
+                confirm_no_dapp_share: None,
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid invocation cap - expected non-zero value"],
+        );
+    }
+
+    #[test]
+    fn name_works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let configure_name = |name: &str| ExecuteMsg::ConfigureDapp {
+            dapp: "dapp".to_owned(),
+            name: Some(name.to_owned()),
+            percent: None,
+            collector: None,
+            repo_url: None,
+            clear_repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        };
+
+        let res = parse_hub_exec(&mock_api, msg_info.clone(), configure_name("new_name")).unwrap();
+
+        let Msg {
+            kind: Kind::Config(Configure::DappMetadata { metadata, .. }),
+            ..
+        } = res
+        else {
+            unreachable!()
+        };
+
+        check(pretty(&metadata.name), expect![[r#"Some("new_name")"#]]);
+
+        for name in ["", "  "] {
+            let res =
+                parse_hub_exec(&mock_api, msg_info.clone(), configure_name(name)).unwrap_err();
+
+            check(res, expect!["invalid name - expected a non-empty name"]);
+        }
+
+        let res =
+            parse_hub_exec(&mock_api, msg_info, configure_name(&"n".repeat(129))).unwrap_err();
+
+        check(
+            res,
+            expect!["invalid name - 129 bytes exceeds the maximum of 128"],
+        );
+    }
+
+    fn configure_repo_url(repo_url: Option<&str>, clear_repo_url: Option<bool>) -> ExecuteMsg {
+        ExecuteMsg::ConfigureDapp {
+            dapp: "dapp".to_owned(),
+            name: None,
+            percent: None,
+            collector: None,
+            repo_url: repo_url.map(str::to_owned),
+            clear_repo_url,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        }
+    }
+
+    #[test]
+    fn repo_url_works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let repo_url = |msg| match parse_hub_exec(&mock_api, msg_info.clone(), msg).unwrap() {
+            Msg {
+                kind: Kind::Config(Configure::DappMetadata { metadata, .. }),
+                ..
+            } => pretty(&metadata.repo_url),
+            _ => unreachable!(),
+        };
+
+        check(
+            repo_url(configure_repo_url(
+                Some("https://github.com/org/repo"),
+                None,
+            )),
+            expect![[r#"Some(Set("https://github.com/org/repo"))"#]],
+        );
+
+        check(
+            repo_url(configure_repo_url(
+                Some("http://git.example.com:8080/repo"),
+                Some(false),
+            )),
+            expect![[r#"Some(Set("http://git.example.com:8080/repo"))"#]],
+        );
+
+        check(
+            repo_url(configure_repo_url(None, Some(true))),
+            expect!["Some(Clear)"],
+        );
+
+        check(repo_url(configure_repo_url(None, None)), expect!["None"]);
+
+        // a url right at the length limit is still accepted
+        let longest = format!("https://github.com/{}", "r".repeat(MAX_REPO_URL_LEN - 19));
+
+        assert_eq!(
+            repo_url(configure_repo_url(Some(&longest), None)),
+            pretty(&Some(DappRepoUrl::Set(longest))),
+        );
+    }
+
+    #[test]
+    fn invalid_repo_url_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        for repo_url in [
+            "ftp://github.com/org/repo",
+            "github.com/org/repo",
+            "https://",
+            "https:///org/repo",
+            "https://github com/org/repo",
+        ] {
+            let res = parse_hub_exec(
+                &mock_api,
+                msg_info.clone(),
+                configure_repo_url(Some(repo_url), None),
+            )
+            .unwrap_err();
+
+            check(
+                res,
+                expect!["invalid repo url - expected an http(s) url with a host"],
+            );
+        }
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            configure_repo_url(Some("https://github.com/org/repo"), Some(true)),
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid repo url - cannot both set & clear the repo url"],
+        );
+    }
+}
+
+mod configure_dapps {
+    use referrals_core::hub::{Configure, Kind, Msg};
+    use referrals_cw::ConfigureDappEntry;
+
+    use super::*;
+
+    fn entry(dapp: &str, percent: u8) -> ConfigureDappEntry {
+        ConfigureDappEntry {
+            dapp: dapp.to_owned(),
+            name: None,
+            percent: Some(percent),
+            collector: None,
+            repo_url: None,
+            clear_repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        }
+    }
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("collector"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapps {
+                updates: vec![entry("dapp1", 20), entry("dapp2", 30)],
+            },
+        )
+        .unwrap();
+
+        let Msg {
+            kind: Kind::Config(Configure::DappsMetadata { updates }),
+            ..
+        } = res
+        else {
+            unreachable!()
+        };
+
+        let updates: Vec<_> = updates
+            .iter()
+            .map(|(dapp, metadata)| format!("{dapp} {}", pretty(&metadata.percent)))
+            .collect();
+
+        check(
+            updates.join("\n"),
+            expect![[r#"
+                dapp1 Some((20))
+                dapp2 Some((30))"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_entry_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("collector"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::ConfigureDapps {
+                updates: vec![entry("dapp1", 20), entry("dapp2", 101)],
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid percent - valid value is any integer between 1 & 100"],
+        );
+    }
+}
+
+mod accept_collector {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::AcceptCollector {
+                dapp: "dapp".to_owned(),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(AcceptCollector(
+                    dapp: ("dapp"),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_dapp_address_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::AcceptCollector {
+                dapp: "0".to_owned(),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+}
+
+mod set_campaign {
+    use super::*;
+
+    fn set_campaign(
+        multiplier_num: u64,
+        multiplier_den: u64,
+        start_height: u64,
+        end_height: u64,
+    ) -> ExecuteMsg {
+        ExecuteMsg::SetCampaign {
+            dapp: "dapp".to_owned(),
+            multiplier_num,
+            multiplier_den,
+            start_height,
+            end_height,
+        }
+    }
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(&mock_api, msg_info, set_campaign(2, 1, 100, 200)).unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(DappCampaign(
+                    dapp: ("dapp"),
+                    campaign: (
+                      multiplier_num: 2,
+                      multiplier_den: 1,
+                      start_height: 100,
+                      end_height: 200,
+                    ),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn zero_multiplier_fails() {
+        let mock_api = MockApi::default();
+
+        for msg in [set_campaign(0, 1, 100, 200), set_campaign(2, 0, 100, 200)] {
+            let msg_info = MessageInfo {
+                sender: Addr::unchecked("sender"),
+                funds: vec![],
+            };
+
+            let res = parse_hub_exec(&mock_api, msg_info, msg).unwrap_err();
+
+            check(
+                res,
+                expect!["invalid multiplier - expected non-zero numerator & denominator"],
+            );
+        }
+    }
+
+    #[test]
+    fn start_after_end_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(&mock_api, msg_info, set_campaign(2, 1, 200, 100)).unwrap_err();
+
+        check(
+            res,
+            expect!["invalid campaign - start height is after end height"],
+        );
+    }
+}
+
+mod set_referral_relayer {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetReferralRelayer {
+                dapp: "dapp".to_owned(),
+                relayer: Some("relayer".to_owned()),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(ReferralRelayer(
+                    dapp: ("dapp"),
+                    relayer: Some(("relayer")),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn remove_works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetReferralRelayer {
+                dapp: "dapp".to_owned(),
+                relayer: None,
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(ReferralRelayer(
+                    dapp: ("dapp"),
+                    relayer: None,
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_relayer_address_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetReferralRelayer {
+                dapp: "dapp".to_owned(),
+                relayer: Some("0".to_owned()),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+}
+
+mod set_code_status {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetCodeStatus {
+                code: 1,
+                blocked: true,
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(CodeStatus(
+                    code: (1),
+                    blocked: true,
+                  )),
+                )"#]],
+        );
+    }
+}
+
+mod set_enabled_features {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetEnabledFeatures {
+                register: true,
+                record: true,
+                collect: false,
+                configure: true,
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(EnabledFeatures(
+                    features: (11),
+                  )),
+                )"#]],
+        );
+    }
+}
+
+mod set_min_collect_amount {
+    use cosmwasm_std::Uint128;
+
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetMinCollectAmount {
+                amount: Some(Uint128::new(5000)),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(MinCollectAmount(
+                    amount: Some(5000),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn zero_removes_minimum() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::SetMinCollectAmount {
+                amount: Some(Uint128::zero()),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(MinCollectAmount(
+                    amount: None,
+                  )),
+                )"#]],
+        );
+    }
+}
+
+#[test]
+fn set_allow_self_dapp() {
+    let mock_api = MockApi::default();
+    let msg_info = MessageInfo {
+        sender: Addr::unchecked("sender"),
+        funds: vec![],
+    };
+
+    let res = parse_hub_exec(
+        &mock_api,
+        msg_info,
+        ExecuteMsg::SetAllowSelfDapp { allowed: false },
+    )
+    .unwrap();
+
+    check(
+        pretty(&res),
+        expect![[r#"
+            (
+              sender: ("sender"),
+              kind: Config(AllowSelfDapp(
+                allowed: false,
+              )),
+            )"#]],
+    );
+}
+
+mod handover_pots {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::HandoverPots {
+                dapps: vec!["dapp1".to_owned(), "dapp2".to_owned()],
+                new_admin: "new_hub".to_owned(),
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("sender"),
+                  kind: Config(HandoverPots(
+                    dapps: [
+                      ("dapp1"),
+                      ("dapp2"),
+                    ],
+                    new_admin: ("new_hub"),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_dapp_fails() {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        let res = parse_hub_exec(
+            &mock_api,
+            msg_info,
+            ExecuteMsg::HandoverPots {
+                dapps: vec!["dapp1".to_owned(), "0".to_owned()],
+                new_admin: "new_hub".to_owned(),
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+}
+
+mod simulation {
+    use referrals_parse_cw::parse_hub_simulation;
+
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mock_api = MockApi::default();
+
+        let res = parse_hub_simulation(
+            &mock_api,
+            "dapp",
+            ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: None,
+            },
+        )
+        .unwrap();
+
+        check(
+            pretty(&res),
+            expect![[r#"
+                (
+                  sender: ("dapp"),
+                  kind: Referral(
+                    code: (1),
+                    dapp: None,
+                  ),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn invalid_sender_fails() {
+        let mock_api = MockApi::default();
+
+        let res = parse_hub_simulation(
+            &mock_api,
+            "0",
+            ExecuteMsg::RecordReferral {
+                code: 1,
+                dapp: None,
+            },
+        )
+        .unwrap_err();
+
+        check(
+            res,
+            expect!["invalid address - Generic error: Invalid input: human address too short for this mock implementation (must be >= 3)."],
+        );
+    }
+}
+
+mod init_pot_reply {
+    use cosmwasm_std::{to_binary, Binary, Reply, SubMsgResponse, SubMsgResult};
+    use referrals_core::Id;
+    use referrals_cw::rewards_pot::InstantiateResponse;
+    use referrals_parse_cw::{parse_init_pot_reply, Error};
+
+    use super::*;
+
+    /// Protobuf encode a short length delimited field.
+    fn encode_field(field: u8, bytes: &[u8], out: &mut Vec<u8>) {
+        out.push((field << 3) | 2);
+        out.push(u8::try_from(bytes.len()).unwrap());
+        out.extend_from_slice(bytes);
+    }
+
+    /// A successful instantiation reply from `rewards_pot` reporting `dapp`.
+    fn reply(dapp: &str) -> Reply {
+        let response = to_binary(&InstantiateResponse {
+            dapp: dapp.to_owned(),
+        })
+        .unwrap();
+
+        let mut data = vec![];
+        encode_field(1, b"rewards_pot", &mut data);
+        encode_field(2, response.as_slice(), &mut data);
+
+        Reply {
+            id: 0,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary(data)),
+            }),
+        }
+    }
+
+    fn parse(dapp: &str) -> Result<referrals_core::hub::Msg, Error> {
+        parse_init_pot_reply(
+            &MockApi::default(),
+            reply(dapp),
+            Id::from("hub"),
+            Some(Id::from(dapp)),
+            Some(Id::from("rewards_pot")),
+        )
+    }
+
+    #[test]
+    fn works() {
+        check(
+            pretty(&parse("dapp").unwrap()),
+            expect![[r#"
+                (
+                  sender: ("rewards_pot"),
+                  kind: Register(RewardsPot(
+                    dapp: ("dapp"),
+                    rewards_pot: ("rewards_pot"),
+                  )),
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn unexpected_address_fails() {
+        let res = parse_init_pot_reply(
+            &MockApi::default(),
+            reply("dapp"),
+            Id::from("hub"),
+            Some(Id::from("dapp")),
+            Some(Id::from("expected_pot")),
+        );
+
+        check(
+            res.unwrap_err(),
+            expect![
+                "invalid reply - rewards pot instantiated at rewards_pot, expected expected_pot"
+            ],
+        );
+
+        let res = parse_init_pot_reply(
+            &MockApi::default(),
+            reply("dapp"),
+            Id::from("hub"),
+            None,
+            None,
+        );
+
+        check(
+            res.unwrap_err(),
+            expect!["invalid reply - no rewards pot instantiation pending"],
+        );
+    }
+
+    #[test]
+    fn malicious_dapp_fails() {
+        check(parse("").unwrap_err(), expect!["invalid id - empty id"]);
+
+        check(parse("   ").unwrap_err(), expect!["invalid id - empty id"]);
+
+        check(
+            parse("dapp\0").unwrap_err(),
+            expect!["invalid id - id contains a control character"],
+        );
+
+        check(
+            parse(&"d".repeat(129)).unwrap_err(),
+            expect!["invalid id - id too long - 129 characters, the maximum is 128"],
+        );
+
+        // a well formed id must still be a valid address
+        assert!(matches!(parse("DApp"), Err(Error::InvalidAddress(_))));
+    }
+}
+
+mod oversized_input {
+    use super::*;
+
+    fn parse(msg: ExecuteMsg) -> String {
+        let mock_api = MockApi::default();
+        let msg_info = MessageInfo {
+            sender: Addr::unchecked("sender"),
+            funds: vec![],
+        };
+
+        parse_hub_exec(&mock_api, msg_info, msg)
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn address_too_long_fails() {
+        let res = parse(ExecuteMsg::CollectDapp {
+            dapp: "a".repeat(91),
+            recipient: None,
+        });
+
+        check(
+            res,
+            expect!["invalid address - 91 bytes exceeds the maximum of 90"],
+        );
+
+        let res = parse(ExecuteMsg::ConfigureDapp {
+            dapp: "dapp".to_owned(),
+            name: None,
+            percent: None,
+            collector: Some("c".repeat(1024)),
+            repo_url: None,
+            clear_repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        });
+
+        check(
+            res,
+            expect!["invalid address - 1024 bytes exceeds the maximum of 90"],
+        );
+    }
+
+    #[test]
+    fn name_too_long_fails() {
+        let res = parse(ExecuteMsg::ActivateDapp {
+            name: "n".repeat(129),
+            percent: 50,
+            collector: "collector".to_owned(),
+            fee: None,
+            confirm_no_dapp_share: None,
+        });
+
+        check(
+            res,
+            expect!["invalid name - 129 bytes exceeds the maximum of 128"],
+        );
+
+        let res = parse(ExecuteMsg::ActivateDappTrackingOnly {
+            name: "n".repeat(129),
+            collector: "collector".to_owned(),
+        });
+
+        check(
+            res,
+            expect!["invalid name - 129 bytes exceeds the maximum of 128"],
+        );
+    }
+
+    #[test]
+    fn repo_url_too_long_fails() {
+        let res = parse(ExecuteMsg::ConfigureDapp {
+            dapp: "dapp".to_owned(),
+            name: None,
+            percent: None,
+            collector: None,
+            repo_url: Some("r".repeat(513)),
+            clear_repo_url: None,
+            allow_self_referral: None,
+            max_invocations_per_code: None,
+            confirm_no_dapp_share: None,
+        });
+
+        check(
+            res,
+            expect!["invalid repo url - 513 bytes exceeds the maximum of 512"],
+        );
+    }
 }
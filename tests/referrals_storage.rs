@@ -1,16 +1,21 @@
 use std::collections::BTreeMap;
+use std::num::{NonZeroU128, NonZeroU64};
 
 use kv_storage::{Deserializer, Fallible, HasKey, KvStore, Read, Remove, Serializer, Write};
 use serde::{de::DeserializeOwned, Serialize};
 
 use referrals_core::hub::{
-    MutableCollectStore, MutableDappStore, MutableReferralStore, ReadonlyCollectStore,
-    ReadonlyDappStore, ReadonlyReferralStore, ReferralCode,
+    Activity, DappCampaign, DappsQuery, MutableCollectStore, MutableDappStore,
+    MutableReferralStore, ReadonlyCollectStore, ReadonlyDappStore, ReadonlyReferralStore,
+    Recording, ReferrersQuery, TotalsQuery,
+};
+use referrals_core::{Id, IdRef};
+use referrals_storage::{
+    composed_key_len, RecordingStore, Storage as CoreStorage, MAX_ID_KEY_LEN, MAX_INT_KEY_LEN,
+    MAX_KEY_LEN,
 };
-use referrals_core::Id;
-use referrals_storage::Storage as CoreStorage;
 
-use crate::{check, expect, nz, nzp};
+use crate::{check, code, expect, nz, nzp, pretty};
 
 #[derive(Default)]
 pub struct RonSerde(String);
@@ -80,6 +85,24 @@ impl Remove for Repo {
     }
 }
 
+// A borrowed repo serves as the readonly snapshot of a `RecordingStore`
+
+impl Fallible for &Repo {
+    type Error = std::convert::Infallible;
+}
+
+impl Read for &Repo {
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Repo::read(self, key)
+    }
+}
+
+impl HasKey for &Repo {
+    fn has_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Repo::has_key(self, key)
+    }
+}
+
 #[test]
 fn dapp_storage_works() {
     let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
@@ -115,6 +138,8 @@ fn dapp_storage_works() {
             	referrals_storage::hub::dapp::dapp_index::00000000 => "id1"
             	referrals_storage::hub::dapp::dapp_index::00000001 => "id2"
             	referrals_storage::hub::dapp::dapp_last_index => 1
+            	referrals_storage::hub::dapp::dapp_names::dapp1 => "id1"
+            	referrals_storage::hub::dapp::dapp_names::dapp2 => "id2"
             	referrals_storage::hub::dapp::dapp_reverse_index::id1 => 0
             	referrals_storage::hub::dapp::dapp_reverse_index::id2 => 1
             	referrals_storage::hub::dapp::dapps::id1 => "dapp1"
@@ -125,6 +150,7 @@ fn dapp_storage_works() {
             	referrals_storage::hub::dapp::repo_url::id2 => "some_other_repo"
             	referrals_storage::hub::dapp::rewards_pot::id1 => "rewards_pot_1"
             	referrals_storage::hub::dapp::rewards_pot::id2 => "rewards_pot_2"
+            	referrals_storage::hub::totals::active_dapps => 2
             }
         "#]],
     );
@@ -137,7 +163,10 @@ fn dapp_storage_works() {
 
     assert!(storage.dapp_exists(&id2).unwrap());
 
-    check(storage.percent(&id2).unwrap().to_u8(), expect!["75"]);
+    check(
+        storage.percent(&id2).unwrap().unwrap().to_u8(),
+        expect!["75"],
+    );
 
     check(
         storage.collector(&id2).unwrap().into_string(),
@@ -158,13 +187,261 @@ fn dapp_storage_works() {
     assert!(!storage.has_rewards_pot(&Id::from("no_pot")).unwrap());
 }
 
+#[test]
+fn dapp_campaign_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    assert!(storage.campaign(&dapp).unwrap().is_none());
+
+    let campaign = DappCampaign {
+        multiplier_num: NonZeroU64::new(3).unwrap(),
+        multiplier_den: NonZeroU64::new(2).unwrap(),
+        start_height: 100,
+        end_height: 200,
+    };
+
+    storage.set_campaign(&dapp, campaign).unwrap();
+
+    assert_eq!(storage.campaign(&dapp).unwrap(), Some(campaign));
+}
+
+#[test]
+fn dapp_invocation_cap_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    assert!(storage.max_invocations_per_code(&dapp).unwrap().is_none());
+
+    storage
+        .set_max_invocations_per_code(&dapp, NonZeroU64::new(5).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        storage.max_invocations_per_code(&dapp).unwrap(),
+        NonZeroU64::new(5)
+    );
+}
+
+#[test]
+fn dapp_relayer_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    assert!(storage.relayer(&dapp).unwrap().is_none());
+
+    storage.set_relayer(&dapp, Id::from("relayer")).unwrap();
+
+    assert_eq!(storage.relayer(&dapp).unwrap(), Some(Id::from("relayer")));
+
+    storage.remove_relayer(&dapp).unwrap();
+
+    assert!(storage.relayer(&dapp).unwrap().is_none());
+}
+
+#[test]
+fn all_dapp_ids_skips_index_gaps() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    for id in ["id1", "id2", "id3"] {
+        storage.add_dapp(&Id::from(id), id.to_owned()).unwrap();
+    }
+
+    storage.remove_dapp(&Id::from("id3")).unwrap();
+
+    check(storage.active_dapp_count().unwrap(), expect!["2"]);
+
+    let middle_index_key = [
+        b"referrals_storage::hub::dapp::dapp_index::".as_slice(),
+        &1u64.to_be_bytes(),
+    ]
+    .concat();
+
+    storage.inner_mut().remove(&middle_index_key).unwrap();
+
+    check(
+        storage.inner().repo(),
+        expect![[r#"
+            {
+            	referrals_storage::hub::dapp::dapp_index::00000000 => "id1"
+            	referrals_storage::hub::dapp::dapp_index::00000002 => "id3"
+            	referrals_storage::hub::dapp::dapp_last_index => 2
+            	referrals_storage::hub::dapp::dapp_names::id1 => "id1"
+            	referrals_storage::hub::dapp::dapp_names::id2 => "id2"
+            	referrals_storage::hub::dapp::dapp_reverse_index::id1 => 0
+            	referrals_storage::hub::dapp::dapp_reverse_index::id2 => 1
+            	referrals_storage::hub::dapp::dapp_reverse_index::id3 => 2
+            	referrals_storage::hub::dapp::dapps::id1 => "id1"
+            	referrals_storage::hub::dapp::dapps::id2 => "id2"
+            	referrals_storage::hub::totals::active_dapps => 2
+            }
+        "#]],
+    );
+
+    let ids: Vec<String> = storage
+        .all_dapp_ids(None, None)
+        .unwrap()
+        .into_iter()
+        .map(Id::into_string)
+        .collect();
+
+    check(ids.join(","), expect!["id1,id3"]);
+
+    check(storage.active_dapp_count().unwrap(), expect!["2"]);
+}
+
+#[test]
+fn all_dapp_ids_past_end_is_empty() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    for id in ["id1", "id2", "id3"] {
+        storage.add_dapp(&Id::from(id), id.to_owned()).unwrap();
+    }
+
+    let ids = |start| -> Vec<String> {
+        storage
+            .all_dapp_ids(Some(start), Some(10))
+            .unwrap()
+            .into_iter()
+            .map(Id::into_string)
+            .collect()
+    };
+
+    check(ids(2).join(","), expect!["id3"]);
+    check(ids(3).join(","), expect![""]);
+    check(ids(1000).join(","), expect![""]);
+}
+
+#[test]
+fn set_name_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    storage.add_dapp(&dapp, "dapp_typo".to_owned()).unwrap();
+    storage.set_name(&dapp, "dapp".to_owned()).unwrap();
+
+    check(
+        format!("{:?}", storage.dapp_name(&dapp).unwrap()),
+        expect![[r#"Some("dapp")"#]],
+    );
+
+    // the previous name is freed
+    assert_eq!(storage.dapp_by_name("dapp_typo").unwrap(), None);
+    assert_eq!(storage.dapp_by_name("dapp").unwrap(), Some(dapp.clone()));
+
+    // renaming keeps the dApp's place in the index
+    check(
+        storage.inner().repo(),
+        expect![[r#"
+            {
+            	referrals_storage::hub::dapp::dapp_index::00000000 => "dapp"
+            	referrals_storage::hub::dapp::dapp_last_index => 0
+            	referrals_storage::hub::dapp::dapp_names::dapp => "dapp"
+            	referrals_storage::hub::dapp::dapp_reverse_index::dapp => 0
+            	referrals_storage::hub::dapp::dapps::dapp => "dapp"
+            	referrals_storage::hub::totals::active_dapps => 1
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn dapp_by_name_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    storage.add_dapp(&dapp, "name".to_owned()).unwrap();
+
+    assert_eq!(storage.dapp_by_name("name").unwrap(), Some(dapp.clone()));
+    assert_eq!(storage.dapp_by_name("other").unwrap(), None);
+
+    // a frozen dApp keeps it's name
+    storage.set_dapp_frozen(&dapp, true).unwrap();
+
+    assert_eq!(storage.dapp_by_name("name").unwrap(), Some(dapp.clone()));
+
+    storage.remove_dapp(&dapp).unwrap();
+
+    assert_eq!(storage.dapp_by_name("name").unwrap(), None);
+
+    // a freed name may be taken by another dApp
+    let other = Id::from("other");
+
+    storage.add_dapp(&other, "name".to_owned()).unwrap();
+
+    assert_eq!(storage.dapp_by_name("name").unwrap(), Some(other));
+}
+
+#[test]
+fn clear_repo_url_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    storage.add_dapp(&dapp, "dapp".to_owned()).unwrap();
+    storage
+        .set_repo_url(&dapp, "https://github.com/org/repo".to_owned())
+        .unwrap();
+
+    check(
+        format!("{:?}", storage.dapp_repo_url(&dapp).unwrap()),
+        expect![[r#"Some("https://github.com/org/repo")"#]],
+    );
+
+    storage.clear_repo_url(&dapp).unwrap();
+
+    check(
+        format!("{:?}", storage.dapp_repo_url(&dapp).unwrap()),
+        expect!["None"],
+    );
+
+    // clearing an unset repo url is a no-op
+    storage.clear_repo_url(&dapp).unwrap();
+}
+
+#[test]
+fn all_dapp_ids_respects_limit() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    for id in ["id1", "id2", "id3"] {
+        storage.add_dapp(&Id::from(id), id.to_owned()).unwrap();
+    }
+
+    let ids = |start, limit| -> Vec<String> {
+        storage
+            .all_dapp_ids(Some(start), Some(limit))
+            .unwrap()
+            .into_iter()
+            .map(Id::into_string)
+            .collect()
+    };
+
+    check(ids(0, 2).join(","), expect!["id1,id2"]);
+    check(ids(1, 1).join(","), expect!["id2"]);
+    check(ids(0, 0).join(","), expect![""]);
+
+    check(
+        format!("{:?}", storage.dapp_index(&Id::from("id3")).unwrap()),
+        expect!["Some(2)"],
+    );
+    check(
+        format!("{:?}", storage.dapp_index(&Id::from("id4")).unwrap()),
+        expect!["None"],
+    );
+}
+
 #[test]
 fn referral_storage_works() {
     let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
 
-    let code1 = ReferralCode::from(1);
-    let code2 = ReferralCode::from(2);
-    let code3 = ReferralCode::from(3);
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let code3 = code!(3);
     let id1 = Id::from("id1");
     let id2 = Id::from("id2");
     let id3 = Id::from("id3");
@@ -178,39 +455,63 @@ fn referral_storage_works() {
 
     storage.set_code_owner(code2, id2.clone()).unwrap();
 
-    storage.increment_invocations(&dapp1, code1).unwrap();
+    storage
+        .increment_invocations(dapp1.as_id_ref(), code1)
+        .unwrap();
 
-    storage.increment_invocations(&dapp2, code1).unwrap();
+    storage
+        .increment_invocations(dapp2.as_id_ref(), code1)
+        .unwrap();
 
-    storage.increment_invocations(&dapp2, code2).unwrap();
+    storage
+        .increment_invocations(dapp2.as_id_ref(), code2)
+        .unwrap();
 
     storage.set_total_earnings(code1, nz!(2000)).unwrap();
 
     storage.set_total_earnings(code2, nz!(1000)).unwrap();
 
-    storage.set_dapp_earnings(&dapp1, code1, nz!(1000)).unwrap();
+    storage
+        .set_dapp_earnings(dapp1.as_id_ref(), code1, nz!(1000))
+        .unwrap();
 
-    storage.set_dapp_earnings(&dapp2, code1, nz!(1000)).unwrap();
+    storage
+        .set_dapp_earnings(dapp2.as_id_ref(), code1, nz!(1000))
+        .unwrap();
 
-    storage.set_dapp_earnings(&dapp2, code2, nz!(1000)).unwrap();
+    storage
+        .set_dapp_earnings(dapp2.as_id_ref(), code2, nz!(1000))
+        .unwrap();
 
-    storage.set_dapp_contributions(&dapp1, nz!(1000)).unwrap();
+    storage
+        .set_dapp_contributions(dapp1.as_id_ref(), nz!(1000))
+        .unwrap();
 
-    storage.set_dapp_contributions(&dapp2, nz!(2000)).unwrap();
+    storage
+        .set_dapp_contributions(dapp2.as_id_ref(), nz!(2000))
+        .unwrap();
 
     check(
         storage.inner().repo(),
         expect![[r#"
             {
+            	referrals_storage::hub::referral::code_dapp_count::00000001 => 2
+            	referrals_storage::hub::referral::code_dapp_count::00000002 => 1
             	referrals_storage::hub::referral::code_dapp_earnings::dapp1:00000001 => 1000
             	referrals_storage::hub::referral::code_dapp_earnings::dapp2:00000001 => 1000
             	referrals_storage::hub::referral::code_dapp_earnings::dapp2:00000002 => 1000
+            	referrals_storage::hub::referral::code_dapp_list::00000001:00000000 => "dapp1"
+            	referrals_storage::hub::referral::code_dapp_list::00000001:00000001 => "dapp2"
+            	referrals_storage::hub::referral::code_dapp_list::00000002:00000000 => "dapp2"
             	referrals_storage::hub::referral::code_owners::id1 => 1
             	referrals_storage::hub::referral::code_owners::id2 => 2
             	referrals_storage::hub::referral::code_total_earnings::00000001 => 2000
             	referrals_storage::hub::referral::code_total_earnings::00000002 => 1000
             	referrals_storage::hub::referral::codes::00000001 => "id1"
             	referrals_storage::hub::referral::codes::00000002 => "id2"
+            	referrals_storage::hub::referral::dapp_code_list::dapp1:00000000 => 1
+            	referrals_storage::hub::referral::dapp_code_list::dapp2:00000000 => 1
+            	referrals_storage::hub::referral::dapp_code_list::dapp2:00000001 => 2
             	referrals_storage::hub::referral::dapp_contributions::dapp1 => 1000
             	referrals_storage::hub::referral::dapp_contributions::dapp2 => 2000
             	referrals_storage::hub::referral::discrete_referrers::dapp1 => 1
@@ -221,6 +522,9 @@ fn referral_storage_works() {
             	referrals_storage::hub::referral::latest_code => 2
             	referrals_storage::hub::referral::total_invocation_counts::dapp1 => 1
             	referrals_storage::hub::referral::total_invocation_counts::dapp2 => 2
+            	referrals_storage::hub::totals::contributions => 3000
+            	referrals_storage::hub::totals::invocations => 3
+            	referrals_storage::hub::totals::referral_codes => 2
             }
         "#]],
     );
@@ -229,9 +533,9 @@ fn referral_storage_works() {
     assert!(storage.code_exists(code2).unwrap());
     assert!(!storage.code_exists(code3).unwrap());
 
-    assert!(storage.owner_exists(&id1).unwrap());
-    assert!(storage.owner_exists(&id2).unwrap());
-    assert!(!storage.owner_exists(&id3).unwrap());
+    assert!(storage.owner_exists(id1.as_id_ref()).unwrap());
+    assert!(storage.owner_exists(id2.as_id_ref()).unwrap());
+    assert!(!storage.owner_exists(id3.as_id_ref()).unwrap());
 
     check(
         storage.owner_of(code1).unwrap().unwrap().into_string(),
@@ -260,102 +564,409 @@ fn referral_storage_works() {
     assert!(storage.total_earnings(code3).unwrap().is_none());
 
     check(
-        storage.dapp_earnings(&dapp1, code1).unwrap().unwrap(),
+        storage
+            .dapp_earnings(dapp1.as_id_ref(), code1)
+            .unwrap()
+            .unwrap(),
         expect!["1000"],
     );
 
     check(
-        storage.dapp_earnings(&dapp2, code2).unwrap().unwrap(),
+        storage
+            .dapp_earnings(dapp2.as_id_ref(), code2)
+            .unwrap()
+            .unwrap(),
         expect!["1000"],
     );
 
-    assert!(storage.dapp_earnings(&dapp1, code2).unwrap().is_none());
+    assert!(storage
+        .dapp_earnings(dapp1.as_id_ref(), code2)
+        .unwrap()
+        .is_none());
 
     check(
-        storage.dapp_contributions(&dapp1).unwrap().unwrap(),
+        storage
+            .dapp_contributions(dapp1.as_id_ref())
+            .unwrap()
+            .unwrap(),
         expect!["1000"],
     );
 
     check(
-        storage.dapp_contributions(&dapp2).unwrap().unwrap(),
+        storage
+            .dapp_contributions(dapp2.as_id_ref())
+            .unwrap()
+            .unwrap(),
         expect!["2000"],
     );
 
-    assert!(storage.dapp_contributions(&dapp3).unwrap().is_none());
+    assert!(storage
+        .dapp_contributions(dapp3.as_id_ref())
+        .unwrap()
+        .is_none());
+
+    check(storage.code_dapp_count(code1).unwrap(), expect!["2"]);
+
+    check(
+        storage.code_dapp(code1, 1).unwrap().unwrap().into_string(),
+        expect!["dapp2"],
+    );
+
+    assert!(storage.code_dapp(code2, 1).unwrap().is_none());
 }
 
 #[test]
-fn collect_storage_works() {
+fn dapps_for_code_and_codes_for_dapp_work() {
     let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
 
-    let code1 = ReferralCode::from(1);
-    let code2 = ReferralCode::from(2);
-    let code3 = ReferralCode::from(3);
     let dapp1 = Id::from("dapp1");
     let dapp2 = Id::from("dapp2");
-    let dapp3 = Id::from("dapp3");
 
+    for (dapp, code) in [(&dapp2, code!(2)), (&dapp1, code!(1)), (&dapp2, code!(1))] {
+        storage
+            .increment_invocations(dapp.as_id_ref(), code)
+            .unwrap();
+        storage
+            .set_dapp_earnings(dapp.as_id_ref(), code, nz!(500))
+            .unwrap();
+    }
+
+    // later referrals & earnings leave the lists as they are
     storage
-        .set_referrer_total_collected(code1, nz!(1000))
-        .unwrap();
-    storage
-        .set_referrer_dapp_collected(&dapp1, code1, nz!(500))
+        .increment_invocations(dapp2.as_id_ref(), code!(2))
         .unwrap();
     storage
-        .set_referrer_dapp_collected(&dapp2, code1, nz!(500))
+        .set_dapp_earnings(dapp2.as_id_ref(), code!(2), nz!(1000))
         .unwrap();
 
+    check(
+        format!("{:?}", storage.dapps_for_code(code!(1)).unwrap()),
+        expect![[r#"[Id("dapp1"), Id("dapp2")]"#]],
+    );
+    check(
+        format!("{:?}", storage.dapps_for_code(code!(2)).unwrap()),
+        expect![[r#"[Id("dapp2")]"#]],
+    );
+    assert!(storage.dapps_for_code(code!(3)).unwrap().is_empty());
+
+    check(
+        format!("{:?}", storage.codes_for_dapp(&dapp1).unwrap()),
+        expect!["[Code(1)]"],
+    );
+    check(
+        format!("{:?}", storage.codes_for_dapp(&dapp2).unwrap()),
+        expect!["[Code(2), Code(1)]"],
+    );
+    assert!(storage
+        .codes_for_dapp(&Id::from("dapp3"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn referral_activity_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let dapp = Id::from("dapp");
+
+    // no activity until the first referral is recorded
+    assert!(storage.dapp_last_active(&dapp).unwrap().is_none());
+    assert!(storage
+        .referrer_last_active(&dapp, code1)
+        .unwrap()
+        .is_none());
+
+    let earlier = Activity {
+        height: 100,
+        time: 500,
+    };
+
+    let later = Activity {
+        height: 110,
+        time: 550,
+    };
+
     storage
-        .set_referrer_total_collected(code2, nz!(3000))
-        .unwrap();
-    storage
-        .set_referrer_dapp_collected(&dapp1, code2, nz!(2000))
+        .increment_invocations(dapp.as_id_ref(), code1)
         .unwrap();
+    storage.touch(dapp.as_id_ref(), code1, earlier).unwrap();
+
     storage
-        .set_referrer_dapp_collected(&dapp2, code2, nz!(1000))
+        .increment_invocations(dapp.as_id_ref(), code2)
         .unwrap();
+    storage.touch(dapp.as_id_ref(), code2, later).unwrap();
 
-    storage.set_dapp_total_collected(&dapp1, nz!(200)).unwrap();
-    storage.set_dapp_total_collected(&dapp2, nz!(500)).unwrap();
+    assert_eq!(storage.dapp_last_active(&dapp).unwrap(), Some(later));
+    assert_eq!(
+        storage.referrer_last_active(&dapp, code1).unwrap(),
+        Some(earlier)
+    );
+    assert_eq!(
+        storage.referrer_last_active(&dapp, code2).unwrap(),
+        Some(later)
+    );
 
     check(
-        storage.inner().repo(),
-        expect![[r#"
-            {
-            	referrals_storage::hub::collect::dapp_total::dapp1 => 200
-            	referrals_storage::hub::collect::dapp_total::dapp2 => 500
-            	referrals_storage::hub::collect::referrer_dapp::dapp1:00000001 => 500
-            	referrals_storage::hub::collect::referrer_dapp::dapp1:00000002 => 2000
-            	referrals_storage::hub::collect::referrer_dapp::dapp2:00000001 => 500
-            	referrals_storage::hub::collect::referrer_dapp::dapp2:00000002 => 1000
-            	referrals_storage::hub::collect::referrer_total::00000001 => 1000
-            	referrals_storage::hub::collect::referrer_total::00000002 => 3000
-            }
-        "#]],
+        storage.referrer_invocations(&dapp, code1).unwrap(),
+        expect!["1"],
     );
-
     check(
-        storage.referrer_total_collected(code1).unwrap().unwrap(),
-        expect!["1000"],
+        storage.invocations(dapp.as_id_ref(), code1).unwrap(),
+        expect!["1"],
     );
-
     check(
-        storage.referrer_total_collected(code2).unwrap().unwrap(),
-        expect!["3000"],
+        storage.referrer_invocations(&dapp, code!(3)).unwrap(),
+        expect!["0"],
     );
+}
 
-    assert!(storage.referrer_total_collected(code3).unwrap().is_none());
+#[test]
+fn daily_invocations_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+    let other = Id::from("other");
 
     check(
-        storage
-            .referrer_dapp_collected(&dapp1, code1)
-            .unwrap()
-            .unwrap(),
-        expect!["500"],
+        storage.dapp_daily_invocations(&dapp, 100).unwrap(),
+        expect!["0"],
     );
 
-    check(
-        storage
+    storage
+        .increment_daily_invocations(dapp.as_id_ref(), 100)
+        .unwrap();
+    storage
+        .increment_daily_invocations(dapp.as_id_ref(), 101)
+        .unwrap();
+    storage
+        .increment_daily_invocations(dapp.as_id_ref(), 101)
+        .unwrap();
+
+    check(
+        storage.dapp_daily_invocations(&dapp, 100).unwrap(),
+        expect!["1"],
+    );
+    check(
+        storage.dapp_daily_invocations(&dapp, 101).unwrap(),
+        expect!["2"],
+    );
+    check(
+        storage.dapp_daily_invocations(&other, 101).unwrap(),
+        expect!["0"],
+    );
+}
+
+#[test]
+fn max_length_keys_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    // the longest address accepted by the parse layer
+    let dapp = Id::from("a".repeat(90));
+    let collector = Id::from("c".repeat(90));
+
+    storage.add_dapp(&dapp, "d".repeat(128)).unwrap();
+    storage.set_collector(&dapp, collector.clone()).unwrap();
+    storage.set_repo_url(&dapp, "r".repeat(512)).unwrap();
+
+    assert!(storage.dapp_exists(&dapp).unwrap());
+    assert_eq!(storage.collector(&dapp).unwrap(), collector);
+
+    let key_len = composed_key_len(
+        "referrals_storage::hub::referral::code_dapp_earnings",
+        &[MAX_ID_KEY_LEN, MAX_INT_KEY_LEN],
+    );
+
+    check(key_len, expect!["584"]);
+    assert!(key_len <= MAX_KEY_LEN);
+}
+
+#[test]
+fn increment_invocations_overflow_fails() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+    let code = code!(1);
+
+    storage
+        .increment_invocations(dapp.as_id_ref(), code)
+        .unwrap();
+
+    storage
+        .inner_mut()
+        .write(
+            b"referrals_storage::hub::totals::invocations",
+            u64::MAX.to_string().as_bytes(),
+        )
+        .unwrap();
+
+    let err = storage
+        .increment_invocations(dapp.as_id_ref(), code)
+        .unwrap_err();
+
+    check(err, expect!["counter overflow"]);
+
+    // no count is incremented when any would overflow
+    check(
+        storage.invocations(dapp.as_id_ref(), code).unwrap(),
+        expect!["1"],
+    );
+    check(storage.dapp_total_invocations(&dapp).unwrap(), expect!["1"]);
+    check(
+        storage.total_invocations().unwrap(),
+        expect!["18446744073709551615"],
+    );
+}
+
+#[test]
+fn totals_overflow_fails() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let dapp = Id::from("dapp");
+
+    for (total, max) in [
+        ("active_dapps", u64::MAX.to_string()),
+        ("referral_codes", u64::MAX.to_string()),
+        ("contributions", u128::MAX.to_string()),
+        ("collected", u128::MAX.to_string()),
+    ] {
+        storage
+            .inner_mut()
+            .write(
+                format!("referrals_storage::hub::totals::{total}").as_bytes(),
+                max.as_bytes(),
+            )
+            .unwrap();
+    }
+
+    let err = storage.add_dapp(&dapp, "dapp".to_owned()).unwrap_err();
+
+    check(err, expect!["counter overflow"]);
+
+    let err = storage
+        .set_code_owner(code!(1), Id::from("owner"))
+        .unwrap_err();
+
+    check(err, expect!["counter overflow"]);
+
+    let err = storage
+        .set_dapp_contributions(dapp.as_id_ref(), nz!(100))
+        .unwrap_err();
+
+    check(err, expect!["counter overflow"]);
+
+    let err = storage
+        .set_referrer_total_collected(code!(1), nz!(100))
+        .unwrap_err();
+
+    check(err, expect!["counter overflow"]);
+
+    // the dApp's referrers total overflows on it's own
+    storage
+        .set_referrer_dapp_collected(&dapp, code!(1), NonZeroU128::new(u128::MAX).unwrap())
+        .unwrap();
+
+    let err = storage
+        .set_referrer_dapp_collected(&dapp, code!(2), nz!(1))
+        .unwrap_err();
+
+    check(err, expect!["counter overflow"]);
+}
+
+#[test]
+fn blocked_code_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code = code!(1);
+
+    assert!(!storage.code_blocked(code).unwrap());
+
+    storage.set_code_blocked(code, true).unwrap();
+
+    assert!(storage.code_blocked(code).unwrap());
+    assert!(!storage.code_blocked(code!(2)).unwrap());
+
+    storage.set_code_blocked(code, false).unwrap();
+
+    assert!(!storage.code_blocked(code).unwrap());
+}
+
+#[test]
+fn collect_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let code3 = code!(3);
+    let dapp1 = Id::from("dapp1");
+    let dapp2 = Id::from("dapp2");
+    let dapp3 = Id::from("dapp3");
+
+    storage
+        .set_referrer_total_collected(code1, nz!(1000))
+        .unwrap();
+    storage
+        .set_referrer_dapp_collected(&dapp1, code1, nz!(500))
+        .unwrap();
+    storage
+        .set_referrer_dapp_collected(&dapp2, code1, nz!(500))
+        .unwrap();
+
+    storage
+        .set_referrer_total_collected(code2, nz!(3000))
+        .unwrap();
+    storage
+        .set_referrer_dapp_collected(&dapp1, code2, nz!(2000))
+        .unwrap();
+    storage
+        .set_referrer_dapp_collected(&dapp2, code2, nz!(1000))
+        .unwrap();
+
+    storage.set_dapp_total_collected(&dapp1, nz!(200)).unwrap();
+    storage.set_dapp_total_collected(&dapp2, nz!(500)).unwrap();
+
+    check(
+        storage.inner().repo(),
+        expect![[r#"
+            {
+            	referrals_storage::hub::collect::dapp_referrers::dapp1 => 2500
+            	referrals_storage::hub::collect::dapp_referrers::dapp2 => 1500
+            	referrals_storage::hub::collect::dapp_total::dapp1 => 200
+            	referrals_storage::hub::collect::dapp_total::dapp2 => 500
+            	referrals_storage::hub::collect::referrer_dapp::dapp1:00000001 => 500
+            	referrals_storage::hub::collect::referrer_dapp::dapp1:00000002 => 2000
+            	referrals_storage::hub::collect::referrer_dapp::dapp2:00000001 => 500
+            	referrals_storage::hub::collect::referrer_dapp::dapp2:00000002 => 1000
+            	referrals_storage::hub::collect::referrer_total::00000001 => 1000
+            	referrals_storage::hub::collect::referrer_total::00000002 => 3000
+            	referrals_storage::hub::totals::collected => 4000
+            }
+        "#]],
+    );
+
+    check(
+        storage.referrer_total_collected(code1).unwrap().unwrap(),
+        expect!["1000"],
+    );
+
+    check(
+        storage.referrer_total_collected(code2).unwrap().unwrap(),
+        expect!["3000"],
+    );
+
+    assert!(storage.referrer_total_collected(code3).unwrap().is_none());
+
+    check(
+        storage
+            .referrer_dapp_collected(&dapp1, code1)
+            .unwrap()
+            .unwrap(),
+        expect!["500"],
+    );
+
+    check(
+        storage
             .referrer_dapp_collected(&dapp1, code2)
             .unwrap()
             .unwrap(),
@@ -399,6 +1010,161 @@ fn collect_storage_works() {
     );
 
     assert!(storage.dapp_total_collected(&dapp3).unwrap().is_none());
+
+    // only the increase on a referrer's previous collections is added
+    storage
+        .set_referrer_dapp_collected(&dapp1, code1, nz!(800))
+        .unwrap();
+
+    check(
+        storage.dapp_referrers_collected(&dapp1).unwrap().unwrap(),
+        expect!["2800"],
+    );
+
+    check(
+        storage.dapp_referrers_collected(&dapp2).unwrap().unwrap(),
+        expect!["1500"],
+    );
+
+    assert!(storage.dapp_referrers_collected(&dapp3).unwrap().is_none());
+}
+
+#[test]
+fn dapp_referrers_collected_before_total_kept_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let dapp = Id::from("dapp");
+
+    for (code, collected) in [(code1, nz!(300)), (code2, nz!(200))] {
+        storage
+            .increment_invocations(dapp.as_id_ref(), code)
+            .unwrap();
+        storage
+            .set_referrer_dapp_collected(&dapp, code, collected)
+            .unwrap();
+    }
+
+    // the referrers collected before the dApp's total was kept
+    let total_key = storage
+        .inner()
+        .repo()
+        .0
+        .keys()
+        .find(|key| key.contains("dapp_referrers"))
+        .cloned()
+        .unwrap();
+
+    storage.inner_mut().remove(total_key.as_bytes()).unwrap();
+
+    assert!(storage.dapp_referrers_collected(&dapp).unwrap().is_none());
+
+    // the next collection keeps the total again, counting only what it added
+    storage
+        .set_referrer_dapp_collected(&dapp, code1, nz!(400))
+        .unwrap();
+
+    assert!(storage.inner().repo().0.contains_key(&total_key));
+
+    check(
+        storage.dapp_referrers_collected(&dapp).unwrap().unwrap(),
+        expect!["100"],
+    );
+}
+
+#[test]
+fn totals_storage_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let dapp1 = Id::from("dapp1");
+    let dapp2 = Id::from("dapp2");
+
+    let totals = |storage: &CoreStorage<KvStore<RonSerde, Repo>>| {
+        format!(
+            "dapps: {}, active: {}, codes: {}, invocations: {}, contributions: {}, collected: {}",
+            storage.total_dapp_count().unwrap(),
+            storage.active_dapp_count().unwrap(),
+            storage.total_referral_codes().unwrap(),
+            storage.total_invocations().unwrap(),
+            storage.total_contributions().unwrap(),
+            storage.total_collected().unwrap(),
+        )
+    };
+
+    check(
+        totals(&storage),
+        expect!["dapps: 0, active: 0, codes: 0, invocations: 0, contributions: 0, collected: 0"],
+    );
+
+    storage.add_dapp(&dapp1, "dapp1".to_owned()).unwrap();
+    storage.add_dapp(&dapp2, "dapp2".to_owned()).unwrap();
+    // re-activation with a new name is not a new dApp
+    storage
+        .add_dapp(&dapp2, "dapp2_renamed".to_owned())
+        .unwrap();
+
+    storage.set_code_owner(code1, Id::from("id1")).unwrap();
+    storage.set_code_owner(code2, Id::from("id2")).unwrap();
+    // ownership transfer is not a new code
+    storage.set_code_owner(code1, Id::from("id3")).unwrap();
+
+    storage
+        .increment_invocations(dapp1.as_id_ref(), code1)
+        .unwrap();
+    storage
+        .increment_invocations(dapp1.as_id_ref(), code1)
+        .unwrap();
+    storage
+        .increment_invocations(dapp2.as_id_ref(), code2)
+        .unwrap();
+
+    storage
+        .set_dapp_contributions(dapp1.as_id_ref(), nz!(1000))
+        .unwrap();
+    storage
+        .set_dapp_contributions(dapp1.as_id_ref(), nz!(1500))
+        .unwrap();
+    storage
+        .set_dapp_contributions(dapp2.as_id_ref(), nz!(500))
+        .unwrap();
+
+    check(
+        totals(&storage),
+        expect!["dapps: 2, active: 2, codes: 2, invocations: 3, contributions: 2000, collected: 0"],
+    );
+
+    storage
+        .set_referrer_total_collected(code1, nz!(500))
+        .unwrap();
+    storage
+        .set_referrer_total_collected(code1, nz!(800))
+        .unwrap();
+    storage
+        .set_referrer_total_collected(code2, nz!(200))
+        .unwrap();
+
+    storage.remove_dapp(&dapp1).unwrap();
+    // removing an inactive dApp does nothing
+    storage.remove_dapp(&dapp1).unwrap();
+
+    check(
+        totals(&storage),
+        expect![
+            "dapps: 2, active: 1, codes: 2, invocations: 3, contributions: 2000, collected: 1000"
+        ],
+    );
+
+    storage.add_dapp(&dapp1, "dapp1".to_owned()).unwrap();
+
+    check(
+        totals(&storage),
+        expect![
+            "dapps: 2, active: 2, codes: 2, invocations: 3, contributions: 2000, collected: 1000"
+        ],
+    );
 }
 
 impl std::fmt::Display for Repo {
@@ -422,3 +1188,278 @@ impl std::fmt::Display for Repo {
         writeln!(f, "}}")
     }
 }
+
+#[test]
+fn reset_dapp_accounting_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let dapp1 = Id::from("dapp1");
+    let dapp2 = Id::from("dapp2");
+
+    storage.add_dapp(&dapp1, "dapp1".to_owned()).unwrap();
+    storage.add_dapp(&dapp2, "dapp2".to_owned()).unwrap();
+    storage.set_latest(code2).unwrap();
+
+    for (dapp, code, earnings) in [
+        (&dapp1, code1, nz!(500)),
+        (&dapp2, code1, nz!(300)),
+        (&dapp1, code2, nz!(200)),
+    ] {
+        storage
+            .increment_invocations(dapp.as_id_ref(), code)
+            .unwrap();
+        storage
+            .set_dapp_earnings(dapp.as_id_ref(), code, earnings)
+            .unwrap();
+    }
+
+    storage.set_total_earnings(code1, nz!(800)).unwrap();
+    storage.set_total_earnings(code2, nz!(200)).unwrap();
+    storage
+        .set_dapp_contributions(dapp1.as_id_ref(), nz!(700))
+        .unwrap();
+    storage
+        .set_dapp_contributions(dapp2.as_id_ref(), nz!(300))
+        .unwrap();
+
+    storage
+        .set_referrer_dapp_collected(&dapp1, code1, nz!(100))
+        .unwrap();
+    storage
+        .set_referrer_dapp_collected(&dapp2, code1, nz!(300))
+        .unwrap();
+    storage
+        .set_referrer_total_collected(code1, nz!(400))
+        .unwrap();
+    storage.set_dapp_total_collected(&dapp1, nz!(50)).unwrap();
+
+    storage.remove_dapp(&dapp1).unwrap();
+    storage.reset_dapp_accounting(&dapp1).unwrap();
+
+    assert!(storage
+        .dapp_earnings(dapp1.as_id_ref(), code1)
+        .unwrap()
+        .is_none());
+    assert!(storage
+        .dapp_earnings(dapp1.as_id_ref(), code2)
+        .unwrap()
+        .is_none());
+    assert!(storage
+        .dapp_contributions(dapp1.as_id_ref())
+        .unwrap()
+        .is_none());
+    assert!(storage
+        .referrer_dapp_collected(&dapp1, code1)
+        .unwrap()
+        .is_none());
+    assert!(storage.dapp_referrers_collected(&dapp1).unwrap().is_none());
+
+    // the rewards pot is kept, so it's contributions count as collected by the dApp
+    check(
+        storage.dapp_total_collected(&dapp1).unwrap().unwrap(),
+        expect!["750"],
+    );
+
+    check(
+        storage.invocations(dapp1.as_id_ref(), code1).unwrap(),
+        expect!["0"],
+    );
+    check(
+        storage.invocations(dapp1.as_id_ref(), code2).unwrap(),
+        expect!["0"],
+    );
+    check(
+        storage.dapp_discrete_referrers(&dapp1).unwrap(),
+        expect!["0"],
+    );
+    check(
+        storage.dapp_total_invocations(&dapp1).unwrap(),
+        expect!["0"],
+    );
+
+    // code 1 keeps it's earnings & collections with dApp 2, code 2 only earned with dApp 1
+    check(
+        storage.total_earnings(code1).unwrap().unwrap(),
+        expect!["300"],
+    );
+    assert!(storage.total_earnings(code2).unwrap().is_none());
+    check(
+        storage.referrer_total_collected(code1).unwrap().unwrap(),
+        expect!["300"],
+    );
+
+    check(storage.code_dapp_count(code1).unwrap(), expect!["1"]);
+    check(
+        pretty(&storage.code_dapp(code1, 0).unwrap()),
+        expect![[r#"Some(("dapp2"))"#]],
+    );
+    check(storage.code_dapp_count(code2).unwrap(), expect!["0"]);
+
+    check(
+        storage
+            .dapp_earnings(dapp2.as_id_ref(), code1)
+            .unwrap()
+            .unwrap(),
+        expect!["300"],
+    );
+    check(
+        storage
+            .dapp_contributions(dapp2.as_id_ref())
+            .unwrap()
+            .unwrap(),
+        expect!["300"],
+    );
+    check(
+        storage.invocations(dapp2.as_id_ref(), code1).unwrap(),
+        expect!["1"],
+    );
+    check(
+        storage.dapp_referrers_collected(&dapp2).unwrap().unwrap(),
+        expect!["300"],
+    );
+
+    // lifetime totals are unaffected
+    check(storage.total_contributions().unwrap(), expect!["1000"]);
+    check(storage.total_collected().unwrap(), expect!["400"]);
+
+    // re-earning with the dApp lists it once more
+    storage
+        .set_dapp_earnings(dapp1.as_id_ref(), code1, nz!(100))
+        .unwrap();
+
+    check(storage.code_dapp_count(code1).unwrap(), expect!["2"]);
+}
+
+#[test]
+fn reset_dapp_accounting_unlisted_codes_works() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let code1 = code!(1);
+    let code2 = code!(2);
+    let dapp = Id::from("dapp");
+
+    storage.add_dapp(&dapp, "dapp".to_owned()).unwrap();
+
+    for code in [code1, code2] {
+        storage
+            .increment_invocations(dapp.as_id_ref(), code)
+            .unwrap();
+        storage
+            .set_dapp_earnings(dapp.as_id_ref(), code, nz!(500))
+            .unwrap();
+        storage.set_total_earnings(code, nz!(500)).unwrap();
+    }
+
+    // code 1 referred the dApp before it's code list was kept, the list is ordered by index
+    let unlisted = storage
+        .inner()
+        .repo()
+        .0
+        .keys()
+        .find(|key| key.contains("dapp_code_list"))
+        .cloned()
+        .unwrap();
+
+    storage.inner_mut().remove(unlisted.as_bytes()).unwrap();
+
+    storage.remove_dapp(&dapp).unwrap();
+    storage.reset_dapp_accounting(&dapp).unwrap();
+
+    // only listed codes are reset, the unlisted code is left as it was
+    check(
+        storage.dapp_earnings(dapp.as_id_ref(), code1).unwrap(),
+        expect!["Some(500)"],
+    );
+    check(storage.total_earnings(code1).unwrap(), expect!["Some(500)"]);
+    check(
+        storage.invocations(dapp.as_id_ref(), code1).unwrap(),
+        expect!["1"],
+    );
+
+    assert!(storage
+        .dapp_earnings(dapp.as_id_ref(), code2)
+        .unwrap()
+        .is_none());
+    assert!(storage.total_earnings(code2).unwrap().is_none());
+    check(
+        storage.invocations(dapp.as_id_ref(), code2).unwrap(),
+        expect!["0"],
+    );
+
+    check(
+        storage.dapp_discrete_referrers(&dapp).unwrap(),
+        expect!["0"],
+    );
+}
+
+#[test]
+fn reset_dapp_accounting_never_activated_does_nothing() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    storage
+        .set_dapp_contributions(IdRef::from("dapp"), nz!(700))
+        .unwrap();
+
+    storage.reset_dapp_accounting(&Id::from("dapp")).unwrap();
+
+    check(
+        storage
+            .dapp_contributions(IdRef::from("dapp"))
+            .unwrap()
+            .unwrap(),
+        expect!["700"],
+    );
+}
+
+#[test]
+fn recording_storage_commits_nothing() {
+    let mut storage: CoreStorage<KvStore<RonSerde, Repo>> = CoreStorage::new(KvStore::default());
+
+    let id = Id::from("id");
+
+    storage.add_dapp(&id, "dapp".to_owned()).unwrap();
+    storage.set_percent(&id, nzp!(50)).unwrap();
+    storage.set_repo_url(&id, "some_repo".to_owned()).unwrap();
+
+    let before = storage.inner().repo().to_string();
+
+    let mut recording: CoreStorage<KvStore<RonSerde, RecordingStore<&Repo>>> = CoreStorage::new(
+        KvStore::from_repo(RecordingStore::new(storage.inner().repo())),
+    );
+
+    recording.set_percent(&id, nzp!(75)).unwrap();
+    recording.set_collector(&id, Id::from("collector")).unwrap();
+    recording.clear_repo_url(&id).unwrap();
+
+    // reads see the recorded writes over the snapshot
+    assert!(recording.dapp_exists(&id).unwrap());
+    check(
+        recording.percent(&id).unwrap().unwrap().to_u8(),
+        expect!["75"],
+    );
+    check(recording.collector(&id).unwrap(), expect!["collector"]);
+
+    let writes: Vec<_> = recording
+        .recorded_writes()
+        .into_iter()
+        .map(|write| {
+            let key = String::from_utf8(write.key).unwrap();
+            match write.value {
+                Some(value) => format!("{key} => {}", String::from_utf8(value).unwrap()),
+                None => format!("{key} removed"),
+            }
+        })
+        .collect();
+
+    check(
+        writes.join("\n"),
+        expect![[r#"
+            referrals_storage::hub::dapp::collector::id => "collector"
+            referrals_storage::hub::dapp::percent::id => 75
+            referrals_storage::hub::dapp::repo_url::id removed"#]],
+    );
+
+    assert_eq!(storage.inner().repo().to_string(), before);
+}
@@ -83,6 +83,7 @@ pub mod archway {
     use anyhow::{anyhow, Result};
     use bip39::Mnemonic;
     use nanorand::{Rng, WyRand};
+    use referrals_cw::address::{label_salt, predict_instantiate2_address};
     use referrals_cw::{
         DappResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReferralCodeResponse, WithReferralCode,
     };
@@ -94,6 +95,8 @@ pub mod archway {
 
     pub const IMAGE_NAME: &str = "archwayd-xtask";
     pub const CONTAINER_NAME: &str = "local_archwayd_xtask";
+    /// Premium attached to hub collections, also the hub's flat fee
+    pub const CONTRACT_PREMIUM: u128 = 1000;
 
     static VERBOSE: AtomicBool = AtomicBool::new(false);
 
@@ -625,30 +628,9 @@ pub mod archway {
 
         let from_address = account_address(sh, from)?;
 
-        let salt = hex::encode(label);
+        let salt = label_salt(label);
 
-        let out = archwayd_cmd(sh)
-            .args([
-                "query",
-                "wasm",
-                "build-address",
-                &code_hash,
-                &from_address,
-                &salt,
-            ])
-            .ignore_status()
-            .output()?;
-
-        if !out.status.success() {
-            let err = std::str::from_utf8(&out.stderr)?;
-            return Err(anyhow!("{err}"));
-        }
-
-        let address = String::from_utf8(out.stdout)?
-            .split_ascii_whitespace()
-            .next()
-            .unwrap()
-            .to_owned();
+        let address = predict_instantiate2_address(&code_hash, &from_address, &salt)?;
 
         Ok((address, salt))
     }
@@ -731,13 +713,14 @@ pub mod archway {
         msg: Msg,
         gas: Option<u64>,
         premium: u128,
+        funds: u128,
     ) -> Result<JsonValue>
     where
         Msg: Serialize,
     {
         let msg = serde_json::to_string(&msg)?;
 
-        let cmd = archwayd_node_cmd(sh)?.args([
+        let mut cmd = archwayd_node_cmd(sh)?.args([
             "tx",
             "wasm",
             "execute",
@@ -747,6 +730,10 @@ pub mod archway {
             &format!("{premium}stake"),
         ]);
 
+        if funds > 0 {
+            cmd = cmd.args(["--amount", &format!("{funds}stake")]);
+        }
+
         execute_tx(sh, cmd, from, gas)
     }
 
@@ -810,7 +797,10 @@ pub mod archway {
             "referrals_hub",
             InstantiateMsg {
                 rewards_pot_code_id: pot_code_id,
-                contract_premium: 1000u128.into(),
+                contract_premium: CONTRACT_PREMIUM.into(),
+                rewards_denom: "stake".to_owned(),
+                self_activate: None,
+                block_self_referral: None,
             },
         )?;
 
@@ -829,6 +819,7 @@ pub mod archway {
             ExecuteMsg::RegisterReferrer {},
             Some(200_000),
             1000,
+            0,
         )?;
 
         let test_1_address = account_address(sh, "test_1")?;
@@ -857,6 +848,7 @@ pub mod archway {
             },
             Some(500_000),
             1000,
+            0,
         )?;
 
         let test_2_address = account_address(sh, "test_2")?;
@@ -899,7 +891,7 @@ pub mod archway {
             test_1_address, test_1_referral_code, test_1_balance
         );
 
-        println!("Collecting earnings for code: {test_1_referral_code} (costs referrer 1000 in contract premium)...");
+        println!("Collecting earnings for code: {test_1_referral_code} (costs referrer {CONTRACT_PREMIUM} in contract premium)...");
 
         exec_contract(
             sh,
@@ -911,6 +903,7 @@ pub mod archway {
             },
             Some(500_000),
             1000,
+            CONTRACT_PREMIUM,
         )?;
 
         let test_1_balance = account_balance(sh, &test_1_address)?;
@@ -946,7 +939,7 @@ pub mod archway {
         );
         println!("\tTotal Rewards: {}", hub.total_rewards);
 
-        println!("Collecting earnings for Hub owner: {test_0_address} (costs dApp collector 1000 in contract premium)...");
+        println!("Collecting earnings for Hub owner: {test_0_address} (costs dApp collector {CONTRACT_PREMIUM} in contract premium)...");
 
         exec_contract(
             sh,
@@ -957,6 +950,7 @@ pub mod archway {
             },
             Some(500_000),
             1000,
+            CONTRACT_PREMIUM,
         )?;
 
         let test_0_balance = account_balance(sh, &test_0_address)?;